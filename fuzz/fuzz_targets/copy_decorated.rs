@@ -0,0 +1,47 @@
+//! Runs cat's decorators over arbitrary input, split in two to exercise the state carried
+//! between files: `cargo fuzz run copy_decorated`. The first byte picks the split.
+
+#![no_main]
+#[macro_use]
+extern crate libfuzzer_sys;
+extern crate cat;
+
+use cat::{copy_decorated, Decorators, State, BUFSIZE_MIN};
+use std::io::Cursor;
+
+fuzz_target!(|data: &[u8]| {
+    let (split, input) = match data.split_first() {
+        Some((&split, input)) => (split as usize % (input.len() + 1), input),
+        None => return,
+    };
+    for combination in 0..128 {
+        let decorators = Decorators {
+            ends: combination & 1 != 0,
+            number: combination & 2 != 0,
+            number_column: cat::number_column(),
+            squeeze: combination & 4 != 0,
+            null: combination & 8 != 0,
+            line_buffered: combination & 16 != 0,
+            show_nonprinting: combination & 32 != 0,
+            show_tabs: combination & 64 != 0,
+            // The smallest buffer, so that lines span several reads.
+            buffer_size: BUFSIZE_MIN,
+            retries: 0,
+        };
+        let mut state = State::default();
+        let mut output = Vec::new();
+        for part in &[&input[..split], &input[split..]] {
+            copy_decorated(
+                &mut state,
+                &mut Cursor::new(part),
+                &decorators,
+                false,
+                &mut output,
+            )
+            .unwrap();
+        }
+        if !decorators.any() {
+            assert_eq!(output, input);
+        }
+    }
+});
@@ -0,0 +1,29 @@
+//! Checks that seq's format validation never panics, and that whatever it accepts can be
+//! handed to snprintf: `cargo fuzz run validate_format`.
+
+#![no_main]
+#[macro_use]
+extern crate libfuzzer_sys;
+extern crate seq;
+
+use seq::{seq_to_string, validate_format, SeqConfig};
+
+fuzz_target!(|format: &str| {
+    if validate_format(format).is_err() {
+        return;
+    }
+    let config = SeqConfig {
+        separator: b",".to_vec(),
+        equal_width: true,
+        first: -1.5,
+        inc: 1.0,
+        last: 1.5,
+        format: format.to_owned(),
+        json: false,
+    };
+    // A NUL in the format makes it unusable for snprintf, which is an error rather than a
+    // panic; anything else must print.
+    if !format.contains('\0') {
+        seq_to_string(&config).unwrap();
+    }
+});
@@ -0,0 +1,5 @@
+extern crate base_encoding;
+
+fn main() {
+    base_encoding::main(&base_encoding::BASE64);
+}
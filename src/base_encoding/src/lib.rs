@@ -0,0 +1,376 @@
+//! Base32 and base64 as RFC 4648 describes them, and the command line shared by the base32
+//! and base64 tools.
+
+use std::env;
+use std::io::{self, Read, Write};
+
+#[macro_use(die)]
+extern crate utils;
+
+use utils::args::ArgParser;
+use utils::io::FileOrStdin;
+
+/// An alphabet of 2^bits characters, each standing for that many bits of the input. Blocks of
+/// `block_bytes` input bytes encode to whole blocks of `block_chars` characters.
+pub struct Encoding {
+    pub name: &'static str,
+    alphabet: &'static [u8],
+    bits: u32,
+    block_bytes: usize,
+    block_chars: usize,
+}
+
+pub const BASE32: Encoding = Encoding {
+    name: "base32",
+    alphabet: b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567",
+    bits: 5,
+    block_bytes: 5,
+    block_chars: 8,
+};
+
+pub const BASE64: Encoding = Encoding {
+    name: "base64",
+    alphabet: b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/",
+    bits: 6,
+    block_bytes: 3,
+    block_chars: 4,
+};
+
+/// The padding that fills up the last block of the encoding.
+const PAD: u8 = b'=';
+
+impl Encoding {
+    fn value(&self, c: u8) -> Option<u64> {
+        self.alphabet
+            .iter()
+            .position(|&a| a == c)
+            .map(|value| value as u64)
+    }
+
+    /// Encodes @p input, padding the last block if it is short. Only the last piece of an
+    /// input may have a length which is not a multiple of `block_bytes`.
+    pub fn encode(&self, input: &[u8]) -> Vec<u8> {
+        let blocks = input.len().div_ceil(self.block_bytes);
+        let mut output = Vec::with_capacity(blocks * self.block_chars);
+        let mask = (1 << self.bits) - 1;
+        for block in input.chunks(self.block_bytes) {
+            // Pack the block into the high end of a number, then take bits from the top.
+            let mut packed = 0u64;
+            for (n, &byte) in block.iter().enumerate() {
+                packed |= (byte as u64) << (8 * (self.block_bytes - 1 - n));
+            }
+            let total = 8 * self.block_bytes as u32;
+            let chars = (8 * block.len() as u32).div_ceil(self.bits) as usize;
+            for n in 0..chars {
+                let shift = total - self.bits * (n as u32 + 1);
+                output.push(self.alphabet[(packed >> shift & mask) as usize]);
+            }
+            output.resize(output.len() + self.block_chars - chars, PAD);
+        }
+        output
+    }
+}
+
+/// Decodes the characters of an encoding given in pieces, which need not end on block
+/// boundaries.
+pub struct Decoder<'a> {
+    encoding: &'a Encoding,
+    /// Skip characters outside the alphabet rather than fail on them.
+    ignore_garbage: bool,
+    /// The bits which do not make up a whole byte yet, and how many there are.
+    pending: u64,
+    pending_bits: u32,
+    /// The characters of the current block seen so far, and how many of them were padding.
+    block: usize,
+    padding: usize,
+}
+
+impl<'a> Decoder<'a> {
+    pub fn new(encoding: &'a Encoding, ignore_garbage: bool) -> Decoder<'a> {
+        Decoder {
+            encoding,
+            ignore_garbage,
+            pending: 0,
+            pending_bits: 0,
+            block: 0,
+            padding: 0,
+        }
+    }
+
+    /// Decodes @p input onto the end of @p output. White space is skipped.
+    pub fn feed(&mut self, input: &[u8], output: &mut Vec<u8>) -> Result<(), String> {
+        let bits = self.encoding.bits;
+        for &c in input {
+            if c.is_ascii_whitespace() {
+                continue;
+            }
+            if c == PAD {
+                // The bits left over must be fewer than a character's, or a character is
+                // missing before the padding.
+                if self.block == 0 || (self.padding == 0 && self.pending_bits >= bits) {
+                    return Err("invalid input".to_owned());
+                }
+                self.padding += 1;
+            } else {
+                let value = match self.encoding.value(c) {
+                    Some(value) if self.padding == 0 => value,
+                    None if self.ignore_garbage => continue,
+                    _ => return Err("invalid input".to_owned()),
+                };
+                self.pending = self.pending << bits | value;
+                self.pending_bits += bits;
+                if self.pending_bits >= 8 {
+                    self.pending_bits -= 8;
+                    output.push((self.pending >> self.pending_bits) as u8);
+                    self.pending &= (1 << self.pending_bits) - 1;
+                }
+            }
+            self.block += 1;
+            if self.block == self.encoding.block_chars {
+                self.block = 0;
+                self.padding = 0;
+                self.pending = 0;
+                self.pending_bits = 0;
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks that the input ended with a whole block.
+    pub fn finish(&self) -> Result<(), String> {
+        if self.block == 0 {
+            Ok(())
+        } else {
+            Err("invalid input".to_owned())
+        }
+    }
+}
+
+/// Writes @p encoded to @p writer with a new line after every @p wrap characters, or none
+/// if @p wrap is 0. @p column is where the line written so far ends, and is updated.
+fn write_wrapped(
+    writer: &mut dyn Write,
+    mut encoded: &[u8],
+    wrap: usize,
+    column: &mut usize,
+) -> io::Result<()> {
+    if wrap == 0 {
+        return writer.write_all(encoded);
+    }
+    while !encoded.is_empty() {
+        let n = encoded.len().min(wrap - *column);
+        writer.write_all(&encoded[..n])?;
+        encoded = &encoded[n..];
+        *column += n;
+        if *column == wrap {
+            writer.write_all(b"\n")?;
+            *column = 0;
+        }
+    }
+    Ok(())
+}
+
+/// Reads from @p reader until @p buf is full or the input ends, and returns how much was read.
+fn fill(reader: &mut dyn Read, buf: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(filled)
+}
+
+/// Encodes @p reader to @p writer, wrapping lines after @p wrap characters.
+pub fn encode(
+    encoding: &Encoding,
+    reader: &mut dyn Read,
+    writer: &mut dyn Write,
+    wrap: usize,
+) -> io::Result<()> {
+    // A multiple of both block sizes, so that only the last piece may need padding.
+    let mut buf = vec![0; 15 * 4096];
+    let mut column = 0;
+    loop {
+        let n = fill(reader, &mut buf)?;
+        write_wrapped(writer, &encoding.encode(&buf[..n]), wrap, &mut column)?;
+        if n < buf.len() {
+            break;
+        }
+    }
+    if column > 0 {
+        writer.write_all(b"\n")?;
+    }
+    Ok(())
+}
+
+/// Decodes @p reader to @p writer. What was decoded before an error is still written.
+pub fn decode(
+    encoding: &Encoding,
+    reader: &mut dyn Read,
+    writer: &mut dyn Write,
+    ignore_garbage: bool,
+) -> io::Result<()> {
+    let mut decoder = Decoder::new(encoding, ignore_garbage);
+    let mut buf = vec![0; 64 * 1024];
+    let mut output = Vec::new();
+    loop {
+        let n = fill(reader, &mut buf)?;
+        output.clear();
+        let decoded = decoder.feed(&buf[..n], &mut output);
+        writer.write_all(&output)?;
+        decoded
+            .and_then(|_| if n == 0 { decoder.finish() } else { Ok(()) })
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        if n == 0 {
+            return Ok(());
+        }
+    }
+}
+
+fn show_help(encoding: &Encoding, opts: &ArgParser) {
+    let brief = format!(
+        concat!(
+            "Clone of the standard GNU {}.\n",
+            "Usage: {}: [OPTION]... [FILE]\n",
+            "Encode or decode FILE, or the standard input, to the standard output, as RFC 4648\n",
+            "describes {}. When decoding, white space in the input is ignored."
+        ),
+        encoding.name,
+        env::args().next().unwrap(),
+        encoding.name
+    );
+    print!("{}", opts.usage(&brief));
+}
+
+/// The main function of the tool for @p encoding.
+pub fn main(encoding: &Encoding) {
+    let args: Vec<String> = env::args().collect();
+    let mut opts = ArgParser::new();
+    opts.flag("d", "decode", "decode data");
+    opts.flag(
+        "i",
+        "ignore-garbage",
+        "when decoding, ignore non-alphabet characters",
+    );
+    opts.option(
+        "w",
+        "wrap",
+        "wrap encoded lines after COLS characters (default 76), or not at all if 0",
+        "COLS",
+    );
+    opts.flag("h", "help", "display this help and exit");
+    opts.flag("v", "version", "output version information and exit");
+    let options = match opts.parse(&args[1..]) {
+        Ok(m) => m,
+        Err(f) => die!(code = 2, "{}", f.to_string()),
+    };
+    if options.opt_present("h") {
+        return show_help(encoding, &opts);
+    }
+    if options.opt_present("v") {
+        return utils::version::print_version(encoding.name);
+    }
+    if options.free.len() > 1 {
+        die!(code = 2, "extra operand '{}'", options.free[1]);
+    }
+    let wrap = match options.opt_str("w") {
+        Some(wrap) => wrap
+            .parse::<usize>()
+            .unwrap_or_else(|_| die!(code = 2, "invalid wrap size: '{}'", wrap)),
+        None => 76,
+    };
+
+    let input = options.free.first().map_or("-", |name| name.as_str());
+    let mut reader = FileOrStdin::open(input).unwrap_or_else(|e| die!("{}: {}", input, e));
+    let stdout = io::stdout();
+    let mut writer = io::BufWriter::new(stdout.lock());
+    let result = if options.opt_present("d") {
+        decode(encoding, &mut reader, &mut writer, options.opt_present("i"))
+    } else {
+        encode(encoding, &mut reader, &mut writer, wrap)
+    };
+    let flushed = writer.flush();
+    result.and(flushed).unwrap_or_else(|e| die!("{}", e));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The test vectors of RFC 4648, section 10.
+    const VECTORS: &[(&str, &str, &str)] = &[
+        ("", "", ""),
+        ("f", "Zg==", "MY======"),
+        ("fo", "Zm8=", "MZXQ===="),
+        ("foo", "Zm9v", "MZXW6==="),
+        ("foob", "Zm9vYg==", "MZXW6YQ="),
+        ("fooba", "Zm9vYmE=", "MZXW6YTB"),
+        ("foobar", "Zm9vYmFy", "MZXW6YTBOI======"),
+    ];
+
+    fn decoded(encoding: &Encoding, input: &str) -> Result<Vec<u8>, String> {
+        let mut decoder = Decoder::new(encoding, false);
+        let mut output = Vec::new();
+        decoder.feed(input.as_bytes(), &mut output)?;
+        decoder.finish().map(|_| output)
+    }
+
+    #[test]
+    fn rfc_4648_vectors() {
+        for &(plain, base64, base32) in VECTORS {
+            assert_eq!(BASE64.encode(plain.as_bytes()), base64.as_bytes());
+            assert_eq!(BASE32.encode(plain.as_bytes()), base32.as_bytes());
+            assert_eq!(decoded(&BASE64, base64).unwrap(), plain.as_bytes());
+            assert_eq!(decoded(&BASE32, base32).unwrap(), plain.as_bytes());
+        }
+    }
+
+    #[test]
+    fn decoding_in_pieces() {
+        let mut decoder = Decoder::new(&BASE32, false);
+        let mut output = Vec::new();
+        for piece in &["MZ", "XW\n6Y", " TBOI==", "====\n"] {
+            decoder.feed(piece.as_bytes(), &mut output).unwrap();
+        }
+        decoder.finish().unwrap();
+        assert_eq!(output, b"foobar");
+        // Padded blocks may follow one another, as when encodings are concatenated.
+        assert_eq!(decoded(&BASE64, "Zg==Zm8=").unwrap(), b"ffo");
+    }
+
+    #[test]
+    fn invalid_input() {
+        for input in &["Zg", "Zg=", "Z===", "Zg==Z", "Zg=a", "=", "Zm9v!"] {
+            assert!(decoded(&BASE64, input).is_err(), "{}", input);
+        }
+        for input in &["MY", "M=======", "MZX=====", "MZXW6Y==", "a"] {
+            assert!(decoded(&BASE32, input).is_err(), "{}", input);
+        }
+        let mut decoder = Decoder::new(&BASE64, true);
+        let mut output = Vec::new();
+        decoder.feed(b"Z!m9*v", &mut output).unwrap();
+        assert_eq!(output, b"foo");
+    }
+
+    #[test]
+    fn wrapping() {
+        let mut output = Vec::new();
+        encode(&BASE32, &mut &b"foobar"[..], &mut output, 5).unwrap();
+        assert_eq!(output, b"MZXW6\nYTBOI\n=====\n=\n");
+        let mut output = Vec::new();
+        encode(&BASE64, &mut &b"foobar"[..], &mut output, 0).unwrap();
+        assert_eq!(output, b"Zm9vYmFy");
+        let mut output = Vec::new();
+        encode(&BASE64, &mut &b""[..], &mut output, 76).unwrap();
+        assert!(output.is_empty());
+        // Input longer than one read is encoded without padding in between.
+        let long = vec![b'x'; 15 * 4096 + 1];
+        let mut output = Vec::new();
+        encode(&BASE64, &mut &long[..], &mut output, 0).unwrap();
+        assert_eq!(output, BASE64.encode(&long));
+    }
+}
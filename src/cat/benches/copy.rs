@@ -0,0 +1,112 @@
+//! Times copy_raw and copy_decorated in memory, with every combination of decorators and at
+//! several buffer sizes: `cargo bench -p cat --bench copy`. Unlike the decorated benchmark,
+//! this leaves out the cost of the system calls. The input is 64 MiB unless CAT_BENCH_SIZE
+//! gives another size in bytes.
+
+extern crate cat;
+
+use cat::{copy_decorated, copy_raw, Decorators, State, BUFSIZE};
+use std::env;
+use std::hint::black_box;
+use std::io::{self, BufReader, Cursor, Write};
+use std::time::{Duration, Instant};
+
+/// Lines of assorted lengths, blank ones included, so that the decorators have work to do.
+const TEXT: &[u8] = b"The quick brown fox jumps over the lazy dog.\n\
+    \n\
+    Pack my box with five dozen liquor jugs, then pack another one.\n\
+    How vexingly quick daft zebras jump!\n\
+    \n\
+    \n\
+    Sphinx of black quartz, judge my vow.\n";
+
+/// How many times each case runs; the fastest run counts.
+const RUNS: usize = 5;
+
+/// A writer that throws its input away, but not in a way the optimizer can see through.
+struct Sink;
+
+impl Write for Sink {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        black_box(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+fn decorators(flags: &str, buffer_size: usize) -> Decorators {
+    Decorators {
+        ends: flags.contains('E'),
+        number: flags.contains('n'),
+        number_column: cat::number_column(),
+        squeeze: flags.contains('s'),
+        show_nonprinting: false,
+        show_tabs: false,
+        null: false,
+        line_buffered: false,
+        buffer_size,
+        retries: 0,
+    }
+}
+
+/// Copies @p input as cat would with @p flags, and returns the time it took.
+fn time(input: &[u8], flags: &str, buffer_size: usize) -> Duration {
+    let decorators = decorators(flags, buffer_size);
+    let mut writer = io::BufWriter::with_capacity(2 * buffer_size, Sink);
+    let start = Instant::now();
+    if decorators.any() {
+        let mut reader = Cursor::new(black_box(input));
+        copy_decorated(
+            &mut State::default(),
+            &mut reader,
+            &decorators,
+            false,
+            &mut writer,
+        )
+        .unwrap();
+    } else {
+        // Like copy_or_die, read through a buffer of the size asked for.
+        let mut reader = BufReader::with_capacity(buffer_size, Cursor::new(black_box(input)));
+        copy_raw(&mut reader, &mut writer, false, false).unwrap();
+    }
+    writer.flush().unwrap();
+    start.elapsed()
+}
+
+fn main() {
+    let size = env::var("CAT_BENCH_SIZE")
+        .ok()
+        .and_then(|size| size.parse().ok())
+        .unwrap_or(64 << 20);
+    let input: Vec<u8> = TEXT.iter().cycle().take(size).cloned().collect();
+
+    let mut cases: Vec<(&str, usize)> = ["", "n", "E", "s", "nE", "ns", "Es", "nEs"]
+        .iter()
+        .map(|&flags| (flags, BUFSIZE))
+        .collect();
+    for &buffer_size in &[4 << 10, 64 << 10, 256 << 10] {
+        cases.push(("", buffer_size));
+        cases.push(("nEs", buffer_size));
+    }
+    for &(flags, buffer_size) in &cases {
+        let elapsed = (0..RUNS)
+            .map(|_| time(&input, flags, buffer_size))
+            .min()
+            .unwrap();
+        let flags = if flags.is_empty() {
+            String::new()
+        } else {
+            format!("-{}", flags)
+        };
+        println!(
+            "cat {:<5} {:>4} KiB buffer {:>8.3} s {:>8.1} MiB/s",
+            flags,
+            buffer_size >> 10,
+            elapsed.as_secs_f64(),
+            size as f64 / (1 << 20) as f64 / elapsed.as_secs_f64()
+        );
+    }
+}
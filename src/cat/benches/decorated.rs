@@ -0,0 +1,68 @@
+//! Times cat over a large file, plain and with decorators, and at several buffer sizes:
+//! `cargo bench -p cat`. The file is 1 GiB unless CAT_BENCH_SIZE gives another size in bytes.
+
+use std::env;
+use std::fs;
+use std::io::{self, Write};
+use std::process::{Command, Stdio};
+use std::time::Instant;
+
+/// Lines of assorted lengths, blank ones included, so that the decorators have work to do.
+const TEXT: &[u8] = b"The quick brown fox jumps over the lazy dog.\n\
+    \n\
+    Pack my box with five dozen liquor jugs, then pack another one.\n\
+    How vexingly quick daft zebras jump!\n\
+    \n\
+    \n\
+    Sphinx of black quartz, judge my vow.\n";
+
+fn write_input(path: &str, size: u64) -> io::Result<()> {
+    let mut file = io::BufWriter::new(fs::File::create(path)?);
+    let mut written = 0;
+    while written < size {
+        file.write_all(TEXT)?;
+        written += TEXT.len() as u64;
+    }
+    file.flush()
+}
+
+fn main() {
+    let size = env::var("CAT_BENCH_SIZE")
+        .ok()
+        .and_then(|size| size.parse().ok())
+        .unwrap_or(1 << 30);
+    let path = env::temp_dir().join(format!("cat-bench-{}", std::process::id()));
+    let path = path.to_str().unwrap();
+    write_input(path, size).unwrap();
+
+    let runs = [
+        &[][..],
+        &["-n"][..],
+        &["-nE"][..],
+        &["-s"][..],
+        &["--buffer-size=4k"][..],
+        &["--buffer-size=64k"][..],
+        &["--buffer-size=256k"][..],
+        &["--buffer-size=1m"][..],
+        &["-n", "--buffer-size=4k"][..],
+        &["-n", "--buffer-size=1m"][..],
+    ];
+    for flags in &runs {
+        let start = Instant::now();
+        let status = Command::new(env!("CARGO_BIN_EXE_cat"))
+            .args(*flags)
+            .arg(path)
+            .stdout(Stdio::null())
+            .status()
+            .unwrap();
+        let elapsed = start.elapsed();
+        assert!(status.success());
+        println!(
+            "cat {:<22} {:>8.3} s {:>8.1} MiB/s",
+            flags.join(" "),
+            elapsed.as_secs_f64(),
+            size as f64 / (1 << 20) as f64 / elapsed.as_secs_f64()
+        );
+    }
+    fs::remove_file(path).unwrap();
+}
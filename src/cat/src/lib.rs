@@ -0,0 +1,838 @@
+//! The implementation of cat: copying inputs, optionally decorated, to a writer.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::Duration;
+
+extern crate memchr;
+#[macro_use(die)]
+extern crate utils;
+
+use memchr::memchr;
+use utils::fmt::ColumnFormatter;
+use utils::io::FileOrStdin;
+
+/// The ways output can be decorated, as requested by the options.
+pub struct Decorators {
+    pub ends: bool,
+    pub number: bool,
+    // The column the line numbers of `number` are written in, built once by number_column.
+    pub number_column: ColumnFormatter,
+    pub squeeze: bool,
+    // Control characters and bytes above 127 are shown in ^ and M- notation, except for TAB
+    // and new line.
+    pub show_nonprinting: bool,
+    // TAB is shown as ^I.
+    pub show_tabs: bool,
+    // Records end with NUL rather than new line.
+    pub null: bool,
+    // The output is flushed after every line, for readers at the other end of a pipe.
+    pub line_buffered: bool,
+    // How much input is read at a time, from BUFSIZE_MIN to BUFSIZE_MAX.
+    pub buffer_size: usize,
+    // How many times a read failing with EAGAIN or EINTR is tried again before giving up.
+    pub retries: u32,
+}
+
+/// What the decorators need to remember from one input to the next.
+pub struct State {
+    empty_streak: i32,
+    current_line: i64,
+    // Set while in the middle of a line, i.e. after its prefix was already written.
+    pending_line_data: bool,
+}
+
+impl Default for State {
+    fn default() -> State {
+        State {
+            empty_streak: 1,
+            current_line: 1,
+            pending_line_data: false,
+        }
+    }
+}
+
+impl Decorators {
+    pub fn any(&self) -> bool {
+        self.ends || self.number || self.squeeze || self.show_nonprinting || self.show_tabs
+    }
+
+    fn delimiter(&self) -> u8 {
+        if self.null {
+            b'\0'
+        } else {
+            b'\n'
+        }
+    }
+}
+
+/// The width of the line numbers of -n, as in GNU cat, however many lines there are.
+const NUMBER_WIDTH: usize = 6;
+
+/// The column -n writes line numbers in: NUMBER_WIDTH wide, followed by a TAB.
+pub fn number_column() -> ColumnFormatter {
+    ColumnFormatter::new(NUMBER_WIDTH, "\t")
+}
+
+pub const BUFSIZE: usize = 65536;
+pub const BUFSIZE_MIN: usize = 512;
+pub const BUFSIZE_MAX: usize = 128 << 20;
+
+/// Parses the N of --buffer-size: a number of bytes, optionally followed by k or m for KiB or
+/// MiB, between BUFSIZE_MIN and BUFSIZE_MAX.
+pub fn parse_buffer_size(size: &str) -> Result<usize, String> {
+    let (number, multiplier) = match size.chars().last().map(|c| c.to_ascii_lowercase()) {
+        Some('k') => (&size[..size.len() - 1], 1 << 10),
+        Some('m') => (&size[..size.len() - 1], 1 << 20),
+        _ => (size, 1),
+    };
+    number
+        .parse::<usize>()
+        .ok()
+        .and_then(|n| n.checked_mul(multiplier))
+        .filter(|n| (BUFSIZE_MIN..=BUFSIZE_MAX).contains(n))
+        .ok_or_else(|| {
+            format!(
+                "invalid buffer size: '{}' (must be between {} and {})",
+                size, BUFSIZE_MIN, BUFSIZE_MAX
+            )
+        })
+}
+
+/// The number of retries of --retry without a value.
+pub const RETRIES: u32 = 3;
+
+/// The wait before the first retry of a read, doubled for each one after it.
+const RETRY_DELAY: Duration = Duration::from_millis(10);
+
+/// A reader which tries reads failing with `WouldBlock` or `Interrupted` again, waiting a
+/// little longer each time, as networked file systems and /proc files may need.
+pub struct Retrying<R> {
+    inner: R,
+    retries: u32,
+}
+
+impl<R> Retrying<R> {
+    pub fn new(inner: R, retries: u32) -> Retrying<R> {
+        Retrying { inner, retries }
+    }
+
+    /// Runs @p op until it succeeds, fails for good, or the retries run out.
+    fn retry<T, F: FnMut(&mut R) -> io::Result<T>>(&mut self, mut op: F) -> io::Result<T> {
+        let mut delay = RETRY_DELAY;
+        let mut attempt = 0;
+        loop {
+            match op(&mut self.inner) {
+                Err(ref e) if attempt < self.retries && is_transient(e) => {
+                    thread::sleep(delay);
+                    delay *= 2;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+fn is_transient(e: &io::Error) -> bool {
+    e.kind() == io::ErrorKind::WouldBlock || e.kind() == io::ErrorKind::Interrupted
+}
+
+impl<R: Read> Read for Retrying<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.retry(|inner| inner.read(buf))
+    }
+}
+
+impl<R: BufRead> BufRead for Retrying<R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.retry(|inner| inner.fill_buf().map(|_| ()))?;
+        // The data is buffered now, so this does not read again.
+        self.inner.fill_buf()
+    }
+
+    fn consume(&mut self, amount: usize) {
+        self.inner.consume(amount)
+    }
+}
+
+/// Copies @p from to @p writer unchanged, flushing after every read if @p interactive and
+/// after every line if @p line_buffered. Returns the number of bytes copied.
+pub fn copy_raw(
+    from: &mut dyn BufRead,
+    writer: &mut dyn Write,
+    interactive: bool,
+    line_buffered: bool,
+) -> io::Result<u64> {
+    let mut copied = 0;
+    loop {
+        let len = {
+            let input = from.fill_buf()?;
+            if line_buffered {
+                write_flushing_lines(input, writer)?;
+            } else {
+                writer.write_all(input)?;
+            }
+            input.len()
+        };
+        if len == 0 {
+            return Ok(copied);
+        }
+        copied += len as u64;
+        from.consume(len);
+        if interactive {
+            writer.flush()?;
+        }
+    }
+}
+
+/// Writes @p input to @p writer, flushing it after each new line.
+fn write_flushing_lines(input: &[u8], writer: &mut dyn Write) -> io::Result<()> {
+    let mut p = 0;
+    while let Some(q) = memchr(b'\n', &input[p..]) {
+        writer.write_all(&input[p..p + q + 1])?;
+        writer.flush()?;
+        p += q + 1;
+    }
+    writer.write_all(&input[p..])
+}
+
+/// Writes @p text, a piece of a line, to @p writer, with the characters @p decorators want
+/// shown in ^ and M- notation replaced.
+fn write_text(text: &[u8], decorators: &Decorators, writer: &mut dyn Write) -> io::Result<()> {
+    if !decorators.show_nonprinting && !decorators.show_tabs {
+        return writer.write_all(text);
+    }
+    let mut shown = Vec::with_capacity(text.len());
+    for &c in text {
+        match c {
+            b'\t' if decorators.show_tabs => shown.extend_from_slice(b"^I"),
+            b'\t' | b'\n' => shown.push(c),
+            _ if !decorators.show_nonprinting => shown.push(c),
+            _ => {
+                let mut c = c;
+                if c >= 128 {
+                    shown.extend_from_slice(b"M-");
+                    c -= 128;
+                }
+                match c {
+                    0..=31 => shown.extend_from_slice(&[b'^', c + 64]),
+                    127 => shown.extend_from_slice(b"^?"),
+                    _ => shown.push(c),
+                }
+            }
+        }
+    }
+    writer.write_all(&shown)
+}
+
+/// Copies @p from to @p writer with @p decorators applied, carrying @p state over between
+/// inputs. Returns the number of bytes read.
+pub fn copy_decorated(
+    state: &mut State,
+    reader: &mut dyn BufRead,
+    decorators: &Decorators,
+    interactive: bool,
+    writer: &mut dyn Write,
+) -> io::Result<u64> {
+    let mut input = vec![0u8; decorators.buffer_size];
+    let delimiter = decorators.delimiter();
+    let mut read = 0;
+
+    loop {
+        let len = reader.read(&mut input)?;
+        if len == 0 {
+            break;
+        }
+        read += len as u64;
+
+        let mut p = 0;
+        while p < len {
+            // Attempt to minimize write calls by looking ahead for the delimiter.
+            let newline_offset = memchr(delimiter, &input[p..len]);
+
+            let end = match newline_offset {
+                Some(q) => p + q,
+                None => len,
+            };
+            if end == p && !state.pending_line_data {
+                state.empty_streak += 1;
+            } else {
+                state.empty_streak = 1;
+            }
+
+            if newline_offset.is_some() && decorators.squeeze && state.empty_streak >= 3 {
+                p += 1;
+                continue;
+            }
+            if decorators.number && !state.pending_line_data {
+                decorators.number_column.write(writer, state.current_line)?;
+                state.current_line += 1;
+            }
+
+            if newline_offset.is_none() {
+                // New line not found. We can write entire chunk of data at once, and the
+                // decorators are done for this line until its end (if any) shows up.
+                write_text(&input[p..len], decorators, writer)?;
+                state.empty_streak = 0;
+                state.pending_line_data = true;
+                break;
+            }
+            // Write everything till the new line.
+            write_text(&input[p..end], decorators, writer)?;
+
+            if decorators.ends {
+                writer.write_all(b"$")?;
+            }
+            writer.write_all(&[delimiter])?;
+            p = end + 1;
+            state.pending_line_data = false;
+
+            if interactive || decorators.line_buffered {
+                writer.flush()?;
+            }
+        }
+    }
+    Ok(read)
+}
+
+/// Exits after copying @p name failed with @p e. Output closed early, as by `cat big | head -c1`,
+/// needs no message.
+pub fn die_copying(name: &str, e: io::Error) -> ! {
+    if e.kind() == io::ErrorKind::BrokenPipe {
+        process::exit(1);
+    }
+    die!("{}: {}", name, e)
+}
+
+/// Copies @p from, read as @p name, as `copy_decorated` or `copy_raw` would, and flushes
+/// @p writer. Returns the number of bytes read.
+pub fn copy_or_die(
+    state: &mut State,
+    name: &str,
+    from: &mut dyn BufRead,
+    decorators: &Decorators,
+    writer: &mut dyn Write,
+) -> u64 {
+    let interactive = name == "-";
+    let mut from = Retrying::new(from, decorators.retries);
+    let read = if decorators.any() {
+        copy_decorated(state, &mut from, decorators, interactive, writer)
+    } else {
+        let mut from = BufReader::with_capacity(decorators.buffer_size, from);
+        copy_raw(&mut from, writer, interactive, decorators.line_buffered)
+    };
+    // Reading the next file may fail and exit, so do not keep this one's output buffered.
+    read.and_then(|read| writer.flush().map(|_| read))
+        .unwrap_or_else(|e| die_copying(name, e))
+}
+
+/// Whether the file @p name, "-" for the standard input, is now longer than the @p read bytes
+/// that were read before the end of it: it was replaced or truncated and written again while
+/// cat was reading it. Files whose size says nothing about their contents, such as those in
+/// /proc, are never longer than what was read from them.
+pub fn was_replaced(name: &str, read: u64) -> bool {
+    name != "-" && fs::metadata(name).is_ok_and(|file| file.is_file() && file.len() > read)
+}
+
+/// The most inputs --parallel reads at once.
+pub const PARALLEL_MAX: usize = 8;
+
+/// The contents of files read by several threads at once, in the order the files were given.
+pub struct ParallelInputs {
+    names: Arc<Vec<String>>,
+    next: usize,
+    receiver: mpsc::Receiver<(usize, io::Result<Vec<u8>>)>,
+    // What was read ahead of the next file.
+    pending: HashMap<usize, io::Result<Vec<u8>>>,
+}
+
+/// Reads the files @p names, which must not include "-", with @p jobs threads. Each file is
+/// read whole, so this is meant for many small ones.
+pub fn read_parallel(names: Vec<String>, jobs: usize, retries: u32) -> ParallelInputs {
+    let (sender, receiver) = mpsc::channel();
+    let names = Arc::new(names);
+    let taken = Arc::new(AtomicUsize::new(0));
+    for _ in 0..jobs.min(names.len()) {
+        let (names, taken, sender) = (names.clone(), taken.clone(), sender.clone());
+        thread::spawn(move || loop {
+            let index = taken.fetch_add(1, Ordering::Relaxed);
+            if index >= names.len() {
+                return;
+            }
+            let contents = FileOrStdin::open(&names[index]).and_then(|file| {
+                let mut contents = Vec::new();
+                Retrying::new(file, retries).read_to_end(&mut contents)?;
+                Ok(contents)
+            });
+            // The receiver is only gone once the files are no longer wanted.
+            if sender.send((index, contents)).is_err() {
+                return;
+            }
+        });
+    }
+    ParallelInputs {
+        names,
+        next: 0,
+        receiver,
+        pending: HashMap::new(),
+    }
+}
+
+impl Iterator for ParallelInputs {
+    type Item = (String, io::Result<Vec<u8>>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next == self.names.len() {
+            return None;
+        }
+        while !self.pending.contains_key(&self.next) {
+            let (index, contents) = self.receiver.recv().expect("a reader thread panicked");
+            self.pending.insert(index, contents);
+        }
+        let contents = self.pending.remove(&self.next).unwrap();
+        self.next += 1;
+        Some((self.names[self.next - 1].clone(), contents))
+    }
+}
+
+/// Whether @p name is the null device, which can be skipped without being read. Other files
+/// of size 0, such as /dev/zero or those in /proc, may well have data, so they are not.
+#[cfg(unix)]
+pub fn is_null_device(name: &str) -> bool {
+    use std::fs;
+    use std::os::unix::fs::{FileTypeExt, MetadataExt};
+
+    match (fs::metadata(name), fs::metadata("/dev/null")) {
+        (Ok(file), Ok(null)) => {
+            file.file_type().is_char_device() && file.len() == 0 && file.rdev() == null.rdev()
+        }
+        _ => false,
+    }
+}
+
+#[cfg(not(unix))]
+pub fn is_null_device(_name: &str) -> bool {
+    false
+}
+
+/// Whether @p name, "-" for the standard input, is the same file as @p output. Reading a
+/// regular file while appending to it could go on forever, so @p output only counts if it is
+/// one.
+#[cfg(unix)]
+pub fn is_output_file(name: &str, output: &std::fs::Metadata) -> bool {
+    use std::fs;
+    use std::os::unix::fs::MetadataExt;
+
+    let name = if name == "-" { "/dev/stdin" } else { name };
+    match fs::metadata(name) {
+        Ok(input) => output.is_file() && input.dev() == output.dev() && input.ino() == output.ino(),
+        Err(_) => false,
+    }
+}
+
+#[cfg(not(unix))]
+pub fn is_output_file(_name: &str, _output: &std::fs::Metadata) -> bool {
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decorators(flags: &str) -> Decorators {
+        Decorators {
+            ends: flags.contains('E'),
+            number: flags.contains('n'),
+            number_column: number_column(),
+            squeeze: flags.contains('s'),
+            show_nonprinting: flags.contains('v'),
+            show_tabs: flags.contains('T'),
+            null: flags.contains('z'),
+            line_buffered: false,
+            buffer_size: BUFSIZE,
+            retries: 0,
+        }
+    }
+
+    fn decorated(inputs: &[&[u8]], flags: &str) -> Vec<u8> {
+        let decorators = decorators(flags);
+        let mut state = State::default();
+        let mut output = Vec::new();
+        for input in inputs {
+            let mut reader = io::Cursor::new(input);
+            copy_decorated(&mut state, &mut reader, &decorators, false, &mut output).unwrap();
+        }
+        output
+    }
+
+    /// A writer which remembers how much had been written at each flush.
+    #[derive(Default)]
+    struct FlushRecorder {
+        data: Vec<u8>,
+        flushes: Vec<usize>,
+    }
+
+    impl Write for FlushRecorder {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.data.extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            self.flushes.push(self.data.len());
+            Ok(())
+        }
+    }
+
+    /// A reader which fails with @p error the first @p failures times it is read.
+    struct Flaky {
+        input: io::Cursor<&'static [u8]>,
+        failures: u32,
+        error: io::ErrorKind,
+    }
+
+    impl Read for Flaky {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if self.failures > 0 {
+                self.failures -= 1;
+                return Err(self.error.into());
+            }
+            self.input.read(buf)
+        }
+    }
+
+    fn flaky(failures: u32, error: io::ErrorKind) -> Flaky {
+        Flaky {
+            input: io::Cursor::new(b"a\nb\n"),
+            failures,
+            error,
+        }
+    }
+
+    #[test]
+    fn retrying_transient_errors() {
+        let decorators = decorators("n");
+        let read = |reader: Flaky, retries| {
+            let mut reader = BufReader::new(Retrying::new(reader, retries));
+            let mut output = Vec::new();
+            copy_decorated(
+                &mut State::default(),
+                &mut reader,
+                &decorators,
+                false,
+                &mut output,
+            )
+            .map(|_| output)
+        };
+        let expected = b"     1\ta\n     2\tb\n".to_vec();
+        assert_eq!(
+            read(flaky(1, io::ErrorKind::WouldBlock), 3).unwrap(),
+            expected
+        );
+        assert_eq!(
+            read(flaky(3, io::ErrorKind::Interrupted), 3).unwrap(),
+            expected
+        );
+        // Retries run out, other errors are not retried, and none are by default.
+        let error = read(flaky(4, io::ErrorKind::WouldBlock), 3).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::WouldBlock);
+        let error = read(flaky(1, io::ErrorKind::NotFound), 3).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::NotFound);
+        assert!(read(flaky(1, io::ErrorKind::WouldBlock), 0).is_err());
+
+        let mut reader = BufReader::new(flaky(2, io::ErrorKind::WouldBlock));
+        let mut output = Vec::new();
+        copy_raw(
+            &mut Retrying::new(&mut reader, 2),
+            &mut output,
+            false,
+            false,
+        )
+        .unwrap();
+        assert_eq!(output, b"a\nb\n");
+    }
+
+    #[test]
+    fn line_buffering() {
+        let mut output = FlushRecorder::default();
+        copy_raw(&mut io::Cursor::new(b"ab\n\nc"), &mut output, false, true).unwrap();
+        assert_eq!(output.data, b"ab\n\nc");
+        assert_eq!(output.flushes, [3, 4]);
+
+        let mut decorators = decorators("n");
+        decorators.line_buffered = true;
+        let mut output = FlushRecorder::default();
+        let mut reader = io::Cursor::new(b"ab\n\nc");
+        copy_decorated(
+            &mut State::default(),
+            &mut reader,
+            &decorators,
+            false,
+            &mut output,
+        )
+        .unwrap();
+        assert_eq!(output.flushes, [10, 18]);
+
+        let mut output = FlushRecorder::default();
+        copy_raw(&mut io::Cursor::new(b"ab\n\nc"), &mut output, false, false).unwrap();
+        assert!(output.flushes.is_empty());
+    }
+
+    #[test]
+    fn raw_copy() {
+        let mut output = Vec::new();
+        copy_raw(&mut io::Cursor::new(b"a\n\n\nb"), &mut output, false, false).unwrap();
+        assert_eq!(output, b"a\n\n\nb");
+        assert!(!decorators("").any());
+        assert!(!decorators("z").any());
+    }
+
+    #[test]
+    fn parallel_reads_keep_their_order() {
+        let dir = std::env::temp_dir().join(format!("cat-parallel-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut names = Vec::new();
+        for n in 0..20 {
+            let path = dir.join(n.to_string());
+            // Later files are smaller, so they tend to be read first.
+            std::fs::write(&path, vec![b'a' + n as u8; (20 - n) * 1000]).unwrap();
+            names.push(path.to_str().unwrap().to_owned());
+        }
+        names.insert(5, "/nonexistent".to_owned());
+        let inputs: Vec<_> = read_parallel(names.clone(), 4, 0).collect();
+        assert_eq!(inputs.len(), names.len());
+        for (n, (name, contents)) in inputs.into_iter().enumerate() {
+            assert_eq!(name, names[n]);
+            match contents {
+                Ok(contents) => assert_eq!(contents, std::fs::read(&name).unwrap()),
+                Err(e) => assert_eq!((n, e.kind()), (5, io::ErrorKind::NotFound)),
+            }
+        }
+        assert_eq!(read_parallel(Vec::new(), 4, 0).count(), 0);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn replaced_files() {
+        let path = std::env::temp_dir().join(format!("cat-replaced-test-{}", std::process::id()));
+        std::fs::write(&path, "abc").unwrap();
+        let name = path.to_str().unwrap();
+        let mut output = Vec::new();
+        let mut reader = BufReader::new(std::fs::File::open(&path).unwrap());
+        let read = copy_raw(&mut reader, &mut output, false, false).unwrap();
+        assert_eq!(read, 3);
+        assert!(!was_replaced(name, read));
+        std::fs::write(&path, "longer").unwrap();
+        assert!(was_replaced(name, read));
+        assert!(!was_replaced("-", 0));
+        assert!(!was_replaced("/proc/self/status", 0));
+        assert!(!was_replaced("/nonexistent", 0));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn null_device() {
+        assert!(is_null_device("/dev/null"));
+        assert!(!is_null_device("/dev/zero"));
+        assert!(!is_null_device("/proc/self/status"));
+        assert!(!is_null_device("-"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn output_file() {
+        let path = std::env::temp_dir().join(format!("cat-output-test-{}", std::process::id()));
+        std::fs::write(&path, "x").unwrap();
+        let name = path.to_str().unwrap();
+        let output = std::fs::metadata(&path).unwrap();
+        assert!(is_output_file(name, &output));
+        assert!(!is_output_file("/dev/null", &output));
+        assert!(!is_output_file("/nonexistent", &output));
+        // Devices may well be both read and written.
+        let null = std::fs::metadata("/dev/null").unwrap();
+        assert!(!is_output_file("/dev/null", &null));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn buffer_sizes() {
+        assert_eq!(parse_buffer_size("512"), Ok(512));
+        assert_eq!(parse_buffer_size("4k"), Ok(4096));
+        assert_eq!(parse_buffer_size("1M"), Ok(1 << 20));
+        assert_eq!(parse_buffer_size("128m"), Ok(BUFSIZE_MAX));
+        for invalid in &[
+            "511",
+            "129m",
+            "0",
+            "",
+            "k",
+            "-1k",
+            "1g",
+            "99999999999999999999",
+        ] {
+            assert!(parse_buffer_size(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn lines_span_reads() {
+        let mut input = Vec::new();
+        for n in 0..300 {
+            input.extend(vec![b'x'; n % 7 * 100]);
+            input.push(b'\n');
+        }
+        let mut decorators = decorators("nsE");
+        let expected = decorated(&[&input], "nsE");
+        decorators.buffer_size = BUFSIZE_MIN;
+        let mut output = Vec::new();
+        let mut reader = io::Cursor::new(&input);
+        copy_decorated(
+            &mut State::default(),
+            &mut reader,
+            &decorators,
+            false,
+            &mut output,
+        )
+        .unwrap();
+        assert_eq!(output, expected);
+    }
+
+    #[test]
+    fn crlf_across_reads() {
+        // '\r' is ordinary line data, so a "\r\n" split between two reads still ends one line.
+        let mut input = vec![b'x'; BUFSIZE - 1];
+        input.extend_from_slice(b"\r\n\r\nb\n");
+        let output = decorated(&[&input], "nE");
+        let mut expected = b"     1\t".to_vec();
+        expected.extend_from_slice(&input[..BUFSIZE]);
+        expected.extend_from_slice(b"$\n     2\t\r$\n     3\tb$\n");
+        assert_eq!(output, expected);
+        assert_eq!(decorated(&[&input], "s"), input);
+    }
+
+    #[test]
+    fn single_decorators() {
+        let input: &[u8] = b"a\n\n\n\nb\n";
+        assert_eq!(
+            decorated(&[input], "n"),
+            b"     1\ta\n     2\t\n     3\t\n     4\t\n     5\tb\n"
+        );
+        assert_eq!(decorated(&[input], "E"), b"a$\n$\n$\n$\nb$\n");
+        assert_eq!(decorated(&[input], "s"), b"a\n\nb\n");
+    }
+
+    #[test]
+    fn combined_decorators() {
+        let input: &[u8] = b"a\n\n\n\nb\n";
+        assert_eq!(
+            decorated(&[input], "nE"),
+            b"     1\ta$\n     2\t$\n     3\t$\n     4\t$\n     5\tb$\n"
+        );
+        assert_eq!(
+            decorated(&[input], "ns"),
+            b"     1\ta\n     2\t\n     3\tb\n"
+        );
+        assert_eq!(decorated(&[input], "Es"), b"a$\n$\nb$\n");
+        assert_eq!(
+            decorated(&[input], "nEs"),
+            b"     1\ta$\n     2\t$\n     3\tb$\n"
+        );
+    }
+
+    #[test]
+    fn squeeze_ends_and_number_combinations() {
+        // Blank runs at the start, in the middle and at the end, as GNU cat decorates them.
+        let input: &[u8] = b"\n\n\na\n\n\n\nb\n\n\n";
+        let expected: &[(&str, &[u8])] = &[
+            ("", input),
+            ("s", b"\na\n\nb\n\n"),
+            ("E", b"$\n$\n$\na$\n$\n$\n$\nb$\n$\n$\n"),
+            (
+                "n",
+                b"     1\t\n     2\t\n     3\t\n     4\ta\n     5\t\n     6\t\n     7\t\n\
+                  \x20    8\tb\n     9\t\n    10\t\n",
+            ),
+            ("sE", b"$\na$\n$\nb$\n$\n"),
+            (
+                "sn",
+                b"     1\t\n     2\ta\n     3\t\n     4\tb\n     5\t\n",
+            ),
+            (
+                "En",
+                b"     1\t$\n     2\t$\n     3\t$\n     4\ta$\n     5\t$\n     6\t$\n\
+                  \x20    7\t$\n     8\tb$\n     9\t$\n    10\t$\n",
+            ),
+            (
+                "sEn",
+                b"     1\t$\n     2\ta$\n     3\t$\n     4\tb$\n     5\t$\n",
+            ),
+        ];
+        for &(flags, output) in expected {
+            assert_eq!(decorated(&[input], flags), output, "{}", flags);
+            // The same, with the input split in the middle of a blank run.
+            let split = decorated(&[&input[..5], &input[5..]], flags);
+            assert_eq!(split, output, "{}", flags);
+        }
+    }
+
+    #[test]
+    fn nonprinting_and_tabs() {
+        let input: &[u8] = b"a\tb\x01\x7f\r\n\x80\x89\xa0\xe9\xff\n";
+        assert_eq!(
+            decorated(&[input], "v"),
+            &b"a\tb^A^?^M\nM-^@M-^IM- M-iM-^?\n"[..]
+        );
+        assert_eq!(
+            decorated(&[input], "T"),
+            &b"a^Ib\x01\x7f\r\n\x80\x89\xa0\xe9\xff\n"[..]
+        );
+        assert_eq!(
+            decorated(&[input], "vTE"),
+            &b"a^Ib^A^?^M$\nM-^@M-^IM- M-iM-^?$\n"[..]
+        );
+        assert!(decorators("v").any());
+        assert!(decorators("T").any());
+        // New line is left alone even inside NUL-delimited records.
+        assert_eq!(decorated(&[b"a\nb\0"], "zv"), b"a\nb\0");
+    }
+
+    #[test]
+    fn nonprinting_utf8_across_reads() {
+        // -v works byte by byte, so a character split between two reads needs no state.
+        let mut input = vec![b'x'; BUFSIZE - 1];
+        input.extend_from_slice("é".as_bytes());
+        let mut expected = b"     1\t".to_vec();
+        expected.extend_from_slice(&input[..BUFSIZE - 1]);
+        expected.extend_from_slice(b"M-CM-)");
+        assert_eq!(decorated(&[&input], "nv"), expected);
+        assert_eq!(
+            decorated(&[&input[..BUFSIZE], &input[BUFSIZE..]], "nv"),
+            expected
+        );
+    }
+
+    #[test]
+    fn null_delimited_records() {
+        assert_eq!(decorated(&[b"a\n\0\0\0b\0"], "zsE"), b"a\n$\0$\0b$\0");
+        assert_eq!(decorated(&[b"a\0b"], "zn"), b"     1\ta\0     2\tb");
+    }
+
+    #[test]
+    fn state_carries_over_between_inputs() {
+        assert_eq!(decorated(&[b"a\n", b"b\n"], "n"), b"     1\ta\n     2\tb\n");
+        assert_eq!(
+            decorated(&[b"pa", b"rt\nx\n"], "nE"),
+            b"     1\tpart$\n     2\tx$\n"
+        );
+        assert_eq!(decorated(&[b"a\n\n", b"\n\nb\n"], "s"), b"a\n\nb\n");
+        // The last line has no end marker until it ends.
+        assert_eq!(decorated(&[b"a\nb"], "E"), b"a$\nb");
+    }
+}
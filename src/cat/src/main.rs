@@ -1,144 +1,20 @@
 use std::env;
-use std::io::{self, Write, BufReader};
 use std::fs;
-use std::path;
+use std::io::{self, IsTerminal, Write};
+use std::os::fd::AsFd;
 
-#[macro_use(die)]
+#[macro_use(die, note_error, warn)]
 extern crate utils;
-extern crate getopts;
+extern crate cat;
 
-pub struct Decorators {
-    ends: bool,
-    number: bool,
-    squeeze: bool,
-}
-
-pub struct State {
-    empty_streak: i32,
-    current_line: i32,
-}
-
-impl Decorators {
-    fn any(&self) -> bool {
-        self.ends || self.number || self.squeeze
-    }
-}
-
-fn copy_raw(from: &mut std::io::Read) -> io::Result<u64> {
-    io::copy(from, &mut io::stdout())
-}
-
-fn copy_decorated(
-    state: &mut State,
-    reader: &mut std::io::Read,
-    decorators: &Decorators,
-    interactive: bool,
-) -> io::Result<()> {
-    const BUFSIZE: usize = 65536;
-    let stdout = io::stdout();
-    let mut writer = io::BufWriter::with_capacity(2 * BUFSIZE, stdout.lock());
-    let mut input: [u8; BUFSIZE] = [0u8; BUFSIZE];
-
-    while let Ok(len) = reader.read(&mut input) {
-        if len == 0 {
-            break;
-        }
-
-        let mut p = 0;
-        while p < len {
-            // Attempt to minimize write calls by looking ahead for '\n' character.
-            let newline_offset = match input[p..].iter().position(|c| *c == b'\n') {
-                Some(q) => q as i32,
-                None => -1,
-            };
-
-            if newline_offset < 0 {
-                // New line not found. We can write entire chunk of data at once.
-                writer.write_all(&input[p..])?;
-                state.empty_streak = 0;
-                break;
-            }
-
-            if newline_offset == 0 {
-                state.empty_streak += 1;
-            } else {
-                state.empty_streak = 1;
-            }
-
-            if decorators.squeeze && state.empty_streak >= 3 {
-                p += 1;
-                continue;
-            }
-            if decorators.number {
-                write!(&mut writer, "{:6}: ", state.current_line)?;
-                state.current_line += 1;
-            }
-            // Write everything till the new line.
-            writer.write_all(&input[p..p + newline_offset as usize])?;
-
-            if decorators.ends {
-                writer.write_all(&[b'$'])?;
-            }
-            writer.write_all(&[b'\n'])?;
-            p += 1 + newline_offset as usize;
-
-            if interactive {
-                writer.flush()?;
-            }
-        }
-    }
-    Ok(())
-}
-
-fn copy_or_die(
-    state: &mut State,
-    from: &mut std::io::Read,
-    decorators: &Decorators,
-    interactive: bool,
-) {
-    if decorators.any() {
-        copy_decorated(state, from, decorators, interactive).unwrap();
-    } else {
-        copy_raw(from).unwrap();
-    }
-}
+use cat::{Decorators, State, BUFSIZE};
+use utils::args::ArgParser;
 
-fn get_file(name: &str) -> io::BufReader<fs::File> {
-    match path::Path::new(name).metadata() {
-        Err(e) => {
-            match e.kind() {
-                io::ErrorKind::NotFound => die!("{}: no such file or directory", name),
-                io::ErrorKind::PermissionDenied => die!("{}: permission denied", name),
-                _ => die!("{}: unknown error", name),
-
-            }
-        }
-        Ok(info) => {
-            if info.is_dir() {
-                die!("{}: is a directory", name);
-            }
-        }
-    };
-
-    match fs::File::open(name) {
-        Err(_) => die!("{}: unknown error", name),
-        Ok(f) => BufReader::new(f),
-    }
-}
-
-fn cat_file(state: &mut State, file: &str, decorators: &Decorators) {
-    if file == "-" {
-        copy_or_die(state, &mut io::stdin(), decorators, true);
-    } else {
-        copy_or_die(state, &mut get_file(file), decorators, false);
-    }
-}
-
-fn show_help(opts: &getopts::Options) {
+fn show_help(opts: &ArgParser) {
     let brief =
         format!(
         "Usage: {}: [OPTION]... [FILENAME]...\n{}",
-        env::args().nth(0).unwrap(),
+        env::args().next().unwrap(),
         "Partial implementation of standard GNU cat. Concatenates FILE(s) to standard output.",
     );
     print!("{}", opts.usage(&brief));
@@ -146,48 +22,144 @@ fn show_help(opts: &getopts::Options) {
 
 fn main() {
     let args: Vec<String> = env::args().collect();
-    let mut opts = getopts::Options::new();
-    opts.optflag("h", "help", "show this message and exit");
-    opts.optflag("n", "number", "number all output lines");
-    opts.optflag("E", "show-ends", "display $ at end of each line");
-    opts.optflag(
+    let mut opts = ArgParser::new();
+    opts.flag("h", "help", "show this message and exit");
+    opts.flag("n", "number", "number all output lines");
+    opts.flag("A", "show-all", "equivalent to -vET");
+    opts.flag("e", "", "equivalent to -vE");
+    opts.flag("E", "show-ends", "display $ at end of each line");
+    opts.flag("t", "", "equivalent to -vT");
+    opts.flag("T", "show-tabs", "display TAB characters as ^I");
+    opts.flag(
+        "v",
+        "show-nonprinting",
+        "use ^ and M- notation, except for LFD and TAB",
+    );
+    opts.flag(
         "s",
         "squeeze-blank",
         "squeeze consecutive empty lines into one",
     );
-    opts.optflag("v", "version", "output version information and exit");
+    opts.flag("z", "null", "lines are delimited by NUL, not new line");
+    opts.flag("", "line-buffered", "flush output after every line");
+    opts.option(
+        "",
+        "buffer-size",
+        "read N bytes at a time (default: 64k); N may end in k or m",
+        "N",
+    );
+    opts.optional_option(
+        "",
+        "retry",
+        "retry reads failing with EAGAIN or EINTR up to N times (default: 3)",
+        "N",
+    );
+    opts.option(
+        "",
+        "parallel",
+        "read up to N files at once (default: 1, at most 8); not with -AeEnstTv or -",
+        "N",
+    );
+    opts.flag("", "version", "output version information and exit");
     let options = match opts.parse(&args[1..]) {
         Ok(m) => m,
-        Err(f) => die!("{}", f.to_string()),
+        Err(f) => die!(code = 2, "{}", f.to_string()),
     };
 
     if options.opt_present("h") {
         return show_help(&opts);
     }
-    if options.opt_present("v") {
-        return println!(
-            "Partial implementation of GNU cat, version {}",
-            env!("CARGO_PKG_VERSION")
-        );
+    if options.opt_present("version") {
+        return utils::version::print_version("cat");
     }
+    // -A, -e and -t only stand for combinations of the others.
+    let all = options.opt_present("A");
     let decorators = Decorators {
-        ends: options.opt_present("E"),
+        ends: all || options.opt_present("e") || options.opt_present("E"),
         number: options.opt_present("n"),
+        number_column: cat::number_column(),
         squeeze: options.opt_present("s"),
+        show_nonprinting: all
+            || options.opt_present("e")
+            || options.opt_present("t")
+            || options.opt_present("v"),
+        show_tabs: all || options.opt_present("t") || options.opt_present("T"),
+        null: options.opt_present("z"),
+        line_buffered: options.opt_present("line-buffered"),
+        buffer_size: match options.opt_str("buffer-size") {
+            Some(size) => cat::parse_buffer_size(&size).unwrap_or_else(|e| die!(code = 2, "{}", e)),
+            None => BUFSIZE,
+        },
+        retries: match options.opt_str("retry") {
+            Some(n) => n
+                .parse()
+                .unwrap_or_else(|_| die!(code = 2, "invalid number of retries: '{}'", n)),
+            None if options.opt_present("retry") => cat::RETRIES,
+            None => 0,
+        },
     };
 
-    let mut files: Vec<String> = vec![];
-    if options.free.is_empty() {
-        files.push("-".to_owned());
+    // Only output redirected to a file can be one of the inputs.
+    let stdout = io::stdout();
+    let output = if stdout.is_terminal() {
+        None
     } else {
-        files.append(&mut options.free.clone());
-    }
-
-    let mut state = State {
-        empty_streak: 1,
-        current_line: 1,
+        stdout.as_fd().try_clone_to_owned().ok().and_then(|fd| fs::File::from(fd).metadata().ok())
+    };
+    let jobs = match options.opt_str("parallel") {
+        Some(n) => n
+            .parse()
+            .ok()
+            .filter(|n| (1..=cat::PARALLEL_MAX).contains(n))
+            .unwrap_or_else(|| die!(code = 2, "invalid number of parallel reads: '{}'", n)),
+        None => 1,
     };
-    for file in files {
-        cat_file(&mut state, &file, &decorators);
+    let mut state = State::default();
+    let mut writer = io::BufWriter::with_capacity(2 * decorators.buffer_size, stdout.lock());
+    // Decorators carry state from one input to the next, and the standard input can only be
+    // read in turn, so both need the inputs one at a time. No operands means the standard input.
+    let reads_stdin = options.free.is_empty() || options.free.iter().any(|name| name == "-");
+    if jobs > 1 && !decorators.any() && !reads_stdin {
+        let names = options.free.iter().filter(|name| {
+            let skip = output.as_ref().is_some_and(|output| cat::is_output_file(name, output));
+            if skip {
+                note_error!("{}: input file is output file", name);
+            }
+            !skip
+        });
+        let inputs = cat::read_parallel(names.cloned().collect(), jobs, decorators.retries);
+        for (name, contents) in inputs {
+            let contents = contents.unwrap_or_else(|e| die!("{}: {}", name, e));
+            writer
+                .write_all(&contents)
+                .and_then(|_| writer.flush())
+                .unwrap_or_else(|e| cat::die_copying(&name, e));
+            if cat::was_replaced(&name, contents.len() as u64) {
+                warn!("{}: file was replaced or truncated", name);
+            }
+        }
+        utils::exit_if_error();
+        return;
+    }
+    for (name, reader) in utils::io::open_inputs(&options.free) {
+        let mut reader = match reader {
+            Ok(reader) => reader,
+            Err(e) => {
+                note_error!("{}: {}", name, e);
+                continue;
+            }
+        };
+        if !decorators.any() && cat::is_null_device(&name) {
+            continue;
+        }
+        if output.as_ref().is_some_and(|output| cat::is_output_file(&name, output)) {
+            note_error!("{}: input file is output file", name);
+            continue;
+        }
+        let read = cat::copy_or_die(&mut state, &name, &mut reader, &decorators, &mut writer);
+        if cat::was_replaced(&name, read) {
+            warn!("{}: file was replaced or truncated", name);
+        }
     }
+    utils::exit_if_error();
 }
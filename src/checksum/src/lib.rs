@@ -0,0 +1,234 @@
+//! Checksum algorithms used by `sum` and `cksum`.
+
+use std::io::{self, Read};
+
+/// A checksum computed incrementally over a stream of bytes.
+pub trait Checksum {
+    fn update(&mut self, data: &[u8]);
+    /// The checksum of all data passed to `update` so far.
+    fn value(&self) -> u32;
+    /// The number of bytes passed to `update` so far.
+    fn bytes(&self) -> u64;
+}
+
+/// Feeds everything that can be read from @p reader into each of @p checksums.
+pub fn update_all(reader: &mut dyn Read, checksums: &mut [&mut dyn Checksum]) -> io::Result<()> {
+    let mut buffer = [0u8; 65536];
+    loop {
+        match reader.read(&mut buffer) {
+            Ok(0) => return Ok(()),
+            Ok(len) => {
+                for checksum in checksums.iter_mut() {
+                    checksum.update(&buffer[..len]);
+                }
+            }
+            Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// The 16-bit BSD checksum (`sum -r`), a rotating sum of all bytes.
+#[derive(Debug, Default)]
+pub struct BsdSum {
+    sum: u16,
+    bytes: u64,
+}
+
+impl Checksum for BsdSum {
+    fn update(&mut self, data: &[u8]) {
+        for &byte in data {
+            self.sum = self.sum.rotate_right(1).wrapping_add(byte as u16);
+        }
+        self.bytes += data.len() as u64;
+    }
+
+    fn value(&self) -> u32 {
+        self.sum as u32
+    }
+
+    fn bytes(&self) -> u64 {
+        self.bytes
+    }
+}
+
+/// The System V checksum (`sum -s`), a plain sum of all bytes folded to 16 bits.
+#[derive(Debug, Default)]
+pub struct SysvSum {
+    sum: u32,
+    bytes: u64,
+}
+
+impl Checksum for SysvSum {
+    fn update(&mut self, data: &[u8]) {
+        for &byte in data {
+            self.sum = self.sum.wrapping_add(byte as u32);
+        }
+        self.bytes += data.len() as u64;
+    }
+
+    fn value(&self) -> u32 {
+        let r = (self.sum & 0xffff) + (self.sum >> 16);
+        (r & 0xffff) + (r >> 16)
+    }
+
+    fn bytes(&self) -> u64 {
+        self.bytes
+    }
+}
+
+const fn reflected_table(polynomial: u32) -> [u32; 256] {
+    let mut table = [0u32; 256];
+    let mut n = 0;
+    while n < 256 {
+        let mut crc = n as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ polynomial
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[n] = crc;
+        n += 1;
+    }
+    table
+}
+
+const fn msb_first_table(polynomial: u32) -> [u32; 256] {
+    let mut table = [0u32; 256];
+    let mut n = 0;
+    while n < 256 {
+        let mut crc = (n as u32) << 24;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 0x8000_0000 != 0 {
+                (crc << 1) ^ polynomial
+            } else {
+                crc << 1
+            };
+            bit += 1;
+        }
+        table[n] = crc;
+        n += 1;
+    }
+    table
+}
+
+const CRC32_TABLE: [u32; 256] = reflected_table(0xedb8_8320);
+const CKSUM_TABLE: [u32; 256] = msb_first_table(0x04c1_1db7);
+
+/// The ubiquitous reflected CRC-32 (as used by zlib, PNG and Ethernet).
+#[derive(Debug)]
+pub struct Crc32 {
+    crc: u32,
+    bytes: u64,
+}
+
+impl Default for Crc32 {
+    fn default() -> Crc32 {
+        Crc32 {
+            crc: 0xffff_ffff,
+            bytes: 0,
+        }
+    }
+}
+
+impl Checksum for Crc32 {
+    fn update(&mut self, data: &[u8]) {
+        for &byte in data {
+            self.crc = (self.crc >> 8) ^ CRC32_TABLE[((self.crc ^ byte as u32) & 0xff) as usize];
+        }
+        self.bytes += data.len() as u64;
+    }
+
+    fn value(&self) -> u32 {
+        !self.crc
+    }
+
+    fn bytes(&self) -> u64 {
+        self.bytes
+    }
+}
+
+/// The POSIX `cksum` CRC: the same polynomial as `Crc32` but processed most significant bit
+/// first, without an initial value, and with the input length appended to the data.
+#[derive(Debug, Default)]
+pub struct Cksum {
+    crc: u32,
+    bytes: u64,
+}
+
+fn cksum_step(crc: u32, byte: u8) -> u32 {
+    (crc << 8) ^ CKSUM_TABLE[(((crc >> 24) ^ byte as u32) & 0xff) as usize]
+}
+
+impl Checksum for Cksum {
+    fn update(&mut self, data: &[u8]) {
+        for &byte in data {
+            self.crc = cksum_step(self.crc, byte);
+        }
+        self.bytes += data.len() as u64;
+    }
+
+    fn value(&self) -> u32 {
+        let mut crc = self.crc;
+        let mut length = self.bytes;
+        while length > 0 {
+            crc = cksum_step(crc, (length & 0xff) as u8);
+            length >>= 8;
+        }
+        !crc
+    }
+
+    fn bytes(&self) -> u64 {
+        self.bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn checksum<C: Checksum + Default>(data: &[u8]) -> u32 {
+        let mut checksum = C::default();
+        checksum.update(data);
+        checksum.value()
+    }
+
+    #[test]
+    fn crc32_check_value() {
+        assert_eq!(checksum::<Crc32>(b"123456789"), 0xcbf4_3926);
+        assert_eq!(checksum::<Crc32>(b""), 0);
+    }
+
+    #[test]
+    fn cksum_check_value() {
+        assert_eq!(checksum::<Cksum>(b"123456789"), 930_766_865);
+        assert_eq!(checksum::<Cksum>(b""), 4_294_967_295);
+        assert_eq!(checksum::<Cksum>(b"abc"), 1_219_131_554);
+    }
+
+    #[test]
+    fn bsd_sum() {
+        assert_eq!(checksum::<BsdSum>(b"123456789"), 53615);
+        assert_eq!(checksum::<BsdSum>(b"abc"), 16556);
+    }
+
+    #[test]
+    fn sysv_sum() {
+        assert_eq!(checksum::<SysvSum>(b"123456789"), 477);
+        assert_eq!(checksum::<SysvSum>(b"abc"), 294);
+    }
+
+    #[test]
+    fn incremental_updates() {
+        let mut checksum = Cksum::default();
+        checksum.update(b"1234");
+        update_all(&mut &b"56789"[..], &mut [&mut checksum]).unwrap();
+        assert_eq!(checksum.value(), 930_766_865);
+        assert_eq!(checksum.bytes(), 9);
+    }
+}
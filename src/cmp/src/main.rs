@@ -0,0 +1,416 @@
+use std::env;
+use std::fs;
+use std::io::{self, BufRead, Read, Write};
+use std::process;
+
+#[macro_use(warn)]
+extern crate utils;
+
+use utils::args::ArgParser;
+use utils::io::FileOrStdin;
+
+/// Like `die!`, but with the exit status 2 that cmp reserves for trouble.
+macro_rules! trouble {
+    ($($arg:tt)*) => ({
+        warn!($($arg)*);
+        process::exit(2);
+    });
+}
+
+/// What is reported about the differences.
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Report {
+    First,
+    All,
+    Nothing,
+}
+
+struct Config {
+    report: Report,
+    print_bytes: bool,
+    limit: Option<u64>,
+}
+
+/// How the comparison ended.
+#[derive(Debug, PartialEq)]
+enum Outcome {
+    Identical,
+    Differ,
+    /// Input @p file ended after @p bytes bytes, in line @p line, while the other did not.
+    Eof {
+        file: usize,
+        bytes: u64,
+        line: u64,
+    },
+}
+
+/// Compares @p inputs byte by byte, writing the differences to @p writer as asked for by
+/// @p config. Byte numbers in the -l listing are padded to @p width.
+fn compare(
+    inputs: [&mut dyn BufRead; 2],
+    names: [&str; 2],
+    config: &Config,
+    width: usize,
+    writer: &mut dyn Write,
+) -> io::Result<Outcome> {
+    let [first, second] = inputs;
+    let mut bytes = 0;
+    let mut line = 1;
+    let mut differ = false;
+    let mut ended_line = false;
+    loop {
+        let wanted = config.limit.map_or(usize::MAX, |limit| {
+            (limit - bytes).min(usize::MAX as u64) as usize
+        });
+        if wanted == 0 {
+            break;
+        }
+        let (a, b) = (first.fill_buf()?, second.fill_buf()?);
+        let n = a.len().min(b.len()).min(wanted);
+        if n == 0 {
+            if a.is_empty() && b.is_empty() {
+                break;
+            }
+            // The line of the last byte read, which was already left behind if it was a new line.
+            let line = if ended_line { line - 1 } else { line };
+            let file = if a.is_empty() { 0 } else { 1 };
+            return Ok(Outcome::Eof { file, bytes, line });
+        }
+        for i in 0..n {
+            if a[i] != b[i] {
+                let byte = bytes + i as u64 + 1;
+                match config.report {
+                    Report::Nothing => return Ok(Outcome::Differ),
+                    Report::First => {
+                        write!(
+                            writer,
+                            "{} {} differ: byte {}, line {}",
+                            names[0], names[1], byte, line
+                        )?;
+                        if config.print_bytes {
+                            write!(
+                                writer,
+                                " is {:3o} {} {:3o} {}",
+                                a[i],
+                                printable(a[i]),
+                                b[i],
+                                printable(b[i])
+                            )?;
+                        }
+                        writeln!(writer)?;
+                        return Ok(Outcome::Differ);
+                    }
+                    Report::All => {
+                        if config.print_bytes {
+                            writeln!(
+                                writer,
+                                "{:>w$} {:3o} {:<4} {:3o} {}",
+                                byte,
+                                a[i],
+                                printable(a[i]),
+                                b[i],
+                                printable(b[i]),
+                                w = width
+                            )?;
+                        } else {
+                            writeln!(writer, "{:>w$} {:3o} {:3o}", byte, a[i], b[i], w = width)?;
+                        }
+                        differ = true;
+                    }
+                }
+            }
+            if a[i] == b'\n' {
+                line += 1;
+            }
+        }
+        ended_line = a[n - 1] == b'\n';
+        bytes += n as u64;
+        first.consume(n);
+        second.consume(n);
+    }
+    Ok(if differ {
+        Outcome::Differ
+    } else {
+        Outcome::Identical
+    })
+}
+
+/// How GNU cmp shows @p byte next to its octal value: control characters in ^ notation and
+/// bytes above 127 with an M- prefix.
+fn printable(byte: u8) -> String {
+    let mut result = String::new();
+    let mut byte = byte;
+    if byte >= 128 {
+        result.push_str("M-");
+        byte -= 128;
+    }
+    match byte {
+        0..=31 => {
+            result.push('^');
+            result.push((byte + 64) as char);
+        }
+        127 => result.push_str("^?"),
+        _ => result.push(byte as char),
+    }
+    result
+}
+
+/// Parses a byte count, which may not be negative.
+fn parse_bytes(count: &str) -> u64 {
+    count
+        .parse()
+        .unwrap_or_else(|_| trouble!("invalid byte count '{}'", count))
+}
+
+/// Parses the SKIP1[:SKIP2] argument of -i. A single number applies to both files.
+fn parse_skip(skip: &str) -> (u64, u64) {
+    match skip.find(':') {
+        Some(colon) => (parse_bytes(&skip[..colon]), parse_bytes(&skip[colon + 1..])),
+        None => {
+            let bytes = parse_bytes(skip);
+            (bytes, bytes)
+        }
+    }
+}
+
+/// The number of bytes of @p name left to compare after skipping @p skip of them, if that
+/// is known up front.
+fn bytes_left(name: &str, skip: u64) -> Option<u64> {
+    if name == "-" {
+        return None;
+    }
+    fs::metadata(name)
+        .ok()
+        .filter(|metadata| metadata.is_file())
+        .map(|metadata| metadata.len().saturating_sub(skip))
+}
+
+fn open(name: &str, skip: u64) -> FileOrStdin {
+    let mut reader = FileOrStdin::open(name).unwrap_or_else(|e| trouble!("{}: {}", name, e));
+    io::copy(&mut (&mut reader).take(skip), &mut io::sink())
+        .unwrap_or_else(|e| trouble!("{}: {}", name, e));
+    reader
+}
+
+fn show_help(opts: &ArgParser) {
+    let brief = format!(
+        concat!(
+            "Clone of the standard GNU cmp.\n",
+            "Usage: {}: [OPTION]... FILE1 [FILE2]\n",
+            "Compare two files byte by byte.\n",
+            "If FILE2 is omitted or is -, read standard input.\n",
+            "Exit status is 0 if inputs are the same, 1 if different, 2 if trouble."
+        ),
+        env::args().next().unwrap()
+    );
+    print!("{}", opts.usage(&brief));
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let mut opts = ArgParser::new();
+    opts.flag("b", "print-bytes", "print differing bytes");
+    opts.option(
+        "i",
+        "ignore-initial",
+        "skip first SKIP1 bytes of FILE1 and first SKIP2 bytes of FILE2",
+        "SKIP1[:SKIP2]",
+    );
+    opts.flag(
+        "l",
+        "verbose",
+        "output byte numbers and differing byte values",
+    );
+    opts.option("n", "bytes", "compare at most LIMIT bytes", "LIMIT");
+    opts.flag("s", "quiet", "suppress all normal output");
+    opts.flag("h", "help", "display this help and exit");
+    opts.flag("v", "version", "output version information and exit");
+    let options = match opts.parse(&args[1..]) {
+        Ok(m) => m,
+        Err(f) => trouble!("{}", f.to_string()),
+    };
+    if options.opt_present("h") {
+        return show_help(&opts);
+    }
+    if options.opt_present("v") {
+        return utils::version::print_version("cmp");
+    }
+    match options.free.len() {
+        0 => trouble!("missing operand"),
+        1 | 2 => {}
+        _ => trouble!("extra operand '{}'", options.free[2]),
+    }
+
+    // The last of -l and -s wins.
+    let report = match (
+        options.opt_positions("l").last(),
+        options.opt_positions("s").last(),
+    ) {
+        (Some(verbose), Some(quiet)) if verbose > quiet => Report::All,
+        (_, Some(_)) => Report::Nothing,
+        (Some(_), None) => Report::All,
+        (None, None) => Report::First,
+    };
+    let config = Config {
+        report,
+        print_bytes: options.opt_present("b"),
+        limit: options.opt_str("n").map(|limit| parse_bytes(&limit)),
+    };
+    let skip = options
+        .opt_str("i")
+        .map_or((0, 0), |skip| parse_skip(&skip));
+    let names = [
+        options.free[0].as_str(),
+        options.free.get(1).map_or("-", |name| name.as_str()),
+    ];
+    if names[0] == "-" && names[1] == "-" && skip.0 == skip.1 {
+        // Both are the same input, read at the same position.
+        return;
+    }
+
+    let width = [bytes_left(names[0], skip.0), bytes_left(names[1], skip.1)]
+        .iter()
+        .filter_map(|&left| left)
+        .chain(config.limit)
+        .min()
+        .unwrap_or(u64::MAX)
+        .to_string()
+        .len();
+    let mut first = open(names[0], skip.0);
+    let mut second = open(names[1], skip.1);
+    let stdout = io::stdout();
+    let mut writer = io::BufWriter::new(stdout.lock());
+    let outcome = compare(
+        [&mut first, &mut second],
+        names,
+        &config,
+        width,
+        &mut writer,
+    )
+    .and_then(|outcome| writer.flush().map(|_| outcome))
+    .unwrap_or_else(|e| trouble!("{}", e));
+    match outcome {
+        Outcome::Identical => {}
+        Outcome::Differ => process::exit(1),
+        Outcome::Eof { file, bytes, line } => {
+            if config.report != Report::Nothing {
+                if bytes == 0 {
+                    warn!("EOF on {} which is empty", names[file]);
+                } else if config.report == Report::All {
+                    warn!("EOF on {} after byte {}", names[file], bytes);
+                } else {
+                    warn!("EOF on {} after byte {}, line {}", names[file], bytes, line);
+                }
+            }
+            process::exit(1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(first: &[u8], second: &[u8], config: &Config) -> (Outcome, String) {
+        let mut output = Vec::new();
+        let outcome = compare(
+            [&mut &first[..], &mut &second[..]],
+            ["a", "b"],
+            config,
+            2,
+            &mut output,
+        )
+        .unwrap();
+        (outcome, String::from_utf8(output).unwrap())
+    }
+
+    fn config(report: Report) -> Config {
+        Config {
+            report,
+            print_bytes: false,
+            limit: None,
+        }
+    }
+
+    #[test]
+    fn first_difference() {
+        let config = config(Report::First);
+        assert_eq!(
+            run(b"same", b"same", &config),
+            (Outcome::Identical, String::new())
+        );
+        assert_eq!(
+            run(b"one\ntwo\n", b"one\nt0o\n", &config),
+            (Outcome::Differ, "a b differ: byte 6, line 2\n".to_owned())
+        );
+    }
+
+    #[test]
+    fn end_of_file() {
+        let config = config(Report::First);
+        assert_eq!(
+            run(b"abc", b"abcdef", &config).0,
+            Outcome::Eof {
+                file: 0,
+                bytes: 3,
+                line: 1
+            }
+        );
+        assert_eq!(
+            run(b"a\nb\n", b"a\n", &config).0,
+            Outcome::Eof {
+                file: 1,
+                bytes: 2,
+                line: 1
+            }
+        );
+        assert_eq!(
+            run(b"", b"x", &config).0,
+            Outcome::Eof {
+                file: 0,
+                bytes: 0,
+                line: 1
+            }
+        );
+    }
+
+    #[test]
+    fn verbose_lists_every_difference() {
+        let mut config = config(Report::All);
+        assert_eq!(
+            run(b"hello\nworld\n", b"hellO\nwOrld\n", &config),
+            (Outcome::Differ, " 5 157 117\n 8 157 117\n".to_owned())
+        );
+        config.print_bytes = true;
+        assert_eq!(
+            run(b"a\x01", b"b\xff", &config),
+            (
+                Outcome::Differ,
+                " 1 141 a    142 b\n 2   1 ^A   377 M-^?\n".to_owned()
+            )
+        );
+    }
+
+    #[test]
+    fn limit() {
+        let mut config = config(Report::First);
+        config.limit = Some(3);
+        assert_eq!(run(b"abcX", b"abcY", &config).0, Outcome::Identical);
+        assert_eq!(
+            run(b"ab", b"abc", &config).0,
+            Outcome::Eof {
+                file: 0,
+                bytes: 2,
+                line: 1
+            }
+        );
+        config.limit = Some(0);
+        assert_eq!(run(b"a", b"b", &config).0, Outcome::Identical);
+    }
+
+    #[test]
+    fn skip() {
+        assert_eq!(parse_skip("3"), (3, 3));
+        assert_eq!(parse_skip("1:20"), (1, 20));
+    }
+}
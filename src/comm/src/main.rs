@@ -0,0 +1,345 @@
+use std::env;
+use std::io::{self, BufRead, Write};
+
+#[macro_use(die)]
+extern crate utils;
+
+use utils::args::ArgParser;
+use utils::io::FileOrStdin;
+
+#[derive(Debug)]
+pub struct CommConfig {
+    /// Whether columns 1, 2 and 3 are printed.
+    show: [bool; 3],
+    check_order: bool,
+    delimiter: String,
+    /// Whether a line with the number of lines in each column ends the output.
+    total: bool,
+    /// What ends input and output lines: new line, or NUL with -z.
+    terminator: u8,
+}
+
+/// A line reader which remembers the previous line so it can report unsorted input.
+struct SortedInput<'a> {
+    reader: Box<dyn BufRead + 'a>,
+    terminator: u8,
+    previous: Option<Vec<u8>>,
+    unsorted: bool,
+}
+
+impl<'a> SortedInput<'a> {
+    fn new(reader: Box<dyn BufRead + 'a>, terminator: u8) -> SortedInput<'a> {
+        SortedInput {
+            reader,
+            terminator,
+            previous: None,
+            unsorted: false,
+        }
+    }
+
+    fn next(&mut self) -> io::Result<Option<Vec<u8>>> {
+        let mut line = Vec::new();
+        if self.reader.read_until(self.terminator, &mut line)? == 0 {
+            return Ok(None);
+        }
+        if line.last() == Some(&self.terminator) {
+            line.pop();
+        }
+        if let Some(ref previous) = self.previous {
+            if *previous > line {
+                self.unsorted = true;
+            }
+        }
+        self.previous = Some(line.clone());
+        Ok(Some(line))
+    }
+}
+
+/// Writes @p line into @p column (0-based), prefixed by one delimiter per preceding column
+/// that is being shown.
+fn write_column(
+    writer: &mut dyn Write,
+    config: &CommConfig,
+    column: usize,
+    line: &[u8],
+) -> io::Result<()> {
+    if !config.show[column] {
+        return Ok(());
+    }
+    for shown in &config.show[..column] {
+        if *shown {
+            writer.write_all(config.delimiter.as_bytes())?;
+        }
+    }
+    writer.write_all(line)?;
+    writer.write_all(&[config.terminator])
+}
+
+/// Walks two sorted inputs in lockstep and writes the three-column comparison. Returns whether
+/// any input was found to be unsorted.
+fn comm(
+    first: Box<dyn BufRead + '_>,
+    second: Box<dyn BufRead + '_>,
+    config: &CommConfig,
+    writer: &mut dyn Write,
+) -> io::Result<bool> {
+    let mut inputs = [
+        SortedInput::new(first, config.terminator),
+        SortedInput::new(second, config.terminator),
+    ];
+    let mut lines = [inputs[0].next()?, inputs[1].next()?];
+    let mut warned = false;
+    let mut totals = [0u64; 3];
+    loop {
+        let column = match (&lines[0], &lines[1]) {
+            (None, None) => break,
+            (Some(_), None) => 0,
+            (None, Some(_)) => 1,
+            (Some(a), Some(b)) if a < b => 0,
+            (Some(a), Some(b)) if a > b => 1,
+            _ => 2,
+        };
+        let line = lines[column % 2].take().unwrap();
+        write_column(writer, config, column, &line)?;
+        totals[column] += 1;
+        if column == 2 {
+            lines[0] = inputs[0].next()?;
+            lines[1] = inputs[1].next()?;
+        } else {
+            lines[column] = inputs[column].next()?;
+        }
+
+        if config.check_order && !warned {
+            for (n, input) in inputs.iter().enumerate() {
+                if input.unsorted && !warned {
+                    writer.flush()?;
+                    eprintln!(
+                        "{}: file {} is not in sorted order",
+                        env::args().next().unwrap(),
+                        n + 1
+                    );
+                    warned = true;
+                }
+            }
+        }
+    }
+    if config.total {
+        // Every column is counted, whether or not it is shown.
+        for total in &totals {
+            write!(writer, "{}{}", total, config.delimiter)?;
+        }
+        writer.write_all(b"total")?;
+        writer.write_all(&[config.terminator])?;
+    }
+    Ok(warned)
+}
+
+fn open(name: &str) -> Box<dyn BufRead> {
+    match FileOrStdin::open(name) {
+        Ok(reader) => Box::new(reader),
+        Err(e) => die!("{}: {}", name, e),
+    }
+}
+
+fn show_help(opts: &ArgParser) {
+    let brief = format!(
+        concat!(
+            "Clone of the standard GNU comm.\n",
+            "Usage: {}: [OPTION]... FILE1 FILE2\n",
+            "Compare sorted files FILE1 and FILE2 line by line."
+        ),
+        env::args().next().unwrap()
+    );
+    print!("{}", opts.usage(&brief));
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let mut opts = ArgParser::new();
+    opts.flag("1", "", "suppress column 1 (lines unique to FILE1)");
+    opts.flag("2", "", "suppress column 2 (lines unique to FILE2)");
+    opts.flag(
+        "3",
+        "",
+        "suppress column 3 (lines that appear in both files)",
+    );
+    opts.flag(
+        "",
+        "check-order",
+        "check that the input is correctly sorted (the default)",
+    );
+    opts.flag(
+        "",
+        "nocheck-order",
+        "do not check that the input is correctly sorted",
+    );
+    opts.option("", "output-delimiter", "separate columns with STR", "STR");
+    opts.flag("", "total", "output a summary");
+    opts.flag("z", "zero-terminated", "line delimiter is NUL, not newline");
+    opts.flag("h", "help", "display this help and exit");
+    opts.flag("v", "version", "output version information and exit");
+    let options = match opts.parse(&args[1..]) {
+        Ok(m) => m,
+        Err(f) => die!(code = 2, "{}", f.to_string()),
+    };
+    if options.opt_present("h") {
+        return show_help(&opts);
+    }
+    if options.opt_present("v") {
+        return utils::version::print_version("comm");
+    }
+    if options.free.len() < 2 {
+        die!(code = 2, "missing operand");
+    } else if options.free.len() > 2 {
+        die!(code = 2, "extra operand '{}'", options.free[2]);
+    }
+    // Both would read the one standard input, each taking lines from the other.
+    if options.free[0] == "-" && options.free[1] == "-" {
+        die!(code = 2, "both files cannot be standard input");
+    }
+
+    let config = CommConfig {
+        show: [
+            !options.opt_present("1"),
+            !options.opt_present("2"),
+            !options.opt_present("3"),
+        ],
+        check_order: !options.opt_present("nocheck-order"),
+        delimiter: options
+            .opt_str("output-delimiter")
+            .unwrap_or_else(|| "\t".to_owned()),
+        total: options.opt_present("total"),
+        terminator: if options.opt_present("z") {
+            b'\0'
+        } else {
+            b'\n'
+        },
+    };
+
+    let stdout = io::stdout();
+    let mut writer = io::BufWriter::new(stdout.lock());
+    let unsorted = comm(
+        open(&options.free[0]),
+        open(&options.free[1]),
+        &config,
+        &mut writer,
+    )
+    .and_then(|unsorted| writer.flush().map(|_| unsorted))
+    .unwrap_or_else(|e| die!("{}", e));
+    if unsorted {
+        std::process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(show: [bool; 3], delimiter: &str) -> CommConfig {
+        CommConfig {
+            show,
+            check_order: true,
+            delimiter: delimiter.to_owned(),
+            total: false,
+            terminator: b'\n',
+        }
+    }
+
+    fn run(first: &'static str, second: &'static str, config: &CommConfig) -> (String, bool) {
+        let mut output = Vec::new();
+        let unsorted = comm(
+            Box::new(first.as_bytes()),
+            Box::new(second.as_bytes()),
+            config,
+            &mut output,
+        )
+        .unwrap();
+        (String::from_utf8(output).unwrap(), unsorted)
+    }
+
+    const FIRST: &str = "a\nb\nd\n";
+    const SECOND: &str = "b\nc\nd\ne\n";
+
+    #[test]
+    fn all_flag_combinations() {
+        let expected = [
+            ([true, true, true], "a\n\t\tb\n\tc\n\t\td\n\te\n"),
+            ([false, true, true], "\tb\nc\n\td\ne\n"),
+            ([true, false, true], "a\n\tb\n\td\n"),
+            ([true, true, false], "a\n\tc\n\te\n"),
+            ([false, false, true], "b\nd\n"),
+            ([false, true, false], "c\ne\n"),
+            ([true, false, false], "a\n"),
+            ([false, false, false], ""),
+        ];
+        for &(show, output) in &expected {
+            assert_eq!(
+                run(FIRST, SECOND, &config(show, "\t")),
+                (output.to_owned(), false)
+            );
+        }
+    }
+
+    #[test]
+    fn custom_delimiter() {
+        let (output, _) = run(FIRST, SECOND, &config([true, true, true], "|"));
+        assert_eq!(output, "a\n||b\n|c\n||d\n|e\n");
+    }
+
+    #[test]
+    fn delimiter_is_not_repeated_tabs() {
+        let (output, _) = run(FIRST, SECOND, &config([true, true, true], ""));
+        assert_eq!(output, "a\nb\nc\nd\ne\n");
+        let (output, _) = run(FIRST, SECOND, &config([true, true, true], " <> "));
+        assert_eq!(output, "a\n <>  <> b\n <> c\n <>  <> d\n <> e\n");
+        let (output, _) = run(FIRST, SECOND, &config([false, true, true], "::"));
+        assert_eq!(output, "::b\nc\n::d\ne\n");
+    }
+
+    #[test]
+    fn missing_trailing_newline() {
+        let (output, _) = run("a\nb", "b", &config([true, true, true], "\t"));
+        assert_eq!(output, "a\n\t\tb\n");
+    }
+
+    #[test]
+    fn totals() {
+        let mut config = config([true, true, true], "\t");
+        config.total = true;
+        let (output, _) = run(FIRST, SECOND, &config);
+        assert_eq!(output, "a\n\t\tb\n\tc\n\t\td\n\te\n1\t2\t2\ttotal\n");
+        // Suppressed columns are still counted, and the totals use the output delimiter.
+        config.show = [false, true, false];
+        config.delimiter = "::".to_owned();
+        let (output, _) = run("a\nb\nx\ny\n", "x\n", &config);
+        assert_eq!(output, "3::0::1::total\n");
+        let (output, _) = run("", "", &config);
+        assert_eq!(output, "0::0::0::total\n");
+    }
+
+    #[test]
+    fn zero_terminated() {
+        // As printed by printf '%s\0' a b d and printf '%s\0' b c d e.
+        let mut config = config([true, true, true], "\t");
+        config.terminator = b'\0';
+        let (output, unsorted) = run("a\0b\0d\0", "b\0c\0d\0e\0", &config);
+        assert_eq!(output, "a\0\t\tb\0\tc\0\t\td\0\te\0");
+        assert!(!unsorted);
+        // New lines are ordinary characters within the records.
+        let (output, _) = run("x\ny\0", "x\0", &config);
+        assert_eq!(output, "\tx\0x\ny\0");
+        config.total = true;
+        let (output, _) = run("a\0", "a", &config);
+        assert_eq!(output, "\t\ta\x000\t0\t1\ttotal\0");
+    }
+
+    #[test]
+    fn unsorted_input_is_detected() {
+        assert!(run("b\na\n", "a\n", &config([true, true, true], "\t")).1);
+        assert!(run("a\n", "c\nb\n", &config([true, true, true], "\t")).1);
+
+        let mut unchecked = config([true, true, true], "\t");
+        unchecked.check_order = false;
+        assert!(!run("b\na\n", "a\n", &unchecked).1);
+    }
+}
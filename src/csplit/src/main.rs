@@ -0,0 +1,514 @@
+use std::cmp;
+use std::collections::VecDeque;
+use std::env;
+use std::ffi::CString;
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::os::raw::{c_char, c_uint};
+
+#[macro_use(die, warn)]
+extern crate utils;
+extern crate libc;
+
+use utils::args::ArgParser;
+use utils::io::FileOrStdin;
+use utils::regex::{Regex, Syntax};
+
+enum Kind {
+    /// Split before this line.
+    Line(usize),
+    /// Split at the next line that matches, moved by the offset. With @p skip, the %REGEX%
+    /// form, the lines up to the split are dropped rather than written.
+    Regex {
+        regex: Regex,
+        offset: i64,
+        skip: bool,
+    },
+}
+
+/// How many more times a pattern is applied after the first, from its {N} or {*} suffix.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Repeat {
+    Times(usize),
+    Forever,
+}
+
+struct Pattern {
+    /// The operand the pattern was parsed from, for error messages.
+    text: String,
+    kind: Kind,
+    repeat: Repeat,
+}
+
+/// Parses the PATTERN operands, attaching each {N} to the pattern before it.
+fn parse_patterns(operands: &[String]) -> Result<Vec<Pattern>, String> {
+    let mut patterns: Vec<Pattern> = Vec::new();
+    let mut previous_line = 0;
+    for operand in operands {
+        if operand.starts_with('{') && operand.ends_with('}') {
+            let count = &operand[1..operand.len() - 1];
+            let repeat = match count.parse() {
+                _ if count == "*" => Repeat::Forever,
+                Ok(count) => Repeat::Times(count),
+                Err(_) => return Err(format!("'{}': invalid repetition count", operand)),
+            };
+            match patterns.last_mut() {
+                Some(pattern) => pattern.repeat = repeat,
+                None => return Err(format!("'{}': invalid pattern", operand)),
+            }
+            continue;
+        }
+        let kind = match operand.chars().next() {
+            Some(delimiter @ '/') | Some(delimiter @ '%') => {
+                let end = operand.rfind(delimiter).unwrap();
+                if end == 0 {
+                    return Err(format!(
+                        "{}: closing delimiter '{}' missing",
+                        operand, delimiter
+                    ));
+                }
+                let offset = match &operand[end + 1..] {
+                    "" => 0,
+                    offset => match offset.parse() {
+                        Ok(offset) => offset,
+                        Err(_) => return Err(format!("'{}': invalid pattern", operand)),
+                    },
+                };
+                Kind::Regex {
+                    regex: Regex::new(&[operand[1..end].to_owned()], Syntax::Basic, false)?,
+                    offset,
+                    skip: delimiter == '%',
+                }
+            }
+            _ => match operand.parse() {
+                Ok(0) => {
+                    return Err(format!(
+                        "{}: line number must be greater than zero",
+                        operand
+                    ))
+                }
+                Ok(line) => Kind::Line(line),
+                Err(_) => return Err(format!("'{}': invalid pattern", operand)),
+            },
+        };
+        if let Kind::Line(line) = kind {
+            if line < previous_line {
+                return Err(format!(
+                    "line number '{}' is smaller than preceding line number, {}",
+                    line, previous_line
+                ));
+            } else if line == previous_line {
+                warn!(
+                    "warning: line number '{}' is the same as preceding line number",
+                    line
+                );
+            }
+            previous_line = line;
+        }
+        patterns.push(Pattern {
+            text: operand.clone(),
+            kind,
+            repeat: Repeat::Times(0),
+        });
+    }
+    Ok(patterns)
+}
+
+/// The printf-style format of the numbers that follow the prefix in the names of the pieces.
+struct SuffixFormat(CString);
+
+impl SuffixFormat {
+    /// Checks that @p format contains exactly one integer conversion, like %02d.
+    fn new(format: &str) -> Result<SuffixFormat, String> {
+        let bytes = format.as_bytes();
+        let mut conversions = 0;
+        let mut i = 0;
+        while i < bytes.len() {
+            if bytes[i] != b'%' {
+                i += 1;
+                continue;
+            }
+            i += 1;
+            if bytes.get(i) == Some(&b'%') {
+                i += 1;
+                continue;
+            }
+            while i < bytes.len() && b"-+ #0'".contains(&bytes[i]) {
+                i += 1;
+            }
+            while i < bytes.len() && (bytes[i].is_ascii_digit() || bytes[i] == b'.') {
+                i += 1;
+            }
+            match bytes.get(i) {
+                Some(c) if b"diuoxX".contains(c) => conversions += 1,
+                Some(&c) => {
+                    return Err(format!(
+                        "invalid conversion specifier in suffix: {}",
+                        c as char
+                    ))
+                }
+                None => return Err("missing conversion specifier in suffix".to_owned()),
+            }
+            i += 1;
+        }
+        match conversions {
+            0 => Err("missing % conversion specification in suffix".to_owned()),
+            1 => CString::new(format)
+                .map(SuffixFormat)
+                .map_err(|_| "invalid suffix format".to_owned()),
+            _ => Err("too many % conversion specifications in suffix".to_owned()),
+        }
+    }
+
+    fn format(&self, number: usize) -> String {
+        let mut buffer = vec![0u8; 32];
+        loop {
+            let len = unsafe {
+                libc::snprintf(
+                    buffer.as_mut_ptr() as *mut c_char,
+                    buffer.len(),
+                    self.0.as_ptr(),
+                    number as c_uint,
+                )
+            };
+            let len = if len < 0 { 0 } else { len as usize };
+            if len < buffer.len() {
+                buffer.truncate(len);
+                return String::from_utf8_lossy(&buffer).into_owned();
+            }
+            buffer.resize(len + 1, 0);
+        }
+    }
+}
+
+/// Reads the input a line at a time, keeping the lines that have not been written out yet so
+/// that a split can be moved back from the line that matched.
+struct LineReader<'a> {
+    reader: Box<dyn BufRead + 'a>,
+    lines: VecDeque<Vec<u8>>,
+    /// The 1-based number of the first buffered line.
+    first: usize,
+    eof: bool,
+}
+
+impl<'a> LineReader<'a> {
+    fn new(reader: Box<dyn BufRead + 'a>) -> LineReader<'a> {
+        LineReader {
+            reader,
+            lines: VecDeque::new(),
+            first: 1,
+            eof: false,
+        }
+    }
+
+    /// Line @p n, which must not have been taken yet, or None at the end of the input.
+    fn get(&mut self, n: usize) -> io::Result<Option<&[u8]>> {
+        while self.first + self.lines.len() <= n && !self.eof {
+            let mut line = Vec::new();
+            if self.reader.read_until(b'\n', &mut line)? == 0 {
+                self.eof = true;
+            } else {
+                self.lines.push_back(line);
+            }
+        }
+        Ok(self.lines.get(n - self.first).map(|line| &line[..]))
+    }
+
+    /// Removes the lines before line @p n, which must have been read, from the buffer.
+    fn take_before(&mut self, n: usize) -> Vec<Vec<u8>> {
+        let count = n - self.first;
+        self.first = n;
+        self.lines.drain(..count).collect()
+    }
+
+    fn take_rest(&mut self) -> io::Result<Vec<Vec<u8>>> {
+        let mut rest: Vec<Vec<u8>> = self.lines.drain(..).collect();
+        self.first += rest.len();
+        let mut line = Vec::new();
+        while self.reader.read_until(b'\n', &mut line)? != 0 {
+            rest.push(line.split_off(0));
+            self.first += 1;
+        }
+        self.eof = true;
+        Ok(rest)
+    }
+}
+
+/// Writes the pieces to files named by the prefix and the suffix format.
+struct Output {
+    prefix: String,
+    suffix: SuffixFormat,
+    quiet: bool,
+    elide_empty: bool,
+    /// The files created so far, which are removed if an error stops the split.
+    files: Vec<String>,
+}
+
+impl Output {
+    fn write_piece(&mut self, lines: &[Vec<u8>]) -> Result<(), String> {
+        if lines.is_empty() && self.elide_empty {
+            return Ok(());
+        }
+        let name = format!("{}{}", self.prefix, self.suffix.format(self.files.len()));
+        self.files.push(name.clone());
+        let mut size = 0;
+        fs::File::create(&name)
+            .and_then(|file| {
+                let mut writer = io::BufWriter::new(file);
+                for line in lines {
+                    writer.write_all(line)?;
+                    size += line.len();
+                }
+                writer.flush()
+            })
+            .map_err(|e| format!("{}: {}", name, e))?;
+        if !self.quiet {
+            println!("{}", size);
+        }
+        Ok(())
+    }
+
+    fn remove_files(&self) {
+        for file in &self.files {
+            let _ = fs::remove_file(file);
+        }
+    }
+}
+
+/// Splits @p reader into pieces at the lines @p patterns select and writes them with @p output.
+fn csplit(
+    reader: Box<dyn BufRead + '_>,
+    patterns: &[Pattern],
+    output: &mut Output,
+) -> Result<(), String> {
+    let mut input = LineReader::new(reader);
+    let io_error = |e: io::Error| e.to_string();
+    // The search for the next regular expression starts after this line.
+    let mut searched = 0;
+    for pattern in patterns {
+        let mut repetition = 0;
+        loop {
+            match pattern.repeat {
+                Repeat::Times(times) if repetition > times => break,
+                _ => {}
+            }
+            let error = |problem: &str| {
+                if repetition > 0 {
+                    format!(
+                        "'{}': {} on repetition {}",
+                        pattern.text, problem, repetition
+                    )
+                } else {
+                    format!("'{}': {}", pattern.text, problem)
+                }
+            };
+            match pattern.kind {
+                Kind::Line(line) => {
+                    let split = line * (repetition + 1);
+                    if split < input.first {
+                        return Err(error("line number out of range"));
+                    }
+                    if input.get(split).map_err(io_error)?.is_none() {
+                        output.write_piece(&input.take_rest().map_err(io_error)?)?;
+                        return Err(error("line number out of range"));
+                    }
+                    output.write_piece(&input.take_before(split))?;
+                    searched = split - 1;
+                }
+                Kind::Regex {
+                    ref regex,
+                    offset,
+                    skip,
+                } => {
+                    let mut matched = searched + 1;
+                    loop {
+                        match input.get(matched).map_err(io_error)? {
+                            Some(line)
+                                if regex.is_match(line.strip_suffix(b"\n").unwrap_or(line)) =>
+                            {
+                                break
+                            }
+                            Some(_) => matched += 1,
+                            None if pattern.repeat == Repeat::Forever => break,
+                            None => {
+                                if !skip {
+                                    output.write_piece(&input.take_rest().map_err(io_error)?)?;
+                                }
+                                return Err(error("match not found"));
+                            }
+                        }
+                    }
+                    if input.get(matched).map_err(io_error)?.is_none() {
+                        break;
+                    }
+                    let split = matched as i64 + offset;
+                    if split < input.first as i64 {
+                        return Err(error("line number out of range"));
+                    }
+                    let split = split as usize;
+                    if split > matched && input.get(split - 1).map_err(io_error)?.is_none() {
+                        if !skip {
+                            output.write_piece(&input.take_rest().map_err(io_error)?)?;
+                        }
+                        return Err(error("line number out of range"));
+                    }
+                    let piece = input.take_before(split);
+                    if !skip {
+                        output.write_piece(&piece)?;
+                    }
+                    searched = cmp::max(matched, split);
+                }
+            }
+            repetition += 1;
+        }
+    }
+    output.write_piece(&input.take_rest().map_err(io_error)?)
+}
+
+fn show_help(opts: &ArgParser) {
+    let brief = format!(
+        concat!(
+            "Clone of the standard GNU csplit.\n",
+            "Usage: {}: [OPTION]... FILE PATTERN...\n",
+            "Output pieces of FILE separated by PATTERN(s) to files 'xx00', 'xx01', ...,\n",
+            "and output byte counts of each piece to standard output.\n",
+            "Each PATTERN may be:\n",
+            "  INTEGER            copy up to but not including specified line number\n",
+            "  /REGEXP/[OFFSET]   copy up to but not including a matching line\n",
+            "  %REGEXP%[OFFSET]   skip to, but not including a matching line\n",
+            "  {{INTEGER}}          repeat the previous pattern specified number of times\n",
+            "  {{*}}                repeat the previous pattern as many times as possible"
+        ),
+        env::args().next().unwrap()
+    );
+    print!("{}", opts.usage(&brief));
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let mut opts = ArgParser::new();
+    opts.option(
+        "b",
+        "suffix-format",
+        "use sprintf FORMAT instead of %02d",
+        "FORMAT",
+    );
+    opts.option("f", "prefix", "use PREFIX instead of 'xx'", "PREFIX");
+    opts.flag("k", "keep-files", "do not remove output files on errors");
+    opts.option(
+        "n",
+        "digits",
+        "use specified number of digits instead of 2",
+        "DIGITS",
+    );
+    opts.flag("s", "quiet", "do not print counts of output file sizes");
+    opts.flag("z", "elide-empty-files", "remove empty output files");
+    opts.flag("h", "help", "display this help and exit");
+    opts.flag("v", "version", "output version information and exit");
+    let options = match opts.parse(&args[1..]) {
+        Ok(m) => m,
+        Err(f) => die!(code = 2, "{}", f.to_string()),
+    };
+    if options.opt_present("h") {
+        return show_help(&opts);
+    }
+    if options.opt_present("v") {
+        return utils::version::print_version("csplit");
+    }
+    match options.free.len() {
+        0 => die!(code = 2, "missing operand"),
+        1 => die!(code = 2, "missing operand after '{}'", options.free[0]),
+        _ => {}
+    }
+
+    let suffix = match (options.opt_str("b"), options.opt_str("n")) {
+        (Some(format), _) => format,
+        (None, Some(digits)) => match digits.parse::<usize>() {
+            Ok(digits) => format!("%0{}d", digits),
+            Err(_) => die!(code = 2, "invalid number: '{}'", digits),
+        },
+        (None, None) => "%02d".to_owned(),
+    };
+    let mut output = Output {
+        prefix: options.opt_str("f").unwrap_or_else(|| "xx".to_owned()),
+        suffix: SuffixFormat::new(&suffix).unwrap_or_else(|e| die!(code = 2, "{}", e)),
+        quiet: options.opt_present("s"),
+        elide_empty: options.opt_present("z"),
+        files: Vec::new(),
+    };
+    let patterns = parse_patterns(&options.free[1..]).unwrap_or_else(|e| die!(code = 2, "{}", e));
+    let input = match FileOrStdin::open(&options.free[0]) {
+        Ok(input) => input,
+        Err(e) => die!("{}: {}", options.free[0], e),
+    };
+    if let Err(e) = csplit(Box::new(input), &patterns, &mut output) {
+        warn!("{}", e);
+        if !options.opt_present("k") {
+            output.remove_files();
+        }
+        std::process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn patterns(operands: &[&str]) -> Result<Vec<Pattern>, String> {
+        let operands: Vec<String> = operands.iter().map(|s| s.to_string()).collect();
+        parse_patterns(&operands)
+    }
+
+    #[test]
+    fn pattern_operands() {
+        let parsed = patterns(&["3", "{2}", "/a/-1", "%b%+2", "{*}"]).unwrap();
+        assert_eq!(parsed.len(), 3);
+        match parsed[0].kind {
+            Kind::Line(3) => assert_eq!(parsed[0].repeat, Repeat::Times(2)),
+            _ => panic!("not a line number"),
+        }
+        match parsed[1].kind {
+            Kind::Regex { offset, skip, .. } => assert_eq!((offset, skip), (-1, false)),
+            _ => panic!("not a regular expression"),
+        }
+        match parsed[2].kind {
+            Kind::Regex { offset, skip, .. } => assert_eq!((offset, skip), (2, true)),
+            _ => panic!("not a regular expression"),
+        }
+        assert_eq!(parsed[2].repeat, Repeat::Forever);
+        // The closing delimiter is the last one, so the expression may contain it.
+        assert_eq!(patterns(&["/a/b/"]).unwrap()[0].text, "/a/b/");
+
+        for invalid in &["0", "x", "/a", "/a/x", "{1}", "5 3"] {
+            let operands: Vec<&str> = invalid.split(' ').collect();
+            assert!(patterns(&operands).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn suffix_formats() {
+        assert_eq!(SuffixFormat::new("%02d").unwrap().format(7), "07");
+        assert_eq!(
+            SuffixFormat::new("-%03x.txt").unwrap().format(255),
+            "-0ff.txt"
+        );
+        assert_eq!(SuffixFormat::new("%%%d").unwrap().format(1), "%1");
+        for invalid in &["", "%", "%s", "%d%d", "%%"] {
+            assert!(SuffixFormat::new(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn line_reader() {
+        let mut input = LineReader::new(Box::new(&b"a\nb\nc\nd"[..]));
+        assert_eq!(input.get(3).unwrap(), Some(&b"c\n"[..]));
+        assert_eq!(input.take_before(2), [b"a\n".to_vec()]);
+        assert_eq!(input.get(2).unwrap(), Some(&b"b\n"[..]));
+        assert_eq!(input.get(5).unwrap(), None);
+        assert_eq!(
+            input.take_rest().unwrap(),
+            [b"b\n".to_vec(), b"c\n".to_vec(), b"d".to_vec()]
+        );
+        assert_eq!(input.first, 5);
+    }
+}
@@ -0,0 +1,526 @@
+use std::env;
+use std::ffi::CStr;
+use std::mem;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[macro_use(die)]
+extern crate utils;
+extern crate libc;
+
+use utils::args::ArgParser;
+
+const WEEKDAYS: [&str; 7] = [
+    "Sunday",
+    "Monday",
+    "Tuesday",
+    "Wednesday",
+    "Thursday",
+    "Friday",
+    "Saturday",
+];
+const MONTHS: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+/// The format of GNU date in the C locale.
+const DEFAULT_FORMAT: &str = "%a %b %e %H:%M:%S %Z %Y";
+
+/// A point in time: seconds and nanoseconds since the Unix epoch.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Time {
+    secs: i64,
+    nanos: u32,
+}
+
+impl Time {
+    fn now() -> Time {
+        match SystemTime::now().duration_since(UNIX_EPOCH) {
+            Ok(since) => Time {
+                secs: since.as_secs() as i64,
+                nanos: since.subsec_nanos(),
+            },
+            Err(e) => {
+                let before = e.duration();
+                if before.subsec_nanos() == 0 {
+                    Time {
+                        secs: -(before.as_secs() as i64),
+                        nanos: 0,
+                    }
+                } else {
+                    Time {
+                        secs: -(before.as_secs() as i64) - 1,
+                        nanos: 1_000_000_000 - before.subsec_nanos(),
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Breaks @p secs down into a calendar date and time, in UTC or in the local time zone.
+fn broken_down(secs: i64, utc: bool) -> Option<libc::tm> {
+    let time = secs as libc::time_t;
+    let mut tm: libc::tm = unsafe { mem::zeroed() };
+    let result = if utc {
+        unsafe { libc::gmtime_r(&time, &mut tm) }
+    } else {
+        unsafe { libc::localtime_r(&time, &mut tm) }
+    };
+    if result.is_null() {
+        return None;
+    }
+    if utc {
+        // glibc calls it GMT, but GNU date says UTC.
+        tm.tm_zone = b"UTC\0".as_ptr() as *const libc::c_char;
+    }
+    Some(tm)
+}
+
+fn zone_name(tm: &libc::tm) -> String {
+    if tm.tm_zone.is_null() {
+        return String::new();
+    }
+    unsafe { CStr::from_ptr(tm.tm_zone) }
+        .to_string_lossy()
+        .into_owned()
+}
+
+/// Appends @p value to @p out, padded to @p width with @p pad, where the GNU flags `-`, `_`
+/// and `0` given in @p flag override the padding.
+fn push_number(out: &mut String, value: i64, width: usize, pad: char, flag: Option<char>) {
+    let pad = match flag {
+        Some('-') => return out.push_str(&value.to_string()),
+        Some('_') => ' ',
+        Some('0') => '0',
+        _ => pad,
+    };
+    let digits = value.abs().to_string();
+    if value < 0 {
+        out.push('-');
+    }
+    for _ in digits.len()..width {
+        out.push(pad);
+    }
+    out.push_str(&digits);
+}
+
+/// Expands the strftime-like directives of @p format for @p time, broken down into @p tm.
+fn expand(format: &str, time: Time, tm: &libc::tm) -> String {
+    let mut out = String::new();
+    let mut chars = format.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            out.push(c);
+            continue;
+        }
+        let flag = match chars.peek() {
+            Some(&flag @ '-') | Some(&flag @ '_') | Some(&flag @ '0') => {
+                chars.next();
+                Some(flag)
+            }
+            _ => None,
+        };
+        let colon = chars.peek() == Some(&':');
+        if colon {
+            chars.next();
+        }
+        let directive = match chars.next() {
+            Some(directive) => directive,
+            None => {
+                out.push('%');
+                break;
+            }
+        };
+        let hour12 = match tm.tm_hour % 12 {
+            0 => 12,
+            hour => hour,
+        };
+        let number = |out: &mut String, value: i32, width: usize, pad: char| {
+            push_number(out, value as i64, width, pad, flag)
+        };
+        match directive {
+            'a' => out.push_str(&WEEKDAYS[tm.tm_wday as usize][..3]),
+            'A' => out.push_str(WEEKDAYS[tm.tm_wday as usize]),
+            'b' | 'h' => out.push_str(&MONTHS[tm.tm_mon as usize][..3]),
+            'B' => out.push_str(MONTHS[tm.tm_mon as usize]),
+            'c' => out.push_str(&expand("%a %b %e %H:%M:%S %Y", time, tm)),
+            'C' => number(&mut out, (tm.tm_year + 1900) / 100, 2, '0'),
+            'd' => number(&mut out, tm.tm_mday, 2, '0'),
+            'D' | 'x' => out.push_str(&expand("%m/%d/%y", time, tm)),
+            'e' => number(&mut out, tm.tm_mday, 2, ' '),
+            'F' => out.push_str(&expand("%Y-%m-%d", time, tm)),
+            'H' => number(&mut out, tm.tm_hour, 2, '0'),
+            'I' => number(&mut out, hour12, 2, '0'),
+            'j' => number(&mut out, tm.tm_yday + 1, 3, '0'),
+            'k' => number(&mut out, tm.tm_hour, 2, ' '),
+            'l' => number(&mut out, hour12, 2, ' '),
+            'm' => number(&mut out, tm.tm_mon + 1, 2, '0'),
+            'M' => number(&mut out, tm.tm_min, 2, '0'),
+            'n' => out.push('\n'),
+            'N' => out.push_str(&format!("{:09}", time.nanos)),
+            'p' => out.push_str(if tm.tm_hour < 12 { "AM" } else { "PM" }),
+            'P' => out.push_str(if tm.tm_hour < 12 { "am" } else { "pm" }),
+            'r' => out.push_str(&expand("%I:%M:%S %p", time, tm)),
+            'R' => out.push_str(&expand("%H:%M", time, tm)),
+            's' => push_number(&mut out, time.secs, 1, '0', flag),
+            'S' => number(&mut out, tm.tm_sec, 2, '0'),
+            't' => out.push('\t'),
+            'T' | 'X' => out.push_str(&expand("%H:%M:%S", time, tm)),
+            'u' => number(
+                &mut out,
+                if tm.tm_wday == 0 { 7 } else { tm.tm_wday },
+                1,
+                '0',
+            ),
+            'w' => number(&mut out, tm.tm_wday, 1, '0'),
+            'y' => number(&mut out, (tm.tm_year + 1900) % 100, 2, '0'),
+            'Y' => number(&mut out, tm.tm_year + 1900, 1, '0'),
+            'z' => {
+                let offset = tm.tm_gmtoff / 60;
+                out.push(if offset < 0 { '-' } else { '+' });
+                let separator = if colon { ":" } else { "" };
+                out.push_str(&format!(
+                    "{:02}{}{:02}",
+                    offset.abs() / 60,
+                    separator,
+                    offset.abs() % 60
+                ));
+            }
+            'Z' => out.push_str(&zone_name(tm)),
+            '%' => out.push('%'),
+            other => {
+                // Like GNU date, leave unknown directives as they are.
+                out.push('%');
+                if let Some(flag) = flag {
+                    out.push(flag);
+                }
+                if colon {
+                    out.push(':');
+                }
+                out.push(other);
+            }
+        }
+    }
+    out
+}
+
+/// Parses a number of exactly @p digits digits from the start of @p s.
+fn take_number(s: &mut &str, digits: usize) -> Option<i32> {
+    if s.len() < digits || !s.as_bytes()[..digits].iter().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let value = s[..digits].parse().ok()?;
+    *s = &s[digits..];
+    Some(value)
+}
+
+fn take_char(s: &mut &str, c: char) -> bool {
+    if s.starts_with(c) {
+        *s = &s[c.len_utf8()..];
+        true
+    } else {
+        false
+    }
+}
+
+fn expect_char(s: &mut &str, c: char) -> Option<()> {
+    if take_char(s, c) {
+        Some(())
+    } else {
+        None
+    }
+}
+
+/// Parses a time zone offset like `Z`, `UTC`, `+02`, `+0200` or `-05:30` into seconds east of
+/// UTC.
+fn parse_offset(s: &str) -> Option<i64> {
+    if s == "Z" || s == "UTC" {
+        return Some(0);
+    }
+    let mut rest = s;
+    let sign = if take_char(&mut rest, '+') {
+        1
+    } else if take_char(&mut rest, '-') {
+        -1
+    } else {
+        return None;
+    };
+    let hours = take_number(&mut rest, 2)?;
+    take_char(&mut rest, ':');
+    let minutes = if rest.is_empty() {
+        0
+    } else {
+        take_number(&mut rest, 2)?
+    };
+    if !rest.is_empty() || hours > 24 || minutes > 59 {
+        return None;
+    }
+    Some(sign * (hours as i64 * 3600 + minutes as i64 * 60))
+}
+
+/// Parses an absolute date: `YYYY-MM-DD`, optionally followed by a time `HH:MM[:SS[.N]]`
+/// after a space or a `T`, and a time zone offset. Without an offset, the date is taken to be
+/// in UTC if @p utc is set, and in local time otherwise.
+fn parse_absolute(s: &str, utc: bool) -> Option<Time> {
+    let mut rest = s;
+    let mut tm: libc::tm = unsafe { mem::zeroed() };
+    tm.tm_year = take_number(&mut rest, 4)? - 1900;
+    expect_char(&mut rest, '-')?;
+    tm.tm_mon = take_number(&mut rest, 2)? - 1;
+    expect_char(&mut rest, '-')?;
+    tm.tm_mday = take_number(&mut rest, 2)?;
+    let mut nanos = 0;
+    if take_char(&mut rest, 'T') || take_char(&mut rest, ' ') {
+        tm.tm_hour = take_number(&mut rest, 2)?;
+        expect_char(&mut rest, ':')?;
+        tm.tm_min = take_number(&mut rest, 2)?;
+        if take_char(&mut rest, ':') {
+            tm.tm_sec = take_number(&mut rest, 2)?;
+            if take_char(&mut rest, '.') || take_char(&mut rest, ',') {
+                let digits = rest.bytes().take_while(|c| c.is_ascii_digit()).count();
+                if digits == 0 {
+                    return None;
+                }
+                let fraction = format!("{:0<9}", &rest[..digits.min(9)]);
+                nanos = fraction.parse().ok()?;
+                rest = &rest[digits..];
+            }
+        }
+    }
+    let offset = match rest.trim_start() {
+        "" => None,
+        zone => Some(parse_offset(zone)?),
+    };
+    if tm.tm_mon > 11 || tm.tm_mday < 1 || tm.tm_hour > 23 || tm.tm_min > 59 || tm.tm_sec > 60 {
+        return None;
+    }
+
+    let mday = tm.tm_mday;
+    let secs = match offset {
+        Some(offset) => unsafe { libc::timegm(&mut tm) as i64 - offset },
+        None if utc => unsafe { libc::timegm(&mut tm) as i64 },
+        None => {
+            tm.tm_isdst = -1;
+            unsafe { libc::mktime(&mut tm) as i64 }
+        }
+    };
+    // Both normalize out of range days, so that "02-30" would become March 1st or 2nd.
+    if tm.tm_mday != mday {
+        return None;
+    }
+    Some(Time { secs, nanos })
+}
+
+/// Parses a date relative to @p now: `yesterday`, `tomorrow` or `[+-]N UNIT [ago]`, where
+/// UNIT is one of second, minute, hour, day or week, or their plurals.
+fn parse_relative(s: &str, now: Time) -> Option<Time> {
+    let words: Vec<&str> = s.split_whitespace().collect();
+    let seconds = match words.as_slice() {
+        ["now"] | ["today"] => 0,
+        ["yesterday"] => -86400,
+        ["tomorrow"] => 86400,
+        [amount, unit] | [amount, unit, "ago"] => {
+            let amount: i64 = amount.strip_prefix('+').unwrap_or(amount).parse().ok()?;
+            let unit = match unit.strip_suffix('s').unwrap_or(unit) {
+                "sec" | "second" => 1,
+                "min" | "minute" => 60,
+                "hour" => 3600,
+                "day" => 86400,
+                "week" => 7 * 86400,
+                _ => return None,
+            };
+            let seconds = amount.checked_mul(unit)?;
+            if words.len() == 3 {
+                -seconds
+            } else {
+                seconds
+            }
+        }
+        _ => return None,
+    };
+    Some(Time {
+        secs: now.secs.checked_add(seconds)?,
+        nanos: now.nanos,
+    })
+}
+
+/// Parses the argument of -d: a relative date, an absolute one or `@SECONDS`.
+fn parse_date(s: &str, now: Time, utc: bool) -> Option<Time> {
+    let s = s.trim();
+    if let Some(secs) = s.strip_prefix('@') {
+        return Some(Time {
+            secs: secs.parse().ok()?,
+            nanos: 0,
+        });
+    }
+    parse_relative(&s.to_lowercase(), now).or_else(|| parse_absolute(s, utc))
+}
+
+fn show_help(opts: &ArgParser) {
+    let brief = format!(
+        concat!(
+            "Clone of the standard GNU date.\n",
+            "Usage: {}: [OPTION]... [+FORMAT]\n",
+            "Display the current time, or the one given with -d, in the given FORMAT."
+        ),
+        env::args().next().unwrap()
+    );
+    print!("{}", opts.usage(&brief));
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let mut opts = ArgParser::new();
+    opts.option(
+        "d",
+        "date",
+        "display time described by STRING, not 'now'",
+        "STRING",
+    );
+    opts.flag(
+        "u",
+        "utc",
+        "print or parse Coordinated Universal Time (UTC)",
+    );
+    opts.flag("h", "help", "display this help and exit");
+    opts.flag("v", "version", "output version information and exit");
+    let options = match opts.parse(&args[1..]) {
+        Ok(m) => m,
+        Err(f) => die!(code = 2, "{}", f.to_string()),
+    };
+    if options.opt_present("h") {
+        return show_help(&opts);
+    }
+    if options.opt_present("v") {
+        return utils::version::print_version("date");
+    }
+
+    if options.free.len() > 1 {
+        die!(code = 2, "extra operand '{}'", options.free[1]);
+    }
+    let format_string = match options.free.first() {
+        Some(operand) if operand.starts_with('+') => &operand[1..],
+        Some(operand) => die!(code = 2, "invalid date '{}'", operand),
+        None => DEFAULT_FORMAT,
+    };
+    let utc = options.opt_present("u");
+    let now = Time::now();
+    let time = match options.opt_str("d") {
+        Some(date) => {
+            parse_date(&date, now, utc).unwrap_or_else(|| die!(code = 2, "invalid date '{}'", date))
+        }
+        None => now,
+    };
+    let tm =
+        broken_down(time.secs, utc).unwrap_or_else(|| die!("time {} is out of range", time.secs));
+    println!("{}", expand(format_string, time, &tm));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(secs: i64) -> String {
+        let time = Time { secs, nanos: 5 };
+        expand(
+            "%F %T %j %a %A %b %B %u %w %Z %z %:z %s %N",
+            time,
+            &broken_down(secs, true).unwrap(),
+        )
+    }
+
+    #[test]
+    fn directives() {
+        assert_eq!(
+            utc(0),
+            "1970-01-01 00:00:00 001 Thu Thursday Jan January 4 4 UTC +0000 +00:00 0 000000005"
+        );
+        assert_eq!(
+            utc(1_710_498_030),
+            "2024-03-15 10:20:30 075 Fri Friday Mar March 5 5 UTC +0000 +00:00 1710498030 000000005"
+        );
+        let tm = broken_down(1_710_469_230, true).unwrap();
+        let time = Time {
+            secs: 1_710_469_230,
+            nanos: 0,
+        };
+        assert_eq!(
+            expand("%e|%-d|%_m|%I %l %p %P|%y %C|%%|%Q", time, &tm),
+            "15|15| 3|02  2 AM am|24 20|%|%Q"
+        );
+        assert_eq!(
+            expand("%D %R %r %c", time, &tm),
+            "03/15/24 02:20 02:20:30 AM Fri Mar 15 02:20:30 2024"
+        );
+    }
+
+    #[test]
+    fn absolute_dates() {
+        let at = |secs| Some(Time { secs, nanos: 0 });
+        assert_eq!(parse_absolute("2024-03-15", true), at(1_710_460_800));
+        assert_eq!(
+            parse_absolute("2024-03-15 10:20:30", true),
+            at(1_710_498_030)
+        );
+        assert_eq!(
+            parse_absolute("2024-03-15T10:20:30Z", false),
+            at(1_710_498_030)
+        );
+        assert_eq!(
+            parse_absolute("2024-03-15T12:20:30+02:00", false),
+            at(1_710_498_030)
+        );
+        assert_eq!(
+            parse_absolute("2024-03-15 05:20:30 -0500", false),
+            at(1_710_498_030)
+        );
+        assert_eq!(
+            parse_absolute("1970-01-01 00:00:01.25", true),
+            Some(Time {
+                secs: 1,
+                nanos: 250_000_000
+            })
+        );
+        for invalid in &[
+            "2024-02-30",
+            "2024-13-01",
+            "2024-03-15 24:00",
+            "2024-3-15",
+            "2024-03-15x",
+        ] {
+            assert_eq!(parse_absolute(invalid, true), None, "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn relative_dates() {
+        let now = Time {
+            secs: 1_000_000,
+            nanos: 7,
+        };
+        let at = |secs| Some(Time { secs, nanos: 7 });
+        assert_eq!(parse_date("now", now, true), at(1_000_000));
+        assert_eq!(parse_date("Yesterday", now, true), at(913_600));
+        assert_eq!(parse_date("tomorrow", now, true), at(1_086_400));
+        assert_eq!(parse_date("2 days ago", now, true), at(827_200));
+        assert_eq!(parse_date("+1 week", now, true), at(1_604_800));
+        assert_eq!(parse_date("-3 hours", now, true), at(989_200));
+        assert_eq!(parse_date("1 fortnight", now, true), None);
+        assert_eq!(
+            parse_date("@-5", now, true),
+            Some(Time { secs: -5, nanos: 0 })
+        );
+        assert_eq!(parse_date("@x", now, true), None);
+    }
+}
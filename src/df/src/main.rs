@@ -0,0 +1,446 @@
+use std::collections::HashSet;
+use std::env;
+use std::ffi::CString;
+use std::fs;
+use std::io::{self, Write};
+use std::mem;
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::fs::MetadataExt;
+use std::path::Path;
+
+#[macro_use(die, warn, note_error)]
+extern crate utils;
+extern crate libc;
+
+use utils::args::ArgParser;
+
+/// A line of /proc/mounts.
+#[derive(Debug, PartialEq)]
+struct Mount {
+    source: String,
+    fs_type: String,
+    target: String,
+}
+
+/// Parses the contents of /proc/mounts, skipping malformed lines.
+fn parse_mounts(contents: &str) -> Vec<Mount> {
+    contents
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace().map(unescape);
+            Some(Mount {
+                source: fields.next()?,
+                target: fields.next()?,
+                fs_type: fields.next()?,
+            })
+        })
+        .collect()
+}
+
+/// Decodes the octal escapes the kernel uses for white space and backslashes in mount fields.
+fn unescape(field: &str) -> String {
+    let bytes = field.as_bytes();
+    let mut result = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let digits = bytes.get(i + 1..i + 4);
+        match digits {
+            Some(digits)
+                if bytes[i] == b'\\' && digits.iter().all(|d| (b'0'..=b'7').contains(d)) =>
+            {
+                result.push(
+                    digits
+                        .iter()
+                        .fold(0u8, |acc, d| acc.wrapping_mul(8) + (d - b'0')),
+                );
+                i += 4;
+            }
+            _ => {
+                result.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&result).into_owned()
+}
+
+/// The sizes of a file system, in bytes.
+#[derive(Clone, Copy, Default, Debug, PartialEq)]
+struct Usage {
+    size: u64,
+    used: u64,
+    available: u64,
+}
+
+impl Usage {
+    /// The percentage of the space available to users that is used, rounded up.
+    fn percent_used(&self) -> Option<u64> {
+        let total = self.used as u128 + self.available as u128;
+        if total == 0 {
+            return None;
+        }
+        Some((self.used as u128 * 100).div_ceil(total) as u64)
+    }
+}
+
+/// Queries the usage of the file system containing @p path.
+fn statvfs(path: &Path) -> io::Result<Usage> {
+    let c_path = CString::new(path.as_os_str().as_bytes())
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+    let mut stats: libc::statvfs = unsafe { mem::zeroed() };
+    if unsafe { libc::statvfs(c_path.as_ptr(), &mut stats) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    let unit = stats.f_frsize as u64;
+    let size = stats.f_blocks as u64 * unit;
+    Ok(Usage {
+        size,
+        used: size.saturating_sub(stats.f_bfree as u64 * unit),
+        available: stats.f_bavail as u64 * unit,
+    })
+}
+
+/// A file system to report.
+struct Row {
+    source: String,
+    fs_type: String,
+    usage: Usage,
+    target: String,
+}
+
+/// How the sizes are shown and which columns there are.
+struct Config {
+    block_size: u64,
+    human_readable: bool,
+    show_type: bool,
+}
+
+/// Writes @p rows as a table with a header, aligned the way GNU df does it: every column is
+/// as wide as its widest cell, with a minimum width per column, and the last one is not padded.
+fn print_table(rows: &[Row], config: &Config, writer: &mut dyn Write) -> io::Result<()> {
+    // Each column has its header, its minimum width and whether it is aligned to the left.
+    let mut columns = vec![("Filesystem".to_owned(), 14, true)];
+    if config.show_type {
+        columns.push(("Type".to_owned(), 4, true));
+    }
+    if config.human_readable {
+        columns.push(("Size".to_owned(), 5, false));
+        columns.push(("Used".to_owned(), 5, false));
+        columns.push(("Avail".to_owned(), 5, false));
+    } else {
+        columns.push((block_header(config.block_size), 5, false));
+        columns.push(("Used".to_owned(), 5, false));
+        columns.push(("Available".to_owned(), 5, false));
+    }
+    columns.push(("Use%".to_owned(), 4, false));
+    columns.push(("Mounted on".to_owned(), 0, true));
+
+    let format_size = |bytes: u64| {
+        if config.human_readable {
+            utils::size::human_readable(bytes)
+        } else {
+            bytes.div_ceil(config.block_size).to_string()
+        }
+    };
+    let mut table = vec![columns.iter().map(|c| c.0.clone()).collect::<Vec<_>>()];
+    for row in rows {
+        let mut cells = vec![row.source.clone()];
+        if config.show_type {
+            cells.push(row.fs_type.clone());
+        }
+        cells.push(format_size(row.usage.size));
+        cells.push(format_size(row.usage.used));
+        cells.push(format_size(row.usage.available));
+        cells.push(match row.usage.percent_used() {
+            Some(percent) => format!("{}%", percent),
+            None => "-".to_owned(),
+        });
+        cells.push(row.target.clone());
+        table.push(cells);
+    }
+
+    let widths: Vec<usize> = columns
+        .iter()
+        .enumerate()
+        .map(|(i, &(_, min, _))| {
+            table
+                .iter()
+                .map(|cells| cells[i].chars().count())
+                .fold(min, usize::max)
+        })
+        .collect();
+    for cells in &table {
+        let mut line = String::new();
+        for (i, cell) in cells.iter().enumerate() {
+            if i > 0 {
+                line.push(' ');
+            }
+            if i + 1 == cells.len() {
+                line.push_str(cell);
+            } else if columns[i].2 {
+                line.push_str(&format!("{:<1$}", cell, widths[i]));
+            } else {
+                line.push_str(&format!("{:>1$}", cell, widths[i]));
+            }
+        }
+        writeln!(writer, "{}", line)?;
+    }
+    Ok(())
+}
+
+/// The header of the size column, e.g. 1K-blocks.
+fn block_header(block_size: u64) -> String {
+    match block_size {
+        1024 => "1K-blocks".to_owned(),
+        1048576 => "1M-blocks".to_owned(),
+        _ => format!("{}-blocks", block_size),
+    }
+}
+
+/// The mounted file systems worth reporting: those that have any blocks at all, each only
+/// once even if it is mounted more than once.
+fn mounted_rows(mounts: &[Mount]) -> Vec<Row> {
+    let mut seen = HashSet::new();
+    let mut rows = Vec::new();
+    for mount in mounts {
+        let target = Path::new(&mount.target);
+        let device = match fs::metadata(target) {
+            Ok(metadata) => metadata.dev(),
+            Err(_) => continue,
+        };
+        let usage = match statvfs(target) {
+            Ok(usage) => usage,
+            Err(_) => continue,
+        };
+        if usage.size == 0 || !seen.insert(device) {
+            continue;
+        }
+        rows.push(Row {
+            source: mount.source.clone(),
+            fs_type: mount.fs_type.clone(),
+            usage,
+            target: mount.target.clone(),
+        });
+    }
+    rows
+}
+
+/// The row for the file system containing @p name, which is the one mounted last on a
+/// directory of the same device.
+fn file_row(name: &str, mounts: &[Mount]) -> io::Result<Row> {
+    let device = fs::metadata(name)?.dev();
+    let usage = statvfs(Path::new(name))?;
+    let mount = mounts.iter().rev().find(|mount| {
+        fs::metadata(&mount.target)
+            .map(|metadata| metadata.dev() == device)
+            .unwrap_or(false)
+    });
+    Ok(match mount {
+        Some(mount) => Row {
+            source: mount.source.clone(),
+            fs_type: mount.fs_type.clone(),
+            usage,
+            target: mount.target.clone(),
+        },
+        None => Row {
+            source: "-".to_owned(),
+            fs_type: "-".to_owned(),
+            usage,
+            target: "-".to_owned(),
+        },
+    })
+}
+
+/// The row summing up @p rows.
+fn total_row(rows: &[Row]) -> Row {
+    let mut usage = Usage::default();
+    for row in rows {
+        usage.size += row.usage.size;
+        usage.used += row.usage.used;
+        usage.available += row.usage.available;
+    }
+    Row {
+        source: "total".to_owned(),
+        fs_type: "-".to_owned(),
+        usage,
+        target: "-".to_owned(),
+    }
+}
+
+fn show_help(opts: &ArgParser) {
+    let brief = format!(
+        concat!(
+            "Clone of the standard GNU df.\n",
+            "Usage: {}: [OPTION]... [FILE]...\n",
+            "Show information about the file system on which each FILE resides,\n",
+            "or all file systems by default."
+        ),
+        env::args().next().unwrap()
+    );
+    print!("{}", opts.usage(&brief));
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let mut opts = ArgParser::new();
+    opts.flag(
+        "h",
+        "human-readable",
+        "print sizes in powers of 1024 (e.g., 1023M)",
+    );
+    opts.flag("k", "", "like --block-size=1K");
+    opts.flag("m", "", "like --block-size=1M");
+    opts.flag("T", "print-type", "print file system type");
+    opts.flag("", "total", "produce a grand total");
+    opts.flag("", "help", "display this help and exit");
+    opts.flag("", "version", "output version information and exit");
+    let options = match opts.parse(&args[1..]) {
+        Ok(m) => m,
+        Err(f) => die!(code = 2, "{}", f.to_string()),
+    };
+    if options.opt_present("help") {
+        return show_help(&opts);
+    }
+    if options.opt_present("version") {
+        return utils::version::print_version("df");
+    }
+
+    // The last of -k and -m wins, as they both set the block size.
+    let megabytes = options.opt_positions("m").last() > options.opt_positions("k").last();
+    let config = Config {
+        block_size: if megabytes { 1 << 20 } else { 1 << 10 },
+        human_readable: options.opt_present("h"),
+        show_type: options.opt_present("T"),
+    };
+
+    let mounts = fs::read_to_string("/proc/mounts")
+        .map(|contents| parse_mounts(&contents))
+        .unwrap_or_else(|e| die!("cannot read table of mounted file systems: {}", e));
+    let mut rows = if options.free.is_empty() {
+        mounted_rows(&mounts)
+    } else {
+        options
+            .free
+            .iter()
+            .filter_map(|name| match file_row(name, &mounts) {
+                Ok(row) => Some(row),
+                Err(e) => {
+                    note_error!("{}: {}", name, e);
+                    None
+                }
+            })
+            .collect()
+    };
+    if rows.is_empty() {
+        utils::exit_if_error();
+        die!("no file systems processed");
+    }
+    if options.opt_present("total") {
+        let total = total_row(&rows);
+        rows.push(total);
+    }
+
+    let stdout = io::stdout();
+    let mut writer = io::BufWriter::new(stdout.lock());
+    print_table(&rows, &config, &mut writer)
+        .and_then(|_| writer.flush())
+        .unwrap_or_else(|e| die!("{}", e));
+    utils::exit_if_error();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mounts_are_parsed() {
+        let mounts = parse_mounts(concat!(
+            "/dev/sda1 / ext4 rw,relatime 0 0\n",
+            "server:/share /mnt/with\\040space nfs rw 0 0\n",
+            "truncated\n"
+        ));
+        assert_eq!(
+            mounts,
+            vec![
+                Mount {
+                    source: "/dev/sda1".to_owned(),
+                    fs_type: "ext4".to_owned(),
+                    target: "/".to_owned(),
+                },
+                Mount {
+                    source: "server:/share".to_owned(),
+                    fs_type: "nfs".to_owned(),
+                    target: "/mnt/with space".to_owned(),
+                },
+            ]
+        );
+        assert_eq!(unescape("a\\134b\\011"), "a\\b\t");
+        assert_eq!(unescape("trailing\\04"), "trailing\\04");
+    }
+
+    #[test]
+    fn percent_is_rounded_up() {
+        let usage = |used, available| Usage {
+            size: used + available,
+            used,
+            available,
+        };
+        assert_eq!(usage(1, 2).percent_used(), Some(34));
+        assert_eq!(usage(0, 5).percent_used(), Some(0));
+        assert_eq!(usage(5, 0).percent_used(), Some(100));
+        assert_eq!(usage(0, 0).percent_used(), None);
+    }
+
+    #[test]
+    fn table_layout() {
+        let rows = vec![
+            Row {
+                source: "/dev/vda".to_owned(),
+                fs_type: "ext4".to_owned(),
+                usage: Usage {
+                    size: 264212084 * 1024,
+                    used: 18434540 * 1024,
+                    available: 82367736 * 1024,
+                },
+                target: "/".to_owned(),
+            },
+            Row {
+                source: "tmpfs".to_owned(),
+                fs_type: "tmpfs".to_owned(),
+                usage: Usage {
+                    size: 3079076 * 1024,
+                    used: 0,
+                    available: 3079076 * 1024,
+                },
+                target: "/sys/fs/cgroup".to_owned(),
+            },
+        ];
+        let mut config = Config {
+            block_size: 1024,
+            human_readable: false,
+            show_type: false,
+        };
+        let mut output = Vec::new();
+        print_table(&rows, &config, &mut output).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            concat!(
+                "Filesystem     1K-blocks     Used Available Use% Mounted on\n",
+                "/dev/vda       264212084 18434540  82367736  19% /\n",
+                "tmpfs            3079076        0   3079076   0% /sys/fs/cgroup\n",
+            )
+        );
+
+        config.human_readable = true;
+        config.show_type = true;
+        let mut output = Vec::new();
+        print_table(&rows[..1], &config, &mut output).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            concat!(
+                "Filesystem     Type  Size  Used Avail Use% Mounted on\n",
+                "/dev/vda       ext4  252G   18G   79G  19% /\n",
+            )
+        );
+    }
+}
@@ -0,0 +1,5 @@
+extern crate digest;
+
+fn main() {
+    digest::main::<digest::Md5>("md5sum");
+}
@@ -0,0 +1,5 @@
+extern crate digest;
+
+fn main() {
+    digest::main::<digest::Sha1>("sha1sum");
+}
@@ -0,0 +1,5 @@
+extern crate digest;
+
+fn main() {
+    digest::main::<digest::Sha512>("sha512sum");
+}
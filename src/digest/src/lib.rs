@@ -0,0 +1,566 @@
+//! Message digests, and the command line shared by md5sum, sha1sum, sha256sum and sha512sum.
+
+use std::env;
+use std::io::{self, BufRead, Read, Write};
+use std::process;
+
+#[macro_use(die, note_error, warn)]
+extern crate utils;
+
+use utils::args::ArgParser;
+use utils::io::FileOrStdin;
+
+mod md5;
+mod sha1;
+mod sha2;
+
+pub use md5::Md5;
+pub use sha1::Sha1;
+pub use sha2::{Sha256, Sha512};
+
+/// A message digest computed incrementally over a stream of bytes.
+pub trait Digest: Default {
+    /// The name of the algorithm in the lines of --tag, such as "SHA256".
+    const TAG: &'static str;
+
+    fn update(&mut self, data: &[u8]);
+    /// The digest of all data passed to `update`.
+    fn finalize(self) -> Vec<u8>;
+}
+
+/// The input of a block-based digest that does not fill a block yet, and the length of all of
+/// it. MD5 and the SHA family pad their input the same way, apart from byte order.
+#[derive(Default)]
+struct Blocks {
+    buffer: Vec<u8>,
+    length: u128,
+}
+
+impl Blocks {
+    /// Passes @p data to @p compress in blocks of @p size bytes, keeping what is left over for
+    /// the next call.
+    fn feed<F: FnMut(&[u8])>(&mut self, size: usize, mut data: &[u8], mut compress: F) {
+        self.length += data.len() as u128;
+        if !self.buffer.is_empty() {
+            let n = (size - self.buffer.len()).min(data.len());
+            self.buffer.extend_from_slice(&data[..n]);
+            data = &data[n..];
+            if self.buffer.len() < size {
+                return;
+            }
+            compress(&self.buffer);
+            self.buffer.clear();
+        }
+        let mut blocks = data.chunks_exact(size);
+        for block in &mut blocks {
+            compress(block);
+        }
+        self.buffer.extend_from_slice(blocks.remainder());
+    }
+
+    /// What ends the input in blocks of @p size: a one bit, zeros, and the length of the input
+    /// in bits, in its last @p length_bytes bytes.
+    fn padding(&self, size: usize, length_bytes: usize, big_endian: bool) -> Vec<u8> {
+        let used = (self.length % size as u128) as usize + 1;
+        let mut padding = vec![0x80];
+        padding.resize((2 * size - length_bytes - used) % size + 1, 0);
+        let bits = self.length.wrapping_mul(8);
+        if big_endian {
+            padding.extend_from_slice(&bits.to_be_bytes()[16 - length_bytes..]);
+        } else {
+            padding.extend_from_slice(&bits.to_le_bytes()[..length_bytes]);
+        }
+        padding
+    }
+}
+
+/// The digest of everything that can be read from @p reader.
+pub fn digest_of<D: Digest>(reader: &mut dyn Read) -> io::Result<Vec<u8>> {
+    let mut digest = D::default();
+    let mut buffer = vec![0; 65536];
+    loop {
+        match reader.read(&mut buffer) {
+            Ok(0) => return Ok(digest.finalize()),
+            Ok(len) => digest.update(&buffer[..len]),
+            Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Escapes back slashes and new lines in @p name. Lines with an escaped name start with a back
+/// slash, so it returns whether anything was escaped.
+fn escape(name: &str) -> (bool, String) {
+    let escaped = name.replace('\\', "\\\\").replace('\n', "\\n");
+    (escaped.len() != name.len(), escaped)
+}
+
+fn unescape(name: &str) -> Option<String> {
+    let mut unescaped = String::with_capacity(name.len());
+    let mut chars = name.chars();
+    while let Some(c) = chars.next() {
+        unescaped.push(match c {
+            '\\' => match chars.next() {
+                Some('\\') => '\\',
+                Some('n') => '\n',
+                _ => return None,
+            },
+            _ => c,
+        });
+    }
+    Some(unescaped)
+}
+
+/// The line which gives @p digest for the file @p name, in the format of --tag if @p tag is
+/// given, and with a '*' before the name in binary mode.
+fn format_line(tag: Option<&str>, digest: &[u8], name: &str, binary: bool) -> String {
+    let (escaped, name) = escape(name);
+    let prefix = if escaped { "\\" } else { "" };
+    match tag {
+        Some(tag) => format!("{}{} ({}) = {}", prefix, tag, name, hex(digest)),
+        None => {
+            let mode = if binary { '*' } else { ' ' };
+            format!("{}{} {}{}", prefix, hex(digest), mode, name)
+        }
+    }
+}
+
+/// Parses a line of a checksum file, in either of the formats `format_line` writes, into the
+/// expected digest in hex and the name of the file. @p hex_len is the length of the digest.
+fn parse_line(line: &str, tag: &str, hex_len: usize) -> Option<(String, String)> {
+    let (escaped, line) = match line.strip_prefix('\\') {
+        Some(line) => (true, line),
+        None => (false, line),
+    };
+    let tagged = line
+        .strip_prefix(tag)
+        .and_then(|rest| rest.strip_prefix(" ("))
+        .and_then(|rest| {
+            rest.rfind(") = ")
+                .map(|end| (&rest[end + 4..], &rest[..end]))
+        });
+    let (digest, name) = match tagged {
+        Some(parsed) => parsed,
+        None if line.len() > hex_len + 2 && line.is_char_boundary(hex_len) => {
+            let (digest, rest) = line.split_at(hex_len);
+            let name = rest
+                .strip_prefix("  ")
+                .or_else(|| rest.strip_prefix(" *"))?;
+            (digest, name)
+        }
+        None => return None,
+    };
+    if digest.len() != hex_len || !digest.bytes().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let name = if escaped {
+        unescape(name)?
+    } else {
+        name.to_owned()
+    };
+    Some((digest.to_ascii_lowercase(), name))
+}
+
+/// How --check reports what it finds.
+struct CheckOptions {
+    ignore_missing: bool,
+    // Do not print the files that are fine.
+    quiet: bool,
+    // Print nothing at all: the exit status tells.
+    status: bool,
+    // Fail on improperly formatted lines.
+    strict: bool,
+    // Warn about each improperly formatted line.
+    warn: bool,
+}
+
+fn plural(count: usize, one: &str, many: &str) -> String {
+    format!("{} {}", count, if count == 1 { one } else { many })
+}
+
+/// Checks the files listed in the checksum file @p list, read from @p reader, and returns
+/// whether all of them were found fine.
+fn check<D: Digest>(
+    list: &str,
+    reader: &mut dyn BufRead,
+    options: &CheckOptions,
+    writer: &mut dyn Write,
+) -> io::Result<bool> {
+    let hex_len = 2 * D::default().finalize().len();
+    let (mut improper, mut unreadable, mut mismatched, mut verified) = (0, 0, 0, 0);
+    for (n, line) in reader.split(b'\n').enumerate() {
+        let parsed = String::from_utf8(line?)
+            .ok()
+            .and_then(|line| parse_line(&line, D::TAG, hex_len));
+        let (expected, name) = match parsed {
+            Some(parsed) => parsed,
+            None => {
+                improper += 1;
+                if options.warn {
+                    warn!(
+                        "{}: {}: improperly formatted {} checksum line",
+                        list,
+                        n + 1,
+                        D::TAG
+                    );
+                }
+                continue;
+            }
+        };
+        let digest = FileOrStdin::open(&name).and_then(|mut file| digest_of::<D>(&mut file));
+        // As in GNU, only names with new lines are shown escaped.
+        let shown = if name.contains('\n') {
+            format!("\\{}", escape(&name).1)
+        } else {
+            name.clone()
+        };
+        match digest {
+            Err(ref e) if options.ignore_missing && e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => {
+                unreadable += 1;
+                if !options.status {
+                    warn!("{}: {}", name, e);
+                    writeln!(writer, "{}: FAILED open or read", shown)?;
+                }
+            }
+            Ok(digest) => {
+                verified += 1;
+                if hex(&digest) != expected {
+                    mismatched += 1;
+                    if !options.status {
+                        writeln!(writer, "{}: FAILED", shown)?;
+                    }
+                } else if !options.quiet && !options.status {
+                    writeln!(writer, "{}: OK", shown)?;
+                }
+            }
+        }
+    }
+    writer.flush()?;
+
+    let proper = verified + unreadable;
+    if improper > 0 && proper == 0 && mismatched == 0 {
+        warn!("{}: no properly formatted checksum lines found", list);
+        return Ok(false);
+    }
+    if !options.status {
+        if improper > 0 {
+            let lines = plural(improper, "line is", "lines are");
+            warn!("WARNING: {} improperly formatted", lines);
+        }
+        if unreadable > 0 {
+            let files = plural(unreadable, "listed file", "listed files");
+            warn!("WARNING: {} could not be read", files);
+        }
+        if mismatched > 0 {
+            let checksums = plural(mismatched, "computed checksum", "computed checksums");
+            warn!("WARNING: {} did NOT match", checksums);
+        }
+    }
+    if options.ignore_missing && verified == 0 {
+        warn!("{}: no file was verified", list);
+        return Ok(false);
+    }
+    Ok(unreadable == 0 && mismatched == 0 && !(options.strict && improper > 0))
+}
+
+fn show_help(tool: &str, tag: &str, opts: &ArgParser) {
+    let brief = format!(
+        concat!(
+            "Clone of the standard GNU {}.\n",
+            "Usage: {}: [OPTION]... [FILE]...\n",
+            "Print or check {} checksums. Without FILE, or when FILE is -, read standard input."
+        ),
+        tool,
+        env::args().next().unwrap(),
+        tag
+    );
+    print!("{}", opts.usage(&brief));
+}
+
+/// The main function of the tool @p tool, which computes digests with @p D.
+pub fn main<D: Digest>(tool: &str) {
+    let args: Vec<String> = env::args().collect();
+    let mut opts = ArgParser::new();
+    opts.flag("b", "binary", "read in binary mode");
+    opts.flag("c", "check", "read checksums from the FILEs and check them");
+    opts.flag("", "tag", "create a BSD-style checksum");
+    opts.flag("t", "text", "read in text mode (default)");
+    opts.flag(
+        "",
+        "ignore-missing",
+        "when checking, do not fail or report status for missing files",
+    );
+    opts.flag(
+        "",
+        "quiet",
+        "when checking, do not print OK for each verified file",
+    );
+    opts.flag(
+        "",
+        "status",
+        "when checking, print nothing; the exit status tells",
+    );
+    opts.flag(
+        "",
+        "strict",
+        "when checking, exit non-zero for improperly formatted lines",
+    );
+    opts.flag(
+        "w",
+        "warn",
+        "when checking, warn about improperly formatted lines",
+    );
+    opts.flag("h", "help", "display this help and exit");
+    opts.flag("v", "version", "output version information and exit");
+    let options = match opts.parse(&args[1..]) {
+        Ok(m) => m,
+        Err(f) => die!(code = 2, "{}", f.to_string()),
+    };
+    if options.opt_present("h") {
+        return show_help(tool, D::TAG, &opts);
+    }
+    if options.opt_present("v") {
+        return utils::version::print_version(tool);
+    }
+    let checking = options.opt_present("c");
+    if checking && options.opt_present("tag") {
+        die!(
+            code = 2,
+            "the --tag option is meaningless when verifying checksums"
+        );
+    }
+    for &option in &["ignore-missing", "quiet", "status", "strict", "warn"] {
+        if !checking && options.opt_present(option) {
+            die!(
+                code = 2,
+                "the --{} option is meaningful only when verifying checksums",
+                option
+            );
+        }
+    }
+
+    let stdout = io::stdout();
+    let mut writer = io::BufWriter::new(stdout.lock());
+    let names = if options.free.is_empty() {
+        vec!["-".to_owned()]
+    } else {
+        options.free.clone()
+    };
+    let check_options = CheckOptions {
+        ignore_missing: options.opt_present("ignore-missing"),
+        quiet: options.opt_present("quiet"),
+        status: options.opt_present("status"),
+        strict: options.opt_present("strict"),
+        warn: options.opt_present("w"),
+    };
+    let tag = if options.opt_present("tag") {
+        Some(D::TAG)
+    } else {
+        None
+    };
+    let mut failed = false;
+    for name in &names {
+        let mut reader = match FileOrStdin::open(name) {
+            Ok(reader) => reader,
+            Err(e) => {
+                note_error!("{}: {}", name, e);
+                continue;
+            }
+        };
+        let result = if checking {
+            check::<D>(name, &mut reader, &check_options, &mut writer).map(|fine| failed |= !fine)
+        } else {
+            digest_of::<D>(&mut reader).and_then(|digest| {
+                let line = format_line(tag, &digest, name, options.opt_present("b"));
+                writeln!(writer, "{}", line)
+            })
+        };
+        if let Err(e) = result {
+            note_error!("{}: {}", name, e);
+        }
+    }
+    writer.flush().unwrap_or_else(|e| die!("{}", e));
+    if failed {
+        process::exit(1);
+    }
+    utils::exit_if_error();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex_digest<D: Digest>(input: &[u8]) -> String {
+        hex(&digest_of::<D>(&mut &input[..]).unwrap())
+    }
+
+    #[test]
+    fn rfc_1321_md5_vectors() {
+        let vectors = [
+            ("", "d41d8cd98f00b204e9800998ecf8427e"),
+            ("a", "0cc175b9c0f1b6a831c399e269772661"),
+            ("abc", "900150983cd24fb0d6963f7d28e17f72"),
+            ("message digest", "f96b697d7cb7938d525a2f31aaf161d0"),
+            (
+                "abcdefghijklmnopqrstuvwxyz",
+                "c3fcd3d76192e4007dfb496cca67e13b",
+            ),
+            (
+                "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789",
+                "d174ab98d277d9f5a5611c2c9f419d9f",
+            ),
+            (
+                "12345678901234567890123456789012345678901234567890123456789012345678901234567890",
+                "57edf4a22be3c955ac49da2e2107b67a",
+            ),
+        ];
+        for &(input, digest) in &vectors {
+            assert_eq!(hex_digest::<Md5>(input.as_bytes()), digest, "{}", input);
+        }
+    }
+
+    /// The examples of FIPS 180-4, with the digests of each algorithm.
+    const SHORT: &str = "abc";
+    const TWO_BLOCKS: &str = "abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq";
+    const TWO_BLOCKS_512: &str = concat!(
+        "abcdefghbcdefghicdefghijdefghijkefghijklfghijklmghijklmn",
+        "hijklmnoijklmnopjklmnopqklmnopqrlmnopqrsmnopqrstnopqrstu"
+    );
+
+    #[test]
+    fn fips_180_4_sha1_vectors() {
+        let vectors = [
+            (SHORT, "a9993e364706816aba3e25717850c26c9cd0d89d"),
+            (TWO_BLOCKS, "84983e441c3bd26ebaae4aa1f95129e5e54670f1"),
+            ("", "da39a3ee5e6b4b0d3255bfef95601890afd80709"),
+        ];
+        for &(input, digest) in &vectors {
+            assert_eq!(hex_digest::<Sha1>(input.as_bytes()), digest, "{}", input);
+        }
+    }
+
+    #[test]
+    fn fips_180_4_sha256_vectors() {
+        let vectors = [
+            (
+                SHORT,
+                "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+            ),
+            (
+                TWO_BLOCKS,
+                "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1",
+            ),
+            (
+                "",
+                "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+            ),
+        ];
+        for &(input, digest) in &vectors {
+            assert_eq!(hex_digest::<Sha256>(input.as_bytes()), digest, "{}", input);
+        }
+    }
+
+    #[test]
+    fn fips_180_4_sha512_vectors() {
+        let vectors = [
+            (
+                SHORT,
+                concat!(
+                    "ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a",
+                    "2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f"
+                ),
+            ),
+            (
+                TWO_BLOCKS_512,
+                concat!(
+                    "8e959b75dae313da8cf4f72814fc143f8f7779c6eb9f7fa17299aeadb6889018",
+                    "501d289e4900f7e4331b99dec4b5433ac7d329eeb6dd26545e96e55b874be909"
+                ),
+            ),
+        ];
+        for &(input, digest) in &vectors {
+            assert_eq!(hex_digest::<Sha512>(input.as_bytes()), digest, "{}", input);
+        }
+    }
+
+    #[test]
+    fn input_in_pieces() {
+        // A million a's, fed in pieces which do not line up with the blocks.
+        let input = vec![b'a'; 1_000_000];
+        let mut md5 = Md5::default();
+        let mut sha1 = Sha1::default();
+        let mut sha256 = Sha256::default();
+        let mut sha512 = Sha512::default();
+        for piece in input.chunks(999) {
+            md5.update(piece);
+            sha1.update(piece);
+            sha256.update(piece);
+            sha512.update(piece);
+        }
+        assert_eq!(hex(&md5.finalize()), "7707d6ae4e027c70eea2a935c2296f21");
+        assert_eq!(
+            hex(&sha1.finalize()),
+            "34aa973cd4c4daa4f61eeb2bdbad27316534016f"
+        );
+        assert_eq!(
+            hex(&sha256.finalize()),
+            "cdc76e5c9914fb9281a1c7e284d73e67f1809a48a497200e046d39ccc7112cd0"
+        );
+        assert_eq!(
+            hex(&sha512.finalize()),
+            concat!(
+                "e718483d0ce769644e2e42c7bc15b4638e1f98b13b2044285632a803afa973eb",
+                "de0ff244877ea60a4cb0432ce577c31beb009c5c2c49aa2e4eadb217ad8cc09b"
+            )
+        );
+        // Lengths around the end of a block need another one for the padding.
+        for len in 50..70 {
+            let input = vec![b'x'; len];
+            let mut digest = Sha256::default();
+            digest.update(&input[..len / 2]);
+            digest.update(&input[len / 2..]);
+            assert_eq!(
+                digest.finalize(),
+                digest_of::<Sha256>(&mut &input[..]).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn lines() {
+        let digest = [0xab, 0x01];
+        assert_eq!(format_line(None, &digest, "f", false), "ab01  f");
+        assert_eq!(format_line(None, &digest, "f", true), "ab01 *f");
+        assert_eq!(
+            format_line(Some("MD5"), &digest, "f", false),
+            "MD5 (f) = ab01"
+        );
+        assert_eq!(
+            format_line(None, &digest, "a\\b\nc", false),
+            "\\ab01  a\\\\b\\nc"
+        );
+
+        let parsed = |line| parse_line(line, "MD5", 4);
+        let expected = |name: &str| Some(("ab01".to_owned(), name.to_owned()));
+        assert_eq!(parsed("ab01  f"), expected("f"));
+        assert_eq!(parsed("AB01 *f g"), expected("f g"));
+        assert_eq!(parsed("MD5 (f) = ab01"), expected("f"));
+        assert_eq!(parsed("MD5 (a) = b) = ab01"), expected("a) = b"));
+        assert_eq!(parsed("\\ab01  a\\\\b\\nc"), expected("a\\b\nc"));
+        for line in &[
+            "ab01 f",
+            "ab0  f",
+            "ab0g  f",
+            "ab01  ",
+            "SHA1 (f) = ab01",
+            "\\ab01  \\x",
+        ] {
+            assert_eq!(parsed(line), None, "{}", line);
+        }
+    }
+}
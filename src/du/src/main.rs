@@ -0,0 +1,238 @@
+use std::collections::HashSet;
+use std::env;
+use std::fs;
+use std::io::{self, Write};
+use std::os::unix::fs::MetadataExt;
+use std::path::Path;
+
+#[macro_use(die, warn, note_error)]
+extern crate utils;
+
+use utils::args::ArgParser;
+
+/// How the sizes are shown and which directories are reported.
+struct Config {
+    block_size: u64,
+    human_readable: bool,
+    max_depth: Option<usize>,
+    one_file_system: bool,
+}
+
+/// Walks the file trees, remembering the hard linked files and directories it has already
+/// counted.
+struct DiskUsage<'a> {
+    config: &'a Config,
+    seen: HashSet<(u64, u64)>,
+}
+
+impl<'a> DiskUsage<'a> {
+    fn new(config: &'a Config) -> DiskUsage<'a> {
+        DiskUsage {
+            config,
+            seen: HashSet::new(),
+        }
+    }
+
+    /// Writes the usage of @p path and of its subdirectories down to the maximum depth to
+    /// @p writer, and returns the bytes used by it in total. Files that cannot be read are
+    /// reported and skipped.
+    fn walk(&mut self, path: &Path, writer: &mut dyn Write) -> io::Result<u64> {
+        let metadata = match fs::symlink_metadata(path) {
+            Ok(metadata) => metadata,
+            Err(e) => {
+                note_error!("cannot access '{}': {}", path.display(), e);
+                return Ok(0);
+            }
+        };
+        let total = self.visit(path, &metadata, metadata.dev(), 0, writer)?;
+        if !metadata.is_dir() {
+            self.report(total, path, writer)?;
+        }
+        Ok(total)
+    }
+
+    fn visit(
+        &mut self,
+        path: &Path,
+        metadata: &fs::Metadata,
+        device: u64,
+        depth: usize,
+        writer: &mut dyn Write,
+    ) -> io::Result<u64> {
+        // Every name of a hard linked file shares its blocks, so count them once. Directories
+        // are remembered too, as one may be reached again through another operand.
+        let counted = metadata.is_dir() || metadata.nlink() > 1;
+        if counted && !self.seen.insert((metadata.dev(), metadata.ino())) {
+            return Ok(0);
+        }
+        if !metadata.is_dir() {
+            return Ok(metadata.blocks() * 512);
+        }
+
+        let mut total = metadata.blocks() * 512;
+        match fs::read_dir(path) {
+            Ok(entries) => {
+                for entry in entries {
+                    let entry = match entry {
+                        Ok(entry) => entry,
+                        Err(e) => {
+                            note_error!("cannot read directory '{}': {}", path.display(), e);
+                            break;
+                        }
+                    };
+                    let child = entry.path();
+                    let child_metadata = match fs::symlink_metadata(&child) {
+                        Ok(metadata) => metadata,
+                        Err(e) => {
+                            note_error!("cannot access '{}': {}", child.display(), e);
+                            continue;
+                        }
+                    };
+                    if self.config.one_file_system && child_metadata.dev() != device {
+                        continue;
+                    }
+                    total += self.visit(&child, &child_metadata, device, depth + 1, writer)?;
+                }
+            }
+            Err(e) => note_error!("cannot read directory '{}': {}", path.display(), e),
+        }
+        if self.config.max_depth.is_none_or(|max| depth <= max) {
+            self.report(total, path, writer)?;
+        }
+        Ok(total)
+    }
+
+    fn report(&self, bytes: u64, path: &Path, writer: &mut dyn Write) -> io::Result<()> {
+        writeln!(
+            writer,
+            "{}\t{}",
+            format_size(bytes, self.config),
+            path.display()
+        )
+    }
+}
+
+/// Formats @p bytes either as a number of blocks, rounded up, or in the human readable
+/// form with a single letter unit suffix.
+fn format_size(bytes: u64, config: &Config) -> String {
+    if config.human_readable {
+        utils::size::human_readable(bytes)
+    } else {
+        bytes.div_ceil(config.block_size).to_string()
+    }
+}
+
+fn show_help(opts: &ArgParser) {
+    let brief = format!(
+        concat!(
+            "Clone of the standard GNU du.\n",
+            "Usage: {}: [OPTION]... [FILE]...\n",
+            "Summarize disk usage of each FILE, recursively for directories."
+        ),
+        env::args().next().unwrap()
+    );
+    print!("{}", opts.usage(&brief));
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let mut opts = ArgParser::new();
+    opts.flag("c", "total", "produce a grand total");
+    opts.flag(
+        "h",
+        "human-readable",
+        "print sizes in human readable format (e.g., 1K 234M 2G)",
+    );
+    opts.flag("k", "", "like --block-size=1K");
+    opts.flag("m", "", "like --block-size=1M");
+    opts.option(
+        "d",
+        "max-depth",
+        "print the total for a directory only if it is N or fewer levels below the command line argument",
+        "N",
+    );
+    opts.flag("s", "summarize", "display only a total for each argument");
+    opts.flag(
+        "x",
+        "one-file-system",
+        "skip directories on different file systems",
+    );
+    opts.flag("", "help", "display this help and exit");
+    opts.flag("", "version", "output version information and exit");
+    let options = match opts.parse(&args[1..]) {
+        Ok(m) => m,
+        Err(f) => die!(code = 2, "{}", f.to_string()),
+    };
+    if options.opt_present("help") {
+        return show_help(&opts);
+    }
+    if options.opt_present("version") {
+        return utils::version::print_version("du");
+    }
+
+    let mut max_depth = options.opt_str("d").map(|depth| {
+        depth
+            .parse::<usize>()
+            .unwrap_or_else(|_| die!(code = 2, "invalid maximum depth '{}'", depth))
+    });
+    if options.opt_present("s") {
+        if max_depth.is_some_and(|depth| depth != 0) {
+            die!(
+                code = 2,
+                "warning: summarizing conflicts with --max-depth={}",
+                max_depth.unwrap()
+            );
+        }
+        max_depth = Some(0);
+    }
+    // The last of -k and -m wins, as they both set the block size.
+    let megabytes = options.opt_positions("m").last() > options.opt_positions("k").last();
+    let config = Config {
+        block_size: if megabytes { 1 << 20 } else { 1 << 10 },
+        human_readable: options.opt_present("h"),
+        max_depth,
+        one_file_system: options.opt_present("x"),
+    };
+
+    let paths = if options.free.is_empty() {
+        vec![".".to_owned()]
+    } else {
+        options.free.clone()
+    };
+    let stdout = io::stdout();
+    let mut writer = io::BufWriter::new(stdout.lock());
+    let mut usage = DiskUsage::new(&config);
+    let mut total = 0;
+    for path in &paths {
+        total += usage
+            .walk(Path::new(path), &mut writer)
+            .unwrap_or_else(|e| die!("{}", e));
+    }
+    if options.opt_present("c") {
+        usage
+            .report(total, Path::new("total"), &mut writer)
+            .unwrap_or_else(|e| die!("{}", e));
+    }
+    writer.flush().unwrap_or_else(|e| die!("{}", e));
+    utils::exit_if_error();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn block_sizes_round_up() {
+        let mut config = Config {
+            block_size: 1024,
+            human_readable: false,
+            max_depth: None,
+            one_file_system: false,
+        };
+        assert_eq!(format_size(0, &config), "0");
+        assert_eq!(format_size(512, &config), "1");
+        assert_eq!(format_size(8192, &config), "8");
+        config.block_size = 1 << 20;
+        assert_eq!(format_size(8192, &config), "1");
+    }
+}
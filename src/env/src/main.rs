@@ -0,0 +1,246 @@
+use std::env;
+use std::ffi::{OsStr, OsString};
+use std::io::{self, Write};
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::process::ExitStatusExt;
+use std::process;
+
+#[macro_use(die)]
+extern crate utils;
+
+use utils::args::ArgParser;
+
+#[derive(Debug)]
+pub struct EnvConfig {
+    ignore_environment: bool,
+    unset: Vec<String>,
+    assignments: Vec<(String, String)>,
+    command: Vec<String>,
+}
+
+/// Splits the free arguments into the leading NAME=VALUE assignments and the command line that
+/// follows them.
+fn split_operands(operands: &[String]) -> (Vec<(String, String)>, Vec<String>) {
+    let mut assignments = Vec::new();
+    let mut rest = operands.iter();
+    let mut command = Vec::new();
+    for operand in &mut rest {
+        match operand.find('=') {
+            Some(n) if n > 0 => {
+                assignments.push((operand[..n].to_owned(), operand[n + 1..].to_owned()))
+            }
+            _ => {
+                command.push(operand.clone());
+                break;
+            }
+        }
+    }
+    command.extend(rest.cloned());
+    (assignments, command)
+}
+
+/// Computes the environment the command should run with, starting from @p current.
+///
+/// Names and values are kept as `OsString`s, as nothing guarantees the inherited ones are UTF-8.
+fn build_environment(
+    config: &EnvConfig,
+    current: Vec<(OsString, OsString)>,
+) -> Vec<(OsString, OsString)> {
+    let mut environment = if config.ignore_environment {
+        Vec::new()
+    } else {
+        current
+    };
+    environment.retain(|(name, _)| !config.unset.iter().any(|unset| OsStr::new(unset) == name));
+    for (name, value) in &config.assignments {
+        match environment.iter().position(|(n, _)| n == OsStr::new(name)) {
+            Some(n) => environment[n].1 = value.into(),
+            None => environment.push((name.into(), value.into())),
+        }
+    }
+    environment
+}
+
+fn command(config: &EnvConfig, environment: &[(OsString, OsString)]) -> process::Command {
+    let mut command = process::Command::new(&config.command[0]);
+    command.args(&config.command[1..]);
+    command.env_clear();
+    command.envs(environment.iter().cloned());
+    command
+}
+
+/// Runs the command and returns the exit code `env` should finish with.
+fn run(config: &EnvConfig, environment: &[(OsString, OsString)]) -> i32 {
+    match command(config, environment).status() {
+        Ok(status) => match status.code() {
+            Some(code) => code,
+            None => 128 + status.signal().unwrap_or(0),
+        },
+        Err(e) => {
+            eprintln!(
+                "{}: '{}': {}",
+                env::args().next().unwrap(),
+                config.command[0],
+                e
+            );
+            if e.kind() == io::ErrorKind::NotFound {
+                127
+            } else {
+                126
+            }
+        }
+    }
+}
+
+/// Prints NAME=VALUE byte for byte, whatever the encoding of either.
+fn print_variable(out: &mut dyn Write, name: &OsStr, value: &OsStr) -> io::Result<()> {
+    out.write_all(name.as_bytes())?;
+    out.write_all(b"=")?;
+    out.write_all(value.as_bytes())?;
+    out.write_all(b"\n")
+}
+
+fn show_help(opts: &ArgParser) {
+    let brief = format!(
+        concat!(
+            "Clone of the standard GNU env.\n",
+            "Usage: {}: [OPTION]... [-] [NAME=VALUE]... [COMMAND [ARG]...]\n",
+            "Set each NAME to VALUE in the environment and run COMMAND.\n",
+            "Without COMMAND, print the resulting environment."
+        ),
+        env::args().next().unwrap()
+    );
+    print!("{}", opts.usage(&brief));
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let mut opts = ArgParser::new();
+    opts.stop_at_first_free();
+    opts.flag("i", "ignore-environment", "start with an empty environment");
+    opts.option("u", "unset", "remove variable from the environment", "NAME");
+    opts.flag("h", "help", "display this help and exit");
+    opts.flag("v", "version", "output version information and exit");
+    let options = match opts.parse(&args[1..]) {
+        Ok(m) => m,
+        Err(f) => die!(code = 125, "{}", f.to_string()),
+    };
+    if options.opt_present("h") {
+        return show_help(&opts);
+    }
+    if options.opt_present("v") {
+        return utils::version::print_version("env");
+    }
+
+    // A lone '-' is a historical synonym of -i.
+    let mut free = &options.free[..];
+    let dash = !free.is_empty() && free[0] == "-";
+    if dash {
+        free = &free[1..];
+    }
+    let (assignments, command) = split_operands(free);
+    let config = EnvConfig {
+        ignore_environment: dash || options.opt_present("i"),
+        unset: options.opt_strs("u"),
+        assignments,
+        command,
+    };
+
+    let environment = build_environment(&config, env::vars_os().collect());
+    if config.command.is_empty() {
+        let stdout = io::stdout();
+        let mut out = stdout.lock();
+        for (name, value) in environment {
+            print_variable(&mut out, &name, &value).unwrap_or_else(|e| die!("{}", e));
+        }
+    } else {
+        process::exit(run(&config, &environment));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(args: &[&str]) -> Vec<String> {
+        args.iter().map(|s| s.to_string()).collect()
+    }
+
+    fn config(ignore_environment: bool, unset: &[&str], operands: &[&str]) -> EnvConfig {
+        let (assignments, command) = split_operands(&strings(operands));
+        EnvConfig {
+            ignore_environment,
+            unset: strings(unset),
+            assignments,
+            command,
+        }
+    }
+
+    fn pair(name: &str, value: &str) -> (OsString, OsString) {
+        (name.into(), value.into())
+    }
+
+    fn current() -> Vec<(OsString, OsString)> {
+        vec![pair("HOME", "/root"), pair("PATH", "/bin")]
+    }
+
+    #[test]
+    fn operands_are_split_at_command() {
+        let config = config(false, &[], &["A=1", "B=", "echo", "C=3"]);
+        assert_eq!(
+            config.assignments,
+            vec![
+                ("A".to_owned(), "1".to_owned()),
+                ("B".to_owned(), "".to_owned()),
+            ]
+        );
+        assert_eq!(config.command, strings(&["echo", "C=3"]));
+    }
+
+    #[test]
+    fn ignore_environment_removes_variables() {
+        let environment = build_environment(&config(true, &[], &["A=1"]), current());
+        assert_eq!(environment, vec![pair("A", "1")]);
+    }
+
+    #[test]
+    fn unset_and_override() {
+        let environment =
+            build_environment(&config(false, &["HOME"], &["PATH=/usr/bin"]), current());
+        assert_eq!(environment, vec![pair("PATH", "/usr/bin")]);
+    }
+
+    #[test]
+    fn non_utf8_variables_are_kept() {
+        use std::os::unix::ffi::OsStringExt;
+        let value = OsString::from_vec(b"caf\xe9".to_vec());
+        let environment = build_environment(
+            &config(false, &["HOME"], &[]),
+            vec![("LATIN1".into(), value.clone())],
+        );
+        assert_eq!(environment, vec![("LATIN1".into(), value.clone())]);
+        let mut output = Vec::new();
+        print_variable(&mut output, OsStr::new("LATIN1"), &value).unwrap();
+        assert_eq!(output, b"LATIN1=caf\xe9\n");
+    }
+
+    #[test]
+    fn assignments_reach_child() {
+        let config = config(
+            true,
+            &[],
+            &["FOO=bar", "/bin/sh", "-c", "echo $FOO; echo ${HOME-unset}"],
+        );
+        let environment = build_environment(&config, current());
+        let output = command(&config, &environment).output().unwrap();
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "bar\nunset\n");
+    }
+
+    #[test]
+    fn exit_code_mirrors_child() {
+        let exiting = config(false, &[], &["/bin/sh", "-c", "exit 3"]);
+        assert_eq!(run(&exiting, &current()), 3);
+        let missing = config(false, &[], &["/nonexistent/command"]);
+        assert_eq!(run(&missing, &current()), 127);
+    }
+}
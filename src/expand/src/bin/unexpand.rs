@@ -0,0 +1,77 @@
+use std::env;
+use std::io::{self, Write};
+
+#[macro_use(die, note_error, warn)]
+extern crate utils;
+extern crate expand;
+
+use expand::TabStops;
+use utils::args::ArgParser;
+
+fn show_help(opts: &ArgParser) {
+    let brief = format!(
+        concat!(
+            "Clone of the standard GNU unexpand.\n",
+            "Usage: {}: [OPTION]... [FILE]...\n",
+            "Convert blanks in each FILE to tabs, writing to standard output."
+        ),
+        env::args().next().unwrap()
+    );
+    print!("{}", opts.usage(&brief));
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let mut opts = ArgParser::new();
+    opts.flag(
+        "a",
+        "all",
+        "convert all blanks, instead of just initial blanks",
+    );
+    opts.flag(
+        "",
+        "first-only",
+        "convert only leading sequences of blanks (overrides -a)",
+    );
+    opts.option(
+        "t",
+        "tabs",
+        "have tabs N characters apart instead of 8 (enables -a), or use comma separated list of explicit tab positions",
+        "TABLIST",
+    );
+    opts.flag("h", "help", "display this help and exit");
+    opts.flag("v", "version", "output version information and exit");
+    let options = match opts.parse(&args[1..]) {
+        Ok(m) => m,
+        Err(f) => die!(code = 2, "{}", f.to_string()),
+    };
+    if options.opt_present("h") {
+        return show_help(&opts);
+    }
+    if options.opt_present("v") {
+        return utils::version::print_version("unexpand");
+    }
+
+    let stops = match options.opt_str("t") {
+        Some(list) => TabStops::parse(&list).unwrap_or_else(|e| die!(code = 2, "{}", e)),
+        None => TabStops::default(),
+    };
+    let all = !options.opt_present("first-only")
+        && (options.opt_present("a") || options.opt_present("t"));
+
+    let stdout = io::stdout();
+    let mut writer = io::BufWriter::new(stdout.lock());
+    for (name, reader) in utils::io::open_inputs(&options.free) {
+        let mut reader = match reader {
+            Ok(reader) => reader,
+            Err(e) => {
+                note_error!("{}: {}", name, e);
+                continue;
+            }
+        };
+        expand::unexpand(&mut reader, &mut writer, &stops, all)
+            .unwrap_or_else(|e| note_error!("{}: {}", name, e));
+    }
+    writer.flush().unwrap_or_else(|e| die!("{}", e));
+    utils::exit_if_error();
+}
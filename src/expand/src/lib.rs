@@ -0,0 +1,234 @@
+//! Shared implementation of `expand` and `unexpand`.
+
+use std::io::{self, BufRead, Write};
+
+#[derive(Debug, PartialEq)]
+pub enum TabStops {
+    /// A tab stop every N columns.
+    Every(usize),
+    /// Explicit, strictly increasing tab stop positions.
+    List(Vec<usize>),
+}
+
+impl TabStops {
+    /// Parses a TABLIST: either a single increment or a list of positions separated by commas
+    /// and/or blanks.
+    pub fn parse(list: &str) -> Result<TabStops, String> {
+        let mut stops = Vec::new();
+        for item in list.split(|c: char| c == ',' || c.is_whitespace()) {
+            if item.is_empty() {
+                continue;
+            }
+            let stop = item
+                .parse::<usize>()
+                .map_err(|_| format!("tab size contains invalid character(s): '{}'", item))?;
+            if stop == 0 {
+                return Err("tab size cannot be 0".into());
+            }
+            if stops.last().is_some_and(|last| *last >= stop) {
+                return Err("tab sizes must be ascending".into());
+            }
+            stops.push(stop);
+        }
+        match stops.len() {
+            0 => Err("tab stop list is empty".into()),
+            1 => Ok(TabStops::Every(stops[0])),
+            _ => Ok(TabStops::List(stops)),
+        }
+    }
+
+    /// Returns the first tab stop after @p column, if there is one.
+    pub fn next_stop(&self, column: usize) -> Option<usize> {
+        match *self {
+            TabStops::Every(n) => Some((column / n + 1) * n),
+            TabStops::List(ref stops) => stops.iter().cloned().find(|stop| *stop > column),
+        }
+    }
+}
+
+impl Default for TabStops {
+    fn default() -> TabStops {
+        TabStops::Every(8)
+    }
+}
+
+/// Whether @p byte starts a character, i.e. is not a UTF-8 continuation byte.
+fn starts_char(byte: u8) -> bool {
+    byte & 0xc0 != 0x80
+}
+
+/// Copies @p reader to @p writer replacing tabs with the number of spaces needed to reach the
+/// next tab stop. Tabs past the last explicit stop become a single space.
+pub fn expand(
+    reader: &mut dyn BufRead,
+    writer: &mut dyn Write,
+    stops: &TabStops,
+) -> io::Result<()> {
+    let mut line = Vec::new();
+    while reader.read_until(b'\n', &mut line)? > 0 {
+        let mut column = 0;
+        for &byte in &line {
+            match byte {
+                b'\t' => {
+                    let stop = stops.next_stop(column).unwrap_or(column + 1);
+                    for _ in column..stop {
+                        writer.write_all(b" ")?;
+                    }
+                    column = stop;
+                }
+                b'\x08' => {
+                    column = column.saturating_sub(1);
+                    writer.write_all(&[byte])?;
+                }
+                _ => {
+                    if starts_char(byte) {
+                        column += 1;
+                    }
+                    writer.write_all(&[byte])?;
+                }
+            }
+        }
+        line.clear();
+    }
+    Ok(())
+}
+
+/// Copies @p reader to @p writer replacing runs of blanks which reach a tab stop with tabs.
+/// Unless @p all is set only the leading blanks of each line are converted.
+pub fn unexpand(
+    reader: &mut dyn BufRead,
+    writer: &mut dyn Write,
+    stops: &TabStops,
+    all: bool,
+) -> io::Result<()> {
+    let mut line = Vec::new();
+    while reader.read_until(b'\n', &mut line)? > 0 {
+        let mut column = 0;
+        let mut pending_spaces = 0;
+        let mut converting = true;
+        for &byte in &line {
+            if converting && (byte == b' ' || byte == b'\t') {
+                let stop = stops.next_stop(column);
+                if byte == b' ' {
+                    column += 1;
+                    pending_spaces += 1;
+                    if stop == Some(column) {
+                        // A single space is kept as is, it would not get any shorter.
+                        writer.write_all(if pending_spaces > 1 { b"\t" } else { b" " })?;
+                        pending_spaces = 0;
+                    }
+                } else {
+                    match stop {
+                        Some(stop) => column = stop,
+                        None => {
+                            for _ in 0..pending_spaces {
+                                writer.write_all(b" ")?;
+                            }
+                            column += 1;
+                        }
+                    }
+                    writer.write_all(b"\t")?;
+                    pending_spaces = 0;
+                }
+                continue;
+            }
+            for _ in 0..pending_spaces {
+                writer.write_all(b" ")?;
+            }
+            pending_spaces = 0;
+            if byte == b'\x08' {
+                column = column.saturating_sub(1);
+            } else if starts_char(byte) {
+                column += 1;
+            }
+            converting = all && byte != b'\n';
+            if byte == b'\n' {
+                converting = true;
+                column = 0;
+            }
+            writer.write_all(&[byte])?;
+        }
+        for _ in 0..pending_spaces {
+            writer.write_all(b" ")?;
+        }
+        line.clear();
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn expanded(input: &str, stops: &TabStops) -> String {
+        let mut output = Vec::new();
+        expand(&mut input.as_bytes(), &mut output, stops).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    fn unexpanded(input: &str, stops: &TabStops, all: bool) -> String {
+        let mut output = Vec::new();
+        unexpand(&mut input.as_bytes(), &mut output, stops, all).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn tab_list_parsing() {
+        assert_eq!(TabStops::parse("4"), Ok(TabStops::Every(4)));
+        assert_eq!(TabStops::parse("2,5 9"), Ok(TabStops::List(vec![2, 5, 9])));
+        assert!(TabStops::parse("0").is_err());
+        assert!(TabStops::parse("4,2").is_err());
+        assert!(TabStops::parse("x").is_err());
+        assert!(TabStops::parse("").is_err());
+    }
+
+    #[test]
+    fn expand_default_stops() {
+        let stops = TabStops::default();
+        assert_eq!(expanded("\ta\n", &stops), "        a\n");
+        assert_eq!(expanded("abc\td\n", &stops), "abc     d\n");
+        assert_eq!(expanded("a\n\tb", &stops), "a\n        b");
+    }
+
+    #[test]
+    fn expand_multiple_stops() {
+        let stops = TabStops::parse("2,5").unwrap();
+        assert_eq!(expanded("\ta\tb\tc\n", &stops), "  a  b c\n");
+        assert_eq!(expanded("é\tx\n", &TabStops::Every(4)), "é   x\n");
+    }
+
+    #[test]
+    fn unexpand_leading_only() {
+        let stops = TabStops::Every(4);
+        assert_eq!(
+            unexpanded("        a    b\n", &stops, false),
+            "\t\ta    b\n"
+        );
+        assert_eq!(unexpanded("   a\n", &stops, false), "   a\n");
+        assert_eq!(unexpanded("      a\n", &stops, false), "\t  a\n");
+    }
+
+    #[test]
+    fn unexpand_all() {
+        let stops = TabStops::Every(4);
+        assert_eq!(unexpanded("        a   b\n", &stops, true), "\t\ta\tb\n");
+        assert_eq!(unexpanded("abc d\n", &stops, true), "abc d\n");
+    }
+
+    #[test]
+    fn unexpand_multiple_stops() {
+        let stops = TabStops::parse("2,5").unwrap();
+        assert_eq!(unexpanded("     a\n", &stops, false), "\t\ta\n");
+        assert_eq!(unexpanded("        a\n", &stops, false), "\t\t   a\n");
+    }
+
+    #[test]
+    fn round_trip() {
+        let stops = TabStops::Every(8);
+        let input = "\t\tint x;\n\treturn\t0;\n";
+        assert_eq!(
+            unexpanded(&expanded(input, &stops), &stops, false),
+            "\t\tint x;\n\treturn  0;\n"
+        );
+    }
+}
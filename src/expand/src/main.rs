@@ -0,0 +1,65 @@
+use std::env;
+use std::io::{self, Write};
+
+#[macro_use(die, note_error, warn)]
+extern crate utils;
+extern crate expand;
+
+use expand::TabStops;
+use utils::args::ArgParser;
+
+fn show_help(opts: &ArgParser) {
+    let brief = format!(
+        concat!(
+            "Clone of the standard GNU expand.\n",
+            "Usage: {}: [OPTION]... [FILE]...\n",
+            "Convert tabs in each FILE to spaces, writing to standard output."
+        ),
+        env::args().next().unwrap()
+    );
+    print!("{}", opts.usage(&brief));
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let mut opts = ArgParser::new();
+    opts.option(
+        "t",
+        "tabs",
+        "have tabs N characters apart, or use comma separated list of explicit tab positions",
+        "TABLIST",
+    );
+    opts.flag("h", "help", "display this help and exit");
+    opts.flag("v", "version", "output version information and exit");
+    let options = match opts.parse(&args[1..]) {
+        Ok(m) => m,
+        Err(f) => die!(code = 2, "{}", f.to_string()),
+    };
+    if options.opt_present("h") {
+        return show_help(&opts);
+    }
+    if options.opt_present("v") {
+        return utils::version::print_version("expand");
+    }
+
+    let stops = match options.opt_str("t") {
+        Some(list) => TabStops::parse(&list).unwrap_or_else(|e| die!(code = 2, "{}", e)),
+        None => TabStops::default(),
+    };
+
+    let stdout = io::stdout();
+    let mut writer = io::BufWriter::new(stdout.lock());
+    for (name, reader) in utils::io::open_inputs(&options.free) {
+        let mut reader = match reader {
+            Ok(reader) => reader,
+            Err(e) => {
+                note_error!("{}: {}", name, e);
+                continue;
+            }
+        };
+        expand::expand(&mut reader, &mut writer, &stops)
+            .unwrap_or_else(|e| note_error!("{}: {}", name, e));
+    }
+    writer.flush().unwrap_or_else(|e| die!("{}", e));
+    utils::exit_if_error();
+}
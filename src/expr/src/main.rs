@@ -0,0 +1,473 @@
+use std::cmp::Ordering;
+use std::env;
+use std::fmt;
+use std::process;
+
+#[macro_use(warn)]
+extern crate utils;
+
+use utils::args::ArgParser;
+use utils::regex::{Regex, Syntax};
+
+/// Like `die!`, but with the exit status 2 that expr reserves for errors.
+macro_rules! trouble {
+    ($($arg:tt)*) => ({
+        warn!($($arg)*);
+        process::exit(2);
+    });
+}
+
+type Result<T> = std::result::Result<T, String>;
+
+#[derive(Debug, PartialEq)]
+enum Value {
+    Integer(i128),
+    String(String),
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Value::Integer(n) => write!(f, "{}", n),
+            Value::String(ref s) => f.write_str(s),
+        }
+    }
+}
+
+impl Value {
+    /// Whether the value is null, i.e. the empty string or zero. Like in GNU expr, a string of
+    /// zeros counts as zero too.
+    fn is_null(&self) -> bool {
+        match *self {
+            Value::Integer(n) => n == 0,
+            Value::String(ref s) => {
+                let digits = s.strip_prefix('-').unwrap_or(s);
+                s.is_empty() || (!digits.is_empty() && digits.bytes().all(|c| c == b'0'))
+            }
+        }
+    }
+
+    fn to_integer(&self) -> Result<i128> {
+        match *self {
+            Value::Integer(n) => Ok(n),
+            Value::String(ref s) if looks_like_integer(s) => s
+                .parse()
+                .map_err(|_| format!("{}: Numerical result out of range", s)),
+            Value::String(_) => Err("non-integer argument".to_owned()),
+        }
+    }
+}
+
+/// Whether @p s is an optional minus sign followed by decimal digits.
+fn looks_like_integer(s: &str) -> bool {
+    let digits = s.strip_prefix('-').unwrap_or(s);
+    !digits.is_empty() && digits.bytes().all(|c| c.is_ascii_digit())
+}
+
+/// Compares integers numerically and anything else as strings.
+fn compare(left: &Value, right: &Value) -> Result<Ordering> {
+    let (left, right) = (left.to_string(), right.to_string());
+    if looks_like_integer(&left) && looks_like_integer(&right) {
+        let left = Value::String(left).to_integer()?;
+        Ok(left.cmp(&Value::String(right).to_integer()?))
+    } else {
+        Ok(left.cmp(&right))
+    }
+}
+
+/// Matches @p pattern, a basic regular expression, against the start of @p string. Yields
+/// what the first group matched if there is one, or the length of the match otherwise.
+fn match_string(string: &str, pattern: &str) -> Result<Value> {
+    let regex = Regex::new(&[pattern.to_owned()], Syntax::Basic, false)?;
+    let captures = regex.match_prefix(string.as_bytes());
+    let length = |start: usize, end: usize| {
+        String::from_utf8_lossy(&string.as_bytes()[start..end])
+            .chars()
+            .count()
+    };
+    Ok(match captures {
+        Some(captures) if regex.groups() > 0 => match captures.group(1) {
+            Some((start, end)) => {
+                Value::String(String::from_utf8_lossy(&string.as_bytes()[start..end]).into_owned())
+            }
+            None => Value::String(String::new()),
+        },
+        Some(captures) => Value::Integer(length(0, captures.end) as i128),
+        None if regex.groups() > 0 => Value::String(String::new()),
+        None => Value::Integer(0),
+    })
+}
+
+/// The part of @p string of @p length characters from @p position, counting from one.
+fn substr(string: &str, position: i128, length: i128) -> String {
+    if position < 1 || length < 1 {
+        return String::new();
+    }
+    let skip = (position - 1).min(usize::MAX as i128) as usize;
+    let take = length.min(usize::MAX as i128) as usize;
+    string.chars().skip(skip).take(take).collect()
+}
+
+/// The position of the first character of @p string that is in @p chars, or 0.
+fn index(string: &str, chars: &str) -> usize {
+    string
+        .chars()
+        .position(|c| chars.contains(c))
+        .map_or(0, |i| i + 1)
+}
+
+/// Evaluates the expression in its arguments by recursive descent. Each level handles the
+/// operators of one precedence, from `|` at the lowest to the functions at the highest.
+/// While `evaluate` is false, operands are parsed without reporting errors, so that `0 & 1 / 0`
+/// and `1 | 1 / 0` short-circuit as in GNU expr.
+struct Parser<'a> {
+    args: &'a [String],
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<&'a str> {
+        self.args.get(self.pos).map(|arg| arg.as_str())
+    }
+
+    /// Consumes the next argument if it is @p token.
+    fn next_is(&mut self, token: &str) -> bool {
+        if self.peek() == Some(token) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Consumes the next argument, which is required after @p after.
+    fn next_after(&mut self, after: &str) -> Result<&'a str> {
+        match self.peek() {
+            Some(arg) => {
+                self.pos += 1;
+                Ok(arg)
+            }
+            None => Err(format!("syntax error: missing argument after '{}'", after)),
+        }
+    }
+
+    fn parse_or(&mut self, evaluate: bool) -> Result<Value> {
+        let mut left = self.parse_and(evaluate)?;
+        while self.next_is("|") {
+            self.require_operand("|")?;
+            let right = self.parse_and(evaluate && left.is_null())?;
+            if left.is_null() {
+                left = if right.is_null() {
+                    Value::Integer(0)
+                } else {
+                    right
+                };
+            }
+        }
+        Ok(left)
+    }
+
+    fn parse_and(&mut self, evaluate: bool) -> Result<Value> {
+        let mut left = self.parse_comparison(evaluate)?;
+        while self.next_is("&") {
+            self.require_operand("&")?;
+            let right = self.parse_comparison(evaluate && !left.is_null())?;
+            if left.is_null() || right.is_null() {
+                left = Value::Integer(0);
+            }
+        }
+        Ok(left)
+    }
+
+    fn parse_comparison(&mut self, evaluate: bool) -> Result<Value> {
+        let mut left = self.parse_sum(evaluate)?;
+        loop {
+            let operator = match self.peek() {
+                Some(operator @ "<")
+                | Some(operator @ "<=")
+                | Some(operator @ "=")
+                | Some(operator @ "==")
+                | Some(operator @ "!=")
+                | Some(operator @ ">=")
+                | Some(operator @ ">") => operator,
+                _ => return Ok(left),
+            };
+            self.pos += 1;
+            self.require_operand(operator)?;
+            let right = self.parse_sum(evaluate)?;
+            if evaluate {
+                let ordering = compare(&left, &right)?;
+                let holds = match operator {
+                    "<" => ordering == Ordering::Less,
+                    "<=" => ordering != Ordering::Greater,
+                    "!=" => ordering != Ordering::Equal,
+                    ">=" => ordering != Ordering::Less,
+                    ">" => ordering == Ordering::Greater,
+                    _ => ordering == Ordering::Equal,
+                };
+                left = Value::Integer(holds as i128);
+            }
+        }
+    }
+
+    fn parse_sum(&mut self, evaluate: bool) -> Result<Value> {
+        let mut left = self.parse_product(evaluate)?;
+        loop {
+            let operator = match self.peek() {
+                Some(operator @ "+") | Some(operator @ "-") => operator,
+                _ => return Ok(left),
+            };
+            self.pos += 1;
+            self.require_operand(operator)?;
+            let right = self.parse_product(evaluate)?;
+            if evaluate {
+                let (l, r) = (left.to_integer()?, right.to_integer()?);
+                let result = if operator == "+" {
+                    l.checked_add(r)
+                } else {
+                    l.checked_sub(r)
+                };
+                left = Value::Integer(result.ok_or("integer result too large")?);
+            }
+        }
+    }
+
+    fn parse_product(&mut self, evaluate: bool) -> Result<Value> {
+        let mut left = self.parse_match(evaluate)?;
+        loop {
+            let operator = match self.peek() {
+                Some(operator @ "*") | Some(operator @ "/") | Some(operator @ "%") => operator,
+                _ => return Ok(left),
+            };
+            self.pos += 1;
+            self.require_operand(operator)?;
+            let right = self.parse_match(evaluate)?;
+            if evaluate {
+                let (l, r) = (left.to_integer()?, right.to_integer()?);
+                if operator != "*" && r == 0 {
+                    return Err("division by zero".to_owned());
+                }
+                let result = match operator {
+                    "*" => l.checked_mul(r),
+                    "/" => l.checked_div(r),
+                    _ => l.checked_rem(r),
+                };
+                left = Value::Integer(result.ok_or("integer result too large")?);
+            }
+        }
+    }
+
+    fn parse_match(&mut self, evaluate: bool) -> Result<Value> {
+        let mut left = self.parse_primary(evaluate)?;
+        while self.next_is(":") {
+            self.require_operand(":")?;
+            let right = self.parse_primary(evaluate)?;
+            if evaluate {
+                left = match_string(&left.to_string(), &right.to_string())?;
+            }
+        }
+        Ok(left)
+    }
+
+    fn parse_primary(&mut self, evaluate: bool) -> Result<Value> {
+        let token = match self.peek() {
+            Some(token) => token,
+            None => return Err("syntax error: missing argument".to_owned()),
+        };
+        self.pos += 1;
+        match token {
+            "+" => Ok(Value::String(self.next_after("+")?.to_owned())),
+            "(" => {
+                let value = self.parse_or(evaluate)?;
+                match self.peek() {
+                    Some(")") => {
+                        self.pos += 1;
+                        Ok(value)
+                    }
+                    Some(other) => Err(format!(
+                        "syntax error: expecting ')' instead of '{}'",
+                        other
+                    )),
+                    None => Err(format!(
+                        "syntax error: expecting ')' after '{}'",
+                        self.args[self.pos - 1]
+                    )),
+                }
+            }
+            ")" => Err("syntax error: unexpected ')'".to_owned()),
+            "length" => {
+                self.require_operand("length")?;
+                let string = self.parse_primary(evaluate)?.to_string();
+                Ok(Value::Integer(string.chars().count() as i128))
+            }
+            "match" => {
+                self.require_operand("match")?;
+                let string = self.parse_primary(evaluate)?;
+                self.require_operand("match")?;
+                let pattern = self.parse_primary(evaluate)?;
+                if !evaluate {
+                    return Ok(string);
+                }
+                match_string(&string.to_string(), &pattern.to_string())
+            }
+            "index" => {
+                self.require_operand("index")?;
+                let string = self.parse_primary(evaluate)?.to_string();
+                self.require_operand("index")?;
+                let chars = self.parse_primary(evaluate)?.to_string();
+                Ok(Value::Integer(index(&string, &chars) as i128))
+            }
+            "substr" => {
+                self.require_operand("substr")?;
+                let string = self.parse_primary(evaluate)?;
+                self.require_operand("substr")?;
+                let position = self.parse_primary(evaluate)?;
+                self.require_operand("substr")?;
+                let length = self.parse_primary(evaluate)?;
+                if !evaluate {
+                    return Ok(string);
+                }
+                Ok(Value::String(substr(
+                    &string.to_string(),
+                    position.to_integer()?,
+                    length.to_integer()?,
+                )))
+            }
+            token => Ok(Value::String(token.to_owned())),
+        }
+    }
+
+    fn require_operand(&self, after: &str) -> Result<()> {
+        if self.peek().is_none() {
+            Err(format!("syntax error: missing argument after '{}'", after))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Evaluates the expression made of @p args.
+fn expr(args: &[String]) -> Result<Value> {
+    if args.is_empty() {
+        return Err("missing operand".to_owned());
+    }
+    let mut parser = Parser { args, pos: 0 };
+    let value = parser.parse_or(true)?;
+    match parser.peek() {
+        Some(arg) => Err(format!("syntax error: unexpected argument '{}'", arg)),
+        None => Ok(value),
+    }
+}
+
+fn show_help(opts: &ArgParser) {
+    let brief = format!(
+        concat!(
+            "Clone of the standard GNU expr.\n",
+            "Usage: {}: EXPRESSION\n",
+            "Print the value of EXPRESSION to standard output. Its operators, from the lowest\n",
+            "precedence to the highest, are: | & < <= = == != >= > + - * / % : and the\n",
+            "functions match, substr, index and length. Exit with 0 if the value is neither\n",
+            "null nor 0, with 1 if it is, and with 2 if EXPRESSION is invalid."
+        ),
+        env::args().next().unwrap()
+    );
+    print!("{}", opts.usage(&brief));
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let mut opts = ArgParser::new();
+    opts.flag("", "help", "display this help and exit");
+    opts.flag("", "version", "output version information and exit");
+    // Operands like "-5" look like options, so, as in GNU expr, only a lone argument is one.
+    let mut operands = &args[1..];
+    if operands.len() == 1 && operands[0].starts_with("--") && operands[0] != "--" {
+        let options = match opts.parse(operands) {
+            Ok(m) => m,
+            Err(f) => trouble!("{}", f.to_string()),
+        };
+        if options.opt_present("help") {
+            return show_help(&opts);
+        }
+        return utils::version::print_version("expr");
+    }
+    if operands.first().map(|arg| arg.as_str()) == Some("--") {
+        operands = &operands[1..];
+    }
+
+    let value = expr(operands).unwrap_or_else(|e| trouble!("{}", e));
+    println!("{}", value);
+    if value.is_null() {
+        process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eval(args: &str) -> Result<String> {
+        let args: Vec<String> = args.split(' ').map(|arg| arg.to_owned()).collect();
+        expr(&args).map(|value| value.to_string())
+    }
+
+    #[test]
+    fn precedence() {
+        assert_eq!(eval("1 + 2 * 3"), Ok("7".to_owned()));
+        assert_eq!(eval("( 1 + 2 ) * 3"), Ok("9".to_owned()));
+        assert_eq!(eval("10 - 4 - 3"), Ok("3".to_owned()));
+        assert_eq!(eval("1 + 1 = 2"), Ok("1".to_owned()));
+        assert_eq!(eval("0 | 1 & 0"), Ok("0".to_owned()));
+        assert_eq!(eval("abc : a.* + 1"), Ok("4".to_owned()));
+        assert_eq!(eval("2 * length abc"), Ok("6".to_owned()));
+    }
+
+    #[test]
+    fn null_values() {
+        assert!(Value::String(String::new()).is_null());
+        assert!(Value::String("-00".to_owned()).is_null());
+        assert!(Value::Integer(0).is_null());
+        assert!(!Value::String("-".to_owned()).is_null());
+        assert!(!Value::String("0.0".to_owned()).is_null());
+    }
+
+    #[test]
+    fn short_circuit() {
+        assert_eq!(eval("1 | 1 / 0"), Ok("1".to_owned()));
+        assert_eq!(eval("0 & 1 / 0"), Ok("0".to_owned()));
+        assert_eq!(eval("0 | 1 / 0"), Err("division by zero".to_owned()));
+    }
+
+    #[test]
+    fn comparisons() {
+        assert_eq!(eval("10 > 9"), Ok("1".to_owned()));
+        assert_eq!(eval("10 > 9a"), Ok("0".to_owned()));
+        assert_eq!(eval("01 = 1"), Ok("1".to_owned()));
+        assert_eq!(eval("a != b"), Ok("1".to_owned()));
+    }
+
+    #[test]
+    fn functions() {
+        assert_eq!(substr("hello", 2, 3), "ell");
+        assert_eq!(substr("hello", 0, 3), "");
+        assert_eq!(substr("hello", 4, 10), "lo");
+        assert_eq!(index("hello", "ol"), 3);
+        assert_eq!(index("hello", "z"), 0);
+        assert_eq!(eval("+ length"), Ok("length".to_owned()));
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            eval("1 2"),
+            Err("syntax error: unexpected argument '2'".to_owned())
+        );
+        assert_eq!(
+            eval("1 +"),
+            Err("syntax error: missing argument after '+'".to_owned())
+        );
+        assert_eq!(eval("a + 1"), Err("non-integer argument".to_owned()));
+        assert_eq!(eval("5 % 0"), Err("division by zero".to_owned()));
+        assert!(eval("( 1").is_err());
+    }
+}
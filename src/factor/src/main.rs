@@ -0,0 +1,263 @@
+use std::env;
+use std::io::{self, BufRead, Write};
+
+#[macro_use(die)]
+extern crate utils;
+
+use utils::args::ArgParser;
+
+/// Factors below this bound are found by trial division, everything above by Pollard's rho.
+const TRIAL_DIVISION_LIMIT: u64 = 1000;
+
+fn mul_mod(a: u64, b: u64, m: u64) -> u64 {
+    ((a as u128 * b as u128) % m as u128) as u64
+}
+
+fn pow_mod(mut base: u64, mut exp: u64, m: u64) -> u64 {
+    let mut result = 1;
+    base %= m;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = mul_mod(result, base, m);
+        }
+        base = mul_mod(base, base, m);
+        exp >>= 1;
+    }
+    result
+}
+
+fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        let t = a % b;
+        a = b;
+        b = t;
+    }
+    a
+}
+
+/// Deterministic Miller-Rabin test; the witnesses below are sufficient for every 64-bit @p n.
+fn is_prime(n: u64) -> bool {
+    const WITNESSES: [u64; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+    if n < 2 {
+        return false;
+    }
+    for &p in &WITNESSES {
+        if n.is_multiple_of(p) {
+            return n == p;
+        }
+    }
+    let mut d = n - 1;
+    let mut s = 0;
+    while d.is_multiple_of(2) {
+        d /= 2;
+        s += 1;
+    }
+    'witness: for &a in &WITNESSES {
+        let mut x = pow_mod(a, d, n);
+        if x == 1 || x == n - 1 {
+            continue;
+        }
+        for _ in 1..s {
+            x = mul_mod(x, x, n);
+            if x == n - 1 {
+                continue 'witness;
+            }
+        }
+        return false;
+    }
+    true
+}
+
+/// Finds a non-trivial divisor of the odd composite @p n using Pollard's rho with Floyd's cycle
+/// detection.
+fn pollard_rho(n: u64) -> u64 {
+    let mut c = 1;
+    loop {
+        let f = |x: u64| ((mul_mod(x, x, n) as u128 + c) % n as u128) as u64;
+        let (mut x, mut y, mut d) = (2, 2, 1);
+        while d == 1 {
+            x = f(x);
+            y = f(f(y));
+            d = gcd(x.abs_diff(y), n);
+        }
+        if d != n {
+            return d;
+        }
+        // The walk degenerated, retry with another polynomial.
+        c += 1;
+    }
+}
+
+fn factor_large(n: u64, factors: &mut Vec<u64>) {
+    if n == 1 {
+        return;
+    }
+    if is_prime(n) {
+        factors.push(n);
+        return;
+    }
+    let d = pollard_rho(n);
+    factor_large(d, factors);
+    factor_large(n / d, factors);
+}
+
+/// Returns the prime factors of @p n in ascending order, repeated according to multiplicity.
+fn factor(mut n: u64) -> Vec<u64> {
+    let mut factors = Vec::new();
+    let mut p = 2;
+    while p < TRIAL_DIVISION_LIMIT && p * p <= n {
+        while n.is_multiple_of(p) {
+            factors.push(p);
+            n /= p;
+        }
+        p += if p == 2 { 1 } else { 2 };
+    }
+    if n > 1 && n < p * p {
+        factors.push(n);
+    } else {
+        let start = factors.len();
+        factor_large(n, &mut factors);
+        factors[start..].sort();
+    }
+    factors
+}
+
+fn format_factors(n: u64, factors: &[u64]) -> String {
+    let mut result = format!("{}:", n);
+    for p in factors {
+        result.push_str(&format!(" {}", p));
+    }
+    result
+}
+
+/// Factors a single operand and prints the result, returning false if it was not a valid number.
+fn print_factors(writer: &mut dyn Write, operand: &str) -> bool {
+    match operand.trim().parse::<u64>() {
+        Ok(n) if n > 0 => {
+            writeln!(writer, "{}", format_factors(n, &factor(n))).unwrap_or_else(|e| die!("{}", e));
+            true
+        }
+        _ => {
+            writer.flush().unwrap_or_else(|e| die!("{}", e));
+            eprintln!(
+                "{}: '{}' is not a valid positive integer",
+                env::args().next().unwrap(),
+                operand
+            );
+            false
+        }
+    }
+}
+
+fn show_help(opts: &ArgParser) {
+    let brief = format!(
+        concat!(
+            "Clone of the standard GNU factor.\n",
+            "Usage: {}: [NUMBER]...\n",
+            "Print the prime factors of each specified integer NUMBER. If none\n",
+            "are specified on the command line, read them from standard input."
+        ),
+        env::args().next().unwrap()
+    );
+    print!("{}", opts.usage(&brief));
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let mut opts = ArgParser::new();
+    opts.flag("h", "help", "display this help and exit");
+    opts.flag("v", "version", "output version information and exit");
+    let options = match opts.parse(&args[1..]) {
+        Ok(m) => m,
+        Err(f) => die!(code = 2, "{}", f.to_string()),
+    };
+    if options.opt_present("h") {
+        return show_help(&opts);
+    }
+    if options.opt_present("v") {
+        return utils::version::print_version("factor");
+    }
+
+    let stdout = io::stdout();
+    let mut writer = io::BufWriter::new(stdout.lock());
+    let mut ok = true;
+    if options.free.is_empty() {
+        let stdin = io::stdin();
+        for line in stdin.lock().lines() {
+            let line = line.unwrap_or_else(|e| die!("{}", e));
+            for operand in line.split_whitespace() {
+                ok &= print_factors(&mut writer, operand);
+            }
+        }
+    } else {
+        for operand in &options.free {
+            ok &= print_factors(&mut writer, operand);
+        }
+    }
+    writer.flush().unwrap_or_else(|e| die!("{}", e));
+    if !ok {
+        std::process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn small_numbers() {
+        assert_eq!(factor(1), vec![]);
+        assert_eq!(factor(2), vec![2]);
+        assert_eq!(factor(4), vec![2, 2]);
+        assert_eq!(factor(360), vec![2, 2, 2, 3, 3, 5]);
+        assert_eq!(factor(997), vec![997]);
+    }
+
+    #[test]
+    fn output_format() {
+        assert_eq!(format_factors(1, &factor(1)), "1:");
+        assert_eq!(format_factors(4, &factor(4)), "4: 2 2");
+    }
+
+    #[test]
+    fn invalid_operands() {
+        let mut output = Vec::new();
+        assert!(!print_factors(&mut output, "0"));
+        assert!(!print_factors(&mut output, "-4"));
+        assert!(!print_factors(&mut output, "abc"));
+        assert!(print_factors(&mut output, "12"));
+        assert_eq!(output, b"12: 2 2 3\n");
+    }
+
+    #[test]
+    fn primality() {
+        assert!(is_prime(2));
+        assert!(is_prime(1_000_000_007));
+        assert!(is_prime(18_446_744_073_709_551_557));
+        assert!(!is_prime(1));
+        assert!(!is_prime(561));
+        assert!(!is_prime(3_215_031_751));
+    }
+
+    #[test]
+    fn large_semiprimes() {
+        assert_eq!(
+            factor(1_000_000_016_000_000_063),
+            vec![1_000_000_007, 1_000_000_009]
+        );
+        assert_eq!(
+            factor(18_446_743_979_220_271_189),
+            vec![4_294_967_279, 4_294_967_291]
+        );
+        assert_eq!(factor(600_851_475_143), vec![71, 839, 1471, 6857]);
+    }
+
+    #[test]
+    fn largest_values() {
+        assert_eq!(
+            factor(u64::MAX),
+            vec![3, 5, 17, 257, 641, 65_537, 6_700_417]
+        );
+        assert_eq!(factor(1 << 63), vec![2; 63]);
+    }
+}
@@ -0,0 +1,281 @@
+use std::env;
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+use std::process;
+
+#[macro_use(warn, note_error)]
+extern crate utils;
+
+use utils::args::ArgParser;
+use utils::io::FileOrStdin;
+use utils::regex::{Regex, Syntax};
+
+/// Like `die!`, but with the exit status 2 that grep reserves for trouble.
+macro_rules! trouble {
+    ($($arg:tt)*) => ({
+        warn!($($arg)*);
+        process::exit(2);
+    });
+}
+
+/// How the selected lines are reported.
+struct Config {
+    invert: bool,
+    line_number: bool,
+    count: bool,
+    files_with_matches: bool,
+    with_filename: bool,
+}
+
+/// Searches @p reader, called @p name in the output, for the lines selected by @p regex and
+/// reports them to @p writer. Returns the number of selected lines.
+fn grep(
+    reader: &mut dyn BufRead,
+    name: &str,
+    regex: &Regex,
+    config: &Config,
+    writer: &mut dyn Write,
+) -> io::Result<u64> {
+    let mut selected = 0;
+    let mut line = Vec::new();
+    let mut number = 0;
+    loop {
+        line.clear();
+        if reader.read_until(b'\n', &mut line)? == 0 {
+            break;
+        }
+        number += 1;
+        if line.last() == Some(&b'\n') {
+            line.pop();
+        }
+        if regex.is_match(&line) == config.invert {
+            continue;
+        }
+        selected += 1;
+        if config.files_with_matches {
+            break;
+        }
+        if config.count {
+            continue;
+        }
+        if config.with_filename {
+            write!(writer, "{}:", name)?;
+        }
+        if config.line_number {
+            write!(writer, "{}:", number)?;
+        }
+        writer.write_all(&line)?;
+        writer.write_all(b"\n")?;
+    }
+    if config.files_with_matches {
+        if selected > 0 {
+            writeln!(writer, "{}", name)?;
+        }
+    } else if config.count {
+        if config.with_filename {
+            write!(writer, "{}:", name)?;
+        }
+        writeln!(writer, "{}", selected)?;
+    }
+    Ok(selected)
+}
+
+/// Searches the file @p name, or every file below it if it is a directory and @p recursive
+/// is set. Symbolic links found in directories are only followed if @p dereference is set.
+/// Returns the number of selected lines.
+fn grep_path(
+    name: &str,
+    recursive: bool,
+    dereference: bool,
+    regex: &Regex,
+    config: &Config,
+    writer: &mut dyn Write,
+) -> u64 {
+    // The empty name stands for the current directory, searched when there are no operands.
+    let dir = if name.is_empty() { "." } else { name };
+    if recursive && name != "-" && Path::new(dir).is_dir() {
+        let mut entries: Vec<_> = match fs::read_dir(dir) {
+            Ok(entries) => entries.filter_map(|entry| entry.ok()).collect(),
+            Err(e) => {
+                note_error!("{}: {}", name, e);
+                return 0;
+            }
+        };
+        entries.sort_by_key(|entry| entry.file_name());
+        let mut selected = 0;
+        for entry in entries {
+            let path = Path::new(name).join(entry.file_name());
+            let is_symlink = entry.file_type().map(|t| t.is_symlink()).unwrap_or(false);
+            if is_symlink && !dereference {
+                continue;
+            }
+            selected += grep_path(
+                &path.to_string_lossy(),
+                recursive,
+                dereference,
+                regex,
+                config,
+                writer,
+            );
+        }
+        return selected;
+    }
+
+    let mut reader = match FileOrStdin::open(name) {
+        Ok(reader) => reader,
+        Err(e) => {
+            note_error!("{}: {}", name, e);
+            return 0;
+        }
+    };
+    let shown_name = if name == "-" {
+        "(standard input)"
+    } else {
+        name
+    };
+    grep(&mut reader, shown_name, regex, config, writer).unwrap_or_else(|e| {
+        note_error!("{}: {}", name, e);
+        0
+    })
+}
+
+fn show_help(opts: &ArgParser) {
+    let brief = format!(
+        concat!(
+            "Clone of the standard GNU grep.\n",
+            "Usage: {}: [OPTION]... PATTERNS [FILE]...\n",
+            "Search for PATTERNS in each FILE.\n",
+            "PATTERNS can contain multiple patterns separated by newlines.\n",
+            "Back-references are not supported."
+        ),
+        env::args().next().unwrap()
+    );
+    print!("{}", opts.usage(&brief));
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let mut opts = ArgParser::new();
+    opts.flag(
+        "E",
+        "extended-regexp",
+        "PATTERNS are extended regular expressions",
+    );
+    opts.flag("F", "fixed-strings", "PATTERNS are strings");
+    opts.flag(
+        "G",
+        "basic-regexp",
+        "PATTERNS are basic regular expressions (the default)",
+    );
+    opts.option("e", "regexp", "use PATTERNS for matching", "PATTERNS");
+    opts.flag(
+        "i",
+        "ignore-case",
+        "ignore case distinctions in patterns and data",
+    );
+    opts.flag("v", "invert-match", "select non-matching lines");
+    opts.flag("n", "line-number", "print line number with output lines");
+    opts.flag("H", "with-filename", "print file name with output lines");
+    opts.flag(
+        "h",
+        "no-filename",
+        "suppress the file name prefix on output",
+    );
+    opts.flag(
+        "c",
+        "count",
+        "print only a count of selected lines per FILE",
+    );
+    opts.flag(
+        "l",
+        "files-with-matches",
+        "print only names of FILEs with selected lines",
+    );
+    opts.flag("r", "recursive", "search directories recursively");
+    opts.flag(
+        "R",
+        "dereference-recursive",
+        "likewise, but follow all symlinks",
+    );
+    opts.flag("", "help", "display this help and exit");
+    opts.flag("", "version", "output version information and exit");
+    let options = match opts.parse(&args[1..]) {
+        Ok(m) => m,
+        Err(f) => trouble!("{}", f.to_string()),
+    };
+    if options.opt_present("help") {
+        return show_help(&opts);
+    }
+    if options.opt_present("version") {
+        return utils::version::print_version("grep");
+    }
+
+    let mut files = options.free.clone();
+    let patterns = if options.opt_present("e") {
+        options.opt_strs("e")
+    } else if files.is_empty() {
+        trouble!("missing pattern");
+    } else {
+        vec![files.remove(0)]
+    };
+    let patterns: Vec<String> = patterns
+        .iter()
+        .flat_map(|pattern| pattern.split('\n'))
+        .map(|pattern| pattern.to_owned())
+        .collect();
+
+    // The last of -E, -F and -G wins.
+    let syntax = [
+        ("E", Syntax::Extended),
+        ("F", Syntax::Fixed),
+        ("G", Syntax::Basic),
+    ]
+    .iter()
+    .filter_map(|&(name, syntax)| {
+        options
+            .opt_positions(name)
+            .last()
+            .map(|&position| (position, syntax))
+    })
+    .max_by_key(|&(position, _)| position)
+    .map_or(Syntax::Basic, |(_, syntax)| syntax);
+    let regex = Regex::new(&patterns, syntax, options.opt_present("i"))
+        .unwrap_or_else(|e| trouble!("{}", e));
+
+    let dereference = options.opt_present("R");
+    let recursive = dereference || options.opt_present("r");
+    if files.is_empty() {
+        files.push(if recursive { "" } else { "-" }.to_owned());
+    }
+    let with_filename = match (
+        options.opt_positions("H").last(),
+        options.opt_positions("h").last(),
+    ) {
+        (Some(with), Some(without)) => with > without,
+        (Some(_), None) => true,
+        (None, Some(_)) => false,
+        (None, None) => files.len() > 1 || recursive,
+    };
+    let config = Config {
+        invert: options.opt_present("v"),
+        line_number: options.opt_present("n"),
+        count: options.opt_present("c"),
+        files_with_matches: options.opt_present("l"),
+        with_filename,
+    };
+
+    let stdout = io::stdout();
+    let mut writer = io::BufWriter::new(stdout.lock());
+    let mut selected = 0;
+    for file in &files {
+        selected += grep_path(file, recursive, dereference, &regex, &config, &mut writer);
+    }
+    writer.flush().unwrap_or_else(|e| trouble!("{}", e));
+    if utils::take_error() {
+        process::exit(2);
+    }
+    if selected == 0 {
+        process::exit(1);
+    }
+}
@@ -0,0 +1,171 @@
+use std::env;
+use std::ffi::{CStr, CString};
+use std::io;
+use std::mem;
+use std::ptr;
+
+#[macro_use(die)]
+extern crate utils;
+extern crate libc;
+
+use utils::args::ArgParser;
+use utils::users;
+
+/// Looks up the primary group of the user called @p name.
+fn primary_group(name: &str) -> Option<libc::gid_t> {
+    let c_name = CString::new(name).ok()?;
+    let mut buf = vec![0 as libc::c_char; 4096];
+    let mut pwd: libc::passwd = unsafe { mem::zeroed() };
+    let mut result: *mut libc::passwd = ptr::null_mut();
+    let ret = unsafe {
+        libc::getpwnam_r(
+            c_name.as_ptr(),
+            &mut pwd,
+            buf.as_mut_ptr(),
+            buf.len(),
+            &mut result,
+        )
+    };
+    if ret != 0 || result.is_null() {
+        return None;
+    }
+    Some(pwd.pw_gid)
+}
+
+/// Scans the group database for groups listing @p name as a member.
+fn member_groups(name: &str) -> Vec<libc::gid_t> {
+    let mut groups = Vec::new();
+    unsafe {
+        libc::setgrent();
+        loop {
+            let grp = libc::getgrent();
+            if grp.is_null() {
+                break;
+            }
+            let mut member = (*grp).gr_mem;
+            while !member.is_null() && !(*member).is_null() {
+                if CStr::from_ptr(*member).to_bytes() == name.as_bytes() {
+                    groups.push((*grp).gr_gid);
+                    break;
+                }
+                member = member.offset(1);
+            }
+        }
+        libc::endgrent();
+    }
+    groups
+}
+
+/// The effective group of this process followed by its supplementary groups.
+fn current_groups() -> io::Result<Vec<libc::gid_t>> {
+    let count = unsafe { libc::getgroups(0, ptr::null_mut()) };
+    if count < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    let mut groups = vec![0 as libc::gid_t; count as usize];
+    let count = unsafe { libc::getgroups(count, groups.as_mut_ptr()) };
+    if count < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    groups.truncate(count as usize);
+    Ok(with_primary(unsafe { libc::getegid() }, groups))
+}
+
+/// Puts @p primary in front of @p others, dropping any later duplicates.
+fn with_primary(primary: libc::gid_t, others: Vec<libc::gid_t>) -> Vec<libc::gid_t> {
+    let mut all = vec![primary];
+    for gid in others {
+        if !all.contains(&gid) {
+            all.push(gid);
+        }
+    }
+    all
+}
+
+fn group_names(groups: &[libc::gid_t]) -> String {
+    let names: Vec<String> = groups
+        .iter()
+        .map(|gid| {
+            users::group_name(*gid)
+                .ok()
+                .flatten()
+                .unwrap_or_else(|| gid.to_string())
+        })
+        .collect();
+    names.join(" ")
+}
+
+fn show_help(opts: &ArgParser) {
+    let brief = format!(
+        concat!(
+            "Clone of the standard GNU groups.\n",
+            "Usage: {}: [OPTION]... [USERNAME]...\n",
+            "Print group memberships for each USERNAME or, if no USERNAME is specified, for\n",
+            "the current process."
+        ),
+        env::args().next().unwrap()
+    );
+    print!("{}", opts.usage(&brief));
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let mut opts = ArgParser::new();
+    opts.flag("h", "help", "display this help and exit");
+    opts.flag("v", "version", "output version information and exit");
+    let options = match opts.parse(&args[1..]) {
+        Ok(m) => m,
+        Err(f) => die!(code = 2, "{}", f.to_string()),
+    };
+    if options.opt_present("h") {
+        return show_help(&opts);
+    }
+    if options.opt_present("v") {
+        return utils::version::print_version("groups");
+    }
+
+    if options.free.is_empty() {
+        let groups = current_groups().unwrap_or_else(|e| die!("cannot get groups: {}", e));
+        return println!("{}", group_names(&groups));
+    }
+    let mut failed = false;
+    for user in &options.free {
+        match primary_group(user) {
+            Some(primary) => {
+                let groups = with_primary(primary, member_groups(user));
+                println!("{} : {}", user, group_names(&groups));
+            }
+            None => {
+                eprintln!("{}: '{}': no such user", env::args().next().unwrap(), user);
+                failed = true;
+            }
+        }
+    }
+    if failed {
+        std::process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn current_user_has_a_group() {
+        let groups = current_groups().unwrap();
+        assert!(!groups.is_empty());
+        assert_eq!(groups[0], unsafe { libc::getegid() });
+        assert!(!group_names(&groups).is_empty());
+    }
+
+    #[test]
+    fn named_user() {
+        assert_eq!(primary_group("root"), Some(0));
+        assert_eq!(primary_group("no such user, surely"), None);
+    }
+
+    #[test]
+    fn primary_group_is_not_repeated() {
+        assert_eq!(with_primary(5, vec![3, 5, 7]), vec![5, 3, 7]);
+    }
+}
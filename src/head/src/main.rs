@@ -0,0 +1,289 @@
+use std::collections::VecDeque;
+use std::env;
+use std::io::{self, BufRead, Read, Write};
+
+#[macro_use(die, note_error, warn)]
+extern crate utils;
+
+use utils::args::ArgParser;
+use utils::io::FileOrStdin;
+
+/// How much of each input is printed, in bytes or lines.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Count {
+    /// The first N.
+    First(u64),
+    /// All but the last N.
+    AllBut(u64),
+}
+
+/// Parses the [-]NUM of -c and -n, where NUM may end in k, m or g for powers of 1024.
+fn parse_count(count: &str) -> Option<Count> {
+    let (all_but, number) = match count.strip_prefix('-') {
+        Some(number) => (true, number),
+        None => (false, count),
+    };
+    let (number, multiplier) = match number.chars().last().map(|c| c.to_ascii_lowercase()) {
+        Some('k') => (&number[..number.len() - 1], 1 << 10),
+        Some('m') => (&number[..number.len() - 1], 1 << 20),
+        Some('g') => (&number[..number.len() - 1], 1 << 30),
+        _ => (number, 1),
+    };
+    let n = number.parse::<u64>().ok()?.checked_mul(multiplier)?;
+    Some(if all_but {
+        Count::AllBut(n)
+    } else {
+        Count::First(n)
+    })
+}
+
+fn read_some(reader: &mut dyn Read, buffer: &mut [u8]) -> io::Result<usize> {
+    loop {
+        match reader.read(buffer) {
+            Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+            result => return result,
+        }
+    }
+}
+
+/// Copies all of @p reader but its last @p n bytes to @p writer. The last bytes read are kept
+/// in a window of at most @p n bytes, which is only written once more input pushes them out,
+/// so the input is never held in memory whole.
+fn all_but_last_bytes(reader: &mut dyn Read, writer: &mut dyn Write, n: u64) -> io::Result<()> {
+    let mut window = VecDeque::new();
+    let mut buffer = vec![0; 65536];
+    loop {
+        let len = read_some(reader, &mut buffer)?;
+        if len == 0 {
+            return Ok(());
+        }
+        window.extend(&buffer[..len]);
+        let excess = (window.len() as u64).saturating_sub(n) as usize;
+        if excess > 0 {
+            let (front, back) = window.as_slices();
+            let from_front = excess.min(front.len());
+            writer.write_all(&front[..from_front])?;
+            writer.write_all(&back[..excess - from_front])?;
+            window.drain(..excess);
+        }
+    }
+}
+
+/// Copies all of @p reader but its last @p n lines to @p writer, keeping a window of lines
+/// as `all_but_last_bytes` does with bytes. A last line without @p delimiter counts as one.
+fn all_but_last_lines(
+    reader: &mut dyn BufRead,
+    writer: &mut dyn Write,
+    n: u64,
+    delimiter: u8,
+) -> io::Result<()> {
+    let mut window: VecDeque<Vec<u8>> = VecDeque::new();
+    let mut line = Vec::new();
+    while reader.read_until(delimiter, &mut line)? > 0 {
+        window.push_back(line);
+        line = if window.len() as u64 > n {
+            let mut oldest = window.pop_front().unwrap();
+            writer.write_all(&oldest)?;
+            oldest.clear();
+            oldest
+        } else {
+            Vec::new()
+        };
+    }
+    Ok(())
+}
+
+/// Copies the first @p n lines of @p reader to @p writer.
+fn first_lines(
+    reader: &mut dyn BufRead,
+    writer: &mut dyn Write,
+    n: u64,
+    delimiter: u8,
+) -> io::Result<()> {
+    let mut line = Vec::new();
+    for _ in 0..n {
+        line.clear();
+        if reader.read_until(delimiter, &mut line)? == 0 {
+            break;
+        }
+        writer.write_all(&line)?;
+    }
+    Ok(())
+}
+
+/// Copies the part of @p reader that @p count selects to @p writer.
+fn head(
+    reader: &mut dyn BufRead,
+    writer: &mut dyn Write,
+    count: Count,
+    lines: bool,
+    delimiter: u8,
+) -> io::Result<()> {
+    match (count, lines) {
+        (Count::First(n), false) => io::copy(&mut reader.take(n), writer).map(|_| ()),
+        (Count::AllBut(n), false) => all_but_last_bytes(reader, writer, n),
+        (Count::First(n), true) => first_lines(reader, writer, n, delimiter),
+        (Count::AllBut(n), true) => all_but_last_lines(reader, writer, n, delimiter),
+    }
+}
+
+fn show_help(opts: &ArgParser) {
+    let brief = format!(
+        concat!(
+            "Clone of the standard GNU head.\n",
+            "Usage: {}: [OPTION]... [FILE]...\n",
+            "Print the first 10 lines of each FILE to standard output, with a header giving\n",
+            "the file name if there is more than one. Without FILE, or when FILE is -, read\n",
+            "standard input. NUM may end in k, m or g for powers of 1024."
+        ),
+        env::args().next().unwrap()
+    );
+    print!("{}", opts.usage(&brief));
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let mut opts = ArgParser::new();
+    opts.option(
+        "c",
+        "bytes",
+        "print the first NUM bytes; with a leading '-', all but the last NUM bytes",
+        "[-]NUM",
+    );
+    opts.option(
+        "n",
+        "lines",
+        "print the first NUM lines (default 10); with a leading '-', all but the last NUM",
+        "[-]NUM",
+    );
+    opts.flag("q", "quiet", "never print headers giving file names");
+    opts.flag("v", "verbose", "always print headers giving file names");
+    opts.flag("z", "zero-terminated", "line delimiter is NUL, not newline");
+    opts.flag("h", "help", "display this help and exit");
+    opts.flag("", "version", "output version information and exit");
+    let options = match opts.parse(&args[1..]) {
+        Ok(m) => m,
+        Err(f) => die!(code = 2, "{}", f.to_string()),
+    };
+    if options.opt_present("h") {
+        return show_help(&opts);
+    }
+    if options.opt_present("version") {
+        return utils::version::print_version("head");
+    }
+
+    let (count, lines) = match (options.opt_str("c"), options.opt_str("n")) {
+        (Some(_), Some(_)) => die!(code = 2, "cannot print both bytes and lines"),
+        (Some(bytes), None) => match parse_count(&bytes) {
+            Some(count) => (count, false),
+            None => die!(code = 2, "invalid number of bytes: '{}'", bytes),
+        },
+        (None, Some(lines)) => match parse_count(&lines) {
+            Some(count) => (count, true),
+            None => die!(code = 2, "invalid number of lines: '{}'", lines),
+        },
+        (None, None) => (Count::First(10), true),
+    };
+    let delimiter = if options.opt_present("z") {
+        b'\0'
+    } else {
+        b'\n'
+    };
+    let names = if options.free.is_empty() {
+        vec!["-".to_owned()]
+    } else {
+        options.free.clone()
+    };
+    let headers = options.opt_present("v") || (names.len() > 1 && !options.opt_present("q"));
+
+    let stdout = io::stdout();
+    let mut writer = io::BufWriter::new(stdout.lock());
+    for (n, name) in names.iter().enumerate() {
+        let mut reader = match FileOrStdin::open(name) {
+            Ok(reader) => reader,
+            Err(e) => {
+                note_error!("{}: {}", name, e);
+                continue;
+            }
+        };
+        if headers {
+            let shown = if name == "-" { "standard input" } else { name };
+            let separator = if n > 0 { "\n" } else { "" };
+            writeln!(writer, "{}==> {} <==", separator, shown).unwrap_or_else(|e| die!("{}", e));
+        }
+        if let Err(e) = head(&mut reader, &mut writer, count, lines, delimiter) {
+            note_error!("{}: {}", name, e);
+        }
+    }
+    writer.flush().unwrap_or_else(|e| die!("{}", e));
+    utils::exit_if_error();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headed(input: &[u8], count: Count, lines: bool) -> Vec<u8> {
+        let mut output = Vec::new();
+        head(&mut &input[..], &mut output, count, lines, b'\n').unwrap();
+        output
+    }
+
+    #[test]
+    fn counts() {
+        assert_eq!(parse_count("5"), Some(Count::First(5)));
+        assert_eq!(parse_count("-5"), Some(Count::AllBut(5)));
+        assert_eq!(parse_count("2k"), Some(Count::First(2048)));
+        assert_eq!(parse_count("-1M"), Some(Count::AllBut(1 << 20)));
+        assert_eq!(parse_count("0"), Some(Count::First(0)));
+        for invalid in &["", "-", "x", "--1", "1x", "k", "99999999999g"] {
+            assert_eq!(parse_count(invalid), None, "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn first_bytes_and_lines() {
+        let input = b"a\nb\nc";
+        assert_eq!(headed(input, Count::First(3), false), b"a\nb");
+        assert_eq!(headed(input, Count::First(2), true), b"a\nb\n");
+        assert_eq!(headed(input, Count::First(10), true), input);
+        assert_eq!(headed(input, Count::First(0), true), b"");
+    }
+
+    #[test]
+    fn all_but_the_last_bytes() {
+        let input = b"abcdef";
+        assert_eq!(headed(input, Count::AllBut(2), false), b"abcd");
+        // All is printed for 0, and nothing for more bytes than there are.
+        assert_eq!(headed(input, Count::AllBut(0), false), input);
+        assert_eq!(headed(input, Count::AllBut(6), false), b"");
+        assert_eq!(headed(input, Count::AllBut(100), false), b"");
+        // The window wraps around as reads push bytes through it.
+        let input: Vec<u8> = (0..200_000u32).map(|n| n as u8).collect();
+        assert_eq!(
+            headed(&input, Count::AllBut(70_000), false),
+            &input[..130_000]
+        );
+    }
+
+    #[test]
+    fn all_but_the_last_lines() {
+        let input = b"a\nb\nc\n";
+        assert_eq!(headed(input, Count::AllBut(1), true), b"a\nb\n");
+        assert_eq!(headed(input, Count::AllBut(0), true), input);
+        assert_eq!(headed(input, Count::AllBut(3), true), b"");
+        assert_eq!(headed(input, Count::AllBut(10), true), b"");
+        // A last line without a new line is still a line.
+        assert_eq!(headed(b"a\nb\nc", Count::AllBut(1), true), b"a\nb\n");
+        let mut output = Vec::new();
+        head(
+            &mut &b"a\0b\0c"[..],
+            &mut output,
+            Count::AllBut(2),
+            true,
+            b'\0',
+        )
+        .unwrap();
+        assert_eq!(output, b"a\0");
+    }
+}
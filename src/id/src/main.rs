@@ -0,0 +1,217 @@
+use std::env;
+use std::io;
+use std::ptr;
+
+#[macro_use(die)]
+extern crate utils;
+extern crate libc;
+
+use utils::args::ArgParser;
+use utils::users;
+
+/// The credentials of the running process.
+#[derive(Debug)]
+struct Ids {
+    uid: libc::uid_t,
+    euid: libc::uid_t,
+    gid: libc::gid_t,
+    egid: libc::gid_t,
+    groups: Vec<libc::gid_t>,
+}
+
+impl Ids {
+    fn current() -> io::Result<Ids> {
+        let count = unsafe { libc::getgroups(0, ptr::null_mut()) };
+        if count < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let mut groups = vec![0 as libc::gid_t; count as usize];
+        let count = unsafe { libc::getgroups(count, groups.as_mut_ptr()) };
+        if count < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        groups.truncate(count as usize);
+        unsafe {
+            Ok(Ids {
+                uid: libc::getuid(),
+                euid: libc::geteuid(),
+                gid: libc::getgid(),
+                egid: libc::getegid(),
+                groups,
+            })
+        }
+    }
+
+    /// The effective (or @p real) group followed by the supplementary groups, without
+    /// duplicates.
+    fn all_groups(&self, real: bool) -> Vec<libc::gid_t> {
+        let mut all = vec![if real { self.gid } else { self.egid }];
+        for gid in &self.groups {
+            if !all.contains(gid) {
+                all.push(*gid);
+            }
+        }
+        all
+    }
+}
+
+/// Renders @p id as a name if @p names is set and a name is known, as a number otherwise.
+fn id_or_name(id: u32, name: io::Result<Option<String>>, names: bool) -> String {
+    match name {
+        Ok(Some(name)) if names => name,
+        _ => id.to_string(),
+    }
+}
+
+/// Renders "N(name)", or just "N" for ids without a known name.
+fn id_with_name(id: u32, name: io::Result<Option<String>>) -> String {
+    match name {
+        Ok(Some(name)) => format!("{}({})", id, name),
+        _ => id.to_string(),
+    }
+}
+
+/// The full `uid=... gid=... groups=...` description of @p ids.
+fn full_description(ids: &Ids) -> String {
+    let mut description = format!(
+        "uid={} gid={}",
+        id_with_name(ids.uid, users::user_name(ids.uid)),
+        id_with_name(ids.gid, users::group_name(ids.gid))
+    );
+    if ids.euid != ids.uid {
+        description += &format!(
+            " euid={}",
+            id_with_name(ids.euid, users::user_name(ids.euid))
+        );
+    }
+    if ids.egid != ids.gid {
+        description += &format!(
+            " egid={}",
+            id_with_name(ids.egid, users::group_name(ids.egid))
+        );
+    }
+    let groups: Vec<String> = ids
+        .all_groups(false)
+        .into_iter()
+        .map(|gid| id_with_name(gid, users::group_name(gid)))
+        .collect();
+    description + " groups=" + &groups.join(",")
+}
+
+fn show_help(opts: &ArgParser) {
+    let brief = format!(
+        concat!(
+            "Clone of the standard GNU id.\n",
+            "Usage: {}: [OPTION]...\n",
+            "Print user and group information for the current process."
+        ),
+        env::args().next().unwrap()
+    );
+    print!("{}", opts.usage(&brief));
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let mut opts = ArgParser::new();
+    opts.flag("g", "group", "print only the effective group ID");
+    opts.flag("G", "groups", "print all group IDs");
+    opts.flag("n", "name", "print a name instead of a number, for -ugG");
+    opts.flag(
+        "r",
+        "real",
+        "print the real ID instead of the effective ID, with -ugG",
+    );
+    opts.flag("u", "user", "print only the effective user ID");
+    opts.flag("h", "help", "display this help and exit");
+    opts.flag("v", "version", "output version information and exit");
+    let options = match opts.parse(&args[1..]) {
+        Ok(m) => m,
+        Err(f) => die!(code = 2, "{}", f.to_string()),
+    };
+    if options.opt_present("h") {
+        return show_help(&opts);
+    }
+    if options.opt_present("v") {
+        return utils::version::print_version("id");
+    }
+    if !options.free.is_empty() {
+        die!(code = 2, "extra operand '{}'", options.free[0]);
+    }
+    let selected = ["u", "g", "G"]
+        .iter()
+        .filter(|flag| options.opt_present(flag))
+        .count();
+    if selected > 1 {
+        die!(code = 2, "cannot print \"only\" of more than one choice");
+    }
+    let names = options.opt_present("n");
+    let real = options.opt_present("r");
+    if selected == 0 && (names || real) {
+        die!(
+            code = 2,
+            "cannot print only names or real IDs in default format"
+        );
+    }
+
+    let ids = Ids::current().unwrap_or_else(|e| die!("cannot get groups: {}", e));
+    if options.opt_present("u") {
+        let uid = if real { ids.uid } else { ids.euid };
+        println!("{}", id_or_name(uid, users::user_name(uid), names));
+    } else if options.opt_present("g") {
+        let gid = if real { ids.gid } else { ids.egid };
+        println!("{}", id_or_name(gid, users::group_name(gid), names));
+    } else if options.opt_present("G") {
+        let groups: Vec<String> = ids
+            .all_groups(real)
+            .into_iter()
+            .map(|gid| id_or_name(gid, users::group_name(gid), names))
+            .collect();
+        println!("{}", groups.join(" "));
+    } else {
+        println!("{}", full_description(&ids));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command;
+
+    #[test]
+    fn default_format() {
+        let description = full_description(&Ids::current().unwrap());
+        assert!(description.starts_with("uid="));
+        assert!(description.contains(" gid="));
+        assert!(description.contains(" groups="));
+    }
+
+    #[test]
+    fn user_name_matches_whoami() {
+        let ids = Ids::current().unwrap();
+        let name = id_or_name(ids.euid, users::user_name(ids.euid), true);
+        if let Ok(output) = Command::new("whoami").output() {
+            assert_eq!(String::from_utf8(output.stdout).unwrap().trim_end(), name);
+        }
+    }
+
+    #[test]
+    fn formatting() {
+        assert_eq!(id_with_name(0, Ok(Some("root".to_owned()))), "0(root)");
+        assert_eq!(id_with_name(12345, Ok(None)), "12345");
+        assert_eq!(id_or_name(0, Ok(Some("root".to_owned())), false), "0");
+        assert_eq!(id_or_name(12345, Ok(None), true), "12345");
+    }
+
+    #[test]
+    fn primary_group_comes_first() {
+        let ids = Ids {
+            uid: 1,
+            euid: 1,
+            gid: 5,
+            egid: 6,
+            groups: vec![3, 5, 6],
+        };
+        assert_eq!(ids.all_groups(false), vec![6, 3, 5]);
+        assert_eq!(ids.all_groups(true), vec![5, 3, 6]);
+    }
+}
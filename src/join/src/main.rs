@@ -0,0 +1,567 @@
+use std::cmp::Ordering;
+use std::env;
+use std::io::{self, BufRead, Write};
+use std::str;
+
+#[macro_use(die)]
+extern crate utils;
+
+use utils::args::ArgParser;
+use utils::io::FileOrStdin;
+
+/// A field of the -o output format.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum OutputField {
+    /// The join field, 0 in the format.
+    Key,
+    /// Field N.M as the 0-based file and field numbers.
+    Field(usize, usize),
+}
+
+struct JoinConfig {
+    /// The 0-based join field of each file.
+    fields: [usize; 2],
+    /// The field separator, a single character of one or more bytes; fields are separated by
+    /// runs of blanks if there is none.
+    separator: Option<Vec<u8>>,
+    ignore_case: bool,
+    /// Whether the unpairable lines of each file are printed.
+    unpaired: [bool; 2],
+    /// Whether the joined lines are printed, which -v turns off.
+    paired: bool,
+    format: Option<Vec<OutputField>>,
+    /// What the -o format prints for fields missing from the input.
+    empty: Vec<u8>,
+}
+
+/// A line split into fields, which are kept as ranges of its text.
+struct Line {
+    text: Vec<u8>,
+    fields: Vec<(usize, usize)>,
+}
+
+impl Line {
+    fn new(mut text: Vec<u8>, separator: Option<&[u8]>) -> Line {
+        if text.last() == Some(&b'\n') {
+            text.pop();
+        }
+        let mut fields = Vec::new();
+        match separator {
+            Some(_) if text.is_empty() => {}
+            Some(separator) => {
+                // A UTF-8 separator cannot match in the middle of another character, so
+                // looking for its bytes is enough.
+                let mut start = 0;
+                let mut i = 0;
+                while i + separator.len() <= text.len() {
+                    if text[i..].starts_with(separator) {
+                        fields.push((start, i));
+                        i += separator.len();
+                        start = i;
+                    } else {
+                        i += 1;
+                    }
+                }
+                fields.push((start, text.len()));
+            }
+            None => {
+                let mut start = None;
+                for (i, &c) in text.iter().enumerate() {
+                    if c == b' ' || c == b'\t' {
+                        if let Some(start) = start.take() {
+                            fields.push((start, i));
+                        }
+                    } else if start.is_none() {
+                        start = Some(i);
+                    }
+                }
+                if let Some(start) = start {
+                    fields.push((start, text.len()));
+                }
+            }
+        }
+        Line { text, fields }
+    }
+
+    fn field(&self, n: usize) -> Option<&[u8]> {
+        self.fields
+            .get(n)
+            .map(|&(start, end)| &self.text[start..end])
+    }
+}
+
+/// One of the inputs, with the line at which the merge currently is.
+struct Input<'a> {
+    reader: Box<dyn BufRead + 'a>,
+    /// The 0-based number of the input.
+    file: usize,
+    current: Option<Line>,
+}
+
+impl<'a> Input<'a> {
+    fn new(
+        reader: Box<dyn BufRead + 'a>,
+        file: usize,
+        config: &JoinConfig,
+    ) -> io::Result<Input<'a>> {
+        let mut input = Input {
+            reader,
+            file,
+            current: None,
+        };
+        input.advance(config)?;
+        Ok(input)
+    }
+
+    fn advance(&mut self, config: &JoinConfig) -> io::Result<()> {
+        let mut text = Vec::new();
+        self.current = if self.reader.read_until(b'\n', &mut text)? == 0 {
+            None
+        } else {
+            Some(Line::new(text, config.separator.as_deref()))
+        };
+        Ok(())
+    }
+
+    /// Takes the current line and all the lines that follow it with the same key.
+    fn take_group(&mut self, config: &JoinConfig) -> io::Result<Vec<Line>> {
+        let mut group: Vec<Line> = self.current.take().into_iter().collect();
+        loop {
+            self.advance(config)?;
+            let same = match self.current {
+                Some(ref line) => {
+                    compare(
+                        key(line, self.file, config),
+                        key(&group[0], self.file, config),
+                        config,
+                    ) == Ordering::Equal
+                }
+                None => false,
+            };
+            if !same {
+                return Ok(group);
+            }
+            group.extend(self.current.take());
+        }
+    }
+}
+
+/// The join field of @p line from @p file, which is empty if the line is too short.
+fn key<'l>(line: &'l Line, file: usize, config: &JoinConfig) -> &'l [u8] {
+    line.field(config.fields[file]).unwrap_or(b"")
+}
+
+/// Compares two keys, with -i folding the case of UTF-8 ones fully and of others in ASCII.
+fn compare(a: &[u8], b: &[u8], config: &JoinConfig) -> Ordering {
+    if !config.ignore_case {
+        return a.cmp(b);
+    }
+    match (str::from_utf8(a), str::from_utf8(b)) {
+        (Ok(a), Ok(b)) => a
+            .chars()
+            .flat_map(char::to_lowercase)
+            .cmp(b.chars().flat_map(char::to_lowercase)),
+        _ => a
+            .iter()
+            .map(u8::to_ascii_lowercase)
+            .cmp(b.iter().map(u8::to_ascii_lowercase)),
+    }
+}
+
+/// Writes the output line for a pair of lines, or for an unpairable line if one of @p lines is
+/// missing.
+fn write_line(
+    writer: &mut dyn Write,
+    config: &JoinConfig,
+    lines: [Option<&Line>; 2],
+) -> io::Result<()> {
+    let separator = config.separator.as_deref().unwrap_or(b" ");
+    let join_field = match lines {
+        [Some(line), _] => key(line, 0, config),
+        [None, Some(line)] => key(line, 1, config),
+        [None, None] => b"",
+    };
+    match config.format {
+        Some(ref format) => {
+            for (n, field) in format.iter().enumerate() {
+                if n > 0 {
+                    writer.write_all(separator)?;
+                }
+                let value = match *field {
+                    OutputField::Key if lines.iter().any(Option::is_some) => Some(join_field),
+                    OutputField::Key => None,
+                    OutputField::Field(file, n) => lines[file].and_then(|line| line.field(n)),
+                };
+                writer.write_all(value.unwrap_or(&config.empty))?;
+            }
+        }
+        None => {
+            writer.write_all(join_field)?;
+            for (file, line) in lines.iter().enumerate() {
+                if let Some(line) = *line {
+                    for n in (0..line.fields.len()).filter(|&n| n != config.fields[file]) {
+                        writer.write_all(separator)?;
+                        writer.write_all(line.field(n).unwrap())?;
+                    }
+                }
+            }
+        }
+    }
+    writer.write_all(b"\n")
+}
+
+/// Walks two inputs sorted on their join fields in lockstep and writes the joined lines, and
+/// the unpairable ones that @p config asks for.
+fn join(
+    first: Box<dyn BufRead + '_>,
+    second: Box<dyn BufRead + '_>,
+    config: &JoinConfig,
+    writer: &mut dyn Write,
+) -> io::Result<()> {
+    let mut inputs = [
+        Input::new(first, 0, config)?,
+        Input::new(second, 1, config)?,
+    ];
+    loop {
+        let order = match (&inputs[0].current, &inputs[1].current) {
+            (None, None) => return Ok(()),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (Some(a), Some(b)) => compare(key(a, 0, config), key(b, 1, config), config),
+        };
+        let unpaired = match order {
+            Ordering::Less => 0,
+            Ordering::Greater => 1,
+            Ordering::Equal => {
+                let first = inputs[0].take_group(config)?;
+                let second = inputs[1].take_group(config)?;
+                if config.paired {
+                    for a in &first {
+                        for b in &second {
+                            write_line(writer, config, [Some(a), Some(b)])?;
+                        }
+                    }
+                }
+                continue;
+            }
+        };
+        if config.unpaired[unpaired] {
+            let mut lines = [None, None];
+            lines[unpaired] = inputs[unpaired].current.as_ref();
+            write_line(writer, config, lines)?;
+        }
+        inputs[unpaired].advance(config)?;
+    }
+}
+
+/// Parses a 1-based field number into a 0-based one.
+fn parse_field(field: &str) -> usize {
+    match field.parse::<usize>() {
+        Ok(n) if n > 0 => n - 1,
+        _ => die!(code = 2, "invalid field number: '{}'", field),
+    }
+}
+
+/// Parses a file number, 1 or 2, into a 0-based one.
+fn parse_file(file: &str) -> usize {
+    match file {
+        "1" => 0,
+        "2" => 1,
+        _ => die!(code = 2, "invalid file number: '{}'", file),
+    }
+}
+
+/// Parses the -o format: a list of 0 or N.M separated by commas or blanks.
+fn parse_format(format: &str) -> Vec<OutputField> {
+    format
+        .split([',', ' ', '\t'])
+        .filter(|spec| !spec.is_empty())
+        .map(|spec| {
+            if spec == "0" {
+                return OutputField::Key;
+            }
+            match spec.find('.') {
+                Some(dot) if matches!(&spec[..dot], "1" | "2") => {
+                    OutputField::Field(parse_file(&spec[..dot]), parse_field(&spec[dot + 1..]))
+                }
+                _ => die!(code = 2, "invalid field specifier: '{}'", spec),
+            }
+        })
+        .collect()
+}
+
+fn show_help(opts: &ArgParser) {
+    let brief = format!(
+        concat!(
+            "Clone of the standard GNU join.\n",
+            "Usage: {}: [OPTION]... FILE1 FILE2\n",
+            "For each pair of input lines with identical join fields, write a line to\n",
+            "standard output. The default join field is the first, delimited by blanks.\n",
+            "Both files must be sorted on the join fields."
+        ),
+        env::args().next().unwrap()
+    );
+    print!("{}", opts.usage(&brief));
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let mut opts = ArgParser::new();
+    opts.option(
+        "a",
+        "",
+        "also print unpairable lines from file FILENUM, where FILENUM is 1 or 2",
+        "FILENUM",
+    );
+    opts.option(
+        "e",
+        "",
+        "replace missing input fields with EMPTY in the -o format",
+        "EMPTY",
+    );
+    opts.flag(
+        "i",
+        "ignore-case",
+        "ignore differences in case when comparing fields",
+    );
+    opts.option("j", "", "equivalent to '-1 FIELD -2 FIELD'", "FIELD");
+    opts.option(
+        "o",
+        "",
+        "obey FORMAT while constructing output line",
+        "FORMAT",
+    );
+    opts.option(
+        "t",
+        "",
+        "use CHAR as input and output field separator",
+        "CHAR",
+    );
+    opts.option(
+        "v",
+        "",
+        "like -a FILENUM, but suppress joined output lines",
+        "FILENUM",
+    );
+    opts.option("1", "", "join on this FIELD of file 1", "FIELD");
+    opts.option("2", "", "join on this FIELD of file 2", "FIELD");
+    opts.flag("h", "help", "display this help and exit");
+    opts.flag("", "version", "output version information and exit");
+    let options = match opts.parse(&args[1..]) {
+        Ok(m) => m,
+        Err(f) => die!(code = 2, "{}", f.to_string()),
+    };
+    if options.opt_present("h") {
+        return show_help(&opts);
+    }
+    if options.opt_present("version") {
+        return utils::version::print_version("join");
+    }
+    if options.free.len() < 2 {
+        die!(code = 2, "missing operand");
+    } else if options.free.len() > 2 {
+        die!(code = 2, "extra operand '{}'", options.free[2]);
+    }
+
+    let field = |name: &str| {
+        options
+            .opt_str(name)
+            .or_else(|| options.opt_str("j"))
+            .map_or(0, |field| parse_field(&field))
+    };
+    let separator = options.opt_str("t").map(|tab| match tab.chars().count() {
+        1 => tab.into_bytes(),
+        _ => die!(code = 2, "multi-character tab '{}'", tab),
+    });
+    let mut unpaired = [false, false];
+    for file in options.opt_strs("a").iter().chain(&options.opt_strs("v")) {
+        unpaired[parse_file(file)] = true;
+    }
+    let format = options.opt_strs("o");
+    let config = JoinConfig {
+        fields: [field("1"), field("2")],
+        separator,
+        ignore_case: options.opt_present("i"),
+        unpaired,
+        paired: !options.opt_present("v"),
+        format: if format.is_empty() {
+            None
+        } else {
+            Some(parse_format(&format.join(",")))
+        },
+        empty: options.opt_str("e").unwrap_or_default().into_bytes(),
+    };
+
+    let open = |name: &str| -> Box<dyn BufRead> {
+        match FileOrStdin::open(name) {
+            Ok(input) => Box::new(input),
+            Err(e) => die!("{}: {}", name, e),
+        }
+    };
+    let stdout = io::stdout();
+    let mut writer = io::BufWriter::new(stdout.lock());
+    join(
+        open(&options.free[0]),
+        open(&options.free[1]),
+        &config,
+        &mut writer,
+    )
+    .and_then(|_| writer.flush())
+    .unwrap_or_else(|e| die!("{}", e));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> JoinConfig {
+        JoinConfig {
+            fields: [0, 0],
+            separator: None,
+            ignore_case: false,
+            unpaired: [false, false],
+            paired: true,
+            format: None,
+            empty: Vec::new(),
+        }
+    }
+
+    fn run(first: &'static str, second: &'static str, config: &JoinConfig) -> String {
+        let mut output = Vec::new();
+        join(
+            Box::new(first.as_bytes()),
+            Box::new(second.as_bytes()),
+            config,
+            &mut output,
+        )
+        .unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    const FIRST: &str = "a 1\nb 2\nb 3\nd 4\n";
+    const SECOND: &str = "b x\nb y\nc z\nd w extra\n";
+
+    #[test]
+    fn fields() {
+        let line = Line::new(b"  a \tb  c \n".to_vec(), None);
+        assert_eq!(line.fields.len(), 3);
+        assert_eq!(line.field(1), Some(&b"b"[..]));
+        assert_eq!(line.field(3), None);
+        let line = Line::new(b":a::b".to_vec(), Some(b":"));
+        let fields: Vec<_> = (0..line.fields.len())
+            .map(|n| line.field(n).unwrap())
+            .collect();
+        assert_eq!(fields, [&b""[..], b"a", b"", b"b"]);
+        assert!(Line::new(b"\n".to_vec(), Some(b":")).fields.is_empty());
+        let line = Line::new("x§§y§".as_bytes().to_vec(), Some("§".as_bytes()));
+        let fields: Vec<_> = (0..line.fields.len())
+            .map(|n| line.field(n).unwrap())
+            .collect();
+        assert_eq!(fields, [&b"x"[..], b"", b"y", b""]);
+    }
+
+    #[test]
+    fn groups_are_joined_pairwise() {
+        assert_eq!(
+            run(FIRST, SECOND, &config()),
+            "b 2 x\nb 2 y\nb 3 x\nb 3 y\nd 4 w extra\n"
+        );
+    }
+
+    #[test]
+    fn unpairable_lines() {
+        let expected = [
+            (
+                [true, false],
+                true,
+                "a 1\nb 2 x\nb 2 y\nb 3 x\nb 3 y\nd 4 w extra\n",
+            ),
+            (
+                [false, true],
+                true,
+                "b 2 x\nb 2 y\nb 3 x\nb 3 y\nc z\nd 4 w extra\n",
+            ),
+            (
+                [true, true],
+                true,
+                "a 1\nb 2 x\nb 2 y\nb 3 x\nb 3 y\nc z\nd 4 w extra\n",
+            ),
+            ([true, false], false, "a 1\n"),
+            ([false, true], false, "c z\n"),
+            ([true, true], false, "a 1\nc z\n"),
+        ];
+        for &(unpaired, paired, output) in &expected {
+            let config = JoinConfig {
+                unpaired,
+                paired,
+                ..config()
+            };
+            assert_eq!(run(FIRST, SECOND, &config), output, "{:?}", unpaired);
+        }
+        // Lines left over at the end of either input are unpairable too.
+        let config = JoinConfig {
+            unpaired: [true, true],
+            ..config()
+        };
+        assert_eq!(run("a\nb\nc\n", "a\n", &config), "a\nb\nc\n");
+        assert_eq!(run("", "a\nb\n", &config), "a\nb\n");
+    }
+
+    #[test]
+    fn output_format() {
+        let config = JoinConfig {
+            unpaired: [true, true],
+            format: Some(parse_format("0,1.2 2.2,2.3")),
+            empty: b"NA".to_vec(),
+            ..config()
+        };
+        assert_eq!(
+            run(FIRST, SECOND, &config),
+            "a 1 NA NA\nb 2 x NA\nb 2 y NA\nb 3 x NA\nb 3 y NA\nc NA z NA\nd 4 w extra\n"
+        );
+        assert_eq!(
+            parse_format("2.1, 0"),
+            [OutputField::Field(1, 0), OutputField::Key]
+        );
+    }
+
+    #[test]
+    fn join_fields_and_separator() {
+        let config = JoinConfig {
+            fields: [1, 0],
+            separator: Some(b":".to_vec()),
+            ..config()
+        };
+        assert_eq!(
+            run("1:x:\n2:y\n", "x:a\ny:b:c\n", &config),
+            "x:1::a\ny:2:b:c\n"
+        );
+    }
+
+    #[test]
+    fn ignore_case() {
+        let mut config = config();
+        assert_eq!(run("A 1\n", "a 2\n", &config), "");
+        config.ignore_case = true;
+        assert_eq!(run("A 1\nb 2\n", "a 3\nB 4\n", &config), "A 1 3\nb 2 4\n");
+        // Case is folded beyond ASCII, and other bytes still compare in ASCII.
+        assert_eq!(
+            run("Émile 1\nÖl 2\n", "émile x\nöl y\n", &config),
+            "Émile 1 x\nÖl 2 y\n"
+        );
+        assert_eq!(compare(b"A\xff", b"a\xff", &config), Ordering::Equal);
+    }
+
+    #[test]
+    fn multi_byte_separator() {
+        let config = JoinConfig {
+            separator: Some("§".as_bytes().to_vec()),
+            ignore_case: true,
+            ..config()
+        };
+        assert_eq!(
+            run("ÉTÉ§1§a\nzèbre§2\n", "été§x\nZÈBRE§y§z\n", &config),
+            "ÉTÉ§1§a§x\nzèbre§2§y§z\n"
+        );
+    }
+}
@@ -0,0 +1,84 @@
+use std::env;
+use std::ffi::CStr;
+
+#[macro_use(die)]
+extern crate utils;
+extern crate libc;
+
+use utils::args::ArgParser;
+
+extern "C" {
+    // Part of POSIX, but not exposed by the libc crate.
+    fn getlogin_r(buf: *mut libc::c_char, bufsize: libc::size_t) -> libc::c_int;
+}
+
+/// The name the user logged in with. Unlike `whoami`, which reports the effective user, this
+/// stays the same after `su` or a setuid program switches to another user.
+fn login_name() -> Option<String> {
+    if let Ok(name) = env::var("LOGNAME") {
+        if !name.is_empty() {
+            return Some(name);
+        }
+    }
+    let mut buf = vec![0 as libc::c_char; 256];
+    if unsafe { getlogin_r(buf.as_mut_ptr(), buf.len()) } != 0 {
+        return None;
+    }
+    let name = unsafe { CStr::from_ptr(buf.as_ptr()) }
+        .to_string_lossy()
+        .into_owned();
+    if name.is_empty() {
+        None
+    } else {
+        Some(name)
+    }
+}
+
+fn show_help(opts: &ArgParser) {
+    let brief = format!(
+        concat!(
+            "Clone of the standard GNU logname.\n",
+            "Usage: {}: [OPTION]\n",
+            "Print the name of the current user."
+        ),
+        env::args().next().unwrap()
+    );
+    print!("{}", opts.usage(&brief));
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let mut opts = ArgParser::new();
+    opts.flag("h", "help", "display this help and exit");
+    opts.flag("v", "version", "output version information and exit");
+    let options = match opts.parse(&args[1..]) {
+        Ok(m) => m,
+        Err(f) => die!(code = 2, "{}", f.to_string()),
+    };
+    if options.opt_present("h") {
+        return show_help(&opts);
+    }
+    if options.opt_present("v") {
+        return utils::version::print_version("logname");
+    }
+    if !options.free.is_empty() {
+        die!(code = 2, "extra operand '{}'", options.free[0]);
+    }
+
+    match login_name() {
+        Some(name) => println!("{}", name),
+        None => die!("no login name"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn logname_variable_is_used() {
+        // A typical login session sets LOGNAME; provide it here as test runners may not.
+        env::set_var("LOGNAME", "tester");
+        assert_eq!(login_name(), Some("tester".to_owned()));
+    }
+}
@@ -0,0 +1,297 @@
+use std::cmp::Ordering;
+use std::env;
+use std::fs;
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::process;
+
+#[macro_use(warn)]
+extern crate utils;
+
+use utils::args::ArgParser;
+
+/// Like `die!`, but with the exit status 2 that look reserves for trouble.
+macro_rules! trouble {
+    ($($arg:tt)*) => ({
+        warn!($($arg)*);
+        process::exit(2);
+    });
+}
+
+const DICTIONARY: &str = "/usr/share/dict/words";
+
+/// The string that lines are looked up by, and how they are compared to it.
+struct Key {
+    string: Vec<u8>,
+    fold_case: bool,
+    /// Whether only blanks and alphanumeric characters are compared.
+    dictionary: bool,
+}
+
+impl Key {
+    /// With @p terminator, only the part of @p string up to and including its first occurrence
+    /// is looked up.
+    fn new(string: &[u8], fold_case: bool, dictionary: bool, terminator: Option<u8>) -> Key {
+        let end = terminator
+            .and_then(|terminator| string.iter().position(|&c| c == terminator))
+            .map_or(string.len(), |end| end + 1);
+        let mut key = Key {
+            string: Vec::new(),
+            fold_case,
+            dictionary,
+        };
+        key.string = key.normalize(&string[..end]).collect();
+        key
+    }
+
+    /// The characters of @p text which take part in comparisons.
+    fn normalize<'t>(&self, text: &'t [u8]) -> impl Iterator<Item = u8> + 't {
+        let (fold_case, dictionary) = (self.fold_case, self.dictionary);
+        text.iter()
+            .filter(move |c| {
+                !dictionary || c.is_ascii_alphanumeric() || *c == &b' ' || *c == &b'\t'
+            })
+            .map(move |&c| if fold_case { c.to_ascii_lowercase() } else { c })
+    }
+
+    /// How the start of @p line sorts against the key: Equal if the line begins with it.
+    fn compare(&self, line: &[u8]) -> Ordering {
+        let line = line.strip_suffix(b"\n").unwrap_or(line);
+        let mut line = self.normalize(line);
+        for &c in &self.string {
+            match line.next() {
+                Some(l) if l == c => {}
+                Some(l) => return l.cmp(&c),
+                None => return Ordering::Less,
+            }
+        }
+        Ordering::Equal
+    }
+}
+
+/// Writes @p line, ending it with a newline if the input's last line lacks one.
+fn write_line(writer: &mut dyn Write, line: &[u8]) -> io::Result<()> {
+    writer.write_all(line)?;
+    if line.last() != Some(&b'\n') {
+        writer.write_all(b"\n")?;
+    }
+    Ok(())
+}
+
+/// The offset and contents of the first line that starts at or after @p pos, if any.
+fn line_at<F: Read + Seek>(file: &mut F, pos: u64) -> io::Result<Option<(u64, Vec<u8>)>> {
+    let mut start = pos;
+    let mut reader = if pos == 0 {
+        file.seek(SeekFrom::Start(0))?;
+        BufReader::new(file)
+    } else {
+        // Start one byte early, so that a line which starts right at @p pos is not skipped.
+        file.seek(SeekFrom::Start(pos - 1))?;
+        let mut reader = BufReader::new(file);
+        start = pos - 1 + reader.read_until(b'\n', &mut Vec::new())? as u64;
+        reader
+    };
+    let mut line = Vec::new();
+    if reader.read_until(b'\n', &mut line)? == 0 {
+        return Ok(None);
+    }
+    Ok(Some((start, line)))
+}
+
+/// Looks up the lines that begin with @p key in @p file, which is sorted, by bisecting it.
+/// Returns whether there were any.
+fn look_sorted<F: Read + Seek>(
+    file: &mut F,
+    key: &Key,
+    writer: &mut dyn Write,
+) -> io::Result<bool> {
+    // The lowest offset at which the first line at or after it does not sort before the key.
+    let (mut low, mut high) = (0, file.seek(SeekFrom::End(0))?);
+    while low < high {
+        let mid = low + (high - low) / 2;
+        match line_at(file, mid)? {
+            Some((_, ref line)) if key.compare(line) == Ordering::Less => low = mid + 1,
+            _ => high = mid,
+        }
+    }
+    let start = match line_at(file, low)? {
+        Some((start, _)) => start,
+        None => return Ok(false),
+    };
+    file.seek(SeekFrom::Start(start))?;
+    let mut reader = BufReader::new(file);
+    let mut found = false;
+    let mut line = Vec::new();
+    while reader.read_until(b'\n', &mut line)? != 0 {
+        if key.compare(&line) != Ordering::Equal {
+            break;
+        }
+        write_line(writer, &line)?;
+        found = true;
+        line.clear();
+    }
+    Ok(found)
+}
+
+/// Looks up the lines that begin with @p key by reading all of @p reader.
+fn look_linear(reader: &mut dyn BufRead, key: &Key, writer: &mut dyn Write) -> io::Result<bool> {
+    let mut found = false;
+    let mut line = Vec::new();
+    while reader.read_until(b'\n', &mut line)? != 0 {
+        if key.compare(&line) == Ordering::Equal {
+            write_line(writer, &line)?;
+            found = true;
+        }
+        line.clear();
+    }
+    Ok(found)
+}
+
+fn show_help(opts: &ArgParser) {
+    let brief = format!(
+        concat!(
+            "Clone of the standard BSD look.\n",
+            "Usage: {}: [OPTION]... STRING [FILE]\n",
+            "Display the lines of FILE that begin with STRING. Without FILE, look up\n",
+            "STRING in {} with -d and -f, by binary search.\n",
+            "Exit status is 0 if any line was found, 1 if none was and 2 in case of trouble."
+        ),
+        env::args().next().unwrap(),
+        DICTIONARY
+    );
+    print!("{}", opts.usage(&brief));
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let mut opts = ArgParser::new();
+    opts.flag(
+        "b",
+        "binary",
+        "use a binary search, which needs FILE to be sorted",
+    );
+    opts.flag(
+        "d",
+        "alphanum",
+        "compare only blanks and alphanumeric characters",
+    );
+    opts.flag("f", "ignore-case", "ignore the case of letters");
+    opts.option(
+        "t",
+        "terminate",
+        "look up STRING only up to and including the first CHAR",
+        "CHAR",
+    );
+    opts.flag("h", "help", "display this help and exit");
+    opts.flag("v", "version", "output version information and exit");
+    let options = match opts.parse(&args[1..]) {
+        Ok(m) => m,
+        Err(f) => trouble!("{}", f.to_string()),
+    };
+    if options.opt_present("h") {
+        return show_help(&opts);
+    }
+    if options.opt_present("v") {
+        return utils::version::print_version("look");
+    }
+    match options.free.len() {
+        0 => trouble!("missing operand"),
+        1 | 2 => {}
+        _ => trouble!("extra operand '{}'", options.free[2]),
+    }
+
+    let default = options.free.len() == 1;
+    let name = options.free.get(1).map_or(DICTIONARY, |name| name.as_str());
+    let terminator = options
+        .opt_str("t")
+        .map(|terminator| match terminator.as_bytes() {
+            &[c] => c,
+            _ => trouble!("invalid termination character '{}'", terminator),
+        });
+    let key = Key::new(
+        options.free[0].as_bytes(),
+        default || options.opt_present("f"),
+        default || options.opt_present("d"),
+        terminator,
+    );
+
+    let mut file = fs::File::open(name).unwrap_or_else(|e| trouble!("{}: {}", name, e));
+    let stdout = io::stdout();
+    let mut writer = io::BufWriter::new(stdout.lock());
+    let found = if default || options.opt_present("b") {
+        look_sorted(&mut file, &key, &mut writer)
+    } else {
+        look_linear(&mut BufReader::new(file), &key, &mut writer)
+    };
+    let found = found
+        .and_then(|found| writer.flush().map(|_| found))
+        .unwrap_or_else(|e| trouble!("{}: {}", name, e));
+    if !found {
+        process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    const WORDS: &str = "Aa\nAbby\naardvark\nabacus\nabbey\nbe\nbee\nbee-line\nbeef\nc\n";
+
+    fn look(words: &str, key: &Key) -> (String, String) {
+        let mut sorted = Vec::new();
+        look_sorted(&mut Cursor::new(words.as_bytes()), key, &mut sorted).unwrap();
+        let mut linear = Vec::new();
+        look_linear(&mut words.as_bytes(), key, &mut linear).unwrap();
+        (
+            String::from_utf8(sorted).unwrap(),
+            String::from_utf8(linear).unwrap(),
+        )
+    }
+
+    fn both(output: &str) -> (String, String) {
+        (output.to_owned(), output.to_owned())
+    }
+
+    #[test]
+    fn prefixes() {
+        let key = |string: &str| Key::new(string.as_bytes(), false, false, None);
+        assert_eq!(look(WORDS, &key("be")), both("be\nbee\nbee-line\nbeef\n"));
+        assert_eq!(look(WORDS, &key("bee-")), both("bee-line\n"));
+        assert_eq!(look(WORDS, &key("Aa")), both("Aa\n"));
+        assert_eq!(look(WORDS, &key("c")), both("c\n"));
+        assert_eq!(look(WORDS, &key("")), both(WORDS));
+        for missing in &["AB", "ab ", "bz", "d", "0"] {
+            assert_eq!(look(WORDS, &key(missing)), both(""), "{}", missing);
+        }
+    }
+
+    #[test]
+    fn every_line_is_found_by_bisection() {
+        let lines: Vec<String> = (0..500).map(|n| format!("{:05}", n * 7)).collect();
+        let words = lines.join("\n");
+        for line in &lines {
+            let key = Key::new(line.as_bytes(), false, false, None);
+            assert_eq!(look(&words, &key).0, format!("{}\n", line));
+        }
+    }
+
+    #[test]
+    fn fold_case_and_dictionary_order() {
+        let words = "aardvark\nabbey\nAbby\nbee\nbeef\nbee-line\n";
+        let key = Key::new(b"ABB", true, false, None);
+        assert_eq!(look(words, &key), both("abbey\nAbby\n"));
+        let key = Key::new(b"beel", false, true, None);
+        assert_eq!(look(words, &key), both("bee-line\n"));
+    }
+
+    #[test]
+    fn terminator() {
+        let words = "ab\nab-c\nab-d\nabe\n";
+        let key = Key::new(b"ab-x", false, false, Some(b'-'));
+        assert_eq!(key.string, b"ab-");
+        assert_eq!(look(words, &key), both("ab-c\nab-d\n"));
+        // Without the character in it, the whole string is looked up.
+        let key = Key::new(b"abe", false, false, Some(b'-'));
+        assert_eq!(look(words, &key), both("abe\n"));
+    }
+}
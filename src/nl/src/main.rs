@@ -0,0 +1,410 @@
+use std::env;
+use std::io::{self, BufRead, Write};
+
+#[macro_use(die, note_error, warn)]
+extern crate utils;
+
+use utils::args::ArgParser;
+use utils::fmt::ColumnFormatter;
+use utils::regex::{Regex, Syntax};
+
+/// Which lines of a section get numbers.
+enum Style {
+    All,
+    NonEmpty,
+    None,
+    Matching(Regex),
+}
+
+impl Style {
+    fn parse(style: &str, section: &str) -> Style {
+        match style {
+            "a" => Style::All,
+            "t" => Style::NonEmpty,
+            "n" => Style::None,
+            _ if style.starts_with('p') => {
+                match Regex::new(&[style[1..].to_owned()], Syntax::Basic, false) {
+                    Ok(regex) => Style::Matching(regex),
+                    Err(e) => die!(code = 2, "{}", e),
+                }
+            }
+            _ => die!(code = 2, "invalid {} numbering style: '{}'", section, style),
+        }
+    }
+}
+
+/// How the line numbers are aligned: the -n formats ln, rn and rz.
+#[derive(Clone, Copy)]
+enum Format {
+    Left,
+    Right,
+    RightZero,
+}
+
+/// The sections of a logical page, in the order they appear in.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Section {
+    Header,
+    Body,
+    Footer,
+}
+
+struct Config {
+    /// The styles of the header, body and footer.
+    styles: [Style; 3],
+    /// The two characters which, repeated three, two or one times, make up a line that starts
+    /// the header, body or footer.
+    delimiter: Vec<u8>,
+    start: i64,
+    increment: i64,
+    /// Whether numbering starts over at each section.
+    restart: bool,
+    separator: String,
+    width: usize,
+    format: Format,
+    /// Of a run of empty lines, only every this many is numbered with -b a.
+    join_blank: u64,
+}
+
+/// The numbering state, which carries on from one input file to the next.
+struct State {
+    section: Section,
+    line: i64,
+    blanks: u64,
+}
+
+impl State {
+    fn new(config: &Config) -> State {
+        State {
+            section: Section::Body,
+            line: config.start,
+            blanks: 0,
+        }
+    }
+}
+
+/// The section that @p line starts if it is a delimiter line.
+fn delimiter_section(line: &[u8], delimiter: &[u8]) -> Option<Section> {
+    if delimiter.is_empty() || !line.len().is_multiple_of(delimiter.len()) {
+        return None;
+    }
+    if !line.chunks(delimiter.len()).all(|chunk| chunk == delimiter) {
+        return None;
+    }
+    match line.len() / delimiter.len() {
+        3 => Some(Section::Header),
+        2 => Some(Section::Body),
+        1 => Some(Section::Footer),
+        _ => None,
+    }
+}
+
+/// Whether a line of @p content should be numbered, and updates the count of blank lines.
+fn selected(content: &[u8], config: &Config, state: &mut State) -> bool {
+    match config.styles[state.section as usize] {
+        Style::All if content.is_empty() && config.join_blank > 1 => {
+            state.blanks += 1;
+            if state.blanks == config.join_blank {
+                state.blanks = 0;
+                true
+            } else {
+                false
+            }
+        }
+        Style::All => {
+            state.blanks = 0;
+            true
+        }
+        Style::NonEmpty => !content.is_empty(),
+        Style::None => false,
+        Style::Matching(ref regex) => regex.is_match(content),
+    }
+}
+
+/// Numbers the lines of @p reader and writes them to @p writer.
+fn nl(
+    reader: &mut dyn BufRead,
+    config: &Config,
+    state: &mut State,
+    writer: &mut dyn Write,
+) -> io::Result<()> {
+    // The separator follows whichever format is used, so it is not the column's.
+    let column = ColumnFormatter::new(config.width, "");
+    let mut line = Vec::new();
+    loop {
+        line.clear();
+        if reader.read_until(b'\n', &mut line)? == 0 {
+            return Ok(());
+        }
+        let content = match line.last() {
+            Some(&b'\n') => &line[..line.len() - 1],
+            _ => &line[..],
+        };
+        if let Some(section) = delimiter_section(content, &config.delimiter) {
+            state.section = section;
+            if config.restart {
+                state.line = config.start;
+            }
+            writer.write_all(b"\n")?;
+            continue;
+        }
+        if selected(content, config, state) {
+            let width = config.width;
+            match config.format {
+                Format::Left => write!(writer, "{:<width$}", state.line, width = width)?,
+                Format::Right => column.write(writer, state.line)?,
+                Format::RightZero => write!(writer, "{:0width$}", state.line, width = width)?,
+            }
+            writer.write_all(config.separator.as_bytes())?;
+            state.line = state
+                .line
+                .checked_add(config.increment)
+                .unwrap_or_else(|| die!("line number overflow"));
+        } else {
+            // Like GNU nl, indent unnumbered lines as far as numbered ones.
+            let indent = config.width + config.separator.len();
+            write!(writer, "{:indent$}", "", indent = indent)?;
+        }
+        writer.write_all(&line)?;
+    }
+}
+
+fn show_help(opts: &ArgParser) {
+    let brief = format!(
+        concat!(
+            "Clone of the standard GNU nl.\n",
+            "Usage: {}: [OPTION]... [FILE]...\n",
+            "Write each FILE to standard output, with line numbers added.\n",
+            "STYLE is one of: a (number all lines), t (number only nonempty lines),\n",
+            "n (number no lines) or pBRE (number only lines that match BRE)."
+        ),
+        env::args().next().unwrap()
+    );
+    print!("{}", opts.usage(&brief));
+}
+
+fn parse_number<T: std::str::FromStr>(
+    options: &utils::args::ParsedArgs,
+    name: &str,
+    default: T,
+) -> T {
+    match options.opt_str(name) {
+        Some(value) => value
+            .parse()
+            .unwrap_or_else(|_| die!(code = 2, "invalid argument to -{}: '{}'", name, value)),
+        None => default,
+    }
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let mut opts = ArgParser::new();
+    opts.option(
+        "b",
+        "body-numbering",
+        "use STYLE for numbering body lines",
+        "STYLE",
+    );
+    opts.option(
+        "d",
+        "section-delimiter",
+        "use CC for logical page delimiters",
+        "CC",
+    );
+    opts.option(
+        "f",
+        "footer-numbering",
+        "use STYLE for numbering footer lines",
+        "STYLE",
+    );
+    opts.option(
+        "h",
+        "header-numbering",
+        "use STYLE for numbering header lines",
+        "STYLE",
+    );
+    opts.option(
+        "i",
+        "line-increment",
+        "line number increment at each line",
+        "NUMBER",
+    );
+    opts.option(
+        "l",
+        "join-blank-lines",
+        "group of NUMBER empty lines counted as one",
+        "NUMBER",
+    );
+    opts.option(
+        "n",
+        "number-format",
+        "insert line numbers according to FORMAT: ln, rn or rz",
+        "FORMAT",
+    );
+    opts.flag(
+        "p",
+        "no-renumber",
+        "do not reset line numbers for each section",
+    );
+    opts.option(
+        "s",
+        "number-separator",
+        "add STRING after (possible) line number",
+        "STRING",
+    );
+    opts.option(
+        "v",
+        "starting-line-number",
+        "first line number for each section",
+        "NUMBER",
+    );
+    opts.option(
+        "w",
+        "number-width",
+        "use NUMBER columns for line numbers",
+        "NUMBER",
+    );
+    opts.flag("", "help", "display this help and exit");
+    opts.flag("", "version", "output version information and exit");
+    let options = match opts.parse(&args[1..]) {
+        Ok(m) => m,
+        Err(f) => die!(code = 2, "{}", f.to_string()),
+    };
+    if options.opt_present("help") {
+        return show_help(&opts);
+    }
+    if options.opt_present("version") {
+        return utils::version::print_version("nl");
+    }
+
+    let style = |name: &str, section: &str, default: &str| {
+        Style::parse(
+            &options.opt_str(name).unwrap_or_else(|| default.to_owned()),
+            section,
+        )
+    };
+    let mut delimiter = options
+        .opt_str("d")
+        .unwrap_or_else(|| "\\:".to_owned())
+        .into_bytes();
+    if delimiter.len() == 1 {
+        delimiter.push(b':');
+    }
+    let format = match options.opt_str("n").as_deref() {
+        Some("ln") => Format::Left,
+        Some("rn") | None => Format::Right,
+        Some("rz") => Format::RightZero,
+        Some(other) => die!(code = 2, "invalid line numbering format: '{}'", other),
+    };
+    let width = parse_number(&options, "w", 6);
+    if width == 0 {
+        die!(code = 2, "invalid line number field width: '0'");
+    }
+    let join_blank = parse_number(&options, "l", 1);
+    if join_blank == 0 {
+        die!(code = 2, "invalid line number of blank lines: '0'");
+    }
+    let config = Config {
+        styles: [
+            style("h", "header", "n"),
+            style("b", "body", "t"),
+            style("f", "footer", "n"),
+        ],
+        delimiter,
+        start: parse_number(&options, "v", 1),
+        increment: parse_number(&options, "i", 1),
+        restart: !options.opt_present("p"),
+        separator: options.opt_str("s").unwrap_or_else(|| "\t".to_owned()),
+        width,
+        format,
+        join_blank,
+    };
+
+    let mut state = State::new(&config);
+    let stdout = io::stdout();
+    let mut writer = io::BufWriter::new(stdout.lock());
+    for (name, reader) in utils::io::open_inputs(&options.free) {
+        let mut reader = match reader {
+            Ok(reader) => reader,
+            Err(e) => {
+                note_error!("{}: {}", name, e);
+                continue;
+            }
+        };
+        nl(&mut reader, &config, &mut state, &mut writer)
+            .unwrap_or_else(|e| note_error!("{}: {}", name, e));
+    }
+    writer.flush().unwrap_or_else(|e| die!("{}", e));
+    utils::exit_if_error();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config_with(styles: [Style; 3]) -> Config {
+        Config {
+            styles,
+            delimiter: b"\\:".to_vec(),
+            start: 1,
+            increment: 1,
+            restart: true,
+            separator: "|".to_owned(),
+            width: 2,
+            format: Format::Right,
+            join_blank: 1,
+        }
+    }
+
+    fn number(input: &str, config: &Config) -> String {
+        let mut output = Vec::new();
+        let mut state = State::new(config);
+        nl(&mut input.as_bytes(), config, &mut state, &mut output).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn delimiters() {
+        assert_eq!(
+            delimiter_section(b"\\:\\:\\:", b"\\:"),
+            Some(Section::Header)
+        );
+        assert_eq!(delimiter_section(b"\\:\\:", b"\\:"), Some(Section::Body));
+        assert_eq!(delimiter_section(b"\\:", b"\\:"), Some(Section::Footer));
+        assert_eq!(delimiter_section(b"\\:\\:\\:\\:", b"\\:"), None);
+        assert_eq!(delimiter_section(b"\\:x", b"\\:"), None);
+        assert_eq!(delimiter_section(b"", b"\\:"), None);
+    }
+
+    #[test]
+    fn styles() {
+        let config = config_with([Style::All, Style::NonEmpty, Style::None]);
+        assert_eq!(number("a\n\nb", &config), " 1|a\n   \n 2|b");
+        let config = config_with([Style::None, Style::parse("p^x", "body"), Style::None]);
+        assert_eq!(number("xa\nb\nxc\n", &config), " 1|xa\n   b\n 2|xc\n");
+    }
+
+    #[test]
+    fn sections_restart_numbering() {
+        let mut config = config_with([Style::All, Style::All, Style::All]);
+        let input = "\\:\\:\\:\nh\n\\:\\:\nb\nb\n\\:\nf\n";
+        assert_eq!(number(input, &config), "\n 1|h\n\n 1|b\n 2|b\n\n 1|f\n");
+        config.restart = false;
+        assert_eq!(number(input, &config), "\n 1|h\n\n 2|b\n 3|b\n\n 4|f\n");
+    }
+
+    #[test]
+    fn formats_and_blank_lines() {
+        let mut config = config_with([Style::None, Style::All, Style::None]);
+        config.join_blank = 2;
+        config.format = Format::RightZero;
+        assert_eq!(
+            number("a\n\n\n\nb\n", &config),
+            "01|a\n   \n02|\n   \n03|b\n"
+        );
+        config.format = Format::Left;
+        config.start = 9;
+        config.increment = -3;
+        assert_eq!(number("a\nb\nc\n", &config), "9 |a\n6 |b\n3 |c\n");
+    }
+}
@@ -0,0 +1,191 @@
+use std::env;
+use std::ffi::OsString;
+use std::fs;
+use std::io;
+use std::os::unix::fs::OpenOptionsExt;
+use std::os::unix::io::AsFd;
+use std::os::unix::process::ExitStatusExt;
+use std::path::{Path, PathBuf};
+use std::process::{self, Command, Stdio};
+
+#[macro_use(warn)]
+extern crate utils;
+extern crate libc;
+
+use utils::args::ArgParser;
+
+/// Like `die!`, but with the exit status 125 that nohup reserves for its own failures.
+macro_rules! trouble {
+    ($($arg:tt)*) => ({
+        warn!($($arg)*);
+        process::exit(125);
+    });
+}
+
+/// The exit status when no output file can be opened.
+const NO_OUTPUT: i32 = 127;
+
+/// The files the output may go to, in the order they are tried.
+fn output_candidates(home: Option<OsString>) -> Vec<PathBuf> {
+    let mut candidates = vec![PathBuf::from("nohup.out")];
+    candidates.extend(home.map(|home| Path::new(&home).join("nohup.out")));
+    candidates
+}
+
+/// Opens the first of @p candidates that can be appended to, creating it if needed.
+fn open_output(candidates: &[PathBuf]) -> io::Result<(fs::File, &Path)> {
+    let mut error = io::Error::from(io::ErrorKind::NotFound);
+    for candidate in candidates {
+        let file = fs::OpenOptions::new()
+            .append(true)
+            .create(true)
+            .mode(0o600)
+            .open(candidate);
+        match file {
+            Ok(file) => return Ok((file, candidate)),
+            Err(e) => error = e,
+        }
+    }
+    Err(error)
+}
+
+fn is_terminal(fd: libc::c_int) -> bool {
+    unsafe { libc::isatty(fd) == 1 }
+}
+
+/// Runs @p command immune to hangups and returns the exit status for nohup.
+fn run(command: &[String]) -> i32 {
+    let mut child = Command::new(&command[0]);
+    child.args(&command[1..]);
+    let ignoring_input = is_terminal(libc::STDIN_FILENO);
+    if ignoring_input {
+        child.stdin(Stdio::null());
+    }
+    if is_terminal(libc::STDOUT_FILENO) {
+        let candidates = output_candidates(env::var_os("HOME"));
+        let (output, name) = match open_output(&candidates) {
+            Ok(opened) => opened,
+            Err(e) => {
+                warn!("failed to open '{}': {}", candidates[0].display(), e);
+                return NO_OUTPUT;
+            }
+        };
+        if ignoring_input {
+            warn!(
+                "ignoring input and appending output to '{}'",
+                name.display()
+            );
+        } else {
+            warn!("appending output to '{}'", name.display());
+        }
+        if is_terminal(libc::STDERR_FILENO) {
+            let error = output.try_clone().unwrap_or_else(|e| trouble!("{}", e));
+            child.stderr(error);
+        }
+        child.stdout(output);
+    } else {
+        if ignoring_input {
+            warn!("ignoring input");
+        }
+        if is_terminal(libc::STDERR_FILENO) {
+            warn!("redirecting stderr to stdout");
+            let stdout = io::stdout()
+                .as_fd()
+                .try_clone_to_owned()
+                .unwrap_or_else(|e| trouble!("{}", e));
+            child.stderr(stdout);
+        }
+    }
+
+    unsafe {
+        libc::signal(libc::SIGHUP, libc::SIG_IGN);
+        libc::signal(libc::SIGQUIT, libc::SIG_IGN);
+    }
+    match child.status() {
+        Ok(status) => match status.code() {
+            Some(code) => code,
+            None => 128 + status.signal().unwrap_or(0),
+        },
+        Err(e) => {
+            warn!("failed to run command '{}': {}", command[0], e);
+            if e.kind() == io::ErrorKind::NotFound {
+                127
+            } else {
+                126
+            }
+        }
+    }
+}
+
+fn show_help(opts: &ArgParser) {
+    let brief = format!(
+        concat!(
+            "Clone of the standard GNU nohup.\n",
+            "Usage: {}: COMMAND [ARG]...\n",
+            "Run COMMAND, ignoring hangup and quit signals.\n",
+            "If standard input is a terminal, redirect it from an unreadable file.\n",
+            "If standard output is a terminal, append output to 'nohup.out' if possible,\n",
+            "'$HOME/nohup.out' otherwise.\n",
+            "If standard error is a terminal, redirect it to standard output."
+        ),
+        env::args().next().unwrap()
+    );
+    print!("{}", opts.usage(&brief));
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let mut opts = ArgParser::new();
+    opts.stop_at_first_free();
+    opts.flag("h", "help", "display this help and exit");
+    opts.flag("v", "version", "output version information and exit");
+    let options = match opts.parse(&args[1..]) {
+        Ok(m) => m,
+        Err(f) => trouble!("{}", f.to_string()),
+    };
+    if options.opt_present("h") {
+        return show_help(&opts);
+    }
+    if options.opt_present("v") {
+        return utils::version::print_version("nohup");
+    }
+    if options.free.is_empty() {
+        trouble!("missing operand");
+    }
+    process::exit(run(&options.free));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn candidates() {
+        assert_eq!(
+            output_candidates(Some("/home/me".into())),
+            [
+                PathBuf::from("nohup.out"),
+                PathBuf::from("/home/me/nohup.out")
+            ]
+        );
+        assert_eq!(output_candidates(None), [PathBuf::from("nohup.out")]);
+    }
+
+    #[test]
+    fn output_falls_back_and_appends() {
+        let dir = env::temp_dir().join(format!("nohup-test-{}", process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let output = dir.join("nohup.out");
+        fs::write(&output, "old\n").unwrap();
+        let candidates = [dir.join("missing/nohup.out"), output.clone()];
+        {
+            let (mut file, name) = open_output(&candidates).unwrap();
+            assert_eq!(name, output);
+            file.write_all(b"new\n").unwrap();
+        }
+        assert_eq!(fs::read_to_string(&output).unwrap(), "old\nnew\n");
+        assert!(open_output(&candidates[..1]).is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,275 @@
+use std::env;
+use std::io::{self, BufRead, Write};
+
+#[macro_use(die)]
+extern crate utils;
+
+use utils::args::ArgParser;
+use utils::io::FileOrStdin;
+
+/// Parses -d's LIST into separate delimiters. Each delimiter is one character of LIST, where
+/// `\n`, `\t`, `\\` and `\0` (the empty delimiter) are recognised escapes.
+fn parse_delimiters(list: &str) -> Vec<Vec<u8>> {
+    let mut delimiters = Vec::new();
+    let mut chars = list.chars();
+    while let Some(ch) = chars.next() {
+        let delimiter = if ch == '\\' {
+            match chars.next() {
+                Some('n') => "\n".to_owned(),
+                Some('t') => "\t".to_owned(),
+                Some('0') => String::new(),
+                Some(other) => other.to_string(),
+                None => "\\".to_owned(),
+            }
+        } else {
+            ch.to_string()
+        };
+        delimiters.push(delimiter.into_bytes());
+    }
+    delimiters
+}
+
+/// Reads a single line without its terminator; returns None at end of input.
+fn read_line(reader: &mut dyn BufRead) -> io::Result<Option<Vec<u8>>> {
+    let mut line = Vec::new();
+    if reader.read_until(b'\n', &mut line)? == 0 {
+        return Ok(None);
+    }
+    if line.last() == Some(&b'\n') {
+        line.pop();
+    }
+    Ok(Some(line))
+}
+
+fn write_delimiter(writer: &mut dyn Write, delimiters: &[Vec<u8>], n: usize) -> io::Result<()> {
+    if delimiters.is_empty() {
+        return Ok(());
+    }
+    writer.write_all(&delimiters[n % delimiters.len()])
+}
+
+/// The inputs to paste. Every "-" reads on from the one standard input, so that `paste - -`
+/// takes turns at its lines.
+struct Inputs {
+    stdin: Box<dyn BufRead>,
+    /// The file of each operand, or None for "-".
+    files: Vec<Option<Box<dyn BufRead>>>,
+}
+
+impl Inputs {
+    fn open(names: &[String]) -> Inputs {
+        let files = names
+            .iter()
+            .map(|name| match name.as_str() {
+                "-" => None,
+                _ => match FileOrStdin::open(name) {
+                    Ok(file) => Some(Box::new(file) as Box<dyn BufRead>),
+                    Err(e) => die!("{}: {}", name, e),
+                },
+            })
+            .collect();
+        Inputs {
+            stdin: Box::new(io::stdin().lock()),
+            files,
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    /// Reads the next line of the @p n th input.
+    fn read_line(&mut self, n: usize) -> io::Result<Option<Vec<u8>>> {
+        match self.files[n] {
+            Some(ref mut file) => read_line(file),
+            None => read_line(&mut self.stdin),
+        }
+    }
+}
+
+/// Merges corresponding lines of every input into one output line. Exhausted inputs
+/// contribute empty fields until all of them are exhausted.
+fn paste_parallel(
+    inputs: &mut Inputs,
+    delimiters: &[Vec<u8>],
+    writer: &mut dyn Write,
+) -> io::Result<()> {
+    let mut exhausted = vec![false; inputs.len()];
+    loop {
+        let mut fields = Vec::with_capacity(inputs.len());
+        for (n, done) in exhausted.iter_mut().enumerate() {
+            let line = if *done { None } else { inputs.read_line(n)? };
+            *done = line.is_none();
+            fields.push(line.unwrap_or_default());
+        }
+        if exhausted.iter().all(|done| *done) {
+            return Ok(());
+        }
+        for (n, field) in fields.iter().enumerate() {
+            if n > 0 {
+                write_delimiter(writer, delimiters, n - 1)?;
+            }
+            writer.write_all(field)?;
+        }
+        writer.write_all(b"\n")?;
+    }
+}
+
+/// Joins all lines of each input into a single output line.
+fn paste_serial(
+    inputs: &mut Inputs,
+    delimiters: &[Vec<u8>],
+    writer: &mut dyn Write,
+) -> io::Result<()> {
+    for input in 0..inputs.len() {
+        let mut n = 0;
+        while let Some(line) = inputs.read_line(input)? {
+            if n > 0 {
+                write_delimiter(writer, delimiters, n - 1)?;
+            }
+            writer.write_all(&line)?;
+            n += 1;
+        }
+        writer.write_all(b"\n")?;
+    }
+    Ok(())
+}
+
+fn show_help(opts: &ArgParser) {
+    let brief = format!(
+        concat!(
+            "Clone of the standard GNU paste.\n",
+            "Usage: {}: [OPTION]... [FILE]...\n",
+            "Write lines consisting of the sequentially corresponding lines from\n",
+            "each FILE, separated by TABs, to standard output."
+        ),
+        env::args().next().unwrap()
+    );
+    print!("{}", opts.usage(&brief));
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let mut opts = ArgParser::new();
+    opts.option(
+        "d",
+        "delimiters",
+        "reuse characters from LIST instead of TABs",
+        "LIST",
+    );
+    opts.flag(
+        "s",
+        "serial",
+        "paste one file at a time instead of in parallel",
+    );
+    opts.flag("h", "help", "display this help and exit");
+    opts.flag("v", "version", "output version information and exit");
+    let options = match opts.parse(&args[1..]) {
+        Ok(m) => m,
+        Err(f) => die!(code = 2, "{}", f.to_string()),
+    };
+    if options.opt_present("h") {
+        return show_help(&opts);
+    }
+    if options.opt_present("v") {
+        return utils::version::print_version("paste");
+    }
+
+    let delimiters = parse_delimiters(&options.opt_str("d").unwrap_or_else(|| "\t".to_owned()));
+    let mut files = options.free.clone();
+    if files.is_empty() {
+        files.push("-".to_owned());
+    }
+    let mut inputs = Inputs::open(&files);
+
+    let stdout = io::stdout();
+    let mut writer = io::BufWriter::new(stdout.lock());
+    let result = if options.opt_present("s") {
+        paste_serial(&mut inputs, &delimiters, &mut writer)
+    } else {
+        paste_parallel(&mut inputs, &delimiters, &mut writer)
+    };
+    result
+        .and_then(|_| writer.flush())
+        .unwrap_or_else(|e| die!("{}", e));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn readers(inputs: &[&'static str]) -> Inputs {
+        Inputs {
+            stdin: Box::new(&b""[..]),
+            files: inputs
+                .iter()
+                .map(|input| Some(Box::new(input.as_bytes()) as Box<dyn BufRead>))
+                .collect(),
+        }
+    }
+
+    fn parallel(inputs: &[&'static str], delimiters: &str) -> String {
+        let mut output = Vec::new();
+        paste_parallel(
+            &mut readers(inputs),
+            &parse_delimiters(delimiters),
+            &mut output,
+        )
+        .unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    fn serial(inputs: &[&'static str], delimiters: &str) -> String {
+        let mut output = Vec::new();
+        paste_serial(
+            &mut readers(inputs),
+            &parse_delimiters(delimiters),
+            &mut output,
+        )
+        .unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn delimiter_escapes() {
+        assert_eq!(
+            parse_delimiters("\\t,"),
+            vec![b"\t".to_vec(), b",".to_vec()]
+        );
+        assert_eq!(
+            parse_delimiters("\\0\\n\\\\"),
+            vec![vec![], b"\n".to_vec(), b"\\".to_vec()]
+        );
+        assert_eq!(parse_delimiters("§"), vec!["§".as_bytes().to_vec()]);
+        assert!(parse_delimiters("").is_empty());
+    }
+
+    #[test]
+    fn default_tab() {
+        assert_eq!(parallel(&["1\n2\n", "a\nb\n"], "\t"), "1\ta\n2\tb\n");
+    }
+
+    #[test]
+    fn cycling_delimiters() {
+        assert_eq!(parallel(&["1\n", "2\n", "3\n", "4\n"], ",;"), "1,2;3,4\n");
+        assert_eq!(serial(&["1\n2\n3\n4\n"], ",;"), "1,2;3,4\n");
+    }
+
+    #[test]
+    fn unequal_lengths() {
+        assert_eq!(parallel(&["1\n2\n3\n", "a\n"], ","), "1,a\n2,\n3,\n");
+        assert_eq!(parallel(&["1\n", "a\nb"], ","), "1,a\n,b\n");
+    }
+
+    #[test]
+    fn serial_mode() {
+        assert_eq!(serial(&["1\n2\n3\n", "a\nb\n"], "\t"), "1\t2\t3\na\tb\n");
+        assert_eq!(serial(&[""], "\t"), "\n");
+    }
+
+    #[test]
+    fn empty_delimiter_list() {
+        assert_eq!(parallel(&["1\n2\n", "a\nb\n"], ""), "1a\n2b\n");
+        assert_eq!(serial(&["1\n2\n"], ""), "12\n");
+    }
+}
@@ -0,0 +1,132 @@
+use std::env;
+use std::io::{self, Write};
+use std::os::unix::ffi::OsStrExt;
+
+#[macro_use(die)]
+extern crate utils;
+
+use utils::args::ArgParser;
+
+/// Writes the value of each variable in @p names, or every NAME=VALUE pair if there are no
+/// names. Returns whether all of the named variables were set.
+///
+/// Names and values are written as the bytes they are, whether or not they are UTF-8.
+fn printenv(names: &[String], writer: &mut dyn Write) -> io::Result<bool> {
+    if names.is_empty() {
+        for (name, value) in env::vars_os() {
+            writer.write_all(name.as_bytes())?;
+            writer.write_all(b"=")?;
+            writer.write_all(value.as_bytes())?;
+            writer.write_all(b"\n")?;
+        }
+        return Ok(true);
+    }
+    let mut all_found = true;
+    for name in names {
+        match env::var_os(name) {
+            Some(value) => {
+                writer.write_all(value.as_bytes())?;
+                writer.write_all(b"\n")?;
+            }
+            None => all_found = false,
+        }
+    }
+    Ok(all_found)
+}
+
+fn show_help(opts: &ArgParser) {
+    let brief = format!(
+        concat!(
+            "Clone of the standard GNU printenv.\n",
+            "Usage: {}: [OPTION]... [VARIABLE]...\n",
+            "Print the values of the specified environment VARIABLE(s).\n",
+            "If no VARIABLE is specified, print name and value pairs for them all."
+        ),
+        env::args().next().unwrap()
+    );
+    print!("{}", opts.usage(&brief));
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let mut opts = ArgParser::new();
+    opts.flag("h", "help", "display this help and exit");
+    opts.flag("v", "version", "output version information and exit");
+    let options = match opts.parse(&args[1..]) {
+        Ok(m) => m,
+        Err(f) => die!(code = 2, "{}", f.to_string()),
+    };
+    if options.opt_present("h") {
+        return show_help(&opts);
+    }
+    if options.opt_present("v") {
+        return utils::version::print_version("printenv");
+    }
+
+    let stdout = io::stdout();
+    let mut writer = io::BufWriter::new(stdout.lock());
+    let all_found = printenv(&options.free, &mut writer)
+        .and_then(|all_found| writer.flush().map(|_| all_found))
+        .unwrap_or_else(|e| die!("{}", e));
+    if !all_found {
+        std::process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run_bytes(names: &[&str]) -> (Vec<u8>, bool) {
+        let names: Vec<String> = names.iter().map(|s| s.to_string()).collect();
+        let mut output = Vec::new();
+        let all_found = printenv(&names, &mut output).unwrap();
+        (output, all_found)
+    }
+
+    fn run(names: &[&str]) -> (String, bool) {
+        let (output, all_found) = run_bytes(names);
+        (String::from_utf8_lossy(&output).into_owned(), all_found)
+    }
+
+    #[test]
+    fn named_variables() {
+        env::set_var("PRINTENV_TEST_NAMED", "value");
+        assert_eq!(run(&["PRINTENV_TEST_NAMED"]), ("value\n".to_owned(), true));
+    }
+
+    #[test]
+    fn missing_variable() {
+        env::set_var("PRINTENV_TEST_PRESENT", "here");
+        assert_eq!(
+            run(&["PRINTENV_TEST_MISSING", "PRINTENV_TEST_PRESENT"]),
+            ("here\n".to_owned(), false)
+        );
+    }
+
+    #[test]
+    fn all_variables() {
+        env::set_var("PRINTENV_TEST_ALL", "a=b");
+        let (output, all_found) = run(&[]);
+        assert!(all_found);
+        assert!(output.lines().any(|line| line == "PRINTENV_TEST_ALL=a=b"));
+    }
+
+    #[test]
+    fn non_utf8_values() {
+        use std::ffi::OsString;
+        use std::os::unix::ffi::OsStringExt;
+        env::set_var(
+            "PRINTENV_TEST_LATIN1",
+            OsString::from_vec(b"caf\xe9".to_vec()),
+        );
+        assert_eq!(
+            run_bytes(&["PRINTENV_TEST_LATIN1"]),
+            (b"caf\xe9\n".to_vec(), true)
+        );
+        let (output, _) = run_bytes(&[]);
+        assert!(output
+            .split(|&b| b == b'\n')
+            .any(|line| line == &b"PRINTENV_TEST_LATIN1=caf\xe9"[..]));
+    }
+}
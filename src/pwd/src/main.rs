@@ -0,0 +1,137 @@
+use std::env;
+use std::ffi::OsStr;
+use std::fs;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+
+#[macro_use(die)]
+extern crate utils;
+
+use utils::args::ArgParser;
+
+/// Returns @p pwd if it is usable as the logical working directory, that is an absolute path
+/// without '.' or '..' components which refers to the same directory as @p physical.
+fn logical_path(pwd: &OsStr, physical: &Path) -> Option<PathBuf> {
+    let path = PathBuf::from(pwd);
+    if !path.is_absolute() {
+        return None;
+    }
+    // Path::components() silently drops '.' so the raw string has to be inspected.
+    if pwd
+        .to_string_lossy()
+        .split('/')
+        .any(|part| part == "." || part == "..")
+    {
+        return None;
+    }
+    let logical_meta = fs::metadata(&path).ok()?;
+    let physical_meta = fs::metadata(physical).ok()?;
+    if logical_meta.dev() == physical_meta.dev() && logical_meta.ino() == physical_meta.ino() {
+        Some(path)
+    } else {
+        None
+    }
+}
+
+fn show_help(opts: &ArgParser) {
+    let brief = format!(
+        concat!(
+            "Clone of the standard GNU pwd.\n",
+            "Usage: {}: [OPTION]...\n",
+            "Print the full filename of the current working directory."
+        ),
+        env::args().next().unwrap()
+    );
+    print!("{}", opts.usage(&brief));
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let mut opts = ArgParser::new();
+    opts.flag(
+        "L",
+        "logical",
+        "use PWD from environment, even if it contains symlinks",
+    );
+    opts.flag("P", "physical", "avoid all symlinks (the default)");
+    opts.flag("h", "help", "display this help and exit");
+    opts.flag("v", "version", "output version information and exit");
+    let options = match opts.parse(&args[1..]) {
+        Ok(m) => m,
+        Err(f) => die!(code = 2, "{}", f.to_string()),
+    };
+    if options.opt_present("h") {
+        return show_help(&opts);
+    }
+    if options.opt_present("v") {
+        return utils::version::print_version("pwd");
+    }
+    if !options.free.is_empty() {
+        die!(code = 2, "extra operand '{}'", options.free[0]);
+    }
+
+    // The option given last wins.
+    let logical = match (
+        options.opt_positions("L").last(),
+        options.opt_positions("P").last(),
+    ) {
+        (Some(l), Some(p)) => l > p,
+        (Some(_), None) => true,
+        _ => false,
+    };
+    let physical =
+        env::current_dir().unwrap_or_else(|e| die!("cannot get current directory: {}", e));
+    let path = if logical {
+        env::var_os("PWD")
+            .and_then(|pwd| logical_path(&pwd, &physical))
+            .unwrap_or(physical)
+    } else {
+        physical
+    };
+    println!("{}", path.display());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::symlink;
+
+    #[test]
+    fn physical_path_is_canonical() {
+        let physical = env::current_dir().unwrap();
+        assert!(physical.is_absolute());
+        assert_eq!(fs::canonicalize(&physical).unwrap(), physical);
+    }
+
+    #[test]
+    fn logical_path_through_symlink() {
+        let base = env::temp_dir().join(format!("pwd-test-{}", std::process::id()));
+        let target = base.join("target");
+        let link = base.join("link");
+        fs::create_dir_all(&target).unwrap();
+        symlink(&target, &link).unwrap();
+
+        assert_eq!(logical_path(link.as_os_str(), &target), Some(link.clone()));
+        assert_eq!(
+            logical_path(target.as_os_str(), &target),
+            Some(target.clone())
+        );
+        assert_eq!(logical_path(base.as_os_str(), &target), None);
+
+        fs::remove_dir_all(&base).unwrap();
+    }
+
+    #[test]
+    fn logical_path_rejects_dots_and_relative() {
+        let physical = env::current_dir().unwrap();
+        let dotted = format!("{}/.", physical.display());
+        assert_eq!(logical_path(OsStr::new(&dotted), &physical), None);
+        let dotdot = format!(
+            "{}/../{}",
+            physical.display(),
+            physical.file_name().unwrap().to_string_lossy()
+        );
+        assert_eq!(logical_path(OsStr::new(&dotdot), &physical), None);
+        assert_eq!(logical_path(OsStr::new("relative"), &physical), None);
+    }
+}
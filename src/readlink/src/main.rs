@@ -0,0 +1,259 @@
+use std::collections::VecDeque;
+use std::env;
+use std::ffi::OsString;
+use std::fs;
+use std::io::{self, Write};
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Component, Path, PathBuf};
+use std::process;
+
+#[macro_use(die, warn)]
+extern crate utils;
+extern crate libc;
+
+use utils::args::ArgParser;
+
+/// How much of a path has to exist for it to be canonicalized.
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Existence {
+    All,
+    AllButLast,
+    Nothing,
+}
+
+/// Symbolic links followed before giving up, the same limit Linux uses.
+const MAX_LINKS: usize = 40;
+
+/// The absolute path of @p path with every symbolic link, `.` and `..` resolved. Which of
+/// its components must exist is given by @p existence.
+fn canonicalize(path: &Path, existence: Existence) -> io::Result<PathBuf> {
+    let mut resolved = if path.is_absolute() {
+        PathBuf::from("/")
+    } else {
+        env::current_dir()?
+    };
+    let mut pending = VecDeque::new();
+    push_components(&mut pending, path);
+
+    let mut links = 0;
+    while let Some(name) = pending.pop_front() {
+        if name == ".." {
+            resolved.pop();
+            continue;
+        }
+        let candidate = resolved.join(&name);
+        match fs::symlink_metadata(&candidate) {
+            Ok(ref metadata) if metadata.file_type().is_symlink() => {
+                links += 1;
+                if links > MAX_LINKS {
+                    return Err(io::Error::from_raw_os_error(libc::ELOOP));
+                }
+                let target = fs::read_link(&candidate)?;
+                if target.is_absolute() {
+                    resolved = PathBuf::from("/");
+                }
+                // The target takes the place of the link, ahead of whatever followed it.
+                let rest: Vec<OsString> = pending.drain(..).collect();
+                push_components(&mut pending, &target);
+                pending.extend(rest);
+            }
+            Ok(metadata) => {
+                if !metadata.is_dir() && !pending.is_empty() && existence != Existence::Nothing {
+                    return Err(io::Error::from_raw_os_error(libc::ENOTDIR));
+                }
+                resolved = candidate;
+            }
+            Err(e) => {
+                let allowed = match existence {
+                    Existence::All => false,
+                    Existence::AllButLast => {
+                        pending.is_empty() && e.kind() == io::ErrorKind::NotFound
+                    }
+                    Existence::Nothing => true,
+                };
+                if !allowed {
+                    return Err(e);
+                }
+                resolved = candidate;
+            }
+        }
+    }
+    Ok(resolved)
+}
+
+/// Appends the names in @p path to @p pending, dropping the root and `.` components.
+fn push_components(pending: &mut VecDeque<OsString>, path: &Path) {
+    for component in path.components() {
+        match component {
+            Component::Normal(name) => pending.push_back(name.to_owned()),
+            Component::ParentDir => pending.push_back("..".into()),
+            Component::RootDir | Component::CurDir | Component::Prefix(_) => {}
+        }
+    }
+}
+
+/// The target of the symbolic link @p name, or its canonical path if @p existence is given.
+fn readlink(name: &str, existence: Option<Existence>) -> io::Result<PathBuf> {
+    match existence {
+        Some(existence) => canonicalize(Path::new(name), existence),
+        None => fs::read_link(name),
+    }
+}
+
+fn show_help(opts: &ArgParser) {
+    let brief = format!(
+        concat!(
+            "Clone of the standard GNU readlink.\n",
+            "Usage: {}: [OPTION]... FILE...\n",
+            "Print value of a symbolic link or canonical file name."
+        ),
+        env::args().next().unwrap()
+    );
+    print!("{}", opts.usage(&brief));
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let mut opts = ArgParser::new();
+    opts.flag(
+        "f",
+        "canonicalize",
+        "canonicalize by following every symlink; all but the last component must exist",
+    );
+    opts.flag(
+        "e",
+        "canonicalize-existing",
+        "canonicalize by following every symlink; all components must exist",
+    );
+    opts.flag(
+        "m",
+        "canonicalize-missing",
+        "canonicalize by following every symlink; no component needs to exist",
+    );
+    opts.flag("n", "no-newline", "do not output the trailing delimiter");
+    opts.flag("h", "help", "display this help and exit");
+    opts.flag("", "version", "output version information and exit");
+    let options = match opts.parse(&args[1..]) {
+        Ok(m) => m,
+        Err(f) => die!(code = 2, "{}", f.to_string()),
+    };
+    if options.opt_present("h") {
+        return show_help(&opts);
+    }
+    if options.opt_present("version") {
+        return utils::version::print_version("readlink");
+    }
+    if options.free.is_empty() {
+        die!(code = 2, "missing operand");
+    }
+
+    // As with GNU, the last of the canonicalization modes given wins.
+    let existence = [
+        ("f", Existence::AllButLast),
+        ("e", Existence::All),
+        ("m", Existence::Nothing),
+    ]
+    .iter()
+    .filter_map(|&(name, existence)| {
+        options
+            .opt_positions(name)
+            .last()
+            .map(|&position| (position, existence))
+    })
+    .max_by_key(|&(position, _)| position)
+    .map(|(_, existence)| existence);
+
+    let mut newline = !options.opt_present("n");
+    if !newline && options.free.len() > 1 {
+        warn!("ignoring --no-newline with multiple arguments");
+        newline = true;
+    }
+
+    let stdout = io::stdout();
+    let mut writer = io::BufWriter::new(stdout.lock());
+    let mut failed = false;
+    for name in &options.free {
+        // Like GNU readlink without --verbose, failures only show in the exit status.
+        match readlink(name, existence) {
+            Ok(path) => {
+                writer
+                    .write_all(path.as_os_str().as_bytes())
+                    .and_then(|_| {
+                        if newline {
+                            writer.write_all(b"\n")
+                        } else {
+                            Ok(())
+                        }
+                    })
+                    .unwrap_or_else(|e| die!("{}", e));
+            }
+            Err(_) => failed = true,
+        }
+    }
+    writer.flush().unwrap_or_else(|e| die!("{}", e));
+    if failed {
+        process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::symlink;
+
+    fn scratch(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("readlink-{}-{}", name, process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        // The temporary directory itself may be behind a symlink.
+        fs::canonicalize(dir).unwrap()
+    }
+
+    #[test]
+    fn follows_chains() {
+        let dir = scratch("chain");
+        fs::create_dir(dir.join("real")).unwrap();
+        fs::write(dir.join("real/file"), b"").unwrap();
+        symlink("real", dir.join("first")).unwrap();
+        symlink("first/./file", dir.join("second")).unwrap();
+        for &existence in &[Existence::All, Existence::AllButLast, Existence::Nothing] {
+            assert_eq!(
+                canonicalize(&dir.join("second"), existence).unwrap(),
+                dir.join("real/file")
+            );
+        }
+        assert_eq!(
+            canonicalize(&dir.join("first/../second"), Existence::All).unwrap(),
+            dir.join("real/file")
+        );
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn missing_components() {
+        let dir = scratch("missing");
+        symlink("nowhere", dir.join("dangling")).unwrap();
+        let dangling = dir.join("dangling");
+        assert!(canonicalize(&dangling, Existence::All).is_err());
+        assert_eq!(
+            canonicalize(&dangling, Existence::AllButLast).unwrap(),
+            dir.join("nowhere")
+        );
+        let deeper = dir.join("dangling/deeper");
+        assert!(canonicalize(&deeper, Existence::AllButLast).is_err());
+        assert_eq!(
+            canonicalize(&deeper, Existence::Nothing).unwrap(),
+            dir.join("nowhere/deeper")
+        );
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn symlink_loops() {
+        let dir = scratch("loop");
+        symlink("loop", dir.join("loop")).unwrap();
+        let error = canonicalize(&dir.join("loop"), Existence::Nothing).unwrap_err();
+        assert_eq!(error.raw_os_error(), Some(libc::ELOOP));
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
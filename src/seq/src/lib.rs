@@ -0,0 +1,718 @@
+//! The implementation of seq: validating formats and printing sequences.
+
+use std::collections::HashSet;
+use std::fmt;
+use std::io::{self, Write};
+
+#[cfg(test)]
+extern crate libc;
+
+mod printf;
+
+use printf::{Format, Number};
+
+/// What to print: the numbers from `first` to `last` in steps of `inc`, each printed with the
+/// printf-style `format` and separated by `separator`. With an integer conversion, %d or %i,
+/// the numbers are truncated to integers first.
+#[derive(Clone, Debug)]
+pub struct SeqConfig {
+    pub separator: Vec<u8>,
+    /// Pad the numbers with leading zeros to the width of the widest one.
+    pub equal_width: bool,
+    pub first: f64,
+    pub inc: f64,
+    pub last: f64,
+    pub format: String,
+    /// Print the numbers as a JSON array, "[1,2,3]", instead of separated by `separator`.
+    pub json: bool,
+}
+
+impl SeqConfig {
+    /// What goes between two numbers.
+    fn between(&self) -> &[u8] {
+        if self.json {
+            b","
+        } else {
+            &self.separator
+        }
+    }
+}
+
+/// Pads @p number with zeros after its sign, if any, to @p width bytes.
+fn pad_with_zeros(number: Vec<u8>, width: usize) -> Vec<u8> {
+    if number.len() >= width {
+        return number;
+    }
+    let sign = if number.first() == Some(&b'-') { 1 } else { 0 };
+    let mut padded = number[..sign].to_vec();
+    padded.resize(width - number.len() + sign, b'0');
+    padded.extend_from_slice(&number[sign..]);
+    padded
+}
+
+/// @p value as an i64, if it is a whole number in its range.
+pub fn as_integer(value: f64) -> Option<i64> {
+    if value.fract() == 0.0 && value >= i64::MIN as f64 && value < i64::MAX as f64 {
+        Some(value as i64)
+    } else {
+        None
+    }
+}
+
+/// The format which prints whole numbers of up to @p digits digits as @p format does, if there
+/// is one: %d and %i themselves, and a lone %g or %.Ng, like the ones main picks for integral
+/// operands, with enough precision not to switch to an exponent, which prints them as %d.
+fn integer_format(format: &Format, digits: usize) -> Option<Format> {
+    if format.is_integer() {
+        return Some(format.clone());
+    }
+    match format.general_precision() {
+        Some(precision) if digits <= precision => Some(Format::parse("%d")),
+        _ => None,
+    }
+}
+
+/// Writes @p number in decimal to @p writer, padded with zeros to @p width bytes, as "%d" and
+/// `pad_with_zeros` would, without allocating.
+fn write_decimal<W: Write>(writer: &mut W, number: i64, width: usize) -> io::Result<()> {
+    let mut buffer = [b'0'; 20];
+    let mut start = buffer.len();
+    let mut rest = number.unsigned_abs();
+    loop {
+        start -= 1;
+        buffer[start] = b'0' + (rest % 10) as u8;
+        rest /= 10;
+        if rest == 0 {
+            break;
+        }
+    }
+    let len = buffer.len() - start + if number < 0 { 1 } else { 0 };
+    if number < 0 {
+        writer.write_all(b"-")?;
+    }
+    for _ in len..width {
+        writer.write_all(b"0")?;
+    }
+    writer.write_all(&buffer[start..])
+}
+
+/// Writes the numbers from @p first to @p last in steps of @p inc, as `numbers` would, but
+/// with exact integer arithmetic. @p format is the one `integer_format` made of @p config's.
+fn integer_seq<W: Write>(
+    first: i64,
+    inc: i64,
+    last: i64,
+    format: &Format,
+    config: &SeqConfig,
+    writer: &mut W,
+) -> io::Result<bool> {
+    let width = if config.equal_width {
+        let first = format.format(Number::Integer(first)).len();
+        first.max(format.format(Number::Integer(last)).len())
+    } else {
+        0
+    };
+    // A lone %d is written straight to the writer, rather than formatted into a buffer first.
+    let plain = format.is_plain_integer();
+    let mut current = Some(first);
+    let mut written = false;
+    while let Some(number) = current {
+        if (inc < 0 && number < last) || (inc >= 0 && number > last) {
+            break;
+        }
+        if written {
+            writer.write_all(config.between())?;
+        }
+        if plain {
+            write_decimal(writer, number, width)?;
+        } else {
+            let formatted = format.format(Number::Integer(number));
+            writer.write_all(&pad_with_zeros(formatted, width))?;
+        }
+        written = true;
+        // Stepping out of the range of i64 also goes past last.
+        current = number.checked_add(inc);
+    }
+    Ok(written)
+}
+
+/// Writes the sequence described by @p config to @p writer, the numbers followed by a single
+/// new line, or as a JSON array on a line of its own.
+pub fn seq<W: Write>(config: &SeqConfig, writer: &mut W) -> io::Result<()> {
+    validate_format(&config.format)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e.to_string()))?;
+    if config.json {
+        writer.write_all(b"[")?;
+    }
+    let written = numbers(config, writer)?;
+    if config.json {
+        writer.write_all(b"]\n")
+    } else if written {
+        writer.write_all(b"\n")
+    } else {
+        Ok(())
+    }
+}
+
+/// Writes the numbers of the sequence described by @p config to @p writer, with what goes
+/// between them, and returns whether there were any.
+fn numbers<W: Write>(config: &SeqConfig, writer: &mut W) -> io::Result<bool> {
+    let format = Format::parse(&config.format);
+    // Whole numbers are counted exactly where the format allows, as adding up floats cannot
+    // step past 2^53.
+    let integers = (
+        as_integer(config.first),
+        as_integer(config.inc),
+        as_integer(config.last),
+    );
+    if let (Some(first), Some(inc), Some(last)) = integers {
+        let digits = first
+            .unsigned_abs()
+            .max(last.unsigned_abs())
+            .to_string()
+            .len();
+        if let Some(format) = integer_format(&format, digits) {
+            return integer_seq(first, inc, last, &format, config, writer);
+        }
+    }
+    let integer = format.is_integer();
+    let number = |value: f64| {
+        if integer {
+            Number::Integer(value as i64)
+        } else {
+            Number::Float(value)
+        }
+    };
+    let width = if config.equal_width {
+        let first = format.format(number(config.first)).len();
+        first.max(format.format(number(config.last)).len())
+    } else {
+        0
+    };
+    let mut k: u64 = 0;
+    loop {
+        let current = config.first + (k as f64) * config.inc;
+        if past_last(current, config.inc, config.last) {
+            break;
+        }
+        if k > 0 {
+            writer.write_all(config.between())?;
+        }
+        let formatted = format.format(number(current));
+        writer.write_all(&pad_with_zeros(formatted, width))?;
+        k += 1;
+    }
+    Ok(k > 0)
+}
+
+/// The sequence described by @p config as a string, for callers that want it in memory rather
+/// than written out.
+pub fn seq_to_string(config: &SeqConfig) -> Result<String, String> {
+    let mut output = Vec::new();
+    seq(config, &mut output).map_err(|e| e.to_string())?;
+    String::from_utf8(output).map_err(|e| e.to_string())
+}
+
+/// Whether @p current lies beyond @p last when counting in steps of @p inc.
+pub fn past_last(current: f64, inc: f64, last: f64) -> bool {
+    if inc < 0.0 {
+        current < last
+    } else {
+        current > last
+    }
+}
+
+/// The number of digits after the decimal point of @p float.
+pub fn detect_precision(float: &str) -> usize {
+    if hex_digits(float).is_some() {
+        return 0;
+    }
+    match float.find('.') {
+        Some(n) => float.len() - n - 1,
+        None => 0,
+    }
+}
+
+/// The digits of @p number after its sign and "0x" prefix, if it is a hex number.
+pub fn hex_digits(number: &str) -> Option<&str> {
+    let unsigned = number.strip_prefix(['+', '-']).unwrap_or(number);
+    if unsigned.starts_with("0x") || unsigned.starts_with("0X") {
+        Some(&unsigned[2..])
+    } else {
+        None
+    }
+}
+
+/// Interprets the C-style escapes \n, \t, \\ and \xHH in @p separator, as -e asks for. Other
+/// backslashes are left as they are.
+pub fn unescape_separator(separator: &str) -> Vec<u8> {
+    let bytes = separator.as_bytes();
+    let mut unescaped = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] != b'\\' || i + 1 == bytes.len() {
+            unescaped.push(bytes[i]);
+            i += 1;
+            continue;
+        }
+        match bytes[i + 1] {
+            b'n' => unescaped.push(b'\n'),
+            b't' => unescaped.push(b'\t'),
+            b'\\' => unescaped.push(b'\\'),
+            b'x' => {
+                let digits = bytes[i + 2..]
+                    .iter()
+                    .take(2)
+                    .take_while(|c| c.is_ascii_hexdigit())
+                    .count();
+                if digits == 0 {
+                    unescaped.extend_from_slice(b"\\x");
+                } else {
+                    let hex = &separator[i + 2..i + 2 + digits];
+                    unescaped.push(u8::from_str_radix(hex, 16).unwrap());
+                }
+                i += 2 + digits;
+                continue;
+            }
+            other => unescaped.extend_from_slice(&[b'\\', other]),
+        }
+        i += 2;
+    }
+    unescaped
+}
+
+/// Why a format given with -f cannot be used.
+#[derive(Debug, PartialEq)]
+pub enum FormatError {
+    /// The format ends before the conversion specifier.
+    EmptyFormat,
+    NoFormatFound,
+    DuplicateFlag(char),
+    InvalidSpecifier(char),
+    UnescapedPercent,
+    ExpectedDigits(u32),
+}
+
+impl fmt::Display for FormatError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            FormatError::EmptyFormat => write!(f, "empty format specifier"),
+            FormatError::NoFormatFound => write!(f, "no format found"),
+            FormatError::DuplicateFlag(flag) => write!(f, "duplicated format flag '{}'", flag),
+            FormatError::InvalidSpecifier(c) => write!(f, "invalid specifier '{}'", c),
+            FormatError::UnescapedPercent => write!(f, "unescaped sequence of '%' is invalid"),
+            FormatError::ExpectedDigits(n) => {
+                write!(f, "expected at least {} digits to be found", n)
+            }
+        }
+    }
+}
+
+pub type FormatParseResult = Result<(), FormatError>;
+
+/// Consumes printf's format flags '+', '-', ' ', '#', '0' till they occur. If a flag
+/// is found multiple times an error is reported.
+///
+/// Each consumed character increments @p index by one.
+fn consume_flags_if_any(format: &[u8], index: &mut usize) -> FormatParseResult {
+    // TODO: HashSet is an overkill. Somebody please stop me!
+    let mut flags_found: HashSet<char> = HashSet::new();
+    for ch in format {
+        match *ch as char {
+            '+' | '-' | ' ' | '#' | '0' => {
+                if !flags_found.insert(*ch as char) {
+                    return Err(FormatError::DuplicateFlag(*ch as char));
+                }
+            }
+            _ => break,
+        }
+        *index += 1;
+    }
+    Ok(())
+}
+
+/// Consumes a digit sequence till digits occur, expecting at least @p minimum_digits_expected
+/// digits.
+///
+/// Each consumed character increments @p index by one.
+fn consume_digits(
+    format: &[u8],
+    index: &mut usize,
+    minimum_digits_expected: u32,
+) -> FormatParseResult {
+    let mut digits_found = 0u32;
+    for ch in format {
+        if !(*ch as char).is_ascii_digit() {
+            break;
+        }
+        digits_found += 1;
+        *index += 1;
+    }
+    if minimum_digits_expected > digits_found {
+        Err(FormatError::ExpectedDigits(minimum_digits_expected))
+    } else {
+        Ok(())
+    }
+}
+
+/// Consumes printf's precision specifier '.prec'.
+fn consume_precision_if_any(format: &[u8], index: &mut usize) -> FormatParseResult {
+    if !format.is_empty() && format[0] == b'.' {
+        *index += 1;
+        consume_digits(&format[1..], index, 1)?;
+    }
+    Ok(())
+}
+
+/// Consumes printf's format specifier.
+fn consume_specifier(format: &[u8], index: &mut usize) -> FormatParseResult {
+    if format.is_empty() {
+        return Err(FormatError::EmptyFormat);
+    }
+    if !['a', 'e', 'f', 'g', 'A', 'E', 'F', 'G', 'd', 'i'].contains(&(format[0] as char)) {
+        return Err(FormatError::InvalidSpecifier(format[0] as char));
+    }
+    *index += 1;
+    Ok(())
+}
+
+/// Where the conversion specifier of @p format, which must have passed `validate_format`, is.
+fn conversion_index(format: &[u8]) -> Option<usize> {
+    let mut p = 0;
+    while p < format.len() {
+        if format[p] != b'%' {
+            p += 1;
+        } else if format.get(p + 1) == Some(&b'%') {
+            p += 2;
+        } else {
+            return format[p + 1..]
+                .iter()
+                .position(|c| !b"+- #0123456789.".contains(c))
+                .map(|n| p + 1 + n);
+        }
+    }
+    None
+}
+
+/// Whether the conversion of @p format, which must have passed `validate_format`, is an
+/// integer one: %d or %i.
+pub fn is_integer_format(format: &str) -> bool {
+    match conversion_index(format.as_bytes()) {
+        Some(n) => format.as_bytes()[n] == b'd' || format.as_bytes()[n] == b'i',
+        None => false,
+    }
+}
+
+/// Checks that @p format is one printf-style floating point or integer conversion among
+/// other text.
+pub fn validate_format(format: &str) -> FormatParseResult {
+    let bytes = format.as_bytes();
+    let mut p = 0;
+    let mut found_format = false;
+
+    while p < bytes.len() {
+        // Possibbly a format string.
+        if bytes[p] == b'%' {
+            let num_percents = bytes[p..].iter().take_while(|c| **c == b'%').count();
+
+            if !found_format && num_percents == 1 {
+                // We should definitely expect format string, or else the format is broken.
+                p += 1;
+
+                // printf's [flags]
+                consume_flags_if_any(&bytes[p..], &mut p)?;
+                // printf's [width]
+                consume_digits(&bytes[p..], &mut p, 0)?;
+                // printf's [.prec]
+                consume_precision_if_any(&bytes[p..], &mut p)?;
+                // printf's [specifier]
+                consume_specifier(&bytes[p..], &mut p)?;
+                found_format = true;
+            } else if num_percents % 2 != 0 {
+                // Not fully escaped sequence of %-signs
+                return Err(FormatError::UnescapedPercent);
+            } else {
+                // Leave what follows the escaped percents to the next iteration.
+                p += num_percents;
+            }
+        } else {
+            // Nothing interesting
+            p += 1;
+        }
+    }
+    if found_format {
+        Ok(())
+    } else {
+        Err(FormatError::NoFormatFound)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn precision_detection() {
+        assert_eq!(detect_precision("3.14"), 2);
+        assert_eq!(detect_precision(""), 0);
+        assert_eq!(detect_precision("314"), 0);
+        assert_eq!(detect_precision("0x1.8"), 0);
+    }
+
+    #[test]
+    fn hex_prefix() {
+        assert_eq!(hex_digits("0x1f"), Some("1f"));
+        assert_eq!(hex_digits("-0X1f"), Some("1f"));
+        assert_eq!(hex_digits("+0x1"), Some("1"));
+        assert_eq!(hex_digits("--0x1"), None);
+        assert_eq!(hex_digits("+-0x1"), None);
+        assert_eq!(hex_digits("10"), None);
+    }
+
+    #[test]
+    fn simple_format() {
+        for fmt in ["%a", "%e", "%f", "%g", "%A", "%E", "%F", "%G", "%d", "%i"] {
+            assert!(validate_format(fmt).is_ok());
+        }
+    }
+
+    #[test]
+    fn simple_precision() {
+        assert!(validate_format("%.3f").is_ok());
+        assert!(validate_format("%.32g").is_ok());
+    }
+
+    #[test]
+    fn no_format() {
+        assert_eq!(validate_format(""), Err(FormatError::NoFormatFound));
+        assert_eq!(validate_format("%"), Err(FormatError::EmptyFormat));
+        assert_eq!(validate_format("%%"), Err(FormatError::NoFormatFound));
+        assert_eq!(validate_format("nothing"), Err(FormatError::NoFormatFound));
+    }
+
+    #[test]
+    fn bad_format() {
+        assert_eq!(
+            validate_format("%00f"),
+            Err(FormatError::DuplicateFlag('0'))
+        );
+        assert_eq!(
+            validate_format("%c"),
+            Err(FormatError::InvalidSpecifier('c'))
+        );
+        assert_eq!(validate_format("%f%n"), Err(FormatError::UnescapedPercent));
+        assert_eq!(validate_format("%.f"), Err(FormatError::ExpectedDigits(1)));
+    }
+
+    #[test]
+    fn percent_escape() {
+        assert!(validate_format("%f%%").is_ok());
+        assert_eq!(validate_format("%f%%%"), Err(FormatError::UnescapedPercent));
+        assert!(validate_format("%f%%%%").is_ok());
+        assert_eq!(validate_format("%%f"), Err(FormatError::NoFormatFound));
+        // After an escape, a specifier letter is just text.
+        assert!(validate_format("%f%%f").is_ok());
+        assert!(validate_format("%f%%g").is_ok());
+        assert_eq!(validate_format("%%g%%"), Err(FormatError::NoFormatFound));
+    }
+
+    #[test]
+    fn bad_flag() {
+        assert_eq!(
+            validate_format("%x3f"),
+            Err(FormatError::InvalidSpecifier('x'))
+        );
+        assert_eq!(
+            validate_format("%*3f"),
+            Err(FormatError::InvalidSpecifier('*'))
+        );
+    }
+
+    #[test]
+    fn good_flag() {
+        for flag in ["%0f", "%+f", "%-f", "%#f", "%+#-f", "% f"] {
+            assert!(validate_format(flag).is_ok());
+        }
+    }
+
+    // TODO: Write more test-cases covering width parsing too.
+
+    #[test]
+    fn counting_direction() {
+        assert!(past_last(11.0, 1.0, 10.0));
+        assert!(!past_last(10.0, 1.0, 10.0));
+        assert!(past_last(0.0, -1.0, 1.0));
+        assert!(!past_last(1.0, -1.0, 1.0));
+    }
+
+    fn sequence(first: f64, inc: f64, last: f64, format: &str, equal_width: bool) -> String {
+        let config = SeqConfig {
+            separator: b",".to_vec(),
+            equal_width,
+            first,
+            inc,
+            last,
+            format: format.to_owned(),
+            json: false,
+        };
+        seq_to_string(&config).unwrap()
+    }
+
+    #[test]
+    fn sequences() {
+        assert_eq!(sequence(1.0, 1.0, 5.0, "%g", false), "1,2,3,4,5\n");
+        assert_eq!(sequence(0.5, 0.25, 1.0, "%.2f", false), "0.50,0.75,1.00\n");
+        assert_eq!(sequence(1.0, 1.0, 0.0, "%g", false), "");
+        assert_eq!(sequence(1.0, 1.0, 2.0, "x%gy%%", false), "x1y%,x2y%\n");
+    }
+
+    #[test]
+    fn negative_increments() {
+        assert_eq!(sequence(3.0, -1.0, -1.0, "%g", false), "3,2,1,0,-1\n");
+        assert_eq!(sequence(1.0, -1.0, 5.0, "%g", false), "");
+    }
+
+    #[test]
+    fn equal_width() {
+        assert_eq!(sequence(8.0, 1.0, 10.0, "%g", true), "08,09,10\n");
+        assert_eq!(sequence(-1.0, 1.0, 1.0, "%g", true), "-1,00,01\n");
+        assert_eq!(sequence(1.0, 0.5, 2.0, "%.1f", true), "1.0,1.5,2.0\n");
+        assert_eq!(pad_with_zeros(b"-5".to_vec(), 4), b"-005");
+    }
+
+    #[test]
+    fn integer_formats() {
+        assert!(is_integer_format("%d"));
+        assert!(is_integer_format("%%x%+05i%%"));
+        assert!(!is_integer_format("%%d%g"));
+        assert!(!is_integer_format("%.3f"));
+        assert_eq!(sequence(1.0, 1.0, 5.0, "%d", false), "1,2,3,4,5\n");
+        assert_eq!(
+            sequence(-2.0, 2.0, 2.0, "%+03i%%", false),
+            "-02%,+00%,+02%\n"
+        );
+        assert_eq!(sequence(8.0, 1.0, 10.0, "%d", true), "08,09,10\n");
+        assert_eq!(sequence(1e15, 1.0, 1e15, "%d", false), "1000000000000000\n");
+    }
+
+    #[test]
+    fn integer_fast_path() {
+        let integer = |format: &str, digits: usize| integer_format(&Format::parse(format), digits);
+        let plain = Some(Format::parse("%d"));
+        assert_eq!(integer("%d", 20), plain);
+        assert_eq!(integer("x%+05i", 20), Some(Format::parse("x%+05i")));
+        assert_eq!(integer("%.10g", 10), plain);
+        assert_eq!(integer("%g", 6), plain);
+        assert_eq!(integer("%.0g", 1), plain);
+        assert_eq!(integer("%.2g", 3), None);
+        assert_eq!(integer("%g", 7), None);
+        assert_eq!(integer("%.3f", 1), None);
+        assert_eq!(integer("n%g", 1), None);
+        assert_eq!(as_integer(-3.0), Some(-3));
+        assert_eq!(as_integer(0.5), None);
+        assert_eq!(as_integer(1e19), None);
+
+        // Counting in floats from 2^53 would never get past it, as 2^53 + 1 rounds back.
+        let first = 9007199254740992.0;
+        assert_eq!(
+            sequence(first, 1.0, first + 4.0, "%.16g", false),
+            "9007199254740992,9007199254740993,9007199254740994,9007199254740995,\
+             9007199254740996\n"
+        );
+        assert_eq!(
+            sequence(999999998.0, 1.0, 1e9, "%.10g", true),
+            "0999999998,0999999999,1000000000\n"
+        );
+        // The end of i64's range ends the sequence rather than wrapping around.
+        let last = 9223372036854774784.0;
+        let near_max = sequence(last - 2048.0, 2048.0, last, "%d", false);
+        assert_eq!(near_max, "9223372036854772736,9223372036854774784\n");
+        // Formats which would print an exponent keep to the float path.
+        assert_eq!(sequence(999.0, 1.0, 1000.0, "%.3g", false), "999,1e+03\n");
+    }
+
+    #[test]
+    fn decimal_matches_format() {
+        let format = Format::parse("%d");
+        for &number in &[0, 7, -7, 10, -100, 123456789, i64::MAX, i64::MIN] {
+            for &width in &[0, 3, 25] {
+                let mut output = Vec::new();
+                write_decimal(&mut output, number, width).unwrap();
+                let expected = format.format(Number::Integer(number));
+                assert_eq!(
+                    output,
+                    pad_with_zeros(expected, width),
+                    "{} {}",
+                    number,
+                    width
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn json_arrays() {
+        let mut config = SeqConfig {
+            separator: b"ignored".to_vec(),
+            equal_width: false,
+            first: 1.0,
+            inc: 1.0,
+            last: 3.0,
+            format: "%g".to_owned(),
+            json: true,
+        };
+        assert_eq!(seq_to_string(&config).unwrap(), "[1,2,3]\n");
+        config.format = "%.2f".to_owned();
+        config.inc = 0.5;
+        config.last = 2.0;
+        assert_eq!(seq_to_string(&config).unwrap(), "[1.00,1.50,2.00]\n");
+        config.last = 0.0;
+        assert_eq!(seq_to_string(&config).unwrap(), "[]\n");
+    }
+
+    #[test]
+    fn separator_escapes() {
+        assert_eq!(unescape_separator("\\t"), b"\t");
+        assert_eq!(unescape_separator("a\\nb\\\\"), b"a\nb\\");
+        assert_eq!(unescape_separator("\\x41\\x9\\xff"), b"A\t\xff");
+        assert_eq!(unescape_separator("\\x4142"), b"A42");
+        // Anything else stays as it is.
+        assert_eq!(unescape_separator("\\q\\x\\"), b"\\q\\x\\");
+    }
+
+    #[test]
+    fn invalid_format() {
+        let config = SeqConfig {
+            separator: b"\n".to_vec(),
+            equal_width: false,
+            first: 1.0,
+            inc: 1.0,
+            last: 1.0,
+            format: "%c".to_owned(),
+            json: false,
+        };
+        let fixed = SeqConfig {
+            format: "%g".to_owned(),
+            ..config.clone()
+        };
+        assert_eq!(seq_to_string(&fixed).unwrap(), "1\n");
+        assert_eq!(seq_to_string(&config).unwrap_err(), "invalid specifier 'c'");
+    }
+
+    #[test]
+    fn non_utf8_output() {
+        let config = SeqConfig {
+            separator: b"\xff".to_vec(),
+            equal_width: false,
+            first: 1.0,
+            inc: 1.0,
+            last: 2.0,
+            format: "%g".to_owned(),
+            json: false,
+        };
+        assert!(seq_to_string(&config).is_err());
+        let mut output = Vec::new();
+        seq(&config, &mut output).unwrap();
+        assert_eq!(output, b"1\xff2\n");
+    }
+}
@@ -1,349 +1,313 @@
 use std::cmp;
-use std::collections::HashSet;
+use std::fs;
+use std::io::{self, Write};
 
 #[macro_use(die)]
 extern crate utils;
-extern crate getopts;
 
-#[derive(Debug)]
-pub struct SeqConfig {
-    separator: String,
-    equal_width: bool,
-    first: f64,
-    inc: f64,
-    last: f64,
-    format: String,
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn precision_detection() {
-        assert_eq!(detect_precision("3.14"), 2);
-        assert_eq!(detect_precision(""), 0);
-        assert_eq!(detect_precision("314"), 0);
-    }
-
-    #[test]
-    fn simple_format() {
-        for fmt in vec!["%a", "%e", "%f", "%g", "%A", "%E", "%F", "%G"] {
-            assert!(validate_format(fmt).is_ok());
+extern crate seq;
+
+use utils::args::ArgParser;
+use seq::{
+    as_integer, detect_precision, hex_digits, is_integer_format, unescape_separator, SeqConfig,
+};
+
+/// Finds where the operands start in @p args. Negative numbers would otherwise be taken for
+/// options, so, like GNU seq, option parsing ends at the first one of them.
+fn operands_start(args: &[String]) -> Option<usize> {
+    let mut i = 0;
+    while i < args.len() {
+        let arg = &args[i];
+        if arg == "--" {
+            return None;
         }
-    }
-
-    #[test]
-    fn simple_precision() {
-        assert!(validate_format("%.3f").is_ok());
-        assert!(validate_format("%.32g").is_ok());
-    }
-
-    #[test]
-    fn no_format() {
-        assert!(validate_format("").is_err());
-        assert!(validate_format("%").is_err());
-        assert!(validate_format("%%").is_err());
-        assert!(validate_format("nothing").is_err());
-    }
-
-    #[test]
-    fn bad_format() {
-        assert!(validate_format("%00f").is_err());
-        assert!(validate_format("%c").is_err());
-        assert!(validate_format("%f%n").is_err());
-    }
-
-    #[test]
-    fn percent_escape() {
-        assert!(validate_format("%f%%").is_ok());
-        assert!(validate_format("%f%%%").is_err());
-        assert!(validate_format("%f%%%%").is_ok());
-        assert!(validate_format("%%f").is_err());
-    }
-
-    #[test]
-    fn bad_flag() {
-        assert!(validate_format("%x3f").is_err());
-        assert!(validate_format("%*3f").is_err());
-    }
-
-    #[test]
-    fn good_flag() {
-        for flag in vec!["%0f", "%+f", "%-f", "%#f", "%+#-f", "% f"] {
-            assert!(validate_format(flag).is_ok());
-        }
-    }
-
-    // TODO: Write more test-cases covering width parsing too.
-}
-
-extern crate libc;
-use libc::c_char;
-use libc::c_int;
-use libc::c_double;
-use std::ffi;
-
-#[link(name = "c")]
-extern "C" {
-    fn printf(format: *const c_char, ...) -> c_int;
-}
-
-fn print_formatted_f64(fmt: &str, value: f64) {
-    // TODO: make it locale-independent
-    unsafe {
-        let ret = printf(ffi::CString::new(fmt).unwrap().as_ptr(), value as c_double);
-        if ret < 0 {
-            die!("I/O error");
+        let bytes = arg.as_bytes();
+        if bytes.len() > 1 && bytes[0] == b'-' && (bytes[1].is_ascii_digit() || bytes[1] == b'.') {
+            return Some(i);
         }
-    }
-}
-
-fn print_string(string: &str) {
-    unsafe {
-        let ret = printf(
-            ffi::CString::new("%s").unwrap().as_ptr(),
-            ffi::CString::new(string).unwrap().as_ptr(),
-        );
-        if ret < 0 {
-            die!("I/O error");
+        let with_value = ["-f", "-s", "--format", "--separator", "--from-file", "--format-output"];
+        if with_value.contains(&arg.as_str()) {
+            // The next argument is this option's value.
+            i += 1;
         }
+        i += 1;
     }
+    None
 }
 
-fn seq(config: &SeqConfig) -> FormatParseResult {
-    validate_format(&config.format)?;
-    let mut k: u64 = 0;
-    loop {
-        let current = config.first + (k as f64) * config.inc;
-        if current > config.last {
-            break;
-        }
-        if k > 0 {
-            print_string(&config.separator);
-        }
-        print_formatted_f64(&config.format, current);
-        k += 1;
+/// Reads the operands from the single line of @p file.
+fn read_operands(file: &str) -> Vec<String> {
+    let contents = fs::read_to_string(file).unwrap_or_else(|e| die!("{}: {}", file, e));
+    let contents = contents.strip_suffix('\n').unwrap_or(&contents);
+    if contents.contains('\n') {
+        die!(code = 2, "{}: the operands must be on a single line", file);
     }
-    print_string("\n");
-    Ok(())
+    contents.split_whitespace().map(str::to_owned).collect()
 }
 
-fn show_help(opts: &getopts::Options) {
+fn show_help(opts: &ArgParser) {
     let brief = format!(
         concat!(
             "Clone of the standard GNU seq.\n",
             "Usage: {0}: [OPTION]... LAST\n",
             "  or:  {0}: [OPTION]... FIRST LAST\n",
             "  or:  {0}: [OPTION]... FIRST INCREMENT LAST\n",
-            "Print numbers from FIRST to LAST, in steps of INCREMENT."
+            "Print numbers from FIRST to LAST, in steps of INCREMENT.\n",
+            "Like GNU seq, the separator is used verbatim unless -e is given."
         ),
-        std::env::args().nth(0).unwrap()
+        std::env::args().next().unwrap()
     );
     print!("{}", opts.usage(&brief));
 }
 
-fn detect_precision(float: &str) -> usize {
-    match float.find('.') {
-        Some(n) => float.len() - n - 1,
-        None => 0,
-    }
-}
-
-fn parse_float(float: &str) -> f64 {
-    float.parse::<f64>().unwrap_or_else(|_| {
-        die!("invalid floating point argument '{}'", float)
-    })
+fn all_integral(first: f64, inc: f64, last: f64) -> bool {
+    [first, inc, last].iter().all(|x| x.fract() == 0.0)
 }
 
-type FormatParseResult = Result<(), String>;
-
-/// Consumes printf's format flags '+', '-', ' ', '#', '0' till they occur. If a flag
-/// is found multiple times an error is reported.
-///
-/// Each consumed character increments @p index by one.
-fn consume_flags_if_any(format: &[u8], index: &mut usize) -> FormatParseResult {
-    // TODO: HashSet is an overkill. Somebody please stop me!
-    let mut flags_found: HashSet<char> = HashSet::new();
-    for ch in format {
-        match *ch as char {
-            '+' | '-' | ' ' | '#' | '0' => {
-                if !flags_found.insert(*ch as char) {
-                    return Err("duplicated format flags".into());
-                }
-            }
-            _ => break,
-        }
-        *index += 1;
+/// The format used without -f: "%.Ng" for integral operands, where N is large enough to
+/// print every number in full, or "%.Pf" with the largest precision P among the operands.
+/// Fractions written without a decimal point, like hex floats, are printed with "%g".
+fn default_format(precision: usize, first: f64, inc: f64, last: f64) -> String {
+    if precision == 0 && !all_integral(first, inc, last) {
+        "%g".to_owned()
+    } else if precision == 0 {
+        let digits = [first, inc, last]
+            .iter()
+            .map(|x| format!("{:.0}", x.abs()).len())
+            .max()
+            .unwrap();
+        format!("%.{}g", digits)
+    } else {
+        format!("%.{}f", precision)
     }
-    Ok(())
 }
 
-/// Consumes a digit sequence till digits occur, expecting at least @p minimum_digits_expected
-/// digits.
-///
-/// Each consumed character increments @p index by one.
-fn consume_digits(
-    format: &[u8],
-    index: &mut usize,
-    minimum_digits_expected: u32,
-) -> FormatParseResult {
-    let mut digits_found = 0u32;
-    for ch in format {
-        if !(*ch as char).is_digit(10) {
-            break;
-        }
-        digits_found += 1;
-        *index += 1;
+/// Parses a hex number without its "0x" prefix: hex digits, optionally with a fraction, and
+/// an optional binary exponent as in C99's "1.8p3".
+fn parse_hex(digits: &str) -> Option<f64> {
+    let (mantissa, exponent) = match digits.find(['p', 'P']) {
+        Some(p) => (&digits[..p], digits[p + 1..].parse::<i32>().ok()?),
+        None => (digits, 0),
+    };
+    let (whole, fraction) = match mantissa.find('.') {
+        Some(dot) => (&mantissa[..dot], &mantissa[dot + 1..]),
+        None => (mantissa, ""),
+    };
+    if whole.is_empty() && fraction.is_empty() {
+        return None;
     }
-    if minimum_digits_expected > digits_found {
-        Err(format!(
-            "expected at least {} digits to be found",
-            minimum_digits_expected
-        ))
-    } else {
-        Ok(())
+    let mut value = 0.0;
+    let mut scale = 1.0;
+    for c in whole.chars() {
+        value = value * 16.0 + c.to_digit(16)? as f64;
     }
-}
-
-/// Consumes printf's precision specifier '.prec'.
-fn consume_precision_if_any(format: &[u8], index: &mut usize) -> FormatParseResult {
-    if format.len() > 0 {
-        if format[0] == b'.' {
-            *index += 1;
-            consume_digits(&format[1..], index, 1)?;
-        }
+    for c in fraction.chars() {
+        scale /= 16.0;
+        value += c.to_digit(16)? as f64 * scale;
     }
-    Ok(())
+    Some(value * 2f64.powi(exponent))
 }
 
-/// Consumes printf's format specifier.
-fn consume_specifier(format: &[u8], index: &mut usize) -> FormatParseResult {
-    if format.len() == 0 {
-        return Err("empty format specifier".into());
+/// Parses an operand, which is either a decimal or a hex number. Hex numbers with too large
+/// an exponent are rejected like infinite decimal ones.
+fn parse_numeric(number: &str) -> f64 {
+    match hex_digits(number) {
+        Some(digits) => match parse_hex(digits) {
+            Some(value) if value.is_finite() && number.starts_with('-') => -value,
+            Some(value) if value.is_finite() => value,
+            _ => die!(code = 2, "invalid floating point argument '{}'", number),
+        },
+        None => parse_float(number),
     }
-    if !vec!['a', 'e', 'f', 'g', 'A', 'E', 'F', 'G'].contains(&(format[0] as char)) {
-        return Err(format!("invalid specifier '{}'", format[0] as char));
-    }
-    *index += 1;
-    Ok(())
 }
 
-fn validate_format(format: &str) -> FormatParseResult {
-    let bytes = format.as_bytes();
-    let mut p = 0;
-    let mut found_format = false;
-
-    while p < bytes.len() {
-        // Possibbly a format string.
-        if bytes[p] == b'%' {
-            let num_percents = bytes[p..].iter().take_while(|c| **c == b'%').count();
-
-            if !found_format && num_percents == 1 {
-                // We should definitely expect format string, or else the format is broken.
-                p += 1;
-
-                // printf's [flags]
-                consume_flags_if_any(&bytes[p..], &mut p)?;
-                // printf's [width]
-                consume_digits(&bytes[p..], &mut p, 0)?;
-                // printf's [.prec]
-                consume_precision_if_any(&bytes[p..], &mut p)?;
-                // printf's [specifier]
-                consume_specifier(&bytes[p..], &mut p)?;
-                found_format = true;
-            } else if num_percents % 2 != 0 {
-                // Not fully escaped sequence of %-signs
-                return Err("unescaped sequence of '%' is invalid".into());
-            } else {
-                p += num_percents + 1;
-            }
-        } else {
-            // Nothing interesting
-            p += 1;
-        }
-    }
-    if found_format {
-        Ok(())
-    } else {
-        Err("no format found".into())
+/// Parses a decimal operand. Infinities and NaN are rejected too, as there would be no end to
+/// counting towards them.
+fn parse_float(float: &str) -> f64 {
+    match float.parse::<f64>() {
+        Ok(value) if value.is_finite() => value,
+        _ => die!(code = 2, "invalid floating point argument '{}'", float),
     }
 }
 
 fn main() {
-    let args: Vec<String> = std::env::args().collect();
-    let mut opts = getopts::Options::new();
-    // TODO: Support this
-    //  opts.optflag(
-    //  "w",
-    //  "equal-width",
-    //  "equalize width by padding with leading zeroes",
-    // );
-    opts.optflag("h", "help", "display this help and exit");
-    opts.optflag("v", "version", "output version information and exit");
-    opts.optopt(
+    let mut args: Vec<String> = std::env::args().collect();
+    if let Some(n) = operands_start(&args[1..]) {
+        args.insert(n + 1, "--".to_owned());
+    }
+    let mut opts = ArgParser::new();
+    opts.flag(
+        "w",
+        "equal-width",
+        "equalize width by padding with leading zeroes",
+    );
+    opts.flag("h", "help", "display this help and exit");
+    opts.flag("v", "version", "output version information and exit");
+    opts.option(
         "f",
         "format",
-        "use printf style floating-point FORMAT",
+        "use printf style floating-point FORMAT, or %d or %i for integer operands",
         "FORMAT",
     );
-    opts.optopt(
+    opts.option(
         "s",
         "separator",
         "use STRING to separate numbers (default: \\n)",
         "STRING",
     );
+    opts.option(
+        "",
+        "from-file",
+        "read FIRST, INCREMENT and LAST, as given on the command line, from the single line \
+         of FILE",
+        "FILE",
+    );
+    opts.option(
+        "",
+        "format-output",
+        "print the numbers as 'text' (the default) or as a 'json' array",
+        "KIND",
+    );
+    opts.flag(
+        "e",
+        "sep-escape",
+        "interpret the escapes \\n, \\t, \\\\ and \\xHH in the separator",
+    );
     let options = match opts.parse(&args[1..]) {
         Ok(m) => m,
-        Err(f) => die!("{}", f.to_string()),
+        Err(f) => die!(code = 2, "{}", f.to_string()),
     };
     if options.opt_present("h") {
         return show_help(&opts);
     }
     if options.opt_present("v") {
-        return println!(
-            "Implementation of GNU seq, version {}",
-            env!("CARGO_PKG_VERSION")
-        );
+        return utils::version::print_version("seq");
     }
 
-    if options.free.is_empty() {
-        die!("missing operand");
-    } else if options.free.len() > 3 {
-        die!("extra operand '{}'", options.free[3])
+    let operands = match options.opt_str("from-file") {
+        Some(_) if !options.free.is_empty() => {
+            die!(code = 2, "extra operand '{}': the operands are read from a file", options.free[0])
+        }
+        Some(file) => read_operands(&file),
+        None => options.free.clone(),
+    };
+    if operands.is_empty() {
+        die!(code = 2, "missing operand");
+    } else if operands.len() > 3 {
+        die!(code = 2, "extra operand '{}'", operands[3])
     }
 
     let mut precision = 0;
-    let first: f64 = if options.free.len() > 1 {
-        precision = detect_precision(&options.free[0]);
-        parse_float(&options.free[0])
+    let first: f64 = if operands.len() > 1 {
+        precision = detect_precision(&operands[0]);
+        parse_numeric(&operands[0])
     } else {
         1.0f64
     };
-    let inc: f64 = if options.free.len() > 2 {
-        precision = cmp::max(precision, detect_precision(&options.free[1]));
-        parse_float(&options.free[1])
+    let inc: f64 = if operands.len() > 2 {
+        // With an explicit increment, the last number may ask for more digits too, as in
+        // "seq 1 0.1 2.00".
+        precision = cmp::max(precision, detect_precision(&operands[1]));
+        precision = cmp::max(precision, detect_precision(&operands[2]));
+        parse_numeric(&operands[1])
     } else {
         1.0f64
     };
-    let last: f64 = if options.free.len() >= 2 {
-        parse_float(&options.free[1])
-    } else {
-        parse_float(&options.free[0])
-    };
+    let last: f64 = parse_numeric(operands.last().unwrap());
 
     let config = SeqConfig {
-        separator: options.opt_str("s").unwrap_or("\n".into()),
-        equal_width: false, // options.opt_present("w"),
-        first: first,
-        inc: inc,
-        last: last,
-        format: options.opt_str("f").unwrap_or(
-            format!("%.{}f", precision).into(),
-        ),
+        separator: match options.opt_str("s") {
+            Some(ref separator) if options.opt_present("e") => unescape_separator(separator),
+            Some(separator) => separator.into_bytes(),
+            None => b"\n".to_vec(),
+        },
+        equal_width: options.opt_present("w"),
+        first,
+        inc,
+        last,
+        format: options
+            .opt_str("f")
+            .unwrap_or_else(|| default_format(precision, first, inc, last)),
+        json: match options.opt_str("format-output").as_deref() {
+            None | Some("text") => false,
+            Some("json") => true,
+            Some(kind) => die!(code = 2, "invalid output format '{}'", kind),
+        },
     };
+    // Only an increment of exactly zero would never get anywhere; tiny ones are fine.
+    if config.inc == 0.0 {
+        die!(code = 2, "invalid Zero increment value: '{}'", operands[1]);
+    }
+    // The numbers are printed as long long, which larger ones would saturate.
+    let in_range = [first, inc, last].iter().all(|x| as_integer(*x).is_some());
+    if is_integer_format(&config.format) && !in_range {
+        die!(code = 2, "format '{}' needs integer arguments within 64 bits", config.format);
+    }
+    // JSON has its own separators, and forbids the leading zeros of -w.
+    if config.json && (options.opt_present("s") || config.equal_width) {
+        die!(code = 2, "--format-output=json cannot be combined with --separator or --equal-width");
+    }
+    if config.equal_width && options.opt_present("f") {
+        die!(code = 2, "format string may not be specified when printing equal width strings");
+    }
+    if let Err(e) = seq::validate_format(&config.format) {
+        die!(code = 2, "{}", e);
+    }
+
+    let stdout = io::stdout();
+    let mut writer = io::BufWriter::new(stdout.lock());
+    seq::seq(&config, &mut writer)
+        .and_then(|_| writer.flush())
+        .unwrap_or_else(|e| die!("{}", e));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hex_operands() {
+        assert_eq!(parse_numeric("0x10"), 16.0);
+        assert_eq!(parse_numeric("0XfF"), 255.0);
+        assert_eq!(parse_numeric("-0xa"), -10.0);
+        assert_eq!(parse_numeric("0x1.8"), 1.5);
+        assert_eq!(parse_numeric("0x1.8p1"), 3.0);
+        assert_eq!(parse_numeric("0x.4P-2"), 0.0625);
+        assert_eq!(parse_numeric("12.5"), 12.5);
+        assert_eq!(parse_hex(""), None);
+        assert_eq!(parse_hex("."), None);
+        assert_eq!(parse_hex("1g"), None);
+        assert_eq!(parse_hex("1p"), None);
+    }
+
+    fn strings(args: &[&str]) -> Vec<String> {
+        args.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn integral_detection() {
+        assert!(all_integral(1.0, 1.0, 10.0));
+        assert!(all_integral(-5.0, -1.0, 1e6));
+        assert!(!all_integral(1.0, 0.5, 10.0));
+        assert!(!all_integral(1.0, 1.0, f64::INFINITY));
+    }
+
+    #[test]
+    fn default_formats() {
+        assert_eq!(default_format(0, 1.0, 1.0, 10.0), "%.2g");
+        assert_eq!(default_format(0, 1.0, 1.0, 1_000_000.0), "%.7g");
+        assert_eq!(default_format(0, -100.0, -1.0, 1.0), "%.3g");
+        assert_eq!(default_format(2, 1.0, 0.25, 2.0), "%.2f");
+        assert_eq!(default_format(0, 1.5, 1.0, 3.0), "%g");
+    }
 
-    seq(&config).unwrap_or_else(|e| {
-        die!("{}", e);
-    });
+    #[test]
+    fn negative_operands() {
+        assert_eq!(operands_start(&strings(&["5", "-1", "1"])), Some(1));
+        assert_eq!(operands_start(&strings(&["-1", "1"])), Some(0));
+        assert_eq!(operands_start(&strings(&["-s", "-1", "3"])), None);
+        assert_eq!(operands_start(&strings(&["-s:", "-.5", "1"])), Some(1));
+        assert_eq!(operands_start(&strings(&["--", "-1"])), None);
+        assert_eq!(operands_start(&strings(&["-f", "%g", "3"])), None);
+    }
 }
@@ -0,0 +1,423 @@
+//! printf-style formatting of the numbers seq prints, done in Rust rather than by snprintf, so
+//! that the output does not depend on the C library or its locale.
+
+/// A number to format: integers go with the %d and %i conversions, floats with the others.
+#[derive(Clone, Copy, Debug)]
+pub enum Number {
+    Float(f64),
+    Integer(i64),
+}
+
+/// A format which passed `validate_format`, taken apart once for all the numbers it prints:
+/// the conversion with its flags, width and precision, and the text around it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Format {
+    prefix: Vec<u8>,
+    suffix: Vec<u8>,
+    // The flags: '-', '+', ' ', '#' and '0'.
+    left: bool,
+    plus: bool,
+    space: bool,
+    alternate: bool,
+    zero: bool,
+    width: usize,
+    precision: Option<usize>,
+    conversion: u8,
+}
+
+/// Appends @p text to @p to with each "%%" turned into '%'.
+fn unescape_percents(text: &[u8], to: &mut Vec<u8>) {
+    let mut p = 0;
+    while p < text.len() {
+        to.push(text[p]);
+        p += if text[p] == b'%' { 2 } else { 1 };
+    }
+}
+
+impl Format {
+    /// Parses @p format, which must have passed `validate_format`.
+    pub fn parse(format: &str) -> Format {
+        let bytes = format.as_bytes();
+        let mut start = 0;
+        while bytes[start] != b'%' || bytes[start + 1] == b'%' {
+            start += if bytes[start] == b'%' { 2 } else { 1 };
+        }
+        let mut parsed = Format {
+            prefix: Vec::new(),
+            suffix: Vec::new(),
+            left: false,
+            plus: false,
+            space: false,
+            alternate: false,
+            zero: false,
+            width: 0,
+            precision: None,
+            conversion: 0,
+        };
+        unescape_percents(&bytes[..start], &mut parsed.prefix);
+        let mut p = start + 1;
+        loop {
+            match bytes[p] {
+                b'-' => parsed.left = true,
+                b'+' => parsed.plus = true,
+                b' ' => parsed.space = true,
+                b'#' => parsed.alternate = true,
+                b'0' => parsed.zero = true,
+                _ => break,
+            }
+            p += 1;
+        }
+        let digits = |p: &mut usize| {
+            let start = *p;
+            while bytes[*p].is_ascii_digit() {
+                *p += 1;
+            }
+            format[start..*p].parse::<usize>().unwrap_or(0)
+        };
+        parsed.width = digits(&mut p);
+        if bytes[p] == b'.' {
+            p += 1;
+            parsed.precision = Some(digits(&mut p));
+        }
+        parsed.conversion = bytes[p];
+        unescape_percents(&bytes[p + 1..], &mut parsed.suffix);
+        parsed
+    }
+
+    /// Whether the conversion is an integer one: %d or %i.
+    pub fn is_integer(&self) -> bool {
+        self.conversion == b'd' || self.conversion == b'i'
+    }
+
+    /// Whether this is a lone "%d", which prints a number and nothing else.
+    pub fn is_plain_integer(&self) -> bool {
+        *self == Format::parse("%d")
+    }
+
+    /// The precision of a lone %g or %.Ng, which prints whole numbers with up to that many
+    /// digits as %d does.
+    pub fn general_precision(&self) -> Option<usize> {
+        let general = Format {
+            precision: self.precision,
+            ..Format::parse("%g")
+        };
+        if *self == general {
+            Some(self.precision.unwrap_or(6).max(1))
+        } else {
+            None
+        }
+    }
+
+    /// Formats @p value, which must suit the conversion, as printf would in the C locale.
+    pub fn format(&self, value: Number) -> Vec<u8> {
+        let (negative, body) = match value {
+            Number::Integer(value) => (value < 0, self.integer(value.unsigned_abs())),
+            Number::Float(value) => (value.is_sign_negative(), self.float(value.abs())),
+        };
+        let sign: &[u8] = if negative {
+            b"-"
+        } else if self.plus {
+            b"+"
+        } else if self.space {
+            b" "
+        } else {
+            b""
+        };
+        let finite = match value {
+            Number::Integer(_) => true,
+            Number::Float(value) => value.is_finite(),
+        };
+        // Zeros go after the sign and the "0x" of %a, but an integer precision or '-' turns
+        // them off, and infinity and NaN are padded with spaces.
+        let zeros =
+            self.zero && !self.left && finite && !(self.is_integer() && self.precision.is_some());
+        let radix_prefix = if finite && self.conversion.eq_ignore_ascii_case(&b'a') {
+            2
+        } else {
+            0
+        };
+        let padding = self.width.saturating_sub(sign.len() + body.len());
+        let mut formatted = self.prefix.clone();
+        if zeros {
+            formatted.extend_from_slice(sign);
+            formatted.extend_from_slice(&body[..radix_prefix]);
+            formatted.resize(formatted.len() + padding, b'0');
+            formatted.extend_from_slice(&body[radix_prefix..]);
+        } else if self.left {
+            formatted.extend_from_slice(sign);
+            formatted.extend_from_slice(&body);
+            formatted.resize(formatted.len() + padding, b' ');
+        } else {
+            formatted.resize(formatted.len() + padding, b' ');
+            formatted.extend_from_slice(sign);
+            formatted.extend_from_slice(&body);
+        }
+        formatted.extend_from_slice(&self.suffix);
+        formatted
+    }
+
+    /// The digits of @p value, at least as many as the precision asks for.
+    fn integer(&self, value: u64) -> Vec<u8> {
+        let digits = match self.precision {
+            Some(0) if value == 0 => String::new(),
+            Some(precision) => format!("{:01$}", value, precision),
+            None => value.to_string(),
+        };
+        digits.into_bytes()
+    }
+
+    /// @p value, which is not negative, without its sign.
+    fn float(&self, value: f64) -> Vec<u8> {
+        let upper = self.conversion.is_ascii_uppercase();
+        let formatted = if value.is_infinite() {
+            "inf".to_owned()
+        } else if value.is_nan() {
+            "nan".to_owned()
+        } else {
+            match self.conversion.to_ascii_lowercase() {
+                b'f' => {
+                    let precision = self.precision.unwrap_or(6);
+                    self.point(format!("{:.*}", precision, value), precision)
+                }
+                b'e' => self.exponential(value, self.precision.unwrap_or(6)),
+                b'g' => self.general(value),
+                _ => self.hexadecimal(value),
+            }
+        };
+        if upper {
+            formatted.to_ascii_uppercase().into_bytes()
+        } else {
+            formatted.into_bytes()
+        }
+    }
+
+    /// Ends @p number with a decimal point if it has no digits after one, given by
+    /// @p precision, and '#' asks for the point anyway.
+    fn point(&self, mut number: String, precision: usize) -> String {
+        if precision == 0 && self.alternate {
+            number.push('.');
+        }
+        number
+    }
+
+    /// @p value as %e prints it, with @p precision digits after the point.
+    fn exponential(&self, value: f64, precision: usize) -> String {
+        let formatted = format!("{:.*e}", precision, value);
+        let (mantissa, exponent) = formatted.split_at(formatted.find('e').unwrap());
+        let exponent: i32 = exponent[1..].parse().unwrap();
+        let sign = if exponent < 0 { '-' } else { '+' };
+        let mantissa = self.point(mantissa.to_owned(), precision);
+        format!("{}e{}{:02}", mantissa, sign, exponent.abs())
+    }
+
+    /// @p value as %g prints it: as %e or %f would depending on its exponent, without
+    /// trailing zeros unless '#' keeps them.
+    fn general(&self, value: f64) -> String {
+        let precision = self.precision.unwrap_or(6).max(1);
+        let exponent_of = |formatted: String| -> i32 {
+            formatted[formatted.find('e').unwrap() + 1..]
+                .parse()
+                .unwrap()
+        };
+        let exponent = if value == 0.0 {
+            0
+        } else {
+            exponent_of(format!("{:.*e}", precision - 1, value))
+        };
+        let (mut mantissa, exponent) = if exponent >= -4 && exponent < precision as i32 {
+            let decimals = (precision as i32 - 1 - exponent) as usize;
+            (
+                self.point(format!("{:.*}", decimals, value), decimals),
+                None,
+            )
+        } else {
+            // When rounding carries the value up into this style, glibc keeps the digits %f
+            // would have had: "%#.3g" prints 999.5 as "1.e+03".
+            let digits = if exponent >= precision as i32
+                && exponent_of(format!("{:e}", value)) < precision as i32
+            {
+                0
+            } else {
+                precision - 1
+            };
+            let formatted = self.exponential(value, digits);
+            let e = formatted.find('e').unwrap();
+            (formatted[..e].to_owned(), Some(formatted[e..].to_owned()))
+        };
+        if !self.alternate && mantissa.contains('.') {
+            let trimmed = mantissa.trim_end_matches('0').trim_end_matches('.').len();
+            mantissa.truncate(trimmed);
+        }
+        mantissa + exponent.as_ref().map_or("", |e| e.as_str())
+    }
+
+    /// @p value as %a prints it: "0x1.8p+1", or with a leading 0 when it is subnormal.
+    fn hexadecimal(&self, value: f64) -> String {
+        const FRACTION_DIGITS: usize = 13;
+        let bits = value.to_bits();
+        let biased = (bits >> 52) as i32;
+        let mut fraction = bits & ((1 << 52) - 1);
+        let (mut lead, exponent) = match (biased, fraction) {
+            (0, 0) => (0, 0),
+            (0, _) => (0, -1022),
+            _ => (1, biased - 1023),
+        };
+        let digits = match self.precision {
+            None => {
+                let digits = format!("{:013x}", fraction);
+                digits.trim_end_matches('0').to_owned()
+            }
+            Some(precision) if precision < FRACTION_DIGITS => {
+                // Round to nearest, ties to even, carrying into the leading digit.
+                let shift = 4 * (FRACTION_DIGITS - precision);
+                let dropped = fraction & ((1 << shift) - 1);
+                let half = 1 << (shift - 1);
+                let mut kept = (lead << (4 * precision)) | fraction >> shift;
+                if dropped > half || (dropped == half && kept & 1 == 1) {
+                    kept += 1;
+                }
+                lead = kept >> (4 * precision);
+                fraction = kept & ((1 << (4 * precision)) - 1);
+                if precision == 0 {
+                    String::new()
+                } else {
+                    format!("{:01$x}", fraction, precision)
+                }
+            }
+            Some(precision) => {
+                let zeros = "0".repeat(precision - FRACTION_DIGITS);
+                format!("{:013x}{}", fraction, zeros)
+            }
+        };
+        let point = if digits.is_empty() && !self.alternate {
+            ""
+        } else {
+            "."
+        };
+        let sign = if exponent < 0 { '-' } else { '+' };
+        format!("0x{}{}{}p{}{}", lead, point, digits, sign, exponent.abs())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use libc::{c_char, c_double, c_longlong};
+    use std::ffi::CString;
+
+    /// @p value formatted with @p format by the C library, which `Format` has to agree with.
+    fn snprintf(format: &str, value: Number) -> Vec<u8> {
+        let mut format = format.to_owned();
+        if let Number::Integer(_) = value {
+            let conversion = format.rfind(['d', 'i']).unwrap();
+            format.insert_str(conversion, "ll");
+        }
+        let format = CString::new(format).unwrap();
+        let mut buffer = vec![0u8; 512];
+        let len = unsafe {
+            match value {
+                Number::Integer(value) => libc::snprintf(
+                    buffer.as_mut_ptr() as *mut c_char,
+                    buffer.len(),
+                    format.as_ptr(),
+                    value as c_longlong,
+                ),
+                Number::Float(value) => libc::snprintf(
+                    buffer.as_mut_ptr() as *mut c_char,
+                    buffer.len(),
+                    format.as_ptr(),
+                    value as c_double,
+                ),
+            }
+        };
+        buffer.truncate(len as usize);
+        buffer
+    }
+
+    fn check(format: &str, value: Number) {
+        assert_eq!(
+            String::from_utf8(Format::parse(format).format(value)).unwrap(),
+            String::from_utf8(snprintf(format, value)).unwrap(),
+            "{} {:?}",
+            format,
+            value
+        );
+    }
+
+    const FLAGS: &[&str] = &["", "-", "+", " ", "#", "0", "+0", "-#", " 0#"];
+
+    #[test]
+    fn floats_match_snprintf() {
+        let values = [
+            0.0,
+            -0.0,
+            1.0,
+            -1.5,
+            0.25,
+            0.5,
+            2.5,
+            0.1,
+            123.456,
+            999.5,
+            9.9999995,
+            999999.5,
+            9.9995e-5,
+            0.000123456,
+            1e-5,
+            1e15,
+            1.7976931348623157e308,
+            5e-324,
+            2.2250738585072014e-308,
+            f64::INFINITY,
+            f64::NEG_INFINITY,
+            1.0 / 3.0,
+            65535.99609375,
+        ];
+        for flags in FLAGS {
+            for width in &["", "1", "12", "30"] {
+                for precision in &["", ".0", ".1", ".3", ".13", ".17", ".20"] {
+                    for conversion in &["f", "e", "g", "a", "F", "E", "G", "A"] {
+                        let format = format!("%{}{}{}{}", flags, width, precision, conversion);
+                        for &value in &values {
+                            check(&format, Number::Float(value));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn integers_match_snprintf() {
+        let values = [0, 1, -1, 42, -1234567, i64::MAX, i64::MIN];
+        for flags in FLAGS {
+            for width in &["", "1", "12", "30"] {
+                for precision in &["", ".0", ".1", ".5", ".25"] {
+                    for conversion in &["d", "i"] {
+                        let format = format!("%{}{}{}{}", flags, width, precision, conversion);
+                        for &value in &values {
+                            check(&format, Number::Integer(value));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn text_around_the_conversion() {
+        check("x%%y%+08.2fz%%", Number::Float(-2.71875));
+        check("%%%d%%%%", Number::Integer(7));
+        let format = Format::parse("%%%.3g%%");
+        assert_eq!(format.format(Number::Float(2.0)), b"%2%");
+        assert!(!format.is_integer());
+        assert_eq!(format.general_precision(), None);
+        assert_eq!(Format::parse("%.10g").general_precision(), Some(10));
+        assert_eq!(Format::parse("%.0g").general_precision(), Some(1));
+        assert_eq!(Format::parse("%g").general_precision(), Some(6));
+        assert_eq!(Format::parse("%5g").general_precision(), None);
+        assert!(Format::parse("%d").is_plain_integer());
+        assert!(!Format::parse("%i").is_plain_integer());
+        assert!(Format::parse("%+i").is_integer());
+    }
+}
@@ -0,0 +1,348 @@
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::io::{self, BufRead, Write};
+
+#[macro_use(die)]
+extern crate utils;
+extern crate rand;
+
+use rand::Rng;
+use utils::args::ArgParser;
+use utils::io::FileOrStdin;
+
+/// Shuffles @p items in place using the Fisher-Yates algorithm.
+fn shuffle<T, R: Rng>(items: &mut [T], rng: &mut R) {
+    for i in (1..items.len()).rev() {
+        let j = rng.gen_range(0..=i);
+        items.swap(i, j);
+    }
+}
+
+/// Parses the LO-HI argument of -i.
+fn parse_range(range: &str) -> Result<(u64, u64), String> {
+    let invalid = || format!("invalid input range '{}'", range);
+    let dash = range.find('-').ok_or_else(invalid)?;
+    let lo = range[..dash].parse::<u64>().map_err(|_| invalid())?;
+    let hi = range[dash + 1..].parse::<u64>().map_err(|_| invalid())?;
+    if lo > hi.saturating_add(1) {
+        return Err(invalid());
+    }
+    Ok((lo, hi))
+}
+
+/// Splits the whole input into newline terminated lines, without the terminators.
+fn read_lines(reader: &mut dyn BufRead) -> io::Result<Vec<Vec<u8>>> {
+    let mut lines = Vec::new();
+    loop {
+        let mut line = Vec::new();
+        if reader.read_until(b'\n', &mut line)? == 0 {
+            return Ok(lines);
+        }
+        if line.last() == Some(&b'\n') {
+            line.pop();
+        }
+        lines.push(line);
+    }
+}
+
+/// Writes a random permutation of @p lines (or, with @p repeat, a random selection with
+/// repetitions) to @p writer, stopping after @p count lines if given.
+fn write_shuffled<R: Rng>(
+    writer: &mut dyn Write,
+    mut lines: Vec<Vec<u8>>,
+    count: Option<usize>,
+    repeat: bool,
+    rng: &mut R,
+) -> io::Result<()> {
+    if repeat {
+        if lines.is_empty() {
+            if count == Some(0) {
+                return Ok(());
+            }
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "no lines to repeat",
+            ));
+        }
+        let mut written = 0;
+        while count != Some(written) {
+            let line = &lines[rng.gen_range(0..lines.len())];
+            writer.write_all(line)?;
+            writer.write_all(b"\n")?;
+            written += 1;
+        }
+        return Ok(());
+    }
+    shuffle(&mut lines, rng);
+    let count = count.unwrap_or(lines.len());
+    for line in lines.iter().take(count) {
+        writer.write_all(line)?;
+        writer.write_all(b"\n")?;
+    }
+    Ok(())
+}
+
+/// Writes the numbers @p lo through @p hi as write_shuffled would write them as lines, without
+/// making a line of each first: only the numbers swapped away from their place are remembered,
+/// so that a few can be picked out of a range too large to hold.
+fn write_range<R: Rng>(
+    writer: &mut dyn Write,
+    lo: u64,
+    hi: u64,
+    count: Option<usize>,
+    repeat: bool,
+    rng: &mut R,
+) -> io::Result<()> {
+    if lo > hi {
+        return write_shuffled(writer, Vec::new(), count, repeat, rng);
+    }
+    if repeat {
+        let mut written = 0;
+        while count != Some(written) {
+            writeln!(writer, "{}", rng.gen_range(lo..=hi))?;
+            written += 1;
+        }
+        return Ok(());
+    }
+    let last = hi - lo;
+    match count {
+        Some(count) if count as u64 <= last => {
+            // Fisher-Yates over the offsets from lo, stopping after count of them.
+            let mut swapped = HashMap::new();
+            for i in 0..count as u64 {
+                let j = rng.gen_range(i..=last);
+                let picked = swapped.get(&j).cloned().unwrap_or(j);
+                let displaced = swapped.remove(&i).unwrap_or(i);
+                swapped.insert(j, displaced);
+                writeln!(writer, "{}", lo + picked)?;
+            }
+        }
+        _ => {
+            let mut numbers: Vec<u64> = (lo..=hi).collect();
+            shuffle(&mut numbers, rng);
+            for n in numbers {
+                writeln!(writer, "{}", n)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn show_help(opts: &ArgParser) {
+    let brief = format!(
+        concat!(
+            "Clone of the standard GNU shuf.\n",
+            "Usage: {0}: [OPTION]... [FILE]\n",
+            "  or:  {0}: -e [OPTION]... [ARG]...\n",
+            "  or:  {0}: -i LO-HI [OPTION]...\n",
+            "Write a random permutation of the input lines to standard output."
+        ),
+        env::args().next().unwrap()
+    );
+    print!("{}", opts.usage(&brief));
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let mut opts = ArgParser::new();
+    opts.flag("e", "echo", "treat each ARG as an input line");
+    opts.option(
+        "i",
+        "input-range",
+        "treat each number LO through HI as an input line",
+        "LO-HI",
+    );
+    opts.option("n", "head-count", "output at most COUNT lines", "COUNT");
+    opts.option(
+        "o",
+        "output",
+        "write result to FILE instead of standard output",
+        "FILE",
+    );
+    opts.flag("r", "repeat", "output lines can be repeated");
+    opts.flag("h", "help", "display this help and exit");
+    opts.flag("v", "version", "output version information and exit");
+    let options = match opts.parse(&args[1..]) {
+        Ok(m) => m,
+        Err(f) => die!(code = 2, "{}", f.to_string()),
+    };
+    if options.opt_present("h") {
+        return show_help(&opts);
+    }
+    if options.opt_present("v") {
+        return utils::version::print_version("shuf");
+    }
+
+    let count = options.opt_str("n").map(|n| {
+        n.parse::<usize>()
+            .unwrap_or_else(|_| die!(code = 2, "invalid line count '{}'", n))
+    });
+    if options.opt_present("e") && options.opt_present("i") {
+        die!(code = 2, "cannot combine -e and -i options");
+    }
+    let range = options.opt_str("i").map(|range| {
+        if !options.free.is_empty() {
+            die!(code = 2, "extra operand '{}'", options.free[0]);
+        }
+        parse_range(&range).unwrap_or_else(|e| die!(code = 2, "{}", e))
+    });
+    let lines: Vec<Vec<u8>> = if range.is_some() {
+        Vec::new()
+    } else if options.opt_present("e") {
+        options
+            .free
+            .iter()
+            .map(|arg| arg.clone().into_bytes())
+            .collect()
+    } else {
+        if options.free.len() > 1 {
+            die!(code = 2, "extra operand '{}'", options.free[1]);
+        }
+        let name = options.free.first().map_or("-", |name| name.as_str());
+        let mut reader = FileOrStdin::open(name).unwrap_or_else(|e| die!("{}: {}", name, e));
+        read_lines(&mut reader).unwrap_or_else(|e| die!("read error: {}", e))
+    };
+
+    let stdout = io::stdout();
+    let mut writer: Box<dyn Write> = match options.opt_str("o") {
+        Some(name) => Box::new(io::BufWriter::new(
+            fs::File::create(&name).unwrap_or_else(|e| die!("{}: {}", name, e)),
+        )),
+        None => Box::new(io::BufWriter::new(stdout.lock())),
+    };
+    let repeat = options.opt_present("r");
+    let mut rng = rand::thread_rng();
+    match range {
+        Some((lo, hi)) => write_range(&mut writer, lo, hi, count, repeat, &mut rng),
+        None => write_shuffled(&mut writer, lines, count, repeat, &mut rng),
+    }
+    .and_then(|_| writer.flush())
+    .unwrap_or_else(|e| die!("{}", e));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(items: &[&str]) -> Vec<Vec<u8>> {
+        items.iter().map(|s| s.as_bytes().to_vec()).collect()
+    }
+
+    fn output_lines(output: &[u8]) -> Vec<Vec<u8>> {
+        read_lines(&mut &output[..]).unwrap()
+    }
+
+    #[test]
+    fn permutation_contains_every_line_once() {
+        let input = lines(&["a", "b", "c", "d", "e", "f"]);
+        let mut output = Vec::new();
+        write_shuffled(
+            &mut output,
+            input.clone(),
+            None,
+            false,
+            &mut rand::thread_rng(),
+        )
+        .unwrap();
+        let mut shuffled = output_lines(&output);
+        shuffled.sort();
+        assert_eq!(shuffled, input);
+    }
+
+    #[test]
+    fn head_count_limits_output() {
+        let input = lines(&["1", "2", "3", "4", "5"]);
+        let mut output = Vec::new();
+        write_shuffled(
+            &mut output,
+            input.clone(),
+            Some(2),
+            false,
+            &mut rand::thread_rng(),
+        )
+        .unwrap();
+        let shuffled = output_lines(&output);
+        assert_eq!(shuffled.len(), 2);
+        assert!(shuffled.iter().all(|line| input.contains(line)));
+        assert_ne!(shuffled[0], shuffled[1]);
+
+        let mut output = Vec::new();
+        write_shuffled(&mut output, input, Some(10), false, &mut rand::thread_rng()).unwrap();
+        assert_eq!(output_lines(&output).len(), 5);
+    }
+
+    #[test]
+    fn repeat_can_exceed_input() {
+        let input = lines(&["x", "y"]);
+        let mut output = Vec::new();
+        write_shuffled(
+            &mut output,
+            input.clone(),
+            Some(10),
+            true,
+            &mut rand::thread_rng(),
+        )
+        .unwrap();
+        let repeated = output_lines(&output);
+        assert_eq!(repeated.len(), 10);
+        assert!(repeated.iter().all(|line| input.contains(line)));
+        assert!(write_shuffled(
+            &mut Vec::new(),
+            vec![],
+            Some(1),
+            true,
+            &mut rand::thread_rng()
+        )
+        .is_err());
+    }
+
+    fn range(lo: u64, hi: u64, count: Option<usize>, repeat: bool) -> Vec<u64> {
+        let mut output = Vec::new();
+        write_range(&mut output, lo, hi, count, repeat, &mut rand::thread_rng()).unwrap();
+        output_lines(&output)
+            .iter()
+            .map(|line| String::from_utf8_lossy(line).parse().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn range_permutations() {
+        let mut all = range(3, 12, None, false);
+        all.sort();
+        assert_eq!(all, (3..=12).collect::<Vec<u64>>());
+        let mut most = range(3, 12, Some(9), false);
+        most.sort();
+        most.dedup();
+        assert_eq!(most.len(), 9);
+        assert!(most.iter().all(|n| (3..=12).contains(n)));
+        assert_eq!(range(5, 4, None, false), vec![]);
+        assert_eq!(range(7, 7, Some(3), true), vec![7, 7, 7]);
+    }
+
+    #[test]
+    fn few_numbers_from_a_huge_range() {
+        let mut picked = range(1, u64::MAX, Some(1000), false);
+        assert!(picked.iter().all(|&n| n >= 1));
+        picked.sort();
+        picked.dedup();
+        assert_eq!(picked.len(), 1000);
+        assert_eq!(range(0, u64::MAX, Some(5), true).len(), 5);
+    }
+
+    #[test]
+    fn ranges() {
+        assert_eq!(parse_range("1-10"), Ok((1, 10)));
+        assert_eq!(parse_range("5-5"), Ok((5, 5)));
+        assert_eq!(parse_range("5-4"), Ok((5, 4)));
+        assert!(parse_range("5-3").is_err());
+        assert!(parse_range("5").is_err());
+        assert!(parse_range("a-b").is_err());
+    }
+
+    #[test]
+    fn input_without_trailing_newline() {
+        assert_eq!(output_lines(b"a\nb"), lines(&["a", "b"]));
+        assert_eq!(output_lines(b""), lines(&[]));
+    }
+}
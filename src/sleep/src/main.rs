@@ -0,0 +1,82 @@
+use std::env;
+use std::thread;
+use std::time::Duration;
+
+#[macro_use(die)]
+extern crate utils;
+
+use utils::args::ArgParser;
+use utils::duration::parse_duration;
+
+/// Sums the durations of all @p operands.
+fn total_duration(operands: &[String]) -> Result<Duration, String> {
+    let mut seconds = 0.0;
+    for operand in operands {
+        seconds += parse_duration(operand)?;
+    }
+    Duration::try_from_secs_f64(seconds).map_err(|_| "time interval is too large".to_owned())
+}
+
+fn show_help(opts: &ArgParser) {
+    let brief = format!(
+        concat!(
+            "Clone of the standard GNU sleep.\n",
+            "Usage: {}: NUMBER[SUFFIX]...\n",
+            "Pause for NUMBER seconds. SUFFIX may be 's' for seconds (the default),\n",
+            "'m' for minutes, 'h' for hours or 'd' for days. NUMBER may be a\n",
+            "floating-point number. Given multiple arguments, pause for the sum of them."
+        ),
+        env::args().next().unwrap()
+    );
+    print!("{}", opts.usage(&brief));
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let mut opts = ArgParser::new();
+    opts.flag("h", "help", "display this help and exit");
+    opts.flag("v", "version", "output version information and exit");
+    let options = match opts.parse(&args[1..]) {
+        Ok(m) => m,
+        Err(f) => die!(code = 2, "{}", f.to_string()),
+    };
+    if options.opt_present("h") {
+        return show_help(&opts);
+    }
+    if options.opt_present("v") {
+        return utils::version::print_version("sleep");
+    }
+    if options.free.is_empty() {
+        die!(code = 2, "missing operand");
+    }
+
+    let duration = total_duration(&options.free).unwrap_or_else(|e| die!(code = 2, "{}", e));
+    thread::sleep(duration);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    fn operands(args: &[&str]) -> Vec<String> {
+        args.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn durations_are_summed() {
+        let total = total_duration(&operands(&["1", "0.5", "1m"])).unwrap();
+        assert_eq!(total, Duration::from_millis(61_500));
+        assert!(total_duration(&operands(&["1", "oops"])).is_err());
+    }
+
+    #[test]
+    fn sleeps_for_requested_time() {
+        let duration = total_duration(&operands(&["0.05", "0.05"])).unwrap();
+        let start = Instant::now();
+        thread::sleep(duration);
+        let elapsed = start.elapsed();
+        assert!(elapsed >= Duration::from_millis(100));
+        assert!(elapsed < Duration::from_millis(1000));
+    }
+}
@@ -0,0 +1,345 @@
+use std::env;
+use std::fs;
+use std::io::{self, BufRead, BufReader, Read, Write};
+
+#[macro_use(die)]
+extern crate utils;
+
+use utils::args::ArgParser;
+
+/// Hands out the output files PREFIXaa, PREFIXab, ... one after another.
+struct OutputFiles {
+    prefix: String,
+    suffix_length: usize,
+    numeric: bool,
+    next_index: u64,
+    current: Option<io::BufWriter<fs::File>>,
+}
+
+impl OutputFiles {
+    fn new(prefix: &str, suffix_length: usize, numeric: bool, start: u64) -> OutputFiles {
+        OutputFiles {
+            prefix: prefix.to_owned(),
+            suffix_length,
+            numeric,
+            next_index: start,
+            current: None,
+        }
+    }
+
+    /// Renders @p index as a fixed width suffix, or returns None if it does not fit.
+    fn suffix(&self, mut index: u64) -> Option<String> {
+        let (base, first) = if self.numeric { (10, b'0') } else { (26, b'a') };
+        let mut suffix = vec![first; self.suffix_length];
+        for place in suffix.iter_mut().rev() {
+            *place = first + (index % base) as u8;
+            index /= base;
+        }
+        if index > 0 {
+            None
+        } else {
+            Some(String::from_utf8(suffix).unwrap())
+        }
+    }
+
+    /// Finishes the current output file and starts the next one.
+    fn next(&mut self) -> io::Result<&mut io::BufWriter<fs::File>> {
+        self.finish()?;
+        let suffix = self
+            .suffix(self.next_index)
+            .ok_or_else(|| io::Error::other("output file suffixes exhausted"))?;
+        self.next_index += 1;
+        let file = fs::File::create(format!("{}{}", self.prefix, suffix))?;
+        self.current = Some(io::BufWriter::new(file));
+        Ok(self.current.as_mut().unwrap())
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        match self.current.take() {
+            Some(mut writer) => writer.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Writes every @p lines lines of @p reader into a separate file.
+fn split_lines(reader: &mut dyn BufRead, outputs: &mut OutputFiles, lines: u64) -> io::Result<()> {
+    let mut line = Vec::new();
+    let mut written = lines;
+    while reader.read_until(b'\n', &mut line)? > 0 {
+        if written == lines {
+            outputs.next()?;
+            written = 0;
+        }
+        outputs.current.as_mut().unwrap().write_all(&line)?;
+        written += 1;
+        line.clear();
+    }
+    outputs.finish()
+}
+
+/// Writes every @p bytes bytes of @p reader into a separate file.
+fn split_bytes(reader: &mut dyn Read, outputs: &mut OutputFiles, bytes: u64) -> io::Result<()> {
+    let mut buffer = vec![0u8; 65536];
+    let mut left_in_file = 0;
+    loop {
+        let len = match reader.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(len) => len,
+            Err(ref e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        let mut chunk = &buffer[..len];
+        while !chunk.is_empty() {
+            if left_in_file == 0 {
+                outputs.next()?;
+                left_in_file = bytes;
+            }
+            let n = chunk
+                .len()
+                .min(left_in_file.min(usize::MAX as u64) as usize);
+            outputs.current.as_mut().unwrap().write_all(&chunk[..n])?;
+            left_in_file -= n as u64;
+            chunk = &chunk[n..];
+        }
+    }
+    outputs.finish()
+}
+
+/// Splits @p size bytes of @p reader into exactly @p chunks files of (almost) equal size.
+fn split_chunks(
+    reader: &mut dyn Read,
+    outputs: &mut OutputFiles,
+    size: u64,
+    chunks: u64,
+) -> io::Result<()> {
+    let mut reader = reader.take(size);
+    for n in 0..chunks {
+        let chunk_size = (n + 1) * size / chunks - n * size / chunks;
+        let writer = outputs.next()?;
+        io::copy(&mut (&mut reader).take(chunk_size), writer)?;
+    }
+    outputs.finish()
+}
+
+/// Parses SIZE[SUFFIX] where the suffix is one of k, m, g (powers of 1024).
+fn parse_size(size: &str) -> Result<u64, String> {
+    let invalid = || format!("invalid number of bytes: '{}'", size);
+    let (number, multiplier) = match size.chars().last().map(|c| c.to_ascii_lowercase()) {
+        Some('k') => (&size[..size.len() - 1], 1 << 10),
+        Some('m') => (&size[..size.len() - 1], 1 << 20),
+        Some('g') => (&size[..size.len() - 1], 1 << 30),
+        _ => (size, 1),
+    };
+    let value = number.parse::<u64>().map_err(|_| invalid())?;
+    match value.checked_mul(multiplier) {
+        Some(0) | None => Err(invalid()),
+        Some(bytes) => Ok(bytes),
+    }
+}
+
+fn parse_count(count: &str, what: &str) -> u64 {
+    match count.parse::<u64>() {
+        Ok(n) if n > 0 => n,
+        _ => die!(code = 2, "invalid number of {}: '{}'", what, count),
+    }
+}
+
+fn show_help(opts: &ArgParser) {
+    let brief = format!(
+        concat!(
+            "Clone of the standard GNU split.\n",
+            "Usage: {}: [OPTION]... [INPUT [PREFIX]]\n",
+            "Output pieces of INPUT to PREFIXaa, PREFIXab, ...; default size is 1000\n",
+            "lines, and default PREFIX is 'x'."
+        ),
+        env::args().next().unwrap()
+    );
+    print!("{}", opts.usage(&brief));
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let mut opts = ArgParser::new();
+    opts.option(
+        "a",
+        "suffix-length",
+        "generate suffixes of length N (default 2)",
+        "N",
+    );
+    opts.option("b", "bytes", "put SIZE bytes per output file", "SIZE");
+    opts.option("l", "lines", "put NUMBER lines per output file", "NUMBER");
+    opts.option("n", "number", "generate CHUNKS output files", "CHUNKS");
+    opts.flag(
+        "d",
+        "",
+        "use numeric suffixes starting at 0, not alphabetic",
+    );
+    opts.optional_option(
+        "",
+        "numeric-suffixes",
+        "same as -d, but allow setting the start value",
+        "FROM",
+    );
+    opts.flag("h", "help", "display this help and exit");
+    opts.flag("v", "version", "output version information and exit");
+    let options = match opts.parse(&args[1..]) {
+        Ok(m) => m,
+        Err(f) => die!(code = 2, "{}", f.to_string()),
+    };
+    if options.opt_present("h") {
+        return show_help(&opts);
+    }
+    if options.opt_present("v") {
+        return utils::version::print_version("split");
+    }
+    if options.free.len() > 2 {
+        die!(code = 2, "extra operand '{}'", options.free[2]);
+    }
+    let modes = ["b", "l", "n"]
+        .iter()
+        .filter(|mode| options.opt_present(mode))
+        .count();
+    if modes > 1 {
+        die!(code = 2, "cannot split in more than one way");
+    }
+
+    let input = options.free.first().map_or("-", |s| s.as_str());
+    let prefix = options.free.get(1).map_or("x", |s| s.as_str());
+    let suffix_length = match options.opt_str("a") {
+        Some(n) => parse_count(&n, "suffix length") as usize,
+        None => 2,
+    };
+    let numeric = options.opt_present("d") || options.opt_present("numeric-suffixes");
+    let start = match options.opt_str("numeric-suffixes") {
+        Some(from) => from.parse::<u64>().unwrap_or_else(|_| {
+            die!(
+                code = 2,
+                "invalid start value for numerical suffix: '{}'",
+                from
+            )
+        }),
+        None => 0,
+    };
+    let mut outputs = OutputFiles::new(prefix, suffix_length, numeric, start);
+
+    let mut reader: Box<dyn BufRead> = if input == "-" {
+        Box::new(BufReader::new(io::stdin()))
+    } else {
+        Box::new(BufReader::new(
+            fs::File::open(input).unwrap_or_else(|e| die!("{}: {}", input, e)),
+        ))
+    };
+    let result = if let Some(size) = options.opt_str("b") {
+        let bytes = parse_size(&size).unwrap_or_else(|e| die!(code = 2, "{}", e));
+        split_bytes(&mut reader, &mut outputs, bytes)
+    } else if let Some(chunks) = options.opt_str("n") {
+        let chunks = parse_count(&chunks, "chunks");
+        if input == "-" {
+            die!("-: cannot determine file size");
+        }
+        let size = fs::metadata(input)
+            .unwrap_or_else(|e| die!("{}: {}", input, e))
+            .len();
+        split_chunks(&mut reader, &mut outputs, size, chunks)
+    } else {
+        let lines = match options.opt_str("l") {
+            Some(lines) => parse_count(&lines, "lines"),
+            None => 1000,
+        };
+        split_lines(&mut reader, &mut outputs, lines)
+    };
+    result.unwrap_or_else(|e| die!("{}", e));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::{Path, PathBuf};
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("split-test-{}-{}", std::process::id(), name));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn prefix(dir: &Path) -> String {
+        format!("{}/x", dir.display())
+    }
+
+    fn read(path: &str) -> Vec<u8> {
+        fs::read(path).unwrap()
+    }
+
+    #[test]
+    fn suffixes() {
+        let alpha = OutputFiles::new("x", 2, false, 0);
+        assert_eq!(alpha.suffix(0), Some("aa".to_owned()));
+        assert_eq!(alpha.suffix(1), Some("ab".to_owned()));
+        assert_eq!(alpha.suffix(26), Some("ba".to_owned()));
+        assert_eq!(alpha.suffix(675), Some("zz".to_owned()));
+        assert_eq!(alpha.suffix(676), None);
+
+        let numeric = OutputFiles::new("x", 3, true, 0);
+        assert_eq!(numeric.suffix(42), Some("042".to_owned()));
+        assert_eq!(numeric.suffix(1000), None);
+    }
+
+    #[test]
+    fn sizes() {
+        assert_eq!(parse_size("10"), Ok(10));
+        assert_eq!(parse_size("2k"), Ok(2048));
+        assert_eq!(parse_size("1M"), Ok(1 << 20));
+        assert_eq!(parse_size("1g"), Ok(1 << 30));
+        assert!(parse_size("0").is_err());
+        assert!(parse_size("k").is_err());
+        assert!(parse_size("1x").is_err());
+    }
+
+    #[test]
+    fn lines_roll_over_suffix() {
+        let dir = temp_dir("lines");
+        let mut outputs = OutputFiles::new(&prefix(&dir), 2, false, 0);
+        split_lines(&mut &b"1\n2\n3\n4\n5"[..], &mut outputs, 2).unwrap();
+        assert_eq!(read(&format!("{}aa", prefix(&dir))), b"1\n2\n");
+        assert_eq!(read(&format!("{}ab", prefix(&dir))), b"3\n4\n");
+        assert_eq!(read(&format!("{}ac", prefix(&dir))), b"5");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn bytes_split_exactly() {
+        let dir = temp_dir("bytes");
+        let input: Vec<u8> = (0..250u8).collect();
+        let mut outputs = OutputFiles::new(&prefix(&dir), 2, true, 0);
+        split_bytes(&mut &input[..], &mut outputs, 100).unwrap();
+        assert_eq!(read(&format!("{}00", prefix(&dir))), &input[..100]);
+        assert_eq!(read(&format!("{}01", prefix(&dir))), &input[100..200]);
+        assert_eq!(read(&format!("{}02", prefix(&dir))), &input[200..]);
+        assert!(fs::metadata(format!("{}03", prefix(&dir))).is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn chunks_are_equal_sized() {
+        let dir = temp_dir("chunks");
+        let input = vec![b'x'; 1001];
+        let mut outputs = OutputFiles::new(&prefix(&dir), 2, false, 0);
+        split_chunks(&mut &input[..], &mut outputs, 1001, 4).unwrap();
+        let sizes: Vec<usize> = ["aa", "ab", "ac", "ad"]
+            .iter()
+            .map(|suffix| read(&format!("{}{}", prefix(&dir), suffix)).len())
+            .collect();
+        assert_eq!(sizes.iter().sum::<usize>(), 1001);
+        assert!(sizes.iter().all(|size| *size == 250 || *size == 251));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn suffixes_exhausted() {
+        let dir = temp_dir("exhausted");
+        let mut outputs = OutputFiles::new(&prefix(&dir), 1, true, 8);
+        assert!(split_lines(&mut &b"1\n2\n3\n"[..], &mut outputs, 1).is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
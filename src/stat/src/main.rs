@@ -0,0 +1,335 @@
+use std::env;
+use std::fs;
+use std::io;
+use std::mem;
+use std::os::unix::fs::MetadataExt;
+
+#[macro_use(die)]
+extern crate utils;
+extern crate libc;
+
+use utils::args::ArgParser;
+use utils::users;
+
+const S_IFMT: u32 = 0o170_000;
+const S_IFSOCK: u32 = 0o140_000;
+const S_IFLNK: u32 = 0o120_000;
+const S_IFREG: u32 = 0o100_000;
+const S_IFBLK: u32 = 0o060_000;
+const S_IFDIR: u32 = 0o040_000;
+const S_IFCHR: u32 = 0o020_000;
+const S_IFIFO: u32 = 0o010_000;
+
+/// Describes the file type the same way GNU stat does in its default output and in `%F`.
+fn file_type(meta: &fs::Metadata) -> &'static str {
+    match meta.mode() & S_IFMT {
+        S_IFREG if meta.size() == 0 => "regular empty file",
+        S_IFREG => "regular file",
+        S_IFDIR => "directory",
+        S_IFLNK => "symbolic link",
+        S_IFIFO => "fifo",
+        S_IFSOCK => "socket",
+        S_IFCHR => "character special file",
+        S_IFBLK => "block special file",
+        _ => "weird file",
+    }
+}
+
+/// Renders @p mode in the `ls -l` notation, e.g. `-rw-r--r--`.
+fn mode_string(mode: u32) -> String {
+    let mut result = String::with_capacity(10);
+    result.push(match mode & S_IFMT {
+        S_IFDIR => 'd',
+        S_IFLNK => 'l',
+        S_IFIFO => 'p',
+        S_IFSOCK => 's',
+        S_IFCHR => 'c',
+        S_IFBLK => 'b',
+        _ => '-',
+    });
+    // (read bit, write bit, execute bit, special bit, special char when executable)
+    let triads = [
+        (0o400, 0o200, 0o100, 0o4000, 's'),
+        (0o040, 0o020, 0o010, 0o2000, 's'),
+        (0o004, 0o002, 0o001, 0o1000, 't'),
+    ];
+    for &(r, w, x, special, ch) in &triads {
+        result.push(if mode & r != 0 { 'r' } else { '-' });
+        result.push(if mode & w != 0 { 'w' } else { '-' });
+        result.push(match (mode & x != 0, mode & special != 0) {
+            (true, true) => ch,
+            (false, true) => ch.to_ascii_uppercase(),
+            (true, false) => 'x',
+            (false, false) => '-',
+        });
+    }
+    result
+}
+
+/// The name of a user or group looked up in its database, or "UNKNOWN" if it has none.
+fn name_or_unknown(name: io::Result<Option<String>>) -> String {
+    name.ok().flatten().unwrap_or_else(|| "UNKNOWN".to_owned())
+}
+
+/// Formats a timestamp in local time as `YYYY-MM-DD HH:MM:SS.NNNNNNNNN +ZZZZ`.
+fn format_time(secs: i64, nsecs: i64) -> String {
+    let time = secs as libc::time_t;
+    let mut tm: libc::tm = unsafe { mem::zeroed() };
+    if unsafe { libc::localtime_r(&time, &mut tm) }.is_null() {
+        return format!("{}.{:09}", secs, nsecs);
+    }
+    let offset = tm.tm_gmtoff / 60;
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}.{:09} {}{:02}{:02}",
+        tm.tm_year + 1900,
+        tm.tm_mon + 1,
+        tm.tm_mday,
+        tm.tm_hour,
+        tm.tm_min,
+        tm.tm_sec,
+        nsecs,
+        if offset < 0 { '-' } else { '+' },
+        offset.abs() / 60,
+        offset.abs() % 60
+    )
+}
+
+/// Expands a single format directive (the character following '%').
+fn expand_directive(directive: char, name: &str, meta: &fs::Metadata) -> String {
+    match directive {
+        'n' => name.to_owned(),
+        's' => meta.size().to_string(),
+        'b' => meta.blocks().to_string(),
+        'B' => "512".to_owned(),
+        'o' => meta.blksize().to_string(),
+        'i' => meta.ino().to_string(),
+        'h' => meta.nlink().to_string(),
+        'd' => meta.dev().to_string(),
+        'D' => format!("{:x}", meta.dev()),
+        'f' => format!("{:x}", meta.mode()),
+        'a' => format!("{:o}", meta.mode() & 0o7777),
+        'A' => mode_string(meta.mode()),
+        'F' => file_type(meta).to_owned(),
+        'u' => meta.uid().to_string(),
+        'U' => name_or_unknown(users::user_name(meta.uid())),
+        'g' => meta.gid().to_string(),
+        'G' => name_or_unknown(users::group_name(meta.gid())),
+        'x' => format_time(meta.atime(), meta.atime_nsec()),
+        'X' => meta.atime().to_string(),
+        'y' => format_time(meta.mtime(), meta.mtime_nsec()),
+        'Y' => meta.mtime().to_string(),
+        'z' => format_time(meta.ctime(), meta.ctime_nsec()),
+        'Z' => meta.ctime().to_string(),
+        '%' => "%".to_owned(),
+        _ => "?".to_owned(),
+    }
+}
+
+/// Expands every `%` directive in @p format using the metadata of @p name.
+fn expand_format(format: &str, name: &str, meta: &fs::Metadata) -> String {
+    let mut result = String::new();
+    let mut chars = format.chars();
+    while let Some(ch) = chars.next() {
+        if ch != '%' {
+            result.push(ch);
+            continue;
+        }
+        match chars.next() {
+            Some(directive) => result.push_str(&expand_directive(directive, name, meta)),
+            None => result.push('%'),
+        }
+    }
+    result
+}
+
+fn default_output(name: &str, meta: &fs::Metadata) -> String {
+    let display_name = match fs::read_link(name) {
+        Ok(target) if meta.file_type().is_symlink() => {
+            format!("{} -> {}", name, target.display())
+        }
+        _ => name.to_owned(),
+    };
+    let owner = name_or_unknown(users::user_name(meta.uid()));
+    let group = name_or_unknown(users::group_name(meta.gid()));
+    format!(
+        concat!(
+            "  File: {}\n",
+            "  Size: {:<10}\tBlocks: {:<10} IO Block: {:<6} {}\n",
+            "Device: {:x}h/{}d\tInode: {:<11} Links: {}\n",
+            "Access: ({:04o}/{})  Uid: ({:>5}/{:>8})   Gid: ({:>5}/{:>8})\n",
+            "Access: {}\n",
+            "Modify: {}\n",
+            "Change: {}\n"
+        ),
+        display_name,
+        meta.size(),
+        meta.blocks(),
+        meta.blksize(),
+        file_type(meta),
+        meta.dev(),
+        meta.dev(),
+        meta.ino(),
+        meta.nlink(),
+        meta.mode() & 0o7777,
+        mode_string(meta.mode()),
+        meta.uid(),
+        owner,
+        meta.gid(),
+        group,
+        format_time(meta.atime(), meta.atime_nsec()),
+        format_time(meta.mtime(), meta.mtime_nsec()),
+        format_time(meta.ctime(), meta.ctime_nsec())
+    )
+}
+
+fn show_help(opts: &ArgParser) {
+    let brief = format!(
+        concat!(
+            "Clone of the standard GNU stat.\n",
+            "Usage: {}: [OPTION]... FILE...\n",
+            "Display file status."
+        ),
+        env::args().next().unwrap()
+    );
+    print!("{}", opts.usage(&brief));
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let mut opts = ArgParser::new();
+    opts.flag("L", "dereference", "follow links");
+    opts.option(
+        "c",
+        "format",
+        "use the specified FORMAT instead of the default",
+        "FORMAT",
+    );
+    opts.flag("h", "help", "display this help and exit");
+    opts.flag("v", "version", "output version information and exit");
+    let options = match opts.parse(&args[1..]) {
+        Ok(m) => m,
+        Err(f) => die!(code = 2, "{}", f.to_string()),
+    };
+    if options.opt_present("h") {
+        return show_help(&opts);
+    }
+    if options.opt_present("v") {
+        return utils::version::print_version("stat");
+    }
+    if options.free.is_empty() {
+        die!(code = 2, "missing operand");
+    }
+
+    let format = options.opt_str("c");
+    let mut failed = false;
+    for name in &options.free {
+        let meta = if options.opt_present("L") {
+            fs::metadata(name)
+        } else {
+            fs::symlink_metadata(name)
+        };
+        match meta {
+            Ok(meta) => match format {
+                Some(ref format) => println!("{}", expand_format(format, name, &meta)),
+                None => print!("{}", default_output(name, &meta)),
+            },
+            Err(e) => {
+                eprintln!("{}: cannot stat '{}': {}", args[0], name, e);
+                failed = true;
+            }
+        }
+    }
+    if failed {
+        std::process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use std::path::PathBuf;
+
+    fn temp_file(name: &str, contents: &[u8]) -> PathBuf {
+        let path = env::temp_dir().join(format!("stat-test-{}-{}", std::process::id(), name));
+        fs::File::create(&path)
+            .unwrap()
+            .write_all(contents)
+            .unwrap();
+        path
+    }
+
+    #[test]
+    fn mode_strings() {
+        assert_eq!(mode_string(S_IFREG | 0o644), "-rw-r--r--");
+        assert_eq!(mode_string(S_IFDIR | 0o755), "drwxr-xr-x");
+        assert_eq!(mode_string(S_IFLNK | 0o777), "lrwxrwxrwx");
+        assert_eq!(mode_string(S_IFREG | 0o4755), "-rwsr-xr-x");
+        assert_eq!(mode_string(S_IFDIR | 0o1777), "drwxrwxrwt");
+        assert_eq!(mode_string(S_IFREG | 0o2644), "-rw-r-Sr--");
+    }
+
+    #[test]
+    fn format_specifiers() {
+        let path = temp_file("specifiers", b"hello");
+        let name = path.to_str().unwrap();
+        let meta = fs::symlink_metadata(&path).unwrap();
+
+        assert_eq!(expand_format("%n", name, &meta), name);
+        assert_eq!(expand_format("%s", name, &meta), "5");
+        assert_eq!(expand_format("%i", name, &meta), meta.ino().to_string());
+        assert_eq!(
+            expand_format("%f", name, &meta),
+            format!("{:x}", meta.mode())
+        );
+        assert_eq!(
+            expand_format("%u %g", name, &meta),
+            format!("{} {}", meta.uid(), meta.gid())
+        );
+        assert_eq!(expand_format("%F", name, &meta), "regular file");
+        assert_eq!(
+            expand_format("%U", name, &meta),
+            users::user_name(meta.uid()).unwrap().unwrap()
+        );
+        assert_eq!(
+            expand_format("%G", name, &meta),
+            users::group_name(meta.gid()).unwrap().unwrap()
+        );
+        assert_eq!(
+            expand_format("%X %Y %Z", name, &meta),
+            format!("{} {} {}", meta.atime(), meta.mtime(), meta.ctime())
+        );
+        for directive in &["%x", "%y", "%z"] {
+            let time = expand_format(directive, name, &meta);
+            assert_eq!(time.len(), "2017-11-08 12:00:00.000000000 +0000".len());
+        }
+        assert_eq!(expand_format("100%% %s bytes", name, &meta), "100% 5 bytes");
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn empty_file_and_directory() {
+        let path = temp_file("empty", b"");
+        let meta = fs::metadata(&path).unwrap();
+        assert_eq!(file_type(&meta), "regular empty file");
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(
+            file_type(&fs::metadata(env::temp_dir()).unwrap()),
+            "directory"
+        );
+    }
+
+    #[test]
+    fn default_output_fields() {
+        let path = temp_file("default", b"abc");
+        let name = path.to_str().unwrap();
+        let output = default_output(name, &fs::metadata(&path).unwrap());
+        assert!(output.starts_with(&format!("  File: {}\n", name)));
+        assert!(output.contains("Size: 3 "));
+        assert!(output.contains("regular file"));
+        assert!(output.contains("Modify: "));
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,77 @@
+//! The library that stdbuf preloads into the commands it runs. Before their main function, it
+//! sets the buffering of the standard streams as _STDBUF_I, _STDBUF_O and _STDBUF_E ask for.
+//! This only affects programs that write through C's stdio.
+
+use std::env;
+use std::ptr;
+
+extern crate libc;
+
+use libc::{c_int, size_t, FILE};
+
+extern "C" {
+    static mut stdin: *mut FILE;
+    static mut stdout: *mut FILE;
+    static mut stderr: *mut FILE;
+}
+
+/// Parses the value of a _STDBUF_ variable: "L" for line buffering, "0" for none, or the size
+/// of the buffer in bytes. Returns the mode and size for setvbuf.
+fn parse_mode(mode: &str) -> Option<(c_int, size_t)> {
+    match mode {
+        "L" => Some((libc::_IOLBF, 0)),
+        "0" => Some((libc::_IONBF, 0)),
+        _ => mode.parse::<size_t>().ok().map(|size| (libc::_IOFBF, size)),
+    }
+}
+
+/// Sets the buffering of @p stream as the variable @p name asks for, if it is set.
+unsafe fn apply(stream: *mut FILE, name: &str) {
+    let value = match env::var(name) {
+        Ok(value) => value,
+        Err(_) => return,
+    };
+    let (mode, size) = match parse_mode(&value) {
+        Some(parsed) => parsed,
+        None => {
+            eprintln!("stdbuf: invalid buffering mode {}={}", name, value);
+            return;
+        }
+    };
+    // The buffer must outlive the stream, so it is never freed.
+    let buffer = if size == 0 {
+        ptr::null_mut()
+    } else {
+        libc::malloc(size) as *mut libc::c_char
+    };
+    if (size != 0 && buffer.is_null()) || libc::setvbuf(stream, buffer, mode, size) != 0 {
+        eprintln!("stdbuf: could not set the buffering of {}", name);
+    }
+}
+
+extern "C" fn init() {
+    unsafe {
+        apply(stdin, "_STDBUF_I");
+        apply(stdout, "_STDBUF_O");
+        apply(stderr, "_STDBUF_E");
+    }
+}
+
+/// Runs `init` when the library is loaded, before the program's main.
+#[used]
+#[link_section = ".init_array"]
+static INIT: extern "C" fn() = init;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn modes() {
+        assert_eq!(parse_mode("L"), Some((libc::_IOLBF, 0)));
+        assert_eq!(parse_mode("0"), Some((libc::_IONBF, 0)));
+        assert_eq!(parse_mode("4096"), Some((libc::_IOFBF, 4096)));
+        assert_eq!(parse_mode("l"), None);
+        assert_eq!(parse_mode("1K"), None);
+    }
+}
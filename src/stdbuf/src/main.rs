@@ -0,0 +1,187 @@
+use std::env;
+use std::ffi::OsString;
+use std::io;
+use std::os::unix::process::CommandExt;
+use std::path::{Path, PathBuf};
+use std::process::{self, Command};
+
+#[macro_use(warn)]
+extern crate utils;
+
+use utils::args::ArgParser;
+
+/// Like `die!`, but with the exit status 125 that stdbuf reserves for its own failures.
+macro_rules! trouble {
+    ($($arg:tt)*) => ({
+        warn!($($arg)*);
+        process::exit(125);
+    });
+}
+
+/// The library preloaded into the command, which sets its buffering.
+const LIBRARY: &str = "libstdbuf.so";
+
+/// Parses a MODE operand into the value of its _STDBUF_ variable: "L", "0" or a size in bytes,
+/// written with an optional K, M or G suffix for powers of 1024.
+fn parse_mode(mode: &str) -> Option<String> {
+    if mode == "L" {
+        return Some(mode.to_owned());
+    }
+    let (number, multiplier) = match mode.chars().last().map(|c| c.to_ascii_uppercase()) {
+        Some('K') => (&mode[..mode.len() - 1], 1 << 10),
+        Some('M') => (&mode[..mode.len() - 1], 1 << 20),
+        Some('G') => (&mode[..mode.len() - 1], 1 << 30),
+        _ => (mode, 1),
+    };
+    number
+        .parse::<usize>()
+        .ok()
+        .and_then(|n| n.checked_mul(multiplier))
+        .map(|size| size.to_string())
+}
+
+/// Where the library may be: next to stdbuf, as in the build directory, or in the lib
+/// directory of the prefix it is installed to.
+fn library_candidates(exe: &Path) -> Vec<PathBuf> {
+    let dir = exe.parent().unwrap_or_else(|| Path::new("."));
+    vec![
+        dir.join(LIBRARY),
+        dir.join("../lib/coreutils-rs").join(LIBRARY),
+    ]
+}
+
+/// The value of LD_PRELOAD with @p library in front of what @p current already preloads.
+fn preload(library: &Path, current: Option<OsString>) -> OsString {
+    let mut preload = library.as_os_str().to_owned();
+    if let Some(current) = current.filter(|current| !current.is_empty()) {
+        preload.push(":");
+        preload.push(current);
+    }
+    preload
+}
+
+fn show_help(opts: &ArgParser) {
+    let brief = format!(
+        concat!(
+            "Clone of the standard GNU stdbuf.\n",
+            "Usage: {}: OPTION... COMMAND [ARG]...\n",
+            "Run COMMAND with modified buffering for its standard streams.\n",
+            "MODE is 'L' for line buffering (not for input), '0' for no buffering, or the size\n",
+            "of a full buffer, with an optional K, M or G suffix. This only affects commands\n",
+            "which use C's stdio and do not set the buffering themselves."
+        ),
+        env::args().next().unwrap()
+    );
+    print!("{}", opts.usage(&brief));
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let mut opts = ArgParser::new();
+    opts.stop_at_first_free();
+    opts.option(
+        "i",
+        "input",
+        "adjust the buffering of standard input",
+        "MODE",
+    );
+    opts.option(
+        "o",
+        "output",
+        "adjust the buffering of standard output",
+        "MODE",
+    );
+    opts.option(
+        "e",
+        "error",
+        "adjust the buffering of standard error",
+        "MODE",
+    );
+    opts.flag("h", "help", "display this help and exit");
+    opts.flag("v", "version", "output version information and exit");
+    let options = match opts.parse(&args[1..]) {
+        Ok(m) => m,
+        Err(f) => trouble!("{}", f.to_string()),
+    };
+    if options.opt_present("h") {
+        return show_help(&opts);
+    }
+    if options.opt_present("v") {
+        return utils::version::print_version("stdbuf");
+    }
+    if options.free.is_empty() {
+        trouble!("missing operand");
+    }
+
+    let mut command = Command::new(&options.free[0]);
+    command.args(&options.free[1..]);
+    let mut any = false;
+    for &(option, variable) in &[("i", "_STDBUF_I"), ("o", "_STDBUF_O"), ("e", "_STDBUF_E")] {
+        let mode = match options.opt_str(option) {
+            Some(mode) => mode,
+            None => continue,
+        };
+        if option == "i" && mode == "L" {
+            trouble!("line buffering stdin is meaningless");
+        }
+        match parse_mode(&mode) {
+            Some(value) => command.env(variable, value),
+            None => trouble!("invalid mode '{}'", mode),
+        };
+        any = true;
+    }
+    if !any {
+        trouble!("you must specify a buffering mode option");
+    }
+
+    let exe = env::current_exe().unwrap_or_else(|e| trouble!("cannot find stdbuf: {}", e));
+    let library = library_candidates(&exe)
+        .into_iter()
+        .find(|candidate| candidate.exists())
+        .unwrap_or_else(|| trouble!("failed to find {}", LIBRARY));
+    command.env("LD_PRELOAD", preload(&library, env::var_os("LD_PRELOAD")));
+
+    // Only returns if the command could not be run.
+    let e = command.exec();
+    warn!("failed to run command '{}': {}", options.free[0], e);
+    process::exit(if e.kind() == io::ErrorKind::NotFound {
+        127
+    } else {
+        126
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn modes() {
+        assert_eq!(parse_mode("L"), Some("L".to_owned()));
+        assert_eq!(parse_mode("0"), Some("0".to_owned()));
+        assert_eq!(parse_mode("4096"), Some("4096".to_owned()));
+        assert_eq!(parse_mode("64k"), Some("65536".to_owned()));
+        assert_eq!(parse_mode("1M"), Some("1048576".to_owned()));
+        assert_eq!(parse_mode("l"), None);
+        assert_eq!(parse_mode("-1"), None);
+        assert_eq!(parse_mode("K"), None);
+    }
+
+    #[test]
+    fn preloading() {
+        let library = Path::new("/lib/libstdbuf.so");
+        assert_eq!(preload(library, None), "/lib/libstdbuf.so");
+        assert_eq!(preload(library, Some("".into())), "/lib/libstdbuf.so");
+        assert_eq!(
+            preload(library, Some("a.so".into())),
+            "/lib/libstdbuf.so:a.so"
+        );
+        assert_eq!(
+            library_candidates(Path::new("/usr/bin/stdbuf")),
+            [
+                PathBuf::from("/usr/bin/libstdbuf.so"),
+                PathBuf::from("/usr/bin/../lib/coreutils-rs/libstdbuf.so")
+            ]
+        );
+    }
+}
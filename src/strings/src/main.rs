@@ -0,0 +1,289 @@
+use std::env;
+use std::io::{self, BufRead, Write};
+
+#[macro_use(die, note_error, warn)]
+extern crate utils;
+
+use utils::args::ArgParser;
+
+/// The character encodings that strings can look for.
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Encoding {
+    SevenBit,
+    EightBit,
+    BigEndian16,
+    LittleEndian16,
+    BigEndian32,
+    LittleEndian32,
+}
+
+impl Encoding {
+    /// Parses the argument of -e, named like in GNU strings.
+    fn parse(name: &str) -> Option<Encoding> {
+        match name {
+            "s" => Some(Encoding::SevenBit),
+            "S" => Some(Encoding::EightBit),
+            "b" => Some(Encoding::BigEndian16),
+            "l" => Some(Encoding::LittleEndian16),
+            "B" => Some(Encoding::BigEndian32),
+            "L" => Some(Encoding::LittleEndian32),
+            _ => None,
+        }
+    }
+
+    /// The number of bytes in a character.
+    fn width(self) -> usize {
+        match self {
+            Encoding::SevenBit | Encoding::EightBit => 1,
+            Encoding::BigEndian16 | Encoding::LittleEndian16 => 2,
+            Encoding::BigEndian32 | Encoding::LittleEndian32 => 4,
+        }
+    }
+
+    /// The printable character encoded by @p unit, if it is one.
+    fn decode(self, unit: &[u8]) -> Option<u8> {
+        let code = match self {
+            Encoding::BigEndian16 | Encoding::BigEndian32 => {
+                unit.iter().fold(0u32, |code, &b| code << 8 | b as u32)
+            }
+            Encoding::LittleEndian16 | Encoding::LittleEndian32 => unit
+                .iter()
+                .rev()
+                .fold(0u32, |code, &b| code << 8 | b as u32),
+            Encoding::SevenBit | Encoding::EightBit => unit[0] as u32,
+        };
+        match code {
+            0x09 | 0x20..=0x7e => Some(code as u8),
+            0x80..=0xff if self == Encoding::EightBit => Some(code as u8),
+            _ => None,
+        }
+    }
+}
+
+struct Config {
+    min_length: usize,
+    radix: Option<char>,
+    encoding: Encoding,
+}
+
+/// Writes every run of at least `min_length` printable characters in @p reader to
+/// @p writer, one per line, optionally preceded by its offset in the input.
+fn strings(reader: &mut dyn BufRead, config: &Config, writer: &mut dyn Write) -> io::Result<()> {
+    let width = config.encoding.width();
+    let mut unit = Vec::with_capacity(width);
+    let mut current = Vec::new();
+    let mut offset = 0u64;
+    let mut start = 0u64;
+    loop {
+        let consumed = {
+            let buf = reader.fill_buf()?;
+            if buf.is_empty() {
+                break;
+            }
+            for &byte in buf {
+                unit.push(byte);
+                if unit.len() == width {
+                    let unit_start = offset + 1 - width as u64;
+                    match config.encoding.decode(&unit) {
+                        Some(c) => {
+                            if current.is_empty() {
+                                start = unit_start;
+                            }
+                            current.push(c);
+                        }
+                        None => flush(&mut current, start, config, writer)?,
+                    }
+                    unit.clear();
+                }
+                offset += 1;
+            }
+            buf.len()
+        };
+        reader.consume(consumed);
+    }
+    flush(&mut current, start, config, writer)
+}
+
+/// Writes @p current, found at @p start, if it is long enough, and clears it.
+fn flush(
+    current: &mut Vec<u8>,
+    start: u64,
+    config: &Config,
+    writer: &mut dyn Write,
+) -> io::Result<()> {
+    if current.len() >= config.min_length {
+        match config.radix {
+            Some('o') => write!(writer, "{:7o} ", start)?,
+            Some('d') => write!(writer, "{:7} ", start)?,
+            Some('x') => write!(writer, "{:7x} ", start)?,
+            _ => {}
+        }
+        writer.write_all(current)?;
+        writer.write_all(b"\n")?;
+    }
+    current.clear();
+    Ok(())
+}
+
+fn show_help(opts: &ArgParser) {
+    let brief = format!(
+        concat!(
+            "Clone of the standard GNU strings.\n",
+            "Usage: {}: [OPTION]... [FILE]...\n",
+            "Display printable strings in FILE(s), or standard input if there are none."
+        ),
+        env::args().next().unwrap()
+    );
+    print!("{}", opts.usage(&brief));
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let mut opts = ArgParser::new();
+    opts.flag("a", "all", "scan the entire file (the only mode supported)");
+    opts.option(
+        "n",
+        "bytes",
+        "locate and print any sequence of at least MIN_LEN characters (default 4)",
+        "MIN_LEN",
+    );
+    opts.option(
+        "t",
+        "radix",
+        "print the location of the string in base 8, 10 or 16",
+        "{o,d,x}",
+    );
+    opts.option(
+        "e",
+        "encoding",
+        "select character size and endianness: s = 7-bit, S = 8-bit, {b,l} = 16-bit, {B,L} = 32-bit",
+        "{s,S,b,l,B,L}",
+    );
+    opts.flag("h", "help", "display this help and exit");
+    opts.flag("v", "version", "output version information and exit");
+    let options = match opts.parse(&args[1..]) {
+        Ok(m) => m,
+        Err(f) => die!(code = 2, "{}", f.to_string()),
+    };
+    if options.opt_present("h") {
+        return show_help(&opts);
+    }
+    if options.opt_present("v") {
+        return utils::version::print_version("strings");
+    }
+
+    let min_length = match options.opt_str("n") {
+        Some(n) => match n.parse::<usize>() {
+            Ok(n) if n > 0 => n,
+            _ => die!(code = 2, "invalid minimum string length {}", n),
+        },
+        None => 4,
+    };
+    let radix = options.opt_str("t").map(|radix| match radix.as_str() {
+        "o" | "d" | "x" => radix.chars().next().unwrap(),
+        _ => die!(code = 2, "invalid radix '{}'", radix),
+    });
+    let encoding = options.opt_str("e").map_or(Encoding::SevenBit, |name| {
+        Encoding::parse(&name).unwrap_or_else(|| die!(code = 2, "invalid encoding '{}'", name))
+    });
+    let config = Config {
+        min_length,
+        radix,
+        encoding,
+    };
+
+    let stdout = io::stdout();
+    let mut writer = io::BufWriter::new(stdout.lock());
+    for (name, reader) in utils::io::open_inputs(&options.free) {
+        let mut reader = match reader {
+            Ok(reader) => reader,
+            Err(e) => {
+                note_error!("{}: {}", name, e);
+                continue;
+            }
+        };
+        strings(&mut reader, &config, &mut writer)
+            .unwrap_or_else(|e| note_error!("{}: {}", name, e));
+    }
+    writer.flush().unwrap_or_else(|e| die!("{}", e));
+    utils::exit_if_error();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(input: &[u8], min_length: usize, radix: Option<char>, encoding: Encoding) -> String {
+        let config = Config {
+            min_length,
+            radix,
+            encoding,
+        };
+        let mut output = Vec::new();
+        strings(&mut &input[..], &config, &mut output).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn printable_runs() {
+        let input = b"\x00\x01hello\x02abc\x03tab\there\nnext line\xffend";
+        assert_eq!(
+            run(input, 4, None, Encoding::SevenBit),
+            "hello\ntab\there\nnext line\n"
+        );
+        assert_eq!(
+            run(input, 3, None, Encoding::SevenBit),
+            "hello\nabc\ntab\there\nnext line\nend\n"
+        );
+    }
+
+    #[test]
+    fn offsets() {
+        let input = b"\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00string";
+        assert_eq!(
+            run(input, 4, Some('d'), Encoding::SevenBit),
+            "     10 string\n"
+        );
+        assert_eq!(
+            run(input, 4, Some('o'), Encoding::SevenBit),
+            "     12 string\n"
+        );
+        assert_eq!(
+            run(input, 4, Some('x'), Encoding::SevenBit),
+            "      a string\n"
+        );
+    }
+
+    #[test]
+    fn eight_bit() {
+        let input = b"caf\xe9\x00";
+        assert_eq!(run(input, 4, None, Encoding::SevenBit), "");
+        let config = Config {
+            min_length: 4,
+            radix: None,
+            encoding: Encoding::EightBit,
+        };
+        let mut output = Vec::new();
+        strings(&mut &input[..], &config, &mut output).unwrap();
+        assert_eq!(output, b"caf\xe9\n".to_vec());
+    }
+
+    #[test]
+    fn wide_characters() {
+        let utf16: Vec<u8> = "\u{1}wide\u{100}"
+            .encode_utf16()
+            .flat_map(|c| c.to_le_bytes())
+            .collect();
+        assert_eq!(
+            run(&utf16, 4, Some('d'), Encoding::LittleEndian16),
+            "      2 wide\n"
+        );
+        assert_eq!(run(&utf16, 4, None, Encoding::BigEndian16), "");
+        let utf32: Vec<u8> = "text"
+            .chars()
+            .flat_map(|c| (c as u32).to_be_bytes())
+            .collect();
+        assert_eq!(run(&utf32, 4, None, Encoding::BigEndian32), "text\n");
+        assert_eq!(run(&utf32, 4, None, Encoding::SevenBit), "");
+    }
+}
@@ -0,0 +1,70 @@
+use std::env;
+use std::fs;
+use std::io;
+
+#[macro_use(die)]
+extern crate utils;
+extern crate checksum;
+
+use checksum::{Checksum, Cksum};
+use utils::args::ArgParser;
+
+fn format_line(crc: &Cksum, name: Option<&str>) -> String {
+    match name {
+        Some(name) => format!("{} {} {}", crc.value(), crc.bytes(), name),
+        None => format!("{} {}", crc.value(), crc.bytes()),
+    }
+}
+
+fn show_help(opts: &ArgParser) {
+    let brief = format!(
+        concat!(
+            "Clone of the standard GNU cksum.\n",
+            "Usage: {}: [FILE]...\n",
+            "Print CRC checksum and byte counts of each FILE."
+        ),
+        env::args().next().unwrap()
+    );
+    print!("{}", opts.usage(&brief));
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let mut opts = ArgParser::new();
+    opts.flag("h", "help", "display this help and exit");
+    opts.flag("v", "version", "output version information and exit");
+    let options = match opts.parse(&args[1..]) {
+        Ok(m) => m,
+        Err(f) => die!(code = 2, "{}", f.to_string()),
+    };
+    if options.opt_present("h") {
+        return show_help(&opts);
+    }
+    if options.opt_present("v") {
+        return utils::version::print_version("cksum");
+    }
+
+    if options.free.is_empty() {
+        let mut crc = Cksum::default();
+        checksum::update_all(&mut io::stdin(), &mut [&mut crc])
+            .unwrap_or_else(|e| die!("-: {}", e));
+        println!("{}", format_line(&crc, None));
+        return;
+    }
+
+    let mut total = Cksum::default();
+    for name in &options.free {
+        let mut crc = Cksum::default();
+        let result = if name == "-" {
+            checksum::update_all(&mut io::stdin(), &mut [&mut crc, &mut total])
+        } else {
+            fs::File::open(name)
+                .and_then(|mut f| checksum::update_all(&mut f, &mut [&mut crc, &mut total]))
+        };
+        result.unwrap_or_else(|e| die!("{}: {}", name, e));
+        println!("{}", format_line(&crc, Some(name)));
+    }
+    if options.free.len() > 1 {
+        println!("{}", format_line(&total, Some("total")));
+    }
+}
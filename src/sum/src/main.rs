@@ -0,0 +1,129 @@
+use std::env;
+use std::fs;
+use std::io;
+
+#[macro_use(die)]
+extern crate utils;
+extern crate checksum;
+
+use checksum::{BsdSum, Checksum, SysvSum};
+use utils::args::ArgParser;
+
+/// Formats one line of output: the checksum and the number of blocks, in the layout of the
+/// respective algorithm.
+fn format_line(sum: &dyn Checksum, sysv: bool, name: Option<&str>) -> String {
+    let mut line = if sysv {
+        format!("{} {}", sum.value(), sum.bytes().div_ceil(512))
+    } else {
+        format!("{:05} {:5}", sum.value(), sum.bytes().div_ceil(1024))
+    };
+    if let Some(name) = name {
+        line.push(' ');
+        line.push_str(name);
+    }
+    line
+}
+
+fn new_sum(sysv: bool) -> Box<dyn Checksum> {
+    if sysv {
+        Box::<SysvSum>::default()
+    } else {
+        Box::<BsdSum>::default()
+    }
+}
+
+fn show_help(opts: &ArgParser) {
+    let brief = format!(
+        concat!(
+            "Clone of the standard GNU sum.\n",
+            "Usage: {}: [OPTION]... [FILE]...\n",
+            "Print checksum and block counts for each FILE."
+        ),
+        env::args().next().unwrap()
+    );
+    print!("{}", opts.usage(&brief));
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let mut opts = ArgParser::new();
+    opts.flag(
+        "r",
+        "",
+        "use BSD sum algorithm (the default), use 1K blocks",
+    );
+    opts.flag(
+        "s",
+        "sysv",
+        "use System V sum algorithm, use 512 bytes blocks",
+    );
+    opts.flag("h", "help", "display this help and exit");
+    opts.flag("v", "version", "output version information and exit");
+    let options = match opts.parse(&args[1..]) {
+        Ok(m) => m,
+        Err(f) => die!(code = 2, "{}", f.to_string()),
+    };
+    if options.opt_present("h") {
+        return show_help(&opts);
+    }
+    if options.opt_present("v") {
+        return utils::version::print_version("sum");
+    }
+
+    let sysv = options.opt_present("s");
+    if options.free.is_empty() {
+        let mut sum = new_sum(sysv);
+        checksum::update_all(&mut io::stdin(), &mut [&mut *sum])
+            .unwrap_or_else(|e| die!("-: {}", e));
+        println!("{}", format_line(&*sum, sysv, None));
+        return;
+    }
+
+    let mut total = new_sum(sysv);
+    for name in &options.free {
+        let mut sum = new_sum(sysv);
+        let result = if name == "-" {
+            checksum::update_all(&mut io::stdin(), &mut [&mut *sum, &mut *total])
+        } else {
+            fs::File::open(name)
+                .and_then(|mut f| checksum::update_all(&mut f, &mut [&mut *sum, &mut *total]))
+        };
+        result.unwrap_or_else(|e| die!("{}: {}", name, e));
+        println!("{}", format_line(&*sum, sysv, Some(name)));
+    }
+    if options.free.len() > 1 {
+        println!("{}", format_line(&*total, sysv, Some("total")));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sum_of(data: &[u8], sysv: bool) -> Box<dyn Checksum> {
+        let mut sum = new_sum(sysv);
+        sum.update(data);
+        sum
+    }
+
+    #[test]
+    fn bsd_layout() {
+        assert_eq!(
+            format_line(&*sum_of(b"abc", false), false, Some("a")),
+            "16556     1 a"
+        );
+        assert_eq!(
+            format_line(&*sum_of(&[0; 1025], false), false, None),
+            "00000     2"
+        );
+    }
+
+    #[test]
+    fn sysv_layout() {
+        assert_eq!(
+            format_line(&*sum_of(b"abc", true), true, Some("a")),
+            "294 1 a"
+        );
+        assert_eq!(format_line(&*sum_of(&[0; 513], true), true, None), "0 2");
+    }
+}
@@ -0,0 +1,655 @@
+use std::collections::VecDeque;
+use std::env;
+use std::fs::{self, File};
+use std::io::{self, BufRead, Read, Seek, SeekFrom, Write};
+use std::os::unix::fs::MetadataExt;
+use std::time::Duration;
+
+extern crate libc;
+#[macro_use(die, note_error, warn)]
+extern crate utils;
+
+use utils::args::ArgParser;
+use utils::io::FileOrStdin;
+
+/// Where printing of each input starts, in bytes or lines.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Count {
+    /// The last N.
+    Last(u64),
+    /// From the Nth on, counting from 1.
+    From(u64),
+}
+
+/// How files are followed once their end has been printed.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Follow {
+    /// Keep reading the file that was opened, wherever it is moved.
+    Descriptor,
+    /// Keep reading whatever file the name refers to, reopening it when it is replaced.
+    Name,
+}
+
+/// Parses the [+]NUM of -c and -n, where NUM may end in k, m or g for powers of 1024.
+fn parse_count(count: &str) -> Option<Count> {
+    let (from, number) = match count.strip_prefix('+') {
+        Some(number) => (true, number),
+        None => (false, count.strip_prefix('-').unwrap_or(count)),
+    };
+    let (number, multiplier) = match number.chars().last().map(|c| c.to_ascii_lowercase()) {
+        Some('k') => (&number[..number.len() - 1], 1 << 10),
+        Some('m') => (&number[..number.len() - 1], 1 << 20),
+        Some('g') => (&number[..number.len() - 1], 1 << 30),
+        _ => (number, 1),
+    };
+    // A sign was already taken off, and parse would accept another.
+    if !number.starts_with(|c: char| c.is_ascii_digit()) {
+        return None;
+    }
+    let n = number.parse::<u64>().ok()?.checked_mul(multiplier)?;
+    Some(if from { Count::From(n) } else { Count::Last(n) })
+}
+
+fn read_some(reader: &mut dyn Read, buffer: &mut [u8]) -> io::Result<usize> {
+    loop {
+        match reader.read(buffer) {
+            Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+            result => return result,
+        }
+    }
+}
+
+/// Copies the last @p n bytes of @p reader to @p writer, keeping only those in memory.
+fn last_bytes(reader: &mut dyn Read, writer: &mut dyn Write, n: u64) -> io::Result<()> {
+    let mut window = VecDeque::new();
+    let mut buffer = vec![0; 65536];
+    loop {
+        let len = read_some(reader, &mut buffer)?;
+        if len == 0 {
+            break;
+        }
+        window.extend(&buffer[..len]);
+        let excess = (window.len() as u64).saturating_sub(n) as usize;
+        window.drain(..excess);
+    }
+    let (front, back) = window.as_slices();
+    writer.write_all(front)?;
+    writer.write_all(back)
+}
+
+/// Copies the last @p n lines of @p reader to @p writer, keeping only those in memory. A last
+/// line without @p delimiter counts as one.
+fn last_lines(
+    reader: &mut dyn BufRead,
+    writer: &mut dyn Write,
+    n: u64,
+    delimiter: u8,
+) -> io::Result<()> {
+    let mut window: VecDeque<Vec<u8>> = VecDeque::new();
+    let mut line = Vec::new();
+    while reader.read_until(delimiter, &mut line)? > 0 {
+        window.push_back(line);
+        line = if window.len() as u64 > n {
+            let mut oldest = window.pop_front().unwrap();
+            oldest.clear();
+            oldest
+        } else {
+            Vec::new()
+        };
+    }
+    for line in window {
+        writer.write_all(&line)?;
+    }
+    Ok(())
+}
+
+/// Skips the first @p n lines of @p reader.
+fn skip_lines(reader: &mut dyn BufRead, n: u64, delimiter: u8) -> io::Result<()> {
+    let mut line = Vec::new();
+    for _ in 0..n {
+        line.clear();
+        if reader.read_until(delimiter, &mut line)? == 0 {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// Copies the part of @p reader that @p count selects to @p writer.
+fn tail(
+    reader: &mut dyn BufRead,
+    writer: &mut dyn Write,
+    count: Count,
+    lines: bool,
+    delimiter: u8,
+) -> io::Result<()> {
+    match (count, lines) {
+        (Count::Last(n), false) => last_bytes(reader, writer, n),
+        (Count::Last(n), true) => last_lines(reader, writer, n, delimiter),
+        (Count::From(n), false) => {
+            io::copy(&mut reader.take(n.saturating_sub(1)), &mut io::sink())?;
+            io::copy(reader, writer).map(|_| ())
+        }
+        (Count::From(n), true) => {
+            skip_lines(reader, n.saturating_sub(1), delimiter)?;
+            io::copy(reader, writer).map(|_| ())
+        }
+    }
+}
+
+/// Seeks @p file to the start of its last @p n lines by reading it backwards from its end, so
+/// that only the end of a large file is read.
+fn seek_to_last_lines(file: &mut File, n: u64, delimiter: u8) -> io::Result<()> {
+    let len = file.seek(SeekFrom::End(0))?;
+    if n == 0 {
+        return Ok(());
+    }
+    let mut buffer = vec![0; 65536];
+    let mut found = 0;
+    let mut position = len;
+    while position > 0 {
+        let size = position.min(buffer.len() as u64) as usize;
+        position -= size as u64;
+        file.seek(SeekFrom::Start(position))?;
+        file.read_exact(&mut buffer[..size])?;
+        for i in (0..size).rev() {
+            let start = position + i as u64 + 1;
+            // The delimiter ending the file ends the last line rather than starting another.
+            if buffer[i] != delimiter || start == len {
+                continue;
+            }
+            found += 1;
+            if found == n {
+                file.seek(SeekFrom::Start(start))?;
+                return Ok(());
+            }
+        }
+    }
+    file.seek(SeekFrom::Start(0)).map(|_| ())
+}
+
+/// Copies the part of @p file that @p count selects to @p writer, seeking past what is not
+/// printed when the file is a regular one. Leaves @p file at its end to be followed from.
+fn tail_file(
+    file: &mut File,
+    writer: &mut dyn Write,
+    count: Count,
+    lines: bool,
+    delimiter: u8,
+) -> io::Result<()> {
+    if !file.metadata()?.is_file() {
+        return tail(
+            &mut io::BufReader::new(file),
+            writer,
+            count,
+            lines,
+            delimiter,
+        );
+    }
+    match (count, lines) {
+        (Count::Last(n), false) => {
+            let len = file.seek(SeekFrom::End(0))?;
+            file.seek(SeekFrom::Start(len.saturating_sub(n)))?;
+        }
+        (Count::Last(n), true) => seek_to_last_lines(file, n, delimiter)?,
+        (Count::From(n), false) => {
+            file.seek(SeekFrom::Start(n.saturating_sub(1)))?;
+        }
+        (Count::From(_), true) => {
+            return tail(
+                &mut io::BufReader::new(file),
+                writer,
+                count,
+                lines,
+                delimiter,
+            );
+        }
+    }
+    io::copy(file, writer).map(|_| ())
+}
+
+/// A file being followed.
+struct Followed {
+    name: String,
+    /// None until the file could be opened.
+    file: Option<File>,
+    /// The device and inode of @p file, to notice the name being given to another file.
+    id: (u64, u64),
+    /// Whether the name refers to no file for now. What is still written to the file that had
+    /// it, as a log just rotated, is read until a file of that name appears again.
+    missing: bool,
+}
+
+impl Followed {
+    fn new(name: &str, file: Option<File>) -> Followed {
+        let id = file
+            .as_ref()
+            .and_then(|file| file.metadata().ok())
+            .map_or((0, 0), |metadata| (metadata.dev(), metadata.ino()));
+        Followed {
+            name: name.to_owned(),
+            file,
+            id,
+            missing: false,
+        }
+    }
+
+    /// Reads whatever was appended to the file since it was last read, starting over when the
+    /// file has been truncated.
+    fn read_new(&mut self) -> io::Result<Vec<u8>> {
+        let mut data = Vec::new();
+        if let Some(ref mut file) = self.file {
+            let position = file.stream_position()?;
+            if file.metadata()?.len() < position {
+                warn!("{}: file truncated", self.name);
+                file.seek(SeekFrom::Start(0))?;
+            }
+            file.read_to_end(&mut data)?;
+        }
+        Ok(data)
+    }
+
+    /// Checks what the name refers to now, and reopens it when it is another file than the one
+    /// followed. Returns whether it was reopened.
+    fn reopen(&mut self) -> bool {
+        let metadata = match fs::metadata(&self.name) {
+            Ok(metadata) => metadata,
+            Err(e) => {
+                if self.file.is_some() && !self.missing {
+                    warn!("'{}' has become inaccessible: {}", self.name, e);
+                }
+                self.missing = true;
+                return false;
+            }
+        };
+        if self.file.is_some() && (metadata.dev(), metadata.ino()) == self.id {
+            self.missing = false;
+            return false;
+        }
+        match File::open(&self.name) {
+            Ok(file) => {
+                if self.file.is_some() && !self.missing {
+                    warn!("'{}' has been replaced;  following new file", self.name);
+                } else {
+                    warn!("'{}' has appeared;  following new file", self.name);
+                }
+                *self = Followed::new(&self.name, Some(file));
+                true
+            }
+            Err(_) => false,
+        }
+    }
+}
+
+/// Wakes the follow loop when a followed file changes. With inotify, a change wakes it at
+/// once; without, it only polls every interval.
+struct Watcher {
+    fd: Option<File>,
+    watches: Vec<Option<libc::c_int>>,
+}
+
+impl Watcher {
+    #[cfg(target_os = "linux")]
+    fn new(count: usize) -> Watcher {
+        use std::os::unix::io::FromRawFd;
+        let fd = unsafe { libc::inotify_init1(libc::IN_NONBLOCK | libc::IN_CLOEXEC) };
+        Watcher {
+            fd: if fd >= 0 {
+                Some(unsafe { File::from_raw_fd(fd) })
+            } else {
+                None
+            },
+            watches: vec![None; count],
+        }
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn new(count: usize) -> Watcher {
+        Watcher {
+            fd: None,
+            watches: vec![None; count],
+        }
+    }
+
+    /// Watches the file named @p name for the @p index th followed file, in place of whatever
+    /// was watched for it before.
+    #[cfg(target_os = "linux")]
+    fn watch(&mut self, index: usize, name: &str) {
+        if let Some(watch) = self.watches[index].take() {
+            if let Some(ref fd) = self.fd {
+                unsafe { libc::inotify_rm_watch(std::os::unix::io::AsRawFd::as_raw_fd(fd), watch) };
+            }
+        }
+        // The link count changes on removal, which IN_DELETE_SELF only reports once the file
+        // is closed, so IN_ATTRIB is watched too.
+        let mask = libc::IN_MODIFY | libc::IN_MOVE_SELF | libc::IN_DELETE_SELF | libc::IN_ATTRIB;
+        self.watches[index] = self.add_watch(name, mask);
+    }
+
+    /// Watches the directory holding @p name for files created or moved into it, so that a
+    /// file followed by name is reopened as soon as it appears again.
+    #[cfg(target_os = "linux")]
+    fn watch_directory(&mut self, name: &str) {
+        use std::path::Path;
+        let directory = match Path::new(name).parent() {
+            Some(parent) if parent != Path::new("") => parent.to_string_lossy().into_owned(),
+            _ => ".".to_owned(),
+        };
+        self.add_watch(&directory, libc::IN_CREATE | libc::IN_MOVED_TO);
+    }
+
+    #[cfg(target_os = "linux")]
+    fn add_watch(&self, path: &str, mask: u32) -> Option<libc::c_int> {
+        use std::os::unix::io::AsRawFd;
+        let fd = self.fd.as_ref()?.as_raw_fd();
+        let path = std::ffi::CString::new(path).ok()?;
+        let watch = unsafe { libc::inotify_add_watch(fd, path.as_ptr(), mask) };
+        if watch >= 0 {
+            Some(watch)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn watch(&mut self, _index: usize, _name: &str) {}
+
+    #[cfg(not(target_os = "linux"))]
+    fn watch_directory(&mut self, _name: &str) {}
+
+    /// Waits until a watched file changes, or @p interval passes.
+    fn wait(&mut self, interval: Duration) {
+        let fd = match self.fd {
+            Some(ref mut fd) => fd,
+            None => return std::thread::sleep(interval),
+        };
+        let mut poll = libc::pollfd {
+            fd: std::os::unix::io::AsRawFd::as_raw_fd(fd),
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        let timeout = interval.as_millis().min(libc::c_int::MAX as u128) as libc::c_int;
+        if unsafe { libc::poll(&mut poll, 1, timeout) } > 0 {
+            // Which files changed does not matter, as all of them are checked after waking.
+            let mut events = [0; 4096];
+            while let Ok(len) = fd.read(&mut events) {
+                if len == 0 {
+                    break;
+                }
+            }
+        }
+    }
+}
+
+/// Copies what is appended to @p followed to @p writer until killed, giving a header for the
+/// file the output comes from whenever it changes if @p headers. @p last_shown is the index of
+/// the file whose end was printed last.
+fn follow(
+    followed: &mut [Followed],
+    writer: &mut dyn Write,
+    how: Follow,
+    interval: Duration,
+    headers: bool,
+    mut last_shown: Option<usize>,
+) -> ! {
+    let mut watcher = Watcher::new(followed.len());
+    for (index, input) in followed.iter().enumerate() {
+        watcher.watch(index, &input.name);
+        if how == Follow::Name {
+            watcher.watch_directory(&input.name);
+        }
+    }
+    loop {
+        for (index, input) in followed.iter_mut().enumerate() {
+            let mut data = input.read_new().unwrap_or_else(|e| {
+                note_error!("{}: {}", input.name, e);
+                Vec::new()
+            });
+            if how == Follow::Name && input.reopen() {
+                watcher.watch(index, &input.name);
+                match input.read_new() {
+                    Ok(new) => data.extend(new),
+                    Err(e) => note_error!("{}: {}", input.name, e),
+                }
+            }
+            if data.is_empty() {
+                continue;
+            }
+            if headers && last_shown != Some(index) {
+                writeln!(writer, "\n==> {} <==", input.name).unwrap_or_else(|e| die!("{}", e));
+            }
+            last_shown = Some(index);
+            writer.write_all(&data).unwrap_or_else(|e| die!("{}", e));
+        }
+        writer.flush().unwrap_or_else(|e| die!("{}", e));
+        watcher.wait(interval);
+    }
+}
+
+fn show_help(opts: &ArgParser) {
+    let brief = format!(
+        concat!(
+            "Clone of the standard GNU tail.\n",
+            "Usage: {}: [OPTION]... [FILE]...\n",
+            "Print the last 10 lines of each FILE to standard output, with a header giving\n",
+            "the file name if there is more than one. Without FILE, or when FILE is -, read\n",
+            "standard input. NUM may end in k, m or g for powers of 1024."
+        ),
+        env::args().next().unwrap()
+    );
+    print!("{}", opts.usage(&brief));
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let mut opts = ArgParser::new();
+    opts.option(
+        "c",
+        "bytes",
+        "print the last NUM bytes; with a leading '+', from the NUMth byte on",
+        "[+]NUM",
+    );
+    opts.option(
+        "n",
+        "lines",
+        "print the last NUM lines (default 10); with a leading '+', from the NUMth line on",
+        "[+]NUM",
+    );
+    opts.flag("f", "", "output data appended to the files as they grow");
+    opts.optional_option(
+        "",
+        "follow",
+        "like -f, following each file by 'name' or by 'descriptor' (default)",
+        "HOW",
+    );
+    opts.flag("F", "", "same as --follow=name --retry");
+    opts.flag(
+        "",
+        "retry",
+        "keep trying to open a file that is inaccessible",
+    );
+    opts.option(
+        "s",
+        "sleep-interval",
+        "check the files every N seconds when following (default 0.1)",
+        "N",
+    );
+    opts.flag("q", "quiet", "never print headers giving file names");
+    opts.flag("v", "verbose", "always print headers giving file names");
+    opts.flag("z", "zero-terminated", "line delimiter is NUL, not newline");
+    opts.flag("h", "help", "display this help and exit");
+    opts.flag("", "version", "output version information and exit");
+    let options = match opts.parse(&args[1..]) {
+        Ok(m) => m,
+        Err(f) => die!(code = 2, "{}", f.to_string()),
+    };
+    if options.opt_present("h") {
+        return show_help(&opts);
+    }
+    if options.opt_present("version") {
+        return utils::version::print_version("tail");
+    }
+
+    let (count, lines) = match (options.opt_str("c"), options.opt_str("n")) {
+        (Some(_), Some(_)) => die!(code = 2, "cannot print both bytes and lines"),
+        (Some(bytes), None) => match parse_count(&bytes) {
+            Some(count) => (count, false),
+            None => die!(code = 2, "invalid number of bytes: '{}'", bytes),
+        },
+        (None, Some(lines)) => match parse_count(&lines) {
+            Some(count) => (count, true),
+            None => die!(code = 2, "invalid number of lines: '{}'", lines),
+        },
+        (None, None) => (Count::Last(10), true),
+    };
+    let how = match options.opt_str("follow").as_deref() {
+        _ if options.opt_present("F") => Some(Follow::Name),
+        Some("name") => Some(Follow::Name),
+        Some("descriptor") => Some(Follow::Descriptor),
+        Some(other) => die!(code = 2, "invalid argument '{}' for '--follow'", other),
+        None if options.opt_present("f") || options.opt_present("follow") => {
+            Some(Follow::Descriptor)
+        }
+        None => None,
+    };
+    let retry = options.opt_present("retry") || options.opt_present("F");
+    let interval = match options.opt_str("s") {
+        Some(interval) => match utils::duration::parse_duration(&interval) {
+            Ok(seconds) => Duration::from_secs_f64(seconds),
+            Err(e) => die!(code = 2, "invalid number of seconds: '{}': {}", interval, e),
+        },
+        None => Duration::from_millis(100),
+    };
+    let delimiter = if options.opt_present("z") {
+        b'\0'
+    } else {
+        b'\n'
+    };
+    let names = if options.free.is_empty() {
+        vec!["-".to_owned()]
+    } else {
+        options.free.clone()
+    };
+    if how == Some(Follow::Name) && names.iter().any(|name| name == "-") {
+        die!(code = 2, "cannot follow '-' by name");
+    }
+    let headers = options.opt_present("v") || (names.len() > 1 && !options.opt_present("q"));
+
+    let stdout = io::stdout();
+    let mut writer = io::BufWriter::new(stdout.lock());
+    let mut followed = Vec::new();
+    let mut last_shown = None;
+    for (n, name) in names.iter().enumerate() {
+        if headers {
+            let shown = if name == "-" { "standard input" } else { name };
+            let separator = if n > 0 { "\n" } else { "" };
+            writeln!(writer, "{}==> {} <==", separator, shown).unwrap_or_else(|e| die!("{}", e));
+        }
+        let result = if name == "-" {
+            // Standard input is only followed when it is a regular file, as a pipe ends for
+            // good once it is read to its end.
+            let mut reader = FileOrStdin::open(name).unwrap_or_else(|e| die!("{}", e));
+            tail(&mut reader, &mut writer, count, lines, delimiter)
+        } else {
+            match File::open(name) {
+                Ok(mut file) => {
+                    let result = tail_file(&mut file, &mut writer, count, lines, delimiter);
+                    if file.metadata().is_ok_and(|metadata| metadata.is_file()) {
+                        followed.push(Followed::new(name, Some(file)));
+                        last_shown = Some(followed.len() - 1);
+                    }
+                    result
+                }
+                Err(e) => {
+                    // Only a name can be opened again later.
+                    if retry && how == Some(Follow::Name) {
+                        followed.push(Followed::new(name, None));
+                    }
+                    Err(e)
+                }
+            }
+        };
+        if let Err(e) = result {
+            note_error!("{}: {}", name, e);
+        }
+    }
+    writer.flush().unwrap_or_else(|e| die!("{}", e));
+    if let Some(how) = how {
+        if followed.is_empty() {
+            die!("no files remaining");
+        }
+        follow(
+            &mut followed,
+            &mut writer,
+            how,
+            interval,
+            headers,
+            last_shown,
+        );
+    }
+    utils::exit_if_error();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tailed(input: &[u8], count: Count, lines: bool) -> Vec<u8> {
+        let mut output = Vec::new();
+        tail(&mut &input[..], &mut output, count, lines, b'\n').unwrap();
+        output
+    }
+
+    #[test]
+    fn counts() {
+        assert_eq!(parse_count("5"), Some(Count::Last(5)));
+        assert_eq!(parse_count("-5"), Some(Count::Last(5)));
+        assert_eq!(parse_count("+5"), Some(Count::From(5)));
+        assert_eq!(parse_count("2k"), Some(Count::Last(2048)));
+        assert_eq!(parse_count("+1M"), Some(Count::From(1 << 20)));
+        for invalid in &["", "+", "x", "++1", "+-1", "1x", "k", "99999999999g"] {
+            assert_eq!(parse_count(invalid), None, "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn last_bytes_and_lines() {
+        let input = b"a\nb\nc\n";
+        assert_eq!(tailed(input, Count::Last(3), false), b"\nc\n");
+        assert_eq!(tailed(input, Count::Last(100), false), input);
+        assert_eq!(tailed(input, Count::Last(2), true), b"b\nc\n");
+        assert_eq!(tailed(input, Count::Last(0), true), b"");
+        assert_eq!(tailed(input, Count::Last(10), true), input);
+        // A last line without a new line is still a line.
+        assert_eq!(tailed(b"a\nb\nc", Count::Last(1), true), b"c");
+    }
+
+    #[test]
+    fn from_bytes_and_lines() {
+        let input = b"a\nb\nc\n";
+        assert_eq!(tailed(input, Count::From(3), false), b"b\nc\n");
+        assert_eq!(tailed(input, Count::From(0), false), input);
+        assert_eq!(tailed(input, Count::From(2), true), b"b\nc\n");
+        assert_eq!(tailed(input, Count::From(1), true), input);
+        assert_eq!(tailed(input, Count::From(10), true), b"");
+    }
+
+    #[test]
+    fn seeking_to_the_last_lines() {
+        let dir = env::temp_dir().join(format!("tail-seek-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("lines");
+        // Lines across more than one of the blocks read backwards.
+        let input: Vec<u8> = (0..30_000)
+            .flat_map(|n| format!("{}\n", n).into_bytes())
+            .collect();
+        for &(input, n) in &[(&input[..], 3), (&input[..], 30_000), (b"a\nb" as &[u8], 1)] {
+            fs::write(&path, input).unwrap();
+            let mut file = File::open(&path).unwrap();
+            let mut seeked = Vec::new();
+            tail_file(&mut file, &mut seeked, Count::Last(n), true, b'\n').unwrap();
+            assert_eq!(seeked, tailed(input, Count::Last(n), true), "{}", n);
+        }
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,322 @@
+use std::env;
+use std::io;
+use std::os::unix::process::CommandExt;
+use std::process::{self, Command};
+use std::sync::atomic::{AtomicI32, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[macro_use(warn)]
+extern crate utils;
+extern crate libc;
+
+use utils::args::ArgParser;
+use utils::duration::parse_duration;
+
+/// Like `die!`, but with the exit status 125 that timeout reserves for its own failures.
+macro_rules! trouble {
+    ($($arg:tt)*) => ({
+        warn!($($arg)*);
+        process::exit(125);
+    });
+}
+
+/// The exit status when the command timed out.
+const TIMED_OUT: i32 = 124;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+const SIGNALS: &[(&str, libc::c_int)] = &[
+    ("HUP", libc::SIGHUP),
+    ("INT", libc::SIGINT),
+    ("QUIT", libc::SIGQUIT),
+    ("ILL", libc::SIGILL),
+    ("TRAP", libc::SIGTRAP),
+    ("ABRT", libc::SIGABRT),
+    ("BUS", libc::SIGBUS),
+    ("FPE", libc::SIGFPE),
+    ("KILL", libc::SIGKILL),
+    ("USR1", libc::SIGUSR1),
+    ("SEGV", libc::SIGSEGV),
+    ("USR2", libc::SIGUSR2),
+    ("PIPE", libc::SIGPIPE),
+    ("ALRM", libc::SIGALRM),
+    ("TERM", libc::SIGTERM),
+    ("CHLD", libc::SIGCHLD),
+    ("CONT", libc::SIGCONT),
+    ("STOP", libc::SIGSTOP),
+    ("TSTP", libc::SIGTSTP),
+    ("TTIN", libc::SIGTTIN),
+    ("TTOU", libc::SIGTTOU),
+    ("URG", libc::SIGURG),
+    ("XCPU", libc::SIGXCPU),
+    ("XFSZ", libc::SIGXFSZ),
+    ("VTALRM", libc::SIGVTALRM),
+    ("PROF", libc::SIGPROF),
+    ("WINCH", libc::SIGWINCH),
+    ("SYS", libc::SIGSYS),
+];
+
+/// Parses a signal name, with or without the SIG prefix, or number.
+fn parse_signal(signal: &str) -> Option<libc::c_int> {
+    if let Ok(number) = signal.parse::<libc::c_int>() {
+        return Some(number).filter(|number| (0..65).contains(number));
+    }
+    let name = signal.to_uppercase();
+    let name = name.strip_prefix("SIG").unwrap_or(&name);
+    SIGNALS
+        .iter()
+        .find(|&&(n, _)| n == name)
+        .map(|&(_, number)| number)
+}
+
+fn signal_name(signal: libc::c_int) -> String {
+    match SIGNALS.iter().find(|&&(_, number)| number == signal) {
+        Some(&(name, _)) => name.to_owned(),
+        None => signal.to_string(),
+    }
+}
+
+struct TimeoutConfig {
+    duration: Option<Duration>,
+    signal: libc::c_int,
+    kill_after: Option<Duration>,
+    preserve_status: bool,
+    /// Whether the command stays in timeout's process group, so that it may use the terminal
+    /// but its own children are not signalled.
+    foreground: bool,
+    verbose: bool,
+}
+
+/// A signal that timeout itself received and has yet to pass on to the command.
+static RECEIVED: AtomicI32 = AtomicI32::new(0);
+
+extern "C" fn receive(signal: libc::c_int) {
+    RECEIVED.store(signal, Ordering::SeqCst);
+}
+
+/// Sends @p signal to the command, or to its whole process group unless in the foreground.
+fn send(pid: libc::pid_t, signal: libc::c_int, config: &TimeoutConfig) {
+    let target = if config.foreground { pid } else { -pid };
+    unsafe {
+        libc::kill(target, signal);
+        if !config.foreground && signal != libc::SIGKILL && signal != libc::SIGCONT {
+            // Wake up the command in case it was stopped, so that it can act on the signal.
+            libc::kill(target, libc::SIGCONT);
+        }
+    }
+}
+
+/// Polls the command until it exits, passing on the signals timeout receives meanwhile. Returns
+/// its wait status, or None if it is still running at @p deadline.
+fn wait(pid: libc::pid_t, deadline: Option<Instant>, config: &TimeoutConfig) -> Option<i32> {
+    loop {
+        let mut status = 0;
+        match unsafe { libc::waitpid(pid, &mut status, libc::WNOHANG) } {
+            0 => {}
+            -1 if io::Error::last_os_error().kind() == io::ErrorKind::Interrupted => {}
+            -1 => trouble!("error waiting for command: {}", io::Error::last_os_error()),
+            _ => return Some(status),
+        }
+        let signal = RECEIVED.swap(0, Ordering::SeqCst);
+        if signal != 0 {
+            send(pid, signal, config);
+        }
+        let now = Instant::now();
+        match deadline {
+            Some(deadline) if now >= deadline => return None,
+            Some(deadline) => thread::sleep(POLL_INTERVAL.min(deadline - now)),
+            None => thread::sleep(POLL_INTERVAL),
+        }
+    }
+}
+
+/// The exit status a shell would report for a command that ended with wait status @p status.
+fn exit_code(status: i32) -> i32 {
+    if libc::WIFEXITED(status) {
+        libc::WEXITSTATUS(status)
+    } else {
+        128 + libc::WTERMSIG(status)
+    }
+}
+
+/// Runs @p command, signalling it if it outlasts its time, and returns the exit status for
+/// timeout.
+fn run(command: &[String], config: &TimeoutConfig) -> i32 {
+    for &signal in &[libc::SIGHUP, libc::SIGINT, libc::SIGQUIT, libc::SIGTERM] {
+        unsafe {
+            libc::signal(
+                signal,
+                receive as extern "C" fn(libc::c_int) as libc::sighandler_t,
+            );
+        }
+    }
+    let mut child = Command::new(&command[0]);
+    child.args(&command[1..]);
+    if !config.foreground {
+        child.process_group(0);
+    }
+    let pid = match child.spawn() {
+        Ok(child) => child.id() as libc::pid_t,
+        Err(e) => {
+            warn!("failed to run command '{}': {}", command[0], e);
+            return match e.kind() {
+                io::ErrorKind::NotFound => 127,
+                io::ErrorKind::PermissionDenied => 126,
+                _ => 125,
+            };
+        }
+    };
+
+    let deadline = config.duration.map(|duration| Instant::now() + duration);
+    if let Some(status) = wait(pid, deadline, config) {
+        return exit_code(status);
+    }
+    if config.verbose {
+        warn!(
+            "sending signal {} to command '{}'",
+            signal_name(config.signal),
+            command[0]
+        );
+    }
+    send(pid, config.signal, config);
+    let mut killed = config.signal == libc::SIGKILL;
+    let deadline = config
+        .kill_after
+        .map(|kill_after| Instant::now() + kill_after);
+    let status = match wait(pid, deadline, config) {
+        Some(status) => status,
+        None => {
+            if config.verbose {
+                warn!("sending signal KILL to command '{}'", command[0]);
+            }
+            send(pid, libc::SIGKILL, config);
+            killed = true;
+            wait(pid, None, config).unwrap()
+        }
+    };
+    if config.preserve_status {
+        exit_code(status)
+    } else if killed {
+        // Like GNU timeout, which is killed along with the command.
+        128 + libc::SIGKILL
+    } else {
+        TIMED_OUT
+    }
+}
+
+fn show_help(opts: &ArgParser) {
+    let brief = format!(
+        concat!(
+            "Clone of the standard GNU timeout.\n",
+            "Usage: {}: [OPTION]... DURATION COMMAND [ARG]...\n",
+            "Start COMMAND, and kill it if still running after DURATION.\n",
+            "DURATION is a floating point number with an optional suffix: 's' for seconds\n",
+            "(the default), 'm' for minutes, 'h' for hours or 'd' for days. A duration of 0\n",
+            "disables the timeout.\n",
+            "Exit status is 124 if COMMAND times out, 125 if timeout itself fails, 126 if\n",
+            "COMMAND cannot be invoked, 127 if it cannot be found, 137 if it is killed with\n",
+            "KILL, and otherwise that of COMMAND."
+        ),
+        env::args().next().unwrap()
+    );
+    print!("{}", opts.usage(&brief));
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let mut opts = ArgParser::new();
+    opts.stop_at_first_free();
+    opts.flag(
+        "",
+        "foreground",
+        "when not running timeout directly from a shell prompt, allow COMMAND to read from \
+         the TTY and get TTY signals; in this mode, children of COMMAND will not be timed out",
+    );
+    opts.option(
+        "k",
+        "kill-after",
+        "also send a KILL signal if COMMAND is still running this long after the initial \
+         signal was sent",
+        "DURATION",
+    );
+    opts.flag(
+        "",
+        "preserve-status",
+        "exit with the same status as COMMAND, even when the command times out",
+    );
+    opts.option(
+        "s",
+        "signal",
+        "specify the signal to be sent on timeout; SIGNAL may be a name like 'HUP' or a \
+         number",
+        "SIGNAL",
+    );
+    opts.flag(
+        "v",
+        "verbose",
+        "diagnose to stderr any signal sent upon timeout",
+    );
+    opts.flag("h", "help", "display this help and exit");
+    opts.flag("", "version", "output version information and exit");
+    let options = match opts.parse(&args[1..]) {
+        Ok(m) => m,
+        Err(f) => trouble!("{}", f.to_string()),
+    };
+    if options.opt_present("h") {
+        return show_help(&opts);
+    }
+    if options.opt_present("version") {
+        return utils::version::print_version("timeout");
+    }
+    match options.free.len() {
+        0 => trouble!("missing operand"),
+        1 => trouble!("missing operand after '{}'", options.free[0]),
+        _ => {}
+    }
+
+    let duration = |arg: &str| -> Option<Duration> {
+        let seconds = parse_duration(arg).unwrap_or_else(|e| trouble!("{}", e));
+        if seconds == 0.0 {
+            return None;
+        }
+        Some(Duration::try_from_secs_f64(seconds).unwrap_or(Duration::MAX))
+    };
+    let signal = match options.opt_str("s") {
+        Some(signal) => {
+            parse_signal(&signal).unwrap_or_else(|| trouble!("'{}': invalid signal", signal))
+        }
+        None => libc::SIGTERM,
+    };
+    let config = TimeoutConfig {
+        duration: duration(&options.free[0]),
+        signal,
+        kill_after: options.opt_str("k").and_then(|arg| duration(&arg)),
+        preserve_status: options.opt_present("preserve-status"),
+        foreground: options.opt_present("foreground"),
+        verbose: options.opt_present("v"),
+    };
+    process::exit(run(&options.free[1..], &config));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signals() {
+        assert_eq!(parse_signal("TERM"), Some(libc::SIGTERM));
+        assert_eq!(parse_signal("SIGKILL"), Some(libc::SIGKILL));
+        assert_eq!(parse_signal("hup"), Some(libc::SIGHUP));
+        assert_eq!(parse_signal("9"), Some(9));
+        assert_eq!(parse_signal("FOO"), None);
+        assert_eq!(parse_signal("-1"), None);
+        assert_eq!(signal_name(libc::SIGUSR1), "USR1");
+    }
+
+    #[test]
+    fn exit_codes() {
+        assert_eq!(exit_code(3 << 8), 3);
+        assert_eq!(exit_code(libc::SIGTERM), 128 + libc::SIGTERM);
+    }
+}
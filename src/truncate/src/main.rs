@@ -0,0 +1,233 @@
+use std::env;
+use std::fs;
+use std::io;
+
+#[macro_use(die)]
+extern crate utils;
+
+use utils::args::ArgParser;
+
+/// How the SIZE operand of -s adjusts a file's size.
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum SizeSpec {
+    Absolute(u64),
+    Extend(u64),
+    Shrink(u64),
+    RoundDown(u64),
+    RoundUp(u64),
+}
+
+impl SizeSpec {
+    /// Parses [+-%/]NUMBER[K|M|G], where the suffixes are powers of 1024.
+    fn parse(spec: &str) -> Result<SizeSpec, String> {
+        let invalid = || format!("invalid number: '{}'", spec);
+        let (kind, number): (fn(u64) -> SizeSpec, &str) = match spec.chars().next() {
+            Some('+') => (SizeSpec::Extend, &spec[1..]),
+            Some('-') => (SizeSpec::Shrink, &spec[1..]),
+            Some('%') => (SizeSpec::RoundDown, &spec[1..]),
+            Some('/') => (SizeSpec::RoundUp, &spec[1..]),
+            _ => (SizeSpec::Absolute, spec),
+        };
+        let (digits, multiplier) = match number.chars().last().map(|c| c.to_ascii_uppercase()) {
+            Some('K') => (&number[..number.len() - 1], 1 << 10),
+            Some('M') => (&number[..number.len() - 1], 1 << 20),
+            Some('G') => (&number[..number.len() - 1], 1 << 30),
+            _ => (number, 1),
+        };
+        let value = digits
+            .parse::<u64>()
+            .ok()
+            .and_then(|value| value.checked_mul(multiplier))
+            .ok_or_else(invalid)?;
+        let spec = kind(value);
+        match spec {
+            SizeSpec::RoundDown(0) | SizeSpec::RoundUp(0) => Err("division by zero".to_owned()),
+            _ => Ok(spec),
+        }
+    }
+
+    /// Computes the new size of a file currently @p size bytes long.
+    fn apply(self, size: u64) -> Result<u64, String> {
+        let overflow = || "size is too large".to_owned();
+        match self {
+            SizeSpec::Absolute(n) => Ok(n),
+            SizeSpec::Extend(n) => size.checked_add(n).ok_or_else(overflow),
+            SizeSpec::Shrink(n) => Ok(size.saturating_sub(n)),
+            SizeSpec::RoundDown(n) => Ok(size / n * n),
+            SizeSpec::RoundUp(n) => size.div_ceil(n).checked_mul(n).ok_or_else(overflow),
+        }
+    }
+}
+
+/// Resizes @p name according to @p spec, relative to @p reference_size if given or to the
+/// file's own size otherwise.
+fn truncate(
+    name: &str,
+    spec: Option<SizeSpec>,
+    reference_size: Option<u64>,
+    create: bool,
+) -> Result<(), String> {
+    let file = match fs::OpenOptions::new().write(true).create(create).open(name) {
+        Ok(file) => file,
+        Err(ref e) if !create && e.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(format!("cannot open '{}' for writing: {}", name, e)),
+    };
+    let size = match reference_size {
+        Some(size) => size,
+        None => file
+            .metadata()
+            .map_err(|e| format!("cannot stat '{}': {}", name, e))?
+            .len(),
+    };
+    let new_size = match spec {
+        Some(spec) => spec.apply(size)?,
+        None => size,
+    };
+    file.set_len(new_size)
+        .map_err(|e| format!("failed to truncate '{}' at {} bytes: {}", name, new_size, e))
+}
+
+fn show_help(opts: &ArgParser) {
+    let brief = format!(
+        concat!(
+            "Clone of the standard GNU truncate.\n",
+            "Usage: {}: OPTION... FILE...\n",
+            "Shrink or extend the size of each FILE to the specified size.\n",
+            "SIZE may be prefixed with '+' (extend by), '-' (reduce by), '%' (round down\n",
+            "to a multiple of) or '/' (round up to a multiple of)."
+        ),
+        env::args().next().unwrap()
+    );
+    print!("{}", opts.usage(&brief));
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let mut opts = ArgParser::new();
+    opts.flag("c", "no-create", "do not create any files");
+    opts.option("r", "reference", "base size on RFILE", "RFILE");
+    opts.option(
+        "s",
+        "size",
+        "set or adjust the file size by SIZE bytes",
+        "SIZE",
+    );
+    opts.flag("h", "help", "display this help and exit");
+    opts.flag("v", "version", "output version information and exit");
+    let options = match opts.parse(&args[1..]) {
+        Ok(m) => m,
+        Err(f) => die!(code = 2, "{}", f.to_string()),
+    };
+    if options.opt_present("h") {
+        return show_help(&opts);
+    }
+    if options.opt_present("v") {
+        return utils::version::print_version("truncate");
+    }
+
+    let spec = options
+        .opt_str("s")
+        .map(|spec| SizeSpec::parse(&spec).unwrap_or_else(|e| die!(code = 2, "{}", e)));
+    let reference_size = options.opt_str("r").map(|name| match fs::metadata(&name) {
+        Ok(metadata) => metadata.len(),
+        Err(e) => die!("cannot stat '{}': {}", name, e),
+    });
+    if spec.is_none() && reference_size.is_none() {
+        die!(
+            code = 2,
+            "you must specify either '--size' or '--reference'"
+        );
+    }
+    if let (Some(SizeSpec::Absolute(_)), Some(_)) = (spec, reference_size) {
+        die!(
+            code = 2,
+            "you must specify a relative '--size' with '--reference'"
+        );
+    }
+    if options.free.is_empty() {
+        die!(code = 2, "missing file operand");
+    }
+
+    let create = !options.opt_present("c");
+    let mut failed = false;
+    for name in &options.free {
+        if let Err(e) = truncate(name, spec, reference_size, create) {
+            eprintln!("{}: {}", env::args().next().unwrap(), e);
+            failed = true;
+        }
+    }
+    if failed {
+        std::process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn temp_file(name: &str, contents: &[u8]) -> PathBuf {
+        let path = env::temp_dir().join(format!("truncate-test-{}-{}", std::process::id(), name));
+        fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn size_parsing() {
+        assert_eq!(SizeSpec::parse("10"), Ok(SizeSpec::Absolute(10)));
+        assert_eq!(SizeSpec::parse("+2K"), Ok(SizeSpec::Extend(2048)));
+        assert_eq!(SizeSpec::parse("-1m"), Ok(SizeSpec::Shrink(1 << 20)));
+        assert_eq!(SizeSpec::parse("%4"), Ok(SizeSpec::RoundDown(4)));
+        assert_eq!(SizeSpec::parse("/4"), Ok(SizeSpec::RoundUp(4)));
+        assert!(SizeSpec::parse("%0").is_err());
+        assert!(SizeSpec::parse("x").is_err());
+        assert!(SizeSpec::parse("+").is_err());
+    }
+
+    #[test]
+    fn relative_sizes() {
+        assert_eq!(SizeSpec::Extend(5).apply(10), Ok(15));
+        assert_eq!(SizeSpec::Shrink(5).apply(3), Ok(0));
+        assert_eq!(SizeSpec::RoundDown(4).apply(10), Ok(8));
+        assert_eq!(SizeSpec::RoundDown(4).apply(12), Ok(12));
+        assert_eq!(SizeSpec::RoundUp(4).apply(10), Ok(12));
+        assert_eq!(SizeSpec::RoundUp(4).apply(0), Ok(0));
+        assert!(SizeSpec::Extend(1).apply(u64::MAX).is_err());
+    }
+
+    #[test]
+    fn extends_with_zeros() {
+        let path = temp_file("extend", b"abc");
+        let name = path.to_str().unwrap();
+        truncate(name, Some(SizeSpec::Absolute(8)), None, true).unwrap();
+        assert_eq!(fs::read(&path).unwrap(), b"abc\0\0\0\0\0");
+        truncate(name, Some(SizeSpec::Shrink(6)), None, true).unwrap();
+        assert_eq!(fs::read(&path).unwrap(), b"ab");
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn rounds_down_to_multiple() {
+        let path = temp_file("round", b"0123456789");
+        truncate(
+            path.to_str().unwrap(),
+            Some(SizeSpec::RoundDown(4)),
+            None,
+            true,
+        )
+        .unwrap();
+        assert_eq!(fs::read(&path).unwrap(), b"01234567");
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn reference_and_no_create() {
+        let path = env::temp_dir().join(format!("truncate-test-{}-missing", std::process::id()));
+        let name = path.to_str().unwrap();
+        truncate(name, None, Some(3), false).unwrap();
+        assert!(!path.exists());
+        truncate(name, Some(SizeSpec::Extend(2)), Some(3), true).unwrap();
+        assert_eq!(fs::read(&path).unwrap(), vec![0; 5]);
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,87 @@
+use std::env;
+use std::ffi::CStr;
+use std::process;
+
+#[macro_use(die)]
+extern crate utils;
+extern crate libc;
+
+use utils::args::ArgParser;
+
+/// The file name of the terminal open as @p fd, or None if @p fd is not a terminal.
+fn tty_name(fd: libc::c_int) -> Option<String> {
+    if unsafe { libc::isatty(fd) } != 1 {
+        return None;
+    }
+    let mut buf = vec![0 as libc::c_char; 256];
+    if unsafe { libc::ttyname_r(fd, buf.as_mut_ptr(), buf.len()) } != 0 {
+        return None;
+    }
+    Some(
+        unsafe { CStr::from_ptr(buf.as_ptr()) }
+            .to_string_lossy()
+            .into_owned(),
+    )
+}
+
+fn show_help(opts: &ArgParser) {
+    let brief = format!(
+        concat!(
+            "Clone of the standard GNU tty.\n",
+            "Usage: {}: [OPTION]...\n",
+            "Print the file name of the terminal connected to standard input."
+        ),
+        env::args().next().unwrap()
+    );
+    print!("{}", opts.usage(&brief));
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let mut opts = ArgParser::new();
+    opts.flag("s", "silent", "print nothing, only return an exit status");
+    opts.flag("", "quiet", "same as -s");
+    opts.flag("h", "help", "display this help and exit");
+    opts.flag("v", "version", "output version information and exit");
+    let options = match opts.parse(&args[1..]) {
+        Ok(m) => m,
+        Err(f) => die!(code = 2, "{}", f.to_string()),
+    };
+    if options.opt_present("h") {
+        return show_help(&opts);
+    }
+    if options.opt_present("v") {
+        return utils::version::print_version("tty");
+    }
+    if !options.free.is_empty() {
+        die!(code = 2, "extra operand '{}'", options.free[0]);
+    }
+
+    let silent = options.opt_present("s") || options.opt_present("quiet");
+    match tty_name(0) {
+        Some(name) => {
+            if !silent {
+                println!("{}", name);
+            }
+        }
+        None => {
+            if !silent {
+                println!("not a tty");
+            }
+            process::exit(1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::os::unix::io::AsRawFd;
+
+    #[test]
+    fn regular_file_is_not_a_tty() {
+        let file = fs::File::open("Cargo.toml").unwrap();
+        assert_eq!(tty_name(file.as_raw_fd()), None);
+    }
+}
@@ -0,0 +1,175 @@
+use std::env;
+use std::ffi::CStr;
+use std::io;
+use std::mem;
+
+#[macro_use(die)]
+extern crate utils;
+extern crate libc;
+
+use utils::args::ArgParser;
+
+/// The fields uname can print, in the order POSIX prescribes for -a.
+#[derive(Debug)]
+struct SystemInfo {
+    kernel_name: String,
+    node_name: String,
+    kernel_release: String,
+    kernel_version: String,
+    machine: String,
+    processor: String,
+    hardware_platform: String,
+    operating_system: String,
+}
+
+fn field(chars: &[libc::c_char]) -> String {
+    unsafe { CStr::from_ptr(chars.as_ptr()) }
+        .to_string_lossy()
+        .into_owned()
+}
+
+fn system_info() -> io::Result<SystemInfo> {
+    let mut uts: libc::utsname = unsafe { mem::zeroed() };
+    if unsafe { libc::uname(&mut uts) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    let kernel_name = field(&uts.sysname);
+    let operating_system = if kernel_name == "Linux" {
+        "GNU/Linux".to_owned()
+    } else {
+        kernel_name.clone()
+    };
+    Ok(SystemInfo {
+        kernel_name,
+        node_name: field(&uts.nodename),
+        kernel_release: field(&uts.release),
+        kernel_version: field(&uts.version),
+        machine: field(&uts.machine),
+        processor: "unknown".to_owned(),
+        hardware_platform: "unknown".to_owned(),
+        operating_system,
+    })
+}
+
+/// Selects the fields of @p info named by @p flags (in the order of `SystemInfo`) and joins
+/// them with spaces. With @p all set the unknown processor and platform are left out.
+fn format_info(info: &SystemInfo, flags: &[bool; 8], all: bool) -> String {
+    let fields = [
+        &info.kernel_name,
+        &info.node_name,
+        &info.kernel_release,
+        &info.kernel_version,
+        &info.machine,
+        &info.processor,
+        &info.hardware_platform,
+        &info.operating_system,
+    ];
+    let selected: Vec<&str> = fields
+        .iter()
+        .zip(flags.iter())
+        .filter(|&(value, &flag)| flag || (all && *value != "unknown"))
+        .map(|(value, _)| value.as_str())
+        .collect();
+    if selected.is_empty() {
+        info.kernel_name.clone()
+    } else {
+        selected.join(" ")
+    }
+}
+
+fn show_help(opts: &ArgParser) {
+    let brief = format!(
+        concat!(
+            "Clone of the standard GNU uname.\n",
+            "Usage: {}: [OPTION]...\n",
+            "Print certain system information. With no OPTION, same as -s."
+        ),
+        env::args().next().unwrap()
+    );
+    print!("{}", opts.usage(&brief));
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let mut opts = ArgParser::new();
+    opts.flag(
+        "a",
+        "all",
+        "print all information, omitting -p and -i if unknown",
+    );
+    opts.flag("s", "kernel-name", "print the kernel name");
+    opts.flag("n", "nodename", "print the network node hostname");
+    opts.flag("r", "kernel-release", "print the kernel release");
+    opts.flag("v", "kernel-version", "print the kernel version");
+    opts.flag("m", "machine", "print the machine hardware name");
+    opts.flag("p", "processor", "print the processor type");
+    opts.flag("i", "hardware-platform", "print the hardware platform");
+    opts.flag("o", "operating-system", "print the operating system");
+    opts.flag("h", "help", "display this help and exit");
+    opts.flag("", "version", "output version information and exit");
+    let options = match opts.parse(&args[1..]) {
+        Ok(m) => m,
+        Err(f) => die!(code = 2, "{}", f.to_string()),
+    };
+    if options.opt_present("h") {
+        return show_help(&opts);
+    }
+    if options.opt_present("version") {
+        return utils::version::print_version("uname");
+    }
+    if !options.free.is_empty() {
+        die!(code = 2, "extra operand '{}'", options.free[0]);
+    }
+
+    let mut flags = [false; 8];
+    for (flag, name) in flags
+        .iter_mut()
+        .zip(["s", "n", "r", "v", "m", "p", "i", "o"].iter())
+    {
+        *flag = options.opt_present(name);
+    }
+    let info = system_info().unwrap_or_else(|e| die!("cannot get system name: {}", e));
+    println!("{}", format_info(&info, &flags, options.opt_present("a")));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fields_are_populated() {
+        let info = system_info().unwrap();
+        assert!(!info.kernel_name.is_empty());
+        assert!(!info.kernel_release.is_empty());
+        assert!(!info.machine.is_empty());
+        assert!(!format_info(&info, &[false; 8], true).is_empty());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn kernel_name_on_linux() {
+        let info = system_info().unwrap();
+        let mut flags = [false; 8];
+        flags[0] = true;
+        assert_eq!(format_info(&info, &flags, false), "Linux");
+        assert_eq!(format_info(&info, &[false; 8], false), "Linux");
+        assert_eq!(info.operating_system, "GNU/Linux");
+    }
+
+    #[test]
+    fn field_order() {
+        let info = SystemInfo {
+            kernel_name: "K".to_owned(),
+            node_name: "N".to_owned(),
+            kernel_release: "R".to_owned(),
+            kernel_version: "V".to_owned(),
+            machine: "M".to_owned(),
+            processor: "unknown".to_owned(),
+            hardware_platform: "I".to_owned(),
+            operating_system: "O".to_owned(),
+        };
+        assert_eq!(format_info(&info, &[false; 8], true), "K N R V M I O");
+        let flags = [false, false, false, false, true, true, false, true];
+        assert_eq!(format_info(&info, &flags, false), "M unknown O");
+    }
+}
@@ -0,0 +1,266 @@
+use std::env;
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::str;
+
+#[macro_use(die, note_error, warn)]
+extern crate utils;
+
+use utils::args::ArgParser;
+use utils::fmt::ColumnFormatter;
+use utils::io::FileOrStdin;
+
+/// The counts of an input, in the order they are printed.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct Counts {
+    lines: u64,
+    words: u64,
+    chars: u64,
+    bytes: u64,
+}
+
+impl Counts {
+    fn add(&mut self, other: &Counts) {
+        self.lines += other.lines;
+        self.words += other.words;
+        self.chars += other.chars;
+        self.bytes += other.bytes;
+    }
+}
+
+/// Which of the counts are printed.
+struct Selection {
+    lines: bool,
+    words: bool,
+    chars: bool,
+    bytes: bool,
+}
+
+impl Selection {
+    fn values(&self, counts: &Counts) -> Vec<u64> {
+        let all = [
+            (self.lines, counts.lines),
+            (self.words, counts.words),
+            (self.chars, counts.chars),
+            (self.bytes, counts.bytes),
+        ];
+        all.iter()
+            .filter(|&&(selected, _)| selected)
+            .map(|&(_, count)| count)
+            .collect()
+    }
+}
+
+/// Counts the lines, words, characters and bytes of @p reader. Lines are the new line bytes
+/// whatever the encoding, and characters are Unicode scalar values. Returns whether the input
+/// was valid UTF-8; if it was not, the characters are counted as bytes instead.
+fn count(reader: &mut dyn BufRead) -> io::Result<(Counts, bool)> {
+    let mut counts = Counts::default();
+    let mut valid = true;
+    let mut in_word = false;
+    let mut line = Vec::new();
+    loop {
+        line.clear();
+        if reader.read_until(b'\n', &mut line)? == 0 {
+            break;
+        }
+        counts.bytes += line.len() as u64;
+        if line.last() == Some(&b'\n') {
+            counts.lines += 1;
+        }
+        for &c in &line {
+            // Words are separated by ASCII white space, as in the C locale.
+            if b" \t\n\r\x0b\x0c".contains(&c) {
+                in_word = false;
+            } else if !in_word {
+                in_word = true;
+                counts.words += 1;
+            }
+        }
+        match str::from_utf8(&line) {
+            Ok(text) => counts.chars += text.chars().count() as u64,
+            Err(_) => valid = false,
+        }
+    }
+    if !valid {
+        counts.chars = counts.bytes;
+    }
+    Ok((counts, valid))
+}
+
+/// The width of the columns, as GNU wc picks it: wide enough for the total size of the regular
+/// files among @p names, but at least 7 if any input is something else, whose size cannot be
+/// known up front. A single count of a single input is not padded at all.
+fn column_width(names: &[String], counts: usize) -> usize {
+    if names.len() == 1 && counts == 1 {
+        return 1;
+    }
+    let mut minimum = 1;
+    let mut total = 0;
+    for name in names {
+        match fs::metadata(name) {
+            Ok(ref metadata) if name != "-" && metadata.is_file() => total += metadata.len(),
+            Ok(_) => minimum = 7,
+            Err(_) => {}
+        }
+    }
+    if names.iter().any(|name| name == "-") {
+        minimum = 7;
+    }
+    ColumnFormatter::width_for(total).max(minimum)
+}
+
+/// Writes @p values in columns of @p width, followed by @p name if there is one.
+fn write_counts(
+    writer: &mut dyn Write,
+    values: &[u64],
+    width: usize,
+    name: Option<&str>,
+) -> io::Result<()> {
+    let column = ColumnFormatter::new(width, "");
+    for (n, &value) in values.iter().enumerate() {
+        if n > 0 {
+            writer.write_all(b" ")?;
+        }
+        column.write(writer, value as i64)?;
+    }
+    if let Some(name) = name {
+        write!(writer, " {}", name)?;
+    }
+    writer.write_all(b"\n")
+}
+
+fn show_help(opts: &ArgParser) {
+    let brief = format!(
+        concat!(
+            "Clone of the standard GNU wc.\n",
+            "Usage: {}: [OPTION]... [FILE]...\n",
+            "Print new line, word and byte counts for each FILE, and a total line if more\n",
+            "than one FILE is given. Without FILE, or when FILE is -, read standard input.\n",
+            "Characters are counted as UTF-8; in other input they are counted as bytes."
+        ),
+        env::args().next().unwrap()
+    );
+    print!("{}", opts.usage(&brief));
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let mut opts = ArgParser::new();
+    opts.flag("c", "bytes", "print the byte counts");
+    opts.flag("m", "chars", "print the character counts");
+    opts.flag("l", "lines", "print the new line counts");
+    opts.flag("w", "words", "print the word counts");
+    opts.flag("h", "help", "display this help and exit");
+    opts.flag("v", "version", "output version information and exit");
+    let options = match opts.parse(&args[1..]) {
+        Ok(m) => m,
+        Err(f) => die!(code = 2, "{}", f.to_string()),
+    };
+    if options.opt_present("h") {
+        return show_help(&opts);
+    }
+    if options.opt_present("v") {
+        return utils::version::print_version("wc");
+    }
+
+    let any = ["c", "m", "l", "w"]
+        .iter()
+        .any(|flag| options.opt_present(flag));
+    let selection = Selection {
+        lines: options.opt_present("l") || !any,
+        words: options.opt_present("w") || !any,
+        chars: options.opt_present("m"),
+        bytes: options.opt_present("c") || !any,
+    };
+    let names = if options.free.is_empty() {
+        vec!["-".to_owned()]
+    } else {
+        options.free.clone()
+    };
+    let width = column_width(&names, selection.values(&Counts::default()).len());
+
+    let stdout = io::stdout();
+    let mut writer = io::BufWriter::new(stdout.lock());
+    let mut total = Counts::default();
+    for name in &names {
+        let counted = FileOrStdin::open(name).and_then(|mut reader| count(&mut reader));
+        let (counts, valid) = match counted {
+            Ok(counted) => counted,
+            Err(e) => {
+                note_error!("{}: {}", name, e);
+                continue;
+            }
+        };
+        if !valid && selection.chars {
+            warn!("{}: invalid UTF-8, counting bytes as characters", name);
+        }
+        total.add(&counts);
+        let name = if options.free.is_empty() {
+            None
+        } else {
+            Some(name.as_str())
+        };
+        write_counts(&mut writer, &selection.values(&counts), width, name)
+            .unwrap_or_else(|e| die!("{}", e));
+    }
+    if names.len() > 1 {
+        write_counts(&mut writer, &selection.values(&total), width, Some("total"))
+            .unwrap_or_else(|e| die!("{}", e));
+    }
+    writer.flush().unwrap_or_else(|e| die!("{}", e));
+    utils::exit_if_error();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn counted(input: &[u8]) -> (Counts, bool) {
+        count(&mut &input[..]).unwrap()
+    }
+
+    #[test]
+    fn counting() {
+        let (counts, valid) = counted("héllo wörld\n  two\twords \nlast".as_bytes());
+        assert!(valid);
+        assert_eq!(
+            counts,
+            Counts {
+                lines: 2,
+                words: 5,
+                chars: 29,
+                bytes: 31,
+            }
+        );
+        assert_eq!(counted(b""), (Counts::default(), true));
+    }
+
+    #[test]
+    fn invalid_utf8_counts_bytes() {
+        let (counts, valid) = counted(b"h\xe9llo\n");
+        assert!(!valid);
+        assert_eq!(counts.chars, 6);
+        assert_eq!(counts.bytes, 6);
+    }
+
+    #[test]
+    fn selected_values() {
+        let selection = Selection {
+            lines: true,
+            words: false,
+            chars: true,
+            bytes: true,
+        };
+        let counts = Counts {
+            lines: 1,
+            words: 2,
+            chars: 3,
+            bytes: 4,
+        };
+        assert_eq!(selection.values(&counts), [1, 3, 4]);
+        let mut output = Vec::new();
+        write_counts(&mut output, &[1, 30], 3, Some("f")).unwrap();
+        assert_eq!(output, b"  1  30 f\n");
+    }
+}
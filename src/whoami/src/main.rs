@@ -0,0 +1,76 @@
+use std::env;
+use std::io;
+
+#[macro_use(die)]
+extern crate utils;
+extern crate libc;
+
+use utils::args::ArgParser;
+use utils::users;
+
+/// The name of the effective user, falling back to the numeric UID.
+fn effective_user() -> io::Result<String> {
+    let uid = unsafe { libc::geteuid() };
+    Ok(users::user_name(uid)?.unwrap_or_else(|| uid.to_string()))
+}
+
+fn show_help(opts: &ArgParser) {
+    let brief = format!(
+        concat!(
+            "Clone of the standard GNU whoami.\n",
+            "Usage: {}: [OPTION]...\n",
+            "Print the user name associated with the current effective user ID."
+        ),
+        env::args().next().unwrap()
+    );
+    print!("{}", opts.usage(&brief));
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let mut opts = ArgParser::new();
+    opts.flag("h", "help", "display this help and exit");
+    opts.flag("v", "version", "output version information and exit");
+    let options = match opts.parse(&args[1..]) {
+        Ok(m) => m,
+        Err(f) => die!(code = 2, "{}", f.to_string()),
+    };
+    if options.opt_present("h") {
+        return show_help(&opts);
+    }
+    if options.opt_present("v") {
+        return utils::version::print_version("whoami");
+    }
+    if !options.free.is_empty() {
+        die!(code = 2, "extra operand '{}'", options.free[0]);
+    }
+
+    match effective_user() {
+        Ok(name) => println!("{}", name),
+        Err(e) => die!(
+            "cannot find name for user ID {}: {}",
+            unsafe { libc::geteuid() },
+            e
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn name_is_not_empty() {
+        assert!(!effective_user().unwrap().is_empty());
+    }
+
+    #[test]
+    fn matches_user_variable() {
+        // USER is not set everywhere (e.g. in containers) and may be stale after a setuid.
+        if let Ok(user) = env::var("USER") {
+            if unsafe { libc::getuid() == libc::geteuid() } {
+                assert_eq!(effective_user().unwrap(), user);
+            }
+        }
+    }
+}
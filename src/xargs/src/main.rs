@@ -0,0 +1,590 @@
+use std::env;
+use std::ffi::{OsStr, OsString};
+use std::io::{self, BufRead, BufReader};
+use std::os::unix::ffi::{OsStrExt, OsStringExt};
+use std::process::{self, Child, Command, Stdio};
+
+#[macro_use(die, warn)]
+extern crate utils;
+extern crate libc;
+
+use utils::args::ArgParser;
+
+/// The most bytes the arguments of one command may take, each counted with its terminating
+/// NUL, as GNU xargs uses by default.
+const MAX_CHARS: usize = 128 * 1024;
+
+/// Exit statuses, as GNU xargs reports them.
+const COMMAND_FAILED: i32 = 123;
+const COMMAND_EXITED_255: i32 = 124;
+const COMMAND_KILLED: i32 = 125;
+const CANNOT_RUN: i32 = 126;
+const NOT_FOUND: i32 = 127;
+
+/// How input is split into items.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Separator {
+    /// Blanks and new lines separate items, and quotes and backslashes escape them.
+    Blanks,
+    /// Items are lines: only new lines separate them, as with -I.
+    Lines,
+    /// Each occurrence of the byte ends an item, and nothing is special otherwise.
+    Delimiter(u8),
+}
+
+#[derive(Debug, PartialEq)]
+struct Item {
+    text: Vec<u8>,
+    /// Whether the item ends a logical input line, as counted by -L.
+    ends_line: bool,
+}
+
+/// Reads the items of an input one at a time, so that commands can run before it all arrived.
+struct ItemReader<R> {
+    reader: R,
+    separator: Separator,
+}
+
+impl<R: BufRead> ItemReader<R> {
+    fn next_byte(&mut self) -> io::Result<Option<u8>> {
+        let mut byte = [0];
+        loop {
+            match self.reader.read(&mut byte) {
+                Ok(0) => return Ok(None),
+                Ok(_) => return Ok(Some(byte[0])),
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    fn next_item(&mut self) -> io::Result<Option<Item>> {
+        if let Separator::Delimiter(delimiter) = self.separator {
+            let mut text = Vec::new();
+            if self.reader.read_until(delimiter, &mut text)? == 0 {
+                return Ok(None);
+            }
+            if text.last() == Some(&delimiter) {
+                text.pop();
+            }
+            return Ok(Some(Item {
+                text,
+                ends_line: true,
+            }));
+        }
+
+        let lines = self.separator == Separator::Lines;
+        let mut text = Vec::new();
+        // Set once an item was started, as a quoted empty string is an item too.
+        let mut started = false;
+        loop {
+            let c = match self.next_byte()? {
+                Some(c) => c,
+                None if started => {
+                    return Ok(Some(Item {
+                        text,
+                        ends_line: true,
+                    }))
+                }
+                None => return Ok(None),
+            };
+            match c {
+                b'\n' if started => {
+                    return Ok(Some(Item {
+                        text,
+                        ends_line: true,
+                    }));
+                }
+                b'\n' => {}
+                b' ' | b'\t' if !started => {}
+                b' ' | b'\t' if lines => text.push(c),
+                b' ' | b'\t' => {
+                    // Skip the blanks up to the next item, but not past a new line: a line
+                    // ending in a blank goes on with the next one, so the item does not end it.
+                    while let Some(&next) = self.reader.fill_buf()?.first() {
+                        if next == b'\n' {
+                            self.reader.consume(1);
+                            return Ok(Some(Item {
+                                text,
+                                ends_line: false,
+                            }));
+                        }
+                        if next != b' ' && next != b'\t' {
+                            break;
+                        }
+                        self.reader.consume(1);
+                    }
+                    return Ok(Some(Item {
+                        text,
+                        ends_line: false,
+                    }));
+                }
+                b'\'' | b'"' => {
+                    started = true;
+                    loop {
+                        match self.next_byte()? {
+                            Some(q) if q == c => break,
+                            Some(b'\n') | None => {
+                                let quote = if c == b'\'' { "single" } else { "double" };
+                                return Err(io::Error::new(
+                                    io::ErrorKind::InvalidData,
+                                    format!(
+                                        "unmatched {} quote; by default quotes are special to \
+                                         xargs unless you use the -0 option",
+                                        quote
+                                    ),
+                                ));
+                            }
+                            Some(q) => text.push(q),
+                        }
+                    }
+                }
+                b'\\' => {
+                    started = true;
+                    text.extend(self.next_byte()?);
+                }
+                _ => {
+                    started = true;
+                    text.push(c);
+                }
+            }
+        }
+    }
+}
+
+/// Parses the DELIM of -d: a single character, or one of the escapes \n, \t, \0, \\ and \xHH.
+fn parse_delimiter(delimiter: &str) -> Option<u8> {
+    match delimiter.as_bytes() {
+        [c] => Some(*c),
+        b"\\n" => Some(b'\n'),
+        b"\\t" => Some(b'\t'),
+        b"\\0" => Some(b'\0'),
+        b"\\\\" => Some(b'\\'),
+        [b'\\', b'x', hex @ ..] if !hex.is_empty() && hex.len() <= 2 => {
+            u8::from_str_radix(&delimiter[2..], 16).ok()
+        }
+        _ => None,
+    }
+}
+
+struct XargsConfig {
+    /// The command and its initial arguments.
+    command: Vec<OsString>,
+    separator: Separator,
+    max_args: Option<usize>,
+    max_lines: Option<usize>,
+    /// With -I, the string replaced by each item in the initial arguments.
+    replace: Option<Vec<u8>>,
+    /// How many commands may run at once; 0 for no limit.
+    max_procs: usize,
+    no_run_if_empty: bool,
+    verbose: bool,
+}
+
+/// Replaces every occurrence of @p from in @p arg with @p to.
+fn replace(arg: &OsStr, from: &[u8], to: &[u8]) -> OsString {
+    let arg = arg.as_bytes();
+    let mut replaced = Vec::with_capacity(arg.len());
+    let mut i = 0;
+    while i < arg.len() {
+        if !from.is_empty() && arg[i..].starts_with(from) {
+            replaced.extend_from_slice(to);
+            i += from.len();
+        } else {
+            replaced.push(arg[i]);
+            i += 1;
+        }
+    }
+    OsString::from_vec(replaced)
+}
+
+/// Runs the commands, keeping up to `max_procs` of them running at once.
+struct Executor {
+    max_procs: usize,
+    verbose: bool,
+    running: Vec<(Child, OsString)>,
+    status: i32,
+}
+
+impl Executor {
+    /// Starts @p args as a command, first waiting for a slot if all are taken. Errors are the
+    /// status xargs has to exit with right away.
+    fn spawn(&mut self, args: &[OsString]) -> Result<(), i32> {
+        while self.max_procs != 0 && self.running.len() >= self.max_procs {
+            self.wait_one()?;
+        }
+        if self.verbose {
+            let line: Vec<_> = args.iter().map(|arg| arg.to_string_lossy()).collect();
+            eprintln!("{}", line.join(" "));
+        }
+        let child = Command::new(&args[0])
+            .args(&args[1..])
+            .stdin(Stdio::null())
+            .spawn();
+        match child {
+            Ok(child) => {
+                self.running.push((child, args[0].clone()));
+                Ok(())
+            }
+            Err(e) => {
+                warn!("{}: {}", args[0].to_string_lossy(), e);
+                if e.kind() == io::ErrorKind::NotFound {
+                    Err(NOT_FOUND)
+                } else {
+                    Err(CANNOT_RUN)
+                }
+            }
+        }
+    }
+
+    /// Waits for any running command to end and takes note of how it did.
+    fn wait_one(&mut self) -> Result<(), i32> {
+        let mut status = 0;
+        let pid = loop {
+            let pid = unsafe { libc::waitpid(-1, &mut status, 0) };
+            if pid != -1 || io::Error::last_os_error().kind() != io::ErrorKind::Interrupted {
+                break pid;
+            }
+        };
+        let index = match self
+            .running
+            .iter()
+            .position(|c| c.0.id() as libc::pid_t == pid)
+        {
+            Some(index) => index,
+            None => die!(
+                "error waiting for child process: {}",
+                io::Error::last_os_error()
+            ),
+        };
+        let (_, name) = self.running.swap_remove(index);
+        let name = name.to_string_lossy();
+        if libc::WIFSIGNALED(status) {
+            warn!("{}: terminated by signal {}", name, libc::WTERMSIG(status));
+            return Err(COMMAND_KILLED);
+        }
+        match libc::WEXITSTATUS(status) {
+            0 => {}
+            255 => {
+                warn!("{}: exited with status 255; aborting", name);
+                return Err(COMMAND_EXITED_255);
+            }
+            _ => self.status = COMMAND_FAILED,
+        }
+        Ok(())
+    }
+
+    /// Waits for all running commands and returns the exit status for xargs.
+    fn finish(mut self) -> i32 {
+        while !self.running.is_empty() {
+            if let Err(status) = self.wait_one() {
+                return status;
+            }
+        }
+        self.status
+    }
+}
+
+/// Runs the command of @p config with the items read by @p items and returns the exit status
+/// for xargs.
+fn xargs<R: BufRead>(config: &XargsConfig, items: &mut ItemReader<R>) -> i32 {
+    let mut executor = Executor {
+        max_procs: config.max_procs,
+        verbose: config.verbose,
+        running: Vec::new(),
+        status: 0,
+    };
+    let base_size: usize = config.command.iter().map(|arg| arg.len() + 1).sum();
+    let mut args = config.command.clone();
+    let (mut size, mut lines) = (base_size, 0);
+    let mut ran = false;
+    let result = loop {
+        let item = match items.next_item() {
+            Ok(Some(item)) => item,
+            Ok(None) => break Ok(()),
+            Err(e) => break Err(e),
+        };
+        if let Some(ref from) = config.replace {
+            let mut command = config.command.clone();
+            for arg in &mut command[1..] {
+                *arg = replace(arg, from, &item.text);
+            }
+            if let Err(status) = executor.spawn(&command) {
+                return status;
+            }
+            ran = true;
+            continue;
+        }
+
+        let item_size = item.text.len() + 1;
+        if base_size + item_size > MAX_CHARS {
+            die!("argument line too long");
+        }
+        if size + item_size > MAX_CHARS {
+            if let Err(status) = executor.spawn(&args) {
+                return status;
+            }
+            ran = true;
+            args.truncate(config.command.len());
+            size = base_size;
+            lines = 0;
+        }
+        args.push(OsString::from_vec(item.text));
+        size += item_size;
+        if item.ends_line {
+            lines += 1;
+        }
+        let full = config.max_args == Some(args.len() - config.command.len())
+            || config.max_lines == Some(lines);
+        if full {
+            if let Err(status) = executor.spawn(&args) {
+                return status;
+            }
+            ran = true;
+            args.truncate(config.command.len());
+            size = base_size;
+            lines = 0;
+        }
+    };
+
+    let pending = args.len() > config.command.len();
+    let unused = !ran && !config.no_run_if_empty && config.replace.is_none();
+    // Like GNU xargs, run what was read before an error in the input.
+    if pending || (unused && result.is_ok()) {
+        if let Err(status) = executor.spawn(&args) {
+            return status;
+        }
+    }
+    let status = executor.finish();
+    if let Err(e) = result {
+        warn!("{}", e);
+        return 1;
+    }
+    status
+}
+
+fn show_help(opts: &ArgParser) {
+    let brief = format!(
+        concat!(
+            "Clone of the standard GNU xargs.\n",
+            "Usage: {}: [OPTION]... [COMMAND [INITIAL-ARG]...]\n",
+            "Run COMMAND with the INITIAL-ARGs and more arguments read from standard input.\n",
+            "Input items are separated by blanks or new lines, which quotes and backslashes\n",
+            "escape. The default COMMAND is echo.\n",
+            "Exit status is 123 if any invocation of COMMAND failed, 124 if one exited with\n",
+            "255, 125 if one was killed by a signal, 126 if COMMAND cannot be run, 127 if it\n",
+            "cannot be found and 1 for other errors."
+        ),
+        env::args().next().unwrap()
+    );
+    print!("{}", opts.usage(&brief));
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let mut opts = ArgParser::new();
+    opts.stop_at_first_free();
+    opts.flag(
+        "0",
+        "null",
+        "items are terminated by a NUL, not by blanks, and quotes and backslashes are not \
+         special",
+    );
+    opts.option(
+        "d",
+        "delimiter",
+        "items are terminated by the character DELIM, and quotes and backslashes are not \
+         special",
+        "DELIM",
+    );
+    opts.option(
+        "I",
+        "",
+        "replace R in the INITIAL-ARGs with each input line in turn; implies -L 1",
+        "R",
+    );
+    opts.option(
+        "L",
+        "max-lines",
+        "use at most N non-blank input lines per command",
+        "N",
+    );
+    opts.option("n", "max-args", "use at most N arguments per command", "N");
+    opts.option(
+        "P",
+        "max-procs",
+        "run at most N commands at a time; 0 for as many as possible",
+        "N",
+    );
+    opts.flag(
+        "r",
+        "no-run-if-empty",
+        "if there are no arguments, do not run COMMAND",
+    );
+    opts.flag("t", "verbose", "print commands before running them");
+    opts.flag("h", "help", "display this help and exit");
+    opts.flag("", "version", "output version information and exit");
+    let options = match opts.parse(&args[1..]) {
+        Ok(m) => m,
+        Err(f) => die!(code = 2, "{}", f.to_string()),
+    };
+    if options.opt_present("h") {
+        return show_help(&opts);
+    }
+    if options.opt_present("version") {
+        return utils::version::print_version("xargs");
+    }
+
+    let number = |name: &str, minimum: usize| -> Option<usize> {
+        options.opt_str(name).map(|n| match n.parse::<usize>() {
+            Ok(n) if n >= minimum => n,
+            _ => die!(code = 2, "invalid number '{}' for -{} option", n, name),
+        })
+    };
+    let (mut max_args, mut max_lines) = (number("n", 1), number("L", 1));
+    if max_args.is_some() && max_lines.is_some() {
+        // Like GNU xargs, the last of the two wins.
+        if options.opt_positions("n").last() > options.opt_positions("L").last() {
+            warn!("options --max-lines and --max-args are mutually exclusive, ignoring the former");
+            max_lines = None;
+        } else {
+            warn!("options --max-args and --max-lines are mutually exclusive, ignoring the former");
+            max_args = None;
+        }
+    }
+    let replace = options.opt_str("I").map(String::into_bytes);
+    let separator = if let Some(delimiter) = options.opt_str("d") {
+        Separator::Delimiter(parse_delimiter(&delimiter).unwrap_or_else(|| {
+            die!(
+                code = 2,
+                "invalid input delimiter specification '{}'",
+                delimiter
+            )
+        }))
+    } else if options.opt_present("0") {
+        Separator::Delimiter(b'\0')
+    } else if replace.is_some() {
+        Separator::Lines
+    } else {
+        Separator::Blanks
+    };
+    let mut command: Vec<OsString> = options.free.iter().map(OsString::from).collect();
+    if command.is_empty() {
+        command.push("echo".into());
+    }
+    let config = XargsConfig {
+        command,
+        separator,
+        max_args,
+        max_lines,
+        replace,
+        max_procs: number("P", 0).unwrap_or(1),
+        no_run_if_empty: options.opt_present("r"),
+        verbose: options.opt_present("t"),
+    };
+
+    let stdin = io::stdin();
+    let mut items = ItemReader {
+        reader: BufReader::new(stdin.lock()),
+        separator: config.separator,
+    };
+    process::exit(xargs(&config, &mut items));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn items(input: &str, separator: Separator) -> Vec<(String, bool)> {
+        let mut reader = ItemReader {
+            reader: input.as_bytes(),
+            separator,
+        };
+        let mut items = Vec::new();
+        while let Some(item) = reader.next_item().unwrap() {
+            items.push((String::from_utf8(item.text).unwrap(), item.ends_line));
+        }
+        items
+    }
+
+    fn texts(input: &str, separator: Separator) -> Vec<String> {
+        items(input, separator).into_iter().map(|i| i.0).collect()
+    }
+
+    #[test]
+    fn blank_separated_items() {
+        assert_eq!(
+            texts("a b\tc\n\n  d  \n", Separator::Blanks),
+            ["a", "b", "c", "d"]
+        );
+        assert_eq!(
+            texts("'a b' \"c'd\" e\\ f '' g\\\\", Separator::Blanks),
+            ["a b", "c'd", "e f", "", "g\\"]
+        );
+        assert_eq!(texts("x'y'z", Separator::Blanks), ["xyz"]);
+        assert!(texts("", Separator::Blanks).is_empty());
+    }
+
+    #[test]
+    fn unmatched_quotes() {
+        for input in &["'a", "\"a\nb\"", "a 'b"] {
+            let mut reader = ItemReader {
+                reader: input.as_bytes(),
+                separator: Separator::Blanks,
+            };
+            let error = loop {
+                match reader.next_item() {
+                    Ok(Some(_)) => {}
+                    Ok(None) => panic!("no error for {:?}", input),
+                    Err(e) => break e,
+                }
+            };
+            assert!(error.to_string().contains("unmatched"));
+        }
+    }
+
+    #[test]
+    fn logical_lines() {
+        let input = "a b\nc \nd\n\ne";
+        assert_eq!(
+            items(input, Separator::Blanks),
+            [
+                ("a".to_owned(), false),
+                ("b".to_owned(), true),
+                ("c".to_owned(), false),
+                ("d".to_owned(), true),
+                ("e".to_owned(), true),
+            ]
+        );
+        assert_eq!(
+            texts("  a b \n'c  d'\n", Separator::Lines),
+            ["a b ", "c  d"]
+        );
+    }
+
+    #[test]
+    fn delimited_items() {
+        assert_eq!(
+            texts("a b\0'c\n\0\0", Separator::Delimiter(b'\0')),
+            ["a b", "'c\n", ""]
+        );
+        assert_eq!(texts("a,b", Separator::Delimiter(b',')), ["a", "b"]);
+    }
+
+    #[test]
+    fn delimiters() {
+        assert_eq!(parse_delimiter(","), Some(b','));
+        assert_eq!(parse_delimiter("\\n"), Some(b'\n'));
+        assert_eq!(parse_delimiter("\\0"), Some(0));
+        assert_eq!(parse_delimiter("\\x41"), Some(b'A'));
+        assert_eq!(parse_delimiter("ab"), None);
+        assert_eq!(parse_delimiter("\\xZZ"), None);
+        assert_eq!(parse_delimiter(""), None);
+    }
+
+    #[test]
+    fn replacing() {
+        assert_eq!(replace(OsStr::new("a{}b{}"), b"{}", b"x"), "axbx");
+        assert_eq!(replace(OsStr::new("{"), b"{}", b"x"), "{");
+    }
+}
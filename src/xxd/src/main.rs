@@ -0,0 +1,449 @@
+use std::env;
+use std::io::{self, BufRead, Read, Write};
+
+#[macro_use(die)]
+extern crate utils;
+
+use utils::args::ArgParser;
+
+/// The layout of the dump.
+struct Config {
+    columns: usize,
+    group: usize,
+    uppercase: bool,
+}
+
+impl Config {
+    fn hex(&self, byte: u8) -> String {
+        if self.uppercase {
+            format!("{:02X}", byte)
+        } else {
+            format!("{:02x}", byte)
+        }
+    }
+}
+
+/// Reads from @p reader until @p buf is full or the input ends, and returns the number of
+/// bytes read.
+fn fill(reader: &mut dyn Read, buf: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(filled)
+}
+
+/// Writes the usual hex dump of @p reader: the offset, starting from @p offset, the bytes in
+/// hex and the same bytes as text, with non-printable ones shown as dots.
+fn dump(
+    reader: &mut dyn Read,
+    mut offset: u64,
+    config: &Config,
+    writer: &mut dyn Write,
+) -> io::Result<()> {
+    let mut buf = vec![0; config.columns];
+    loop {
+        let n = fill(reader, &mut buf)?;
+        if n == 0 {
+            return Ok(());
+        }
+        let mut line = if config.uppercase {
+            format!("{:08X}: ", offset)
+        } else {
+            format!("{:08x}: ", offset)
+        };
+        for (i, &byte) in buf.iter().enumerate() {
+            if i < n {
+                line += &config.hex(byte);
+            } else {
+                line += "  ";
+            }
+            if (i + 1) % config.group == 0 || i + 1 == config.columns {
+                line.push(' ');
+            }
+        }
+        line.push(' ');
+        for &byte in &buf[..n] {
+            line.push(if (0x20..0x7f).contains(&byte) {
+                byte as char
+            } else {
+                '.'
+            });
+        }
+        writeln!(writer, "{}", line)?;
+        offset += n as u64;
+    }
+}
+
+/// Writes the bytes of @p reader as a C array, named after the file @p name when there is
+/// one. Without a name only the array's elements are written.
+fn include(
+    reader: &mut dyn Read,
+    name: Option<&str>,
+    config: &Config,
+    writer: &mut dyn Write,
+) -> io::Result<()> {
+    let variable = name.map(variable_name);
+    if let Some(ref variable) = variable {
+        writeln!(writer, "unsigned char {}[] = {{", variable)?;
+    }
+    let prefix = if config.uppercase { "0X" } else { "0x" };
+    let mut buf = vec![0; config.columns];
+    let mut length = 0u64;
+    let mut n = fill(reader, &mut buf)?;
+    while n > 0 {
+        let line: Vec<String> = buf[..n]
+            .iter()
+            .map(|&byte| format!("{}{}", prefix, config.hex(byte)))
+            .collect();
+        length += n as u64;
+        write!(writer, "  {}", line.join(", "))?;
+        n = fill(reader, &mut buf)?;
+        writeln!(writer, "{}", if n > 0 { "," } else { "" })?;
+    }
+    if let Some(ref variable) = variable {
+        writeln!(writer, "}};")?;
+        writeln!(writer, "unsigned int {}_len = {};", variable, length)?;
+    }
+    Ok(())
+}
+
+/// The C identifier xxd -i derives from the file name @p name.
+fn variable_name(name: &str) -> String {
+    let mut variable: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    if variable.starts_with(|c: char| c.is_ascii_digit()) {
+        variable.insert_str(0, "__");
+    }
+    variable
+}
+
+/// An error in line @p line of the dump given to `reverse`.
+fn invalid_dump(line: usize, message: String) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("line {}: {}", line, message),
+    )
+}
+
+/// Parses the hex column of a dump line, space-separated groups of two-digit bytes.
+fn parse_hex_column(hex: &str) -> Result<Vec<u8>, String> {
+    let mut bytes = Vec::new();
+    for group in hex.split(' ').filter(|group| !group.is_empty()) {
+        if let Some(c) = group.chars().find(|c| !c.is_ascii_hexdigit()) {
+            return Err(format!("invalid hex digit '{}'", c));
+        }
+        if group.len() % 2 != 0 {
+            return Err(format!("odd number of hex digits in '{}'", group));
+        }
+        for pair in group.as_bytes().chunks(2) {
+            let pair = std::str::from_utf8(pair).unwrap();
+            bytes.push(u8::from_str_radix(pair, 16).unwrap());
+        }
+    }
+    Ok(bytes)
+}
+
+/// Turns a hex dump in @p reader back into the bytes it shows. Lines may hold any number of
+/// bytes and the offset column is optional, but where it is present it must follow on from
+/// the bytes before it; only the first offset may leave a gap, which is filled with zeros.
+/// Malformed lines are InvalidData errors naming the line.
+fn reverse(reader: &mut dyn BufRead, writer: &mut dyn Write) -> io::Result<()> {
+    let mut written = 0u64;
+    let mut started = false;
+    for (number, line) in reader.split(b'\n').enumerate() {
+        let number = number + 1;
+        let line = line?;
+        let line = String::from_utf8_lossy(&line);
+        // An offset is whatever comes before the first colon, unless that holds the spaces
+        // of the hex or text columns.
+        let (offset, hex) = match line.find(':') {
+            Some(colon) if !line[..colon].contains(' ') => {
+                let offset = u64::from_str_radix(&line[..colon], 16).map_err(|_| {
+                    invalid_dump(number, format!("invalid offset '{}'", &line[..colon]))
+                })?;
+                (Some(offset), &line[colon + 1..])
+            }
+            _ => (None, &line[..]),
+        };
+        // The text column is set off from the hex bytes by at least two spaces.
+        let hex = &hex[..hex.find("  ").unwrap_or(hex.len())];
+        let bytes = parse_hex_column(hex).map_err(|e| invalid_dump(number, e))?;
+        if bytes.is_empty() {
+            continue;
+        }
+        match offset {
+            Some(offset) if !started && offset > written => {
+                io::copy(&mut io::repeat(0).take(offset - written), writer)?;
+                written = offset;
+            }
+            Some(offset) if offset != written => {
+                return Err(invalid_dump(
+                    number,
+                    format!(
+                        "offset {:x} does not follow the {} bytes before it",
+                        offset, written
+                    ),
+                ));
+            }
+            _ => {}
+        }
+        started = true;
+        writer.write_all(&bytes)?;
+        written += bytes.len() as u64;
+    }
+    Ok(())
+}
+
+/// Parses a decimal number, or a hex one with a 0x prefix.
+fn parse_number(number: &str, what: &str) -> u64 {
+    let parsed = if number.starts_with("0x") || number.starts_with("0X") {
+        u64::from_str_radix(&number[2..], 16)
+    } else {
+        number.parse()
+    };
+    parsed.unwrap_or_else(|_| die!(code = 2, "invalid {}: '{}'", what, number))
+}
+
+fn show_help(opts: &ArgParser) {
+    let brief = format!(
+        concat!(
+            "Clone of xxd.\n",
+            "Usage: {}: [OPTION]... [INFILE [OUTFILE]]\n",
+            "Make a hex dump of INFILE or the standard input, or do the reverse."
+        ),
+        env::args().next().unwrap()
+    );
+    print!("{}", opts.usage(&brief));
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let mut opts = ArgParser::new();
+    opts.option(
+        "c",
+        "cols",
+        "format COLS bytes per output line (default 16, 12 with -i)",
+        "COLS",
+    );
+    opts.option(
+        "g",
+        "groupsize",
+        "separate the output of every BYTES bytes by a space (default 2)",
+        "BYTES",
+    );
+    opts.flag("i", "include", "output in C include file style");
+    opts.option("l", "len", "stop after LEN octets", "LEN");
+    opts.flag("r", "reverse", "convert a hex dump into binary");
+    opts.option("s", "seek", "start at SEEK bytes into the input", "SEEK");
+    opts.flag("u", "uppercase", "use upper case hex letters");
+    opts.flag("h", "help", "display this help and exit");
+    opts.flag("v", "version", "output version information and exit");
+    let options = match opts.parse(&args[1..]) {
+        Ok(m) => m,
+        Err(f) => die!(code = 2, "{}", f.to_string()),
+    };
+    if options.opt_present("h") {
+        return show_help(&opts);
+    }
+    if options.opt_present("v") {
+        return utils::version::print_version("xxd");
+    }
+    if options.free.len() > 2 {
+        die!(code = 2, "extra operand '{}'", options.free[2]);
+    }
+
+    let include_style = options.opt_present("i");
+    let columns = match options.opt_str("c") {
+        Some(columns) => match parse_number(&columns, "number of columns") {
+            n @ 1..=256 => n as usize,
+            _ => die!(
+                code = 2,
+                "invalid number of columns (max. 256): '{}'",
+                columns
+            ),
+        },
+        None if include_style => 12,
+        None => 16,
+    };
+    let group = match options.opt_str("g").map(|g| parse_number(&g, "group size")) {
+        // A group size of zero means no grouping at all.
+        Some(0) => columns,
+        Some(group) => group.min(columns as u64) as usize,
+        None => 2,
+    };
+    let config = Config {
+        columns,
+        group,
+        uppercase: options.opt_present("u"),
+    };
+
+    let input = options.free.first().map_or("-", |name| name.as_str());
+    let mut reader =
+        utils::io::FileOrStdin::open(input).unwrap_or_else(|e| die!("{}: {}", input, e));
+    let mut writer: Box<dyn Write> = match options.free.get(1) {
+        Some(output) => Box::new(io::BufWriter::new(
+            std::fs::File::create(output).unwrap_or_else(|e| die!("{}: {}", output, e)),
+        )),
+        None => Box::new(io::BufWriter::new(io::stdout())),
+    };
+
+    let result = if options.opt_present("r") {
+        reverse(&mut reader, &mut writer)
+    } else {
+        let seek = options
+            .opt_str("s")
+            .map_or(0, |seek| parse_number(&seek, "seek offset"));
+        io::copy(&mut (&mut reader).take(seek), &mut io::sink())
+            .unwrap_or_else(|e| die!("{}: {}", input, e));
+        let len = options
+            .opt_str("l")
+            .map_or(u64::MAX, |len| parse_number(&len, "length"));
+        let mut limited = reader.take(len);
+        if include_style {
+            let name = if input == "-" { None } else { Some(input) };
+            include(&mut limited, name, &config, &mut writer)
+        } else {
+            dump(&mut limited, seek, &config, &mut writer)
+        }
+    };
+    result
+        .and_then(|_| writer.flush())
+        .unwrap_or_else(|e| die!("{}", e));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(columns: usize, group: usize) -> Config {
+        Config {
+            columns,
+            group,
+            uppercase: false,
+        }
+    }
+
+    fn run_dump(input: &[u8], offset: u64, config: &Config) -> String {
+        let mut output = Vec::new();
+        dump(&mut &input[..], offset, config, &mut output).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn dump_layout() {
+        let input = b"Hello, world!\nThis is xxd\x00\x01\xff";
+        assert_eq!(
+            run_dump(input, 0, &config(16, 2)),
+            concat!(
+                "00000000: 4865 6c6c 6f2c 2077 6f72 6c64 210a 5468  Hello, world!.Th\n",
+                "00000010: 6973 2069 7320 7878 6400 01ff            is is xxd...\n",
+            )
+        );
+        assert_eq!(
+            run_dump(b"abc", 0x10, &config(4, 3)),
+            "00000010: 616263     abc\n"
+        );
+        assert_eq!(
+            run_dump(b"Hello, w", 0, &config(8, 8)),
+            "00000000: 48656c6c6f2c2077  Hello, w\n"
+        );
+        let mut upper = config(16, 4);
+        upper.uppercase = true;
+        assert_eq!(
+            run_dump(b"\xab\xcd", 0xff, &upper),
+            "000000FF: ABCD                                 ..\n"
+        );
+        assert_eq!(run_dump(b"", 0, &config(16, 2)), "");
+    }
+
+    #[test]
+    fn c_include() {
+        let mut output = Vec::new();
+        include(
+            &mut &b"ab\xff"[..],
+            Some("1weird-name.bin"),
+            &config(2, 2),
+            &mut output,
+        )
+        .unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            concat!(
+                "unsigned char __1weird_name_bin[] = {\n",
+                "  0x61, 0x62,\n",
+                "  0xff\n",
+                "};\n",
+                "unsigned int __1weird_name_bin_len = 3;\n",
+            )
+        );
+        let mut output = Vec::new();
+        include(&mut &b"ab"[..], None, &config(12, 2), &mut output).unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), "  0x61, 0x62\n");
+    }
+
+    #[test]
+    fn reverse_round_trip() {
+        let input: Vec<u8> = (0..=255u8).cycle().skip(7).take(1000).collect();
+        for &(columns, group) in &[(16, 2), (7, 3), (12, 12), (1, 1)] {
+            let dumped = run_dump(&input, 0, &config(columns, group));
+            let mut output = Vec::new();
+            reverse(&mut dumped.as_bytes(), &mut output).unwrap();
+            assert_eq!(output, input);
+        }
+    }
+
+    #[test]
+    fn reverse_fills_gaps() {
+        let mut output = Vec::new();
+        reverse(&mut &b"00000004: 4142 43  ABC\n"[..], &mut output).unwrap();
+        assert_eq!(output, b"\0\0\0\0ABC".to_vec());
+    }
+
+    fn run_reverse(dump: &str) -> io::Result<Vec<u8>> {
+        let mut output = Vec::new();
+        reverse(&mut dump.as_bytes(), &mut output).map(|_| output)
+    }
+
+    #[test]
+    fn reverse_partial_lines() {
+        // Lines of any length, with or without the text and offset columns.
+        let dump = "00000000: 6162 6364 65  abcde\n00000005: 6667\n6869 6a  hij\n\n";
+        assert_eq!(run_reverse(dump).unwrap(), b"abcdefghij".to_vec());
+        assert_eq!(
+            run_reverse("00000000: 61  a:b  c\n").unwrap(),
+            b"a".to_vec()
+        );
+    }
+
+    #[test]
+    fn reverse_rejects_malformed_lines() {
+        for &(dump, error) in &[
+            (
+                "00000000: 616 2  a.\n",
+                "line 1: odd number of hex digits in '616'",
+            ),
+            (
+                "00000000: 6162\n00000000: 6364\n",
+                "line 2: offset 0 does not follow the 2 bytes before it",
+            ),
+            (
+                "00000000: 6162\n00000004: 6364\n",
+                "line 2: offset 4 does not follow the 2 bytes before it",
+            ),
+            ("00000000: 61g2  a.\n", "line 1: invalid hex digit 'g'"),
+            ("0000zz00: 6162\n", "line 1: invalid offset '0000zz00'"),
+        ] {
+            let e = run_reverse(dump).unwrap_err();
+            assert_eq!(e.kind(), io::ErrorKind::InvalidData);
+            assert_eq!(e.to_string(), error);
+        }
+    }
+}
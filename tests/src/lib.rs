@@ -0,0 +1,83 @@
+//! Helpers for the integration tests, which run the tools' binaries as a user would.
+
+use std::collections::HashSet;
+use std::env;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+use std::sync::Mutex;
+
+static BUILT: Mutex<Option<HashSet<String>>> = Mutex::new(None);
+
+/// The path of the binary @p cmd in the workspace's build directory. The binary is (re)built
+/// the first time it is asked for, so the tests never run a stale build.
+pub fn binary(cmd: &str) -> PathBuf {
+    let mut built = BUILT.lock().unwrap_or_else(|e| e.into_inner());
+    let built = built.get_or_insert_with(HashSet::new);
+    if !built.contains(cmd) {
+        let cargo = env::var("CARGO").unwrap_or_else(|_| "cargo".to_owned());
+        let status = Command::new(cargo)
+            .args(["build", "--quiet", "--bin", cmd])
+            .current_dir(workspace_root())
+            .status()
+            .expect("failed to run cargo build");
+        assert!(status.success(), "failed to build {}", cmd);
+        built.insert(cmd.to_owned());
+    }
+    workspace_root().join("target/debug").join(cmd)
+}
+
+fn workspace_root() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("..")
+}
+
+/// Runs @p cmd with @p args, feeding it @p stdin, and collects its output.
+pub fn run(cmd: &str, args: &[&str], stdin: &[u8]) -> Output {
+    let mut child = Command::new(binary(cmd))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap_or_else(|e| panic!("failed to run {}: {}", cmd, e));
+    // A tool may exit without reading its input, so a broken pipe is not an error here.
+    let _ = child.stdin.take().unwrap().write_all(stdin);
+    child.wait_with_output().unwrap()
+}
+
+pub fn stdout(output: &Output) -> String {
+    String::from_utf8(output.stdout.clone()).unwrap()
+}
+
+pub fn stderr(output: &Output) -> String {
+    String::from_utf8(output.stderr.clone()).unwrap()
+}
+
+/// A scratch directory, removed again when dropped.
+pub struct TempDir(PathBuf);
+
+impl TempDir {
+    pub fn new(name: &str) -> TempDir {
+        let path = env::temp_dir().join(format!("integration-{}-{}", name, std::process::id()));
+        fs::create_dir_all(&path).unwrap();
+        TempDir(path)
+    }
+
+    /// Creates the file @p name with @p contents and returns its path as a string.
+    pub fn file(&self, name: &str, contents: &[u8]) -> String {
+        let path = self.0.join(name);
+        fs::write(&path, contents).unwrap();
+        path.to_str().unwrap().to_owned()
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
@@ -0,0 +1,57 @@
+extern crate integration;
+
+use integration::{run, stderr, stdout, TempDir};
+
+#[test]
+fn encode_and_wrap() {
+    let output = run("base64", &[], b"foobar");
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(stdout(&output), "Zm9vYmFy\n");
+    let output = run("base32", &["-w", "5"], b"foobar");
+    assert_eq!(stdout(&output), "MZXW6\nYTBOI\n=====\n=\n");
+    assert_eq!(stdout(&run("base32", &["--wrap=0"], b"f")), "MY======");
+    assert_eq!(stdout(&run("base64", &[], b"")), "");
+    // Lines are 76 characters long by default.
+    let output = run("base64", &[], &[0; 60]);
+    let lines: Vec<_> = stdout(&output).lines().map(str::len).collect();
+    assert_eq!(lines, [76, 4]);
+}
+
+#[test]
+fn decode_files_and_stdin() {
+    let dir = TempDir::new("base_encoding");
+    let file = dir.file("encoded", b"MZXW6\nYTBOI\n=====\n=\n");
+    let output = run("base32", &["-d", &file], b"");
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(stdout(&output), "foobar");
+    let output = run("base64", &["--decode", "-"], b" Zm9v\nYg==\n");
+    assert_eq!(stdout(&output), "foob");
+    let output = run("base64", &["-d", "-i"], b"Zm9v!YmFy");
+    assert_eq!(stdout(&output), "foobar");
+}
+
+#[test]
+fn invalid_input_and_arguments() {
+    // What was decoded before the error is still written.
+    let output = run("base64", &["-d"], b"Zm9v!");
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(stdout(&output), "foo");
+    assert!(stderr(&output).contains("invalid input"));
+    let output = run("base32", &["-d"], b"MZXW6");
+    assert_eq!(output.status.code(), Some(1));
+    for args in &[&["-w", "x"][..], &["a", "b"][..]] {
+        let output = run("base64", args, b"");
+        assert_eq!(output.status.code(), Some(2), "{:?}", args);
+    }
+    let output = run("base32", &["/nonexistent"], b"");
+    assert_eq!(output.status.code(), Some(1));
+}
+
+#[test]
+fn help_and_version() {
+    let help = run("base32", &["--help"], b"");
+    assert!(help.status.success());
+    assert!(stdout(&help).contains("--ignore-garbage"));
+    let version = run("base64", &["--version"], b"");
+    assert_eq!(stdout(&version), "base64 (coreutils-rs) 0.1.0\n");
+}
@@ -0,0 +1,450 @@
+extern crate integration;
+extern crate libc;
+
+use integration::{binary, run, stderr, stdout, TempDir};
+use std::ffi::CString;
+use std::fs;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::os::unix::fs::symlink;
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+#[test]
+fn copies_stdin() {
+    let output = run("cat", &[], b"hello\nworld\n");
+    assert!(output.status.success());
+    assert_eq!(output.stdout, b"hello\nworld\n");
+    assert!(output.stderr.is_empty());
+}
+
+#[test]
+fn copies_binary_data_unchanged() {
+    let data: Vec<u8> = (0..=255u8).cycle().take(100_000).collect();
+    let output = run("cat", &["-"], &data);
+    assert!(output.status.success());
+    assert_eq!(output.stdout, data);
+}
+
+#[test]
+fn concatenates_files() {
+    let dir = TempDir::new("cat-concat");
+    let first = dir.file("first", b"one\n");
+    let second = dir.file("second", b"two\n");
+    let output = run("cat", &[&first, &second], b"");
+    assert_eq!(stdout(&output), "one\ntwo\n");
+}
+
+#[test]
+fn dash_reads_stdin_between_files() {
+    let dir = TempDir::new("cat-dash");
+    let first = dir.file("first", b"one\n");
+    let output = run("cat", &[&first, "-", &first], b"stdin\n");
+    assert_eq!(stdout(&output), "one\nstdin\none\n");
+}
+
+#[test]
+fn number_lines() {
+    let output = run("cat", &["-n"], b"a\nb\n");
+    assert!(output.status.success());
+    assert_eq!(stdout(&output), "     1\ta\n     2\tb\n");
+}
+
+#[test]
+fn number_lines_matches_gnu() {
+    let dir = TempDir::new("cat-number-gnu");
+    let file = dir.file("file", b"first\n\nthird\n");
+    let output = run("cat", &["-n", &file], b"");
+    assert_eq!(
+        output.stdout,
+        b"     1\tfirst\n     2\t\n     3\tthird\n".to_vec()
+    );
+}
+
+#[test]
+fn numbering_continues_across_files() {
+    let dir = TempDir::new("cat-number");
+    let file = dir.file("file", b"x\n");
+    let output = run("cat", &["--number", &file, &file], b"");
+    assert_eq!(stdout(&output), "     1\tx\n     2\tx\n");
+}
+
+#[test]
+fn numbering_is_global_over_three_files() {
+    let dir = TempDir::new("cat-number-three");
+    let first = dir.file("first", b"a\nb\n");
+    let second = dir.file("second", b"\nc\n");
+    let third = dir.file("third", b"d\n");
+    let output = run("cat", &["-n", &first, "-", &second, &third], b"stdin\n");
+    assert_eq!(
+        stdout(&output),
+        "     1\ta\n     2\tb\n     3\tstdin\n     4\t\n     5\tc\n     6\td\n"
+    );
+}
+
+#[test]
+fn show_ends() {
+    let output = run("cat", &["-E"], b"a\n\nb\n");
+    assert_eq!(stdout(&output), "a$\n$\nb$\n");
+}
+
+#[test]
+fn decorates_last_line_without_newline() {
+    // Like GNU cat, the unterminated line is numbered but gets no end marker.
+    let output = run("cat", &["-nE"], b"a\nb\nc");
+    assert_eq!(stdout(&output), "     1\ta$\n     2\tb$\n     3\tc");
+    let output = run("cat", &["-E"], b"a\nb\nc");
+    assert_eq!(stdout(&output), "a$\nb$\nc");
+}
+
+#[test]
+fn numbering_continues_an_unterminated_line_across_files() {
+    let dir = TempDir::new("cat-partial");
+    let partial = dir.file("partial", b"par");
+    let rest = dir.file("rest", b"tial\nnext\n");
+    let output = run("cat", &["-n", &partial, &rest], b"");
+    assert_eq!(stdout(&output), "     1\tpartial\n     2\tnext\n");
+}
+
+#[test]
+fn squeeze_blank() {
+    let output = run("cat", &["-s"], b"a\n\n\n\nb\n\n");
+    assert_eq!(stdout(&output), "a\n\nb\n\n");
+}
+
+#[test]
+fn combined_flags() {
+    let output = run("cat", &["-sE"], b"a\n\n\nb\n");
+    assert_eq!(stdout(&output), "a$\n$\nb$\n");
+    let separate = run("cat", &["-s", "-E"], b"a\n\n\nb\n");
+    assert_eq!(separate.stdout, output.stdout);
+}
+
+#[test]
+fn show_all_and_its_shorthands() {
+    let dir = TempDir::new("cat-show-all");
+    let file = dir.file("mixed", b"a\tb\x1b\x7f\n\xc3\xa9\x80\r\n\n");
+    let output = run("cat", &["-A", &file], b"");
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(output.stdout, b"a^Ib^[^?$\nM-CM-)M-^@^M$\n$\n".to_vec());
+    for args in &[
+        &["-v", "-E", "-T"][..],
+        &["--show-all"][..],
+        &["-vET"][..],
+        &["-e", "-t"][..],
+    ] {
+        let mut args = args.to_vec();
+        args.push(&file);
+        let same = run("cat", &args, b"");
+        assert_eq!(same.stdout, output.stdout, "{:?}", args);
+    }
+    let output = run("cat", &["-e", &file], b"");
+    assert_eq!(output.stdout, b"a\tb^[^?$\nM-CM-)M-^@^M$\n$\n".to_vec());
+    let output = run("cat", &["-t", &file], b"");
+    assert_eq!(output.stdout, b"a^Ib^[^?\nM-CM-)M-^@^M\n\n".to_vec());
+}
+
+#[test]
+fn show_nonprinting_splits_no_state_across_reads() {
+    // Byte 65536 starts a two-byte character, which the first read cuts in half.
+    let dir = TempDir::new("cat-utf8-split");
+    let mut data = vec![b'a'; 65535];
+    data.extend_from_slice(b"\xc3\xa9");
+    let file = dir.file("split", &data);
+    let output = run("cat", &["-nv", &file], b"");
+    assert!(output.status.success(), "{}", stderr(&output));
+    let mut expected = b"     1\t".to_vec();
+    expected.extend_from_slice(&data[..65535]);
+    expected.extend_from_slice(b"M-CM-)");
+    assert_eq!(output.stdout, expected);
+}
+
+#[test]
+fn combined_short_flags() {
+    let output = run("cat", &["-En"], b"a\nb\n");
+    assert_eq!(stdout(&output), "     1\ta$\n     2\tb$\n");
+    let output = run("cat", &["-nEs"], b"a\n\n\nb\n");
+    assert_eq!(stdout(&output), "     1\ta$\n     2\t$\n     3\tb$\n");
+    let output = run("cat", &["-Ec"], b"a\n");
+    assert_eq!(output.status.code(), Some(2));
+    assert!(output.stdout.is_empty());
+    assert!(stderr(&output).contains("invalid option -- 'c'"));
+}
+
+#[test]
+fn null_delimited_records() {
+    let output = run("cat", &["-z", "-n"], b"a\0b\0");
+    assert_eq!(output.stdout, b"     1\ta\0     2\tb\0".to_vec());
+    let output = run("cat", &["--null", "-sE"], b"a\n\0\0\0b\0");
+    assert_eq!(output.stdout, b"a\n$\0$\0b$\0".to_vec());
+}
+
+#[test]
+fn missing_file() {
+    let output = run("cat", &["/nonexistent/file"], b"");
+    assert_eq!(output.status.code(), Some(1));
+    assert!(output.stdout.is_empty());
+    assert!(stderr(&output).contains("/nonexistent/file"));
+
+    // The files around it are still copied.
+    let dir = TempDir::new("cat-missing");
+    let a = dir.file("a", b"a\n");
+    let b = dir.file("b", b"b\n");
+    let output = run("cat", &[&a, "/nonexistent/file", &b], b"");
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(stdout(&output), "a\nb\n");
+}
+
+#[test]
+fn directory_is_an_error() {
+    let dir = TempDir::new("cat-dir");
+    let output = run("cat", &[dir.path().to_str().unwrap()], b"");
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr(&output).contains("is a directory"));
+}
+
+#[test]
+fn closed_output_is_quiet() {
+    let dir = TempDir::new("cat-closed-output");
+    let big = dir.file("big", &vec![b'x'; 1 << 22]);
+    for args in &[&[][..], &["-n"][..], &["--parallel=2"][..]] {
+        let mut child = Command::new(binary("cat"))
+            .args(*args)
+            .args([&big, &big])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap();
+        // Like `cat big | head -c1`: read a little, then close the pipe.
+        let mut byte = [0];
+        child.stdout.take().unwrap().read_exact(&mut byte).unwrap();
+        let output = child.wait_with_output().unwrap();
+        assert!(!output.status.success(), "{:?}", args);
+        assert_eq!(stderr(&output), "", "{:?}", args);
+    }
+}
+
+#[test]
+fn reads_from_fifo() {
+    let dir = TempDir::new("cat-fifo");
+    let fifo = dir.path().join("fifo");
+    let c_path = CString::new(fifo.to_str().unwrap()).unwrap();
+    assert_eq!(unsafe { libc::mkfifo(c_path.as_ptr(), 0o600) }, 0);
+    // Opening a FIFO blocks until the other end is opened too, so write from another thread.
+    let writer_path = fifo.clone();
+    let writer = thread::spawn(move || {
+        let mut fifo = fs::OpenOptions::new()
+            .write(true)
+            .open(writer_path)
+            .unwrap();
+        fifo.write_all(b"through a pipe\n").unwrap();
+    });
+    let output = run("cat", &[fifo.to_str().unwrap()], b"");
+    writer.join().unwrap();
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(stdout(&output), "through a pipe\n");
+}
+
+#[test]
+fn line_buffered_output_reaches_a_pipe_line_by_line() {
+    let dir = TempDir::new("cat-line-buffered");
+    let fifo = dir.path().join("fifo");
+    let c_path = CString::new(fifo.to_str().unwrap()).unwrap();
+    assert_eq!(unsafe { libc::mkfifo(c_path.as_ptr(), 0o600) }, 0);
+    for args in &[&["--line-buffered"][..], &["--line-buffered", "-E"][..]] {
+        let mut child = Command::new(binary("cat"))
+            .args(*args)
+            .arg(&fifo)
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+        // Each line read from cat's output is passed on, so that it can be waited for.
+        let output = BufReader::new(child.stdout.take().unwrap());
+        let (sender, lines) = mpsc::channel();
+        let reader = thread::spawn(move || {
+            for line in output.lines() {
+                sender.send(line.unwrap()).unwrap();
+            }
+        });
+        let mut input = fs::OpenOptions::new().write(true).open(&fifo).unwrap();
+        for line in &["first", "second", "third"] {
+            writeln!(input, "{}", line).unwrap();
+            // The input stays open, so only a flush gets the line through before the next.
+            let received = lines.recv_timeout(Duration::from_secs(10)).unwrap();
+            assert_eq!(received.trim_end_matches('$'), *line, "{:?}", args);
+        }
+        drop(input);
+        assert!(child.wait().unwrap().success());
+        reader.join().unwrap();
+    }
+}
+
+#[test]
+fn input_file_is_output_file() {
+    let dir = TempDir::new("cat-into-itself");
+    let file = dir.file("file", b"data\n");
+    let other = dir.file("other", b"other\n");
+    let output = fs::OpenOptions::new().append(true).open(&file).unwrap();
+    let result = Command::new(binary("cat"))
+        .args([&other, &file, &other])
+        .stdout(output)
+        .output()
+        .unwrap();
+    assert_eq!(result.status.code(), Some(1));
+    assert!(stderr(&result).contains(&format!("{}: input file is output file", file)));
+    // The other inputs are still copied.
+    assert_eq!(fs::read(&file).unwrap(), b"data\nother\nother\n".to_vec());
+
+    // The same through the standard input.
+    let input = fs::File::open(&other).unwrap();
+    let output = fs::OpenOptions::new().append(true).open(&other).unwrap();
+    let result = Command::new(binary("cat"))
+        .stdin(input)
+        .stdout(output)
+        .output()
+        .unwrap();
+    assert_eq!(result.status.code(), Some(1));
+    assert!(stderr(&result).contains("-: input file is output file"));
+    assert_eq!(fs::read(&other).unwrap(), b"other\n".to_vec());
+}
+
+#[test]
+fn reads_dev_stdin() {
+    let output = run("cat", &["/dev/stdin"], b"from stdin\n");
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(stdout(&output), "from stdin\n");
+}
+
+#[test]
+fn dev_null_is_empty() {
+    let output = run("cat", &["/dev/null"], b"");
+    assert_eq!(output.status.code(), Some(0));
+    assert!(output.stdout.is_empty());
+    assert!(output.stderr.is_empty());
+    let output = run("cat", &["-", "/dev/null", "-"], b"data\n");
+    assert_eq!(stdout(&output), "data\n");
+}
+
+#[test]
+fn symlinks_are_followed() {
+    let dir = TempDir::new("cat-symlink");
+    let file = dir.file("file", b"target\n");
+    fs::create_dir(dir.path().join("dir")).unwrap();
+    symlink(&file, dir.path().join("file-link")).unwrap();
+    symlink(dir.path().join("dir"), dir.path().join("dir-link")).unwrap();
+
+    let output = run(
+        "cat",
+        &[dir.path().join("file-link").to_str().unwrap()],
+        b"",
+    );
+    assert_eq!(stdout(&output), "target\n");
+    let output = run("cat", &[dir.path().join("dir-link").to_str().unwrap()], b"");
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr(&output).contains("is a directory"));
+}
+
+#[test]
+fn buffer_size() {
+    let input: Vec<u8> = (0..5000u32)
+        .map(|n| b"ab\n\n\ncd"[n as usize % 7])
+        .collect();
+    for size in &["512", "4k", "1M"] {
+        let arg = format!("--buffer-size={}", size);
+        let output = run("cat", &[&arg], &input);
+        assert!(output.status.success(), "{}", stderr(&output));
+        assert_eq!(output.stdout, input);
+        let numbered = run("cat", &["-ns", &arg], &input);
+        assert_eq!(numbered.stdout, run("cat", &["-ns"], &input).stdout);
+    }
+    for size in &["511", "129m", "1g", "x"] {
+        let output = run("cat", &["--buffer-size", size], b"");
+        assert_eq!(output.status.code(), Some(2), "{}", size);
+        assert!(stderr(&output).contains("invalid buffer size"));
+    }
+}
+
+#[test]
+fn unknown_option() {
+    let output = run("cat", &["--bogus"], b"");
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stderr(&output).contains("unrecognized option '--bogus'"));
+}
+
+#[test]
+fn help_and_version() {
+    let help = run("cat", &["--help"], b"");
+    assert!(help.status.success());
+    assert!(stdout(&help).contains("--number"));
+    let version = run("cat", &["--version"], b"");
+    assert!(version.status.success());
+    assert_eq!(
+        stdout(&version),
+        format!("cat (coreutils-rs) {}\n", env!("CARGO_PKG_VERSION"))
+    );
+}
+
+#[test]
+fn parallel_reads() {
+    let dir = TempDir::new("cat-parallel");
+    let files: Vec<String> = (0..16)
+        .map(|n| {
+            dir.file(
+                &n.to_string(),
+                format!("file {}\n", n).repeat(n * 50).as_bytes(),
+            )
+        })
+        .collect();
+    let args: Vec<&str> = files.iter().map(String::as_str).collect();
+    let sequential = run("cat", &args, b"");
+    let parallel = run("cat", &[&["--parallel=4"][..], &args].concat(), b"");
+    assert!(parallel.status.success(), "{}", stderr(&parallel));
+    assert_eq!(parallel.stdout, sequential.stdout);
+    // Decorators and the standard input make the inputs be read one at a time.
+    let numbered = run(
+        "cat",
+        &[&["-n", "--parallel", "4"][..], &args].concat(),
+        b"",
+    );
+    assert_eq!(
+        numbered.stdout,
+        run("cat", &[&["-n"][..], &args].concat(), b"").stdout
+    );
+    let output = run("cat", &["--parallel=2", &files[1], "-"], b"stdin\n");
+    assert_eq!(stdout(&output), "file 1\n".repeat(50) + "stdin\n");
+    let output = run("cat", &["--parallel=2"], b"stdin\n");
+    assert_eq!(stdout(&output), "stdin\n");
+
+    let output = run("cat", &["--parallel=2", &files[1], "/nonexistent"], b"");
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(stdout(&output), "file 1\n".repeat(50));
+    for jobs in &["0", "9", "x"] {
+        let output = run("cat", &["--parallel", jobs, &files[1]], b"");
+        assert_eq!(output.status.code(), Some(2), "{}", jobs);
+    }
+}
+
+#[test]
+fn file_replaced_while_reading() {
+    let dir = TempDir::new("cat-replaced");
+    let original = vec![b'a'; 1 << 20];
+    let file = dir.file("file", &original);
+    let replacement = dir.file("replacement", &vec![b'b'; 2 << 20]);
+    let child = Command::new(binary("cat"))
+        .arg(&file)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    // cat has opened the file, and waits for the pipe to be read.
+    thread::sleep(Duration::from_millis(300));
+    fs::rename(&replacement, &file).unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    assert_eq!(output.stdout, original);
+    assert!(stderr(&output).contains(&format!("{}: file was replaced or truncated", file)));
+
+    let output = run("cat", &[&file], b"");
+    assert!(output.stderr.is_empty(), "{}", stderr(&output));
+}
@@ -0,0 +1,87 @@
+extern crate integration;
+
+use integration::{run, stderr, stdout, TempDir};
+
+#[test]
+fn identical_files() {
+    let dir = TempDir::new("cmp-same");
+    let first = dir.file("first", b"same\n");
+    let second = dir.file("second", b"same\n");
+    let output = run("cmp", &[&first, &second], b"");
+    assert_eq!(output.status.code(), Some(0));
+    assert!(output.stdout.is_empty());
+}
+
+#[test]
+fn first_difference() {
+    let dir = TempDir::new("cmp-differ");
+    let first = dir.file("first", b"one\ntwo\n");
+    let output = run("cmp", &[&first, "-"], b"one\ntwO\n");
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(
+        stdout(&output),
+        format!("{} - differ: byte 7, line 2\n", first)
+    );
+}
+
+#[test]
+fn verbose_octal_listing() {
+    let dir = TempDir::new("cmp-verbose");
+    let first = dir.file("first", b"hello\nworld\n");
+    let second = dir.file("second", b"hellO\nwOrld\n");
+    let output = run("cmp", &["--verbose", &first, &second], b"");
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(stdout(&output), " 5 157 117\n 8 157 117\n");
+    let output = run("cmp", &["-l", "-b", &first, &second], b"");
+    assert_eq!(stdout(&output), " 5 157 o    117 O\n 8 157 o    117 O\n");
+}
+
+#[test]
+fn truncated_file() {
+    let dir = TempDir::new("cmp-truncated");
+    let full = dir.file("full", b"hello\nworld\n");
+    let truncated = dir.file("truncated", b"hello\nwo");
+    let empty = dir.file("empty", b"");
+
+    let output = run("cmp", &[&full, &truncated], b"");
+    assert_eq!(output.status.code(), Some(1));
+    assert!(output.stdout.is_empty());
+    let expected = format!("EOF on {} after byte 8, line 2\n", truncated);
+    assert!(stderr(&output).ends_with(&expected), "{}", stderr(&output));
+
+    let output = run("cmp", &[&empty, &full], b"");
+    let expected = format!("EOF on {} which is empty\n", empty);
+    assert!(stderr(&output).ends_with(&expected));
+
+    let output = run("cmp", &["-s", &full, &truncated], b"");
+    assert_eq!(output.status.code(), Some(1));
+    assert!(output.stderr.is_empty());
+}
+
+#[test]
+fn limit_and_skip() {
+    let dir = TempDir::new("cmp-limit");
+    let first = dir.file("first", b"abcX");
+    let second = dir.file("second", b"abcY");
+    let shifted = dir.file("shifted", b"__abcX");
+    assert_eq!(
+        run("cmp", &["-n", "3", &first, &second], b"").status.code(),
+        Some(0)
+    );
+    let output = run("cmp", &["--ignore-initial=0:2", &first, &shifted], b"");
+    assert_eq!(output.status.code(), Some(0));
+    let output = run("cmp", &["-i", "1", &first, &second], b"");
+    assert_eq!(
+        stdout(&output),
+        format!("{} {} differ: byte 3, line 1\n", first, second)
+    );
+}
+
+#[test]
+fn trouble() {
+    let output = run("cmp", &["/nonexistent/file", "-"], b"");
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stderr(&output).contains("/nonexistent/file"));
+    assert_eq!(run("cmp", &[], b"").status.code(), Some(2));
+    assert_eq!(run("cmp", &["--bogus"], b"").status.code(), Some(2));
+}
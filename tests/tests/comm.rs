@@ -0,0 +1,33 @@
+extern crate integration;
+
+use integration::{run, stderr, stdout, TempDir};
+
+#[test]
+fn output_delimiter() {
+    let dir = TempDir::new("comm-delimiter");
+    let first = dir.file("first", b"a\nb\n");
+    let second = dir.file("second", b"b\nc\n");
+    let output = run("comm", &["--output-delimiter=->", &first, &second], b"");
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(stdout(&output), "a\n->->b\n->c\n");
+    // An empty delimiter puts the columns right next to each other.
+    let output = run("comm", &["--output-delimiter=", &first, &second], b"");
+    assert_eq!(stdout(&output), "a\nb\nc\n");
+    let output = run(
+        "comm",
+        &["--output-delimiter", "", "-1", &first, &second],
+        b"",
+    );
+    assert_eq!(stdout(&output), "b\nc\n");
+}
+
+#[test]
+fn standard_input_once() {
+    let dir = TempDir::new("comm-stdin");
+    let file = dir.file("file", b"b\nc\n");
+    let output = run("comm", &["-", &file], b"a\nb\n");
+    assert_eq!(stdout(&output), "a\n\t\tb\n\tc\n");
+    let output = run("comm", &["-", "-"], b"a\n");
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stderr(&output).contains("both files cannot be standard input"));
+}
@@ -0,0 +1,157 @@
+extern crate integration;
+
+use integration::{run, stderr, stdout, TempDir};
+use std::fs;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+const INPUT: &[u8] = b"1\n2\n3\n4\nx5\n6\n7\n8\n9\nx10\n11\n12\n";
+
+/// Runs csplit with @p args on @p input, writing the pieces to a scratch directory, and
+/// returns its output with the pieces it left there.
+fn csplit(args: &[&str], input: &[u8]) -> (String, Vec<(String, String)>) {
+    static RUNS: AtomicUsize = AtomicUsize::new(0);
+    let dir = TempDir::new(&format!("csplit-{}", RUNS.fetch_add(1, Ordering::SeqCst)));
+    let prefix = format!("{}/xx", dir.path().display());
+    let mut all_args = vec!["-f", &prefix];
+    all_args.extend_from_slice(args);
+    let output = run("csplit", &all_args, input);
+    let mut pieces: Vec<(String, String)> = fs::read_dir(dir.path())
+        .unwrap()
+        .map(|entry| {
+            let path = entry.unwrap().path();
+            let name = path.file_name().unwrap().to_str().unwrap().to_owned();
+            (name, fs::read_to_string(&path).unwrap())
+        })
+        .collect();
+    pieces.sort();
+    let status = if output.status.success() {
+        String::new()
+    } else {
+        stderr(&output)
+    };
+    (stdout(&output) + &status, pieces)
+}
+
+fn pieces(names_and_contents: &[(&str, &str)]) -> Vec<(String, String)> {
+    names_and_contents
+        .iter()
+        .map(|&(name, contents)| (name.to_owned(), contents.to_owned()))
+        .collect()
+}
+
+#[test]
+fn regular_expressions() {
+    assert_eq!(
+        csplit(&["-", "/^x/", "{*}"], INPUT),
+        (
+            "8\n11\n10\n".to_owned(),
+            pieces(&[
+                ("xx00", "1\n2\n3\n4\n"),
+                ("xx01", "x5\n6\n7\n8\n9\n"),
+                ("xx02", "x10\n11\n12\n"),
+            ])
+        )
+    );
+    // Offsets move the split, and %REGEX% drops what comes before it.
+    assert_eq!(
+        csplit(&["-", "%^x%", "/^x/+1"], INPUT).1,
+        pieces(&[("xx00", "x5\n6\n7\n8\n9\nx10\n"), ("xx01", "11\n12\n")])
+    );
+    assert_eq!(
+        csplit(&["-", "/x5/-1", "/x/"], INPUT).1,
+        pieces(&[
+            ("xx00", "1\n2\n3\n"),
+            ("xx01", "4\nx5\n6\n7\n8\n9\n"),
+            ("xx02", "x10\n11\n12\n"),
+        ])
+    );
+}
+
+#[test]
+fn line_numbers() {
+    assert_eq!(
+        csplit(&["-", "3", "{2}"], INPUT),
+        (
+            "4\n7\n6\n12\n".to_owned(),
+            pieces(&[
+                ("xx00", "1\n2\n"),
+                ("xx01", "3\n4\nx5\n"),
+                ("xx02", "6\n7\n8\n"),
+                ("xx03", "9\nx10\n11\n12\n"),
+            ])
+        )
+    );
+    // A regular expression after a line number may match the line the piece starts at.
+    assert_eq!(
+        csplit(&["-s", "-", "5", "/x5/"], INPUT),
+        (
+            String::new(),
+            pieces(&[
+                ("xx00", "1\n2\n3\n4\n"),
+                ("xx01", ""),
+                ("xx02", "x5\n6\n7\n8\n9\nx10\n11\n12\n"),
+            ])
+        )
+    );
+}
+
+#[test]
+fn names_of_pieces() {
+    let names = |args: &[&str]| -> Vec<String> {
+        csplit(args, INPUT)
+            .1
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect()
+    };
+    assert_eq!(names(&["-n", "3", "-", "5"]), ["xx000", "xx001"]);
+    assert_eq!(
+        names(&["--suffix-format", "%x.txt", "-", "1", "{10}"]),
+        ["xx0.txt", "xx1.txt", "xx2.txt", "xx3.txt", "xx4.txt", "xx5.txt"]
+            .iter()
+            .chain(&["xx6.txt", "xx7.txt", "xx8.txt", "xx9.txt", "xxa.txt", "xxb.txt"])
+            .map(|s| s.to_string())
+            .collect::<Vec<_>>()
+    );
+    // Empty pieces are elided without using up numbers.
+    assert_eq!(names(&["-z", "-", "/1/", "/x5/"]), ["xx00", "xx01"]);
+}
+
+#[test]
+fn errors_remove_the_pieces() {
+    let (output, left) = csplit(&["-", "5", "/nomatch/"], INPUT);
+    assert_eq!(left, []);
+    assert!(
+        output.ends_with("'/nomatch/': match not found\n"),
+        "{}",
+        output
+    );
+
+    // Unless -k keeps them, with the rest of the input in the last one.
+    let (output, left) = csplit(&["-k", "-", "/x/", "{2}"], INPUT);
+    assert!(output.contains("'/x/': match not found on repetition 2"));
+    assert_eq!(
+        left,
+        pieces(&[
+            ("xx00", "1\n2\n3\n4\n"),
+            ("xx01", "x5\n6\n7\n8\n9\n"),
+            ("xx02", "x10\n11\n12\n"),
+        ])
+    );
+
+    let (output, left) = csplit(&["-", "20"], INPUT);
+    assert!(output.contains("'20': line number out of range"));
+    assert_eq!(left, []);
+    let (output, _) = csplit(&["-", "5", "3"], INPUT);
+    assert!(output.contains("line number '3' is smaller than preceding line number, 5"));
+}
+
+#[test]
+fn help_and_version() {
+    let help = run("csplit", &["--help"], b"");
+    assert!(help.status.success());
+    assert!(stdout(&help).contains("--suffix-format"));
+    let version = run("csplit", &["--version"], b"");
+    assert!(version.status.success());
+    assert_eq!(stdout(&version), "csplit (coreutils-rs) 0.1.0\n");
+}
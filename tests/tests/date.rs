@@ -0,0 +1,120 @@
+extern crate integration;
+
+use integration::{binary, run, stderr, stdout};
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Runs date with @p args in the time zone @p tz and returns its output.
+fn date_in(tz: &str, args: &[&str]) -> String {
+    let output = Command::new(binary("date"))
+        .args(args)
+        .env("TZ", tz)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "date {:?}: {}",
+        args,
+        stderr(&output)
+    );
+    stdout(&output)
+}
+
+fn date(args: &[&str]) -> String {
+    date_in("UTC0", args)
+}
+
+#[test]
+fn epoch_seconds_match_system_time() {
+    let before = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    let printed: u64 = date(&["+%s"]).trim().parse().unwrap();
+    let after = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    assert!(before <= printed && printed <= after);
+}
+
+#[test]
+fn dates_round_trip() {
+    for day in &["2024-03-15", "1999-12-31", "2000-02-29", "1970-01-01"] {
+        assert_eq!(date(&["-d", day, "+%Y-%m-%d"]), format!("{}\n", day));
+        assert_eq!(
+            date_in("America/New_York", &["-d", day, "+%Y-%m-%d"]),
+            format!("{}\n", day)
+        );
+    }
+    let secs = date(&["-d", "2024-03-15 10:20:30", "+%s"]);
+    assert_eq!(secs, "1710498030\n");
+    assert_eq!(
+        date(&["-d", &format!("@{}", secs.trim()), "+%F %T"]),
+        "2024-03-15 10:20:30\n"
+    );
+}
+
+#[test]
+fn default_format() {
+    assert_eq!(date(&["-d", "@0"]), "Thu Jan  1 00:00:00 UTC 1970\n");
+    assert_eq!(
+        date_in("America/New_York", &["-d", "@0"]),
+        "Wed Dec 31 19:00:00 EST 1969\n"
+    );
+    assert_eq!(
+        date_in("America/New_York", &["-u", "-d", "@0"]),
+        "Thu Jan  1 00:00:00 UTC 1970\n"
+    );
+}
+
+#[test]
+fn time_zones() {
+    assert_eq!(
+        date_in(
+            "Asia/Kolkata",
+            &["-d", "2024-07-01 12:00", "+%H:%M %z %:z %Z"]
+        ),
+        "12:00 +0530 +05:30 IST\n"
+    );
+    assert_eq!(
+        date(&["-d", "2024-07-01T12:00:00+02:00", "+%F %T"]),
+        "2024-07-01 10:00:00\n"
+    );
+    // With -u, dates without an offset are in UTC too.
+    assert_eq!(
+        date_in("Asia/Kolkata", &["-u", "-d", "2024-07-01 12:00", "+%s"]),
+        "1719835200\n"
+    );
+}
+
+#[test]
+fn relative_dates() {
+    let now: i64 = date(&["+%s"]).trim().parse().unwrap();
+    let ago: i64 = date(&["-d", "2 days ago", "+%s"]).trim().parse().unwrap();
+    assert!((now - 2 * 86400 - ago).abs() <= 1);
+    let tomorrow: i64 = date(&["-d", "tomorrow", "+%s"]).trim().parse().unwrap();
+    assert!((tomorrow - now - 86400).abs() <= 1);
+}
+
+#[test]
+fn invalid_dates() {
+    for invalid in &["2024-02-30", "soon", "@1.5x"] {
+        let output = run("date", &["-d", invalid], b"");
+        assert_eq!(output.status.code(), Some(2));
+        assert!(output.stdout.is_empty());
+        assert!(stderr(&output).contains(&format!("invalid date '{}'", invalid)));
+    }
+    let output = run("date", &["%Y"], b"");
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn help_and_version() {
+    let help = run("date", &["--help"], b"");
+    assert!(help.status.success());
+    assert!(stdout(&help).contains("--date"));
+    let version = run("date", &["--version"], b"");
+    assert!(version.status.success());
+    assert!(stdout(&version).contains("date"));
+}
@@ -0,0 +1,51 @@
+extern crate integration;
+
+use integration::{run, stderr, stdout};
+
+/// The whitespace separated fields of the line after the header.
+fn fields(output: &str) -> Vec<String> {
+    let line = output.lines().nth(1).unwrap();
+    line.split_whitespace().map(|s| s.to_owned()).collect()
+}
+
+#[test]
+fn available_space_of_tmp() {
+    let output = run("df", &["/tmp"], b"");
+    assert!(output.status.success(), "{}", stderr(&output));
+    let output = stdout(&output);
+    assert!(output.starts_with("Filesystem     1K-blocks"));
+    let fields = fields(&output);
+    let size: u64 = fields[1].parse().unwrap();
+    let available: u64 = fields[3].parse().unwrap();
+    assert!(available < size);
+    assert!(fields[4].ends_with('%'));
+}
+
+#[test]
+fn type_and_total() {
+    let output = stdout(&run("df", &["-T", "--total", "/tmp", "/tmp"], b""));
+    assert!(output.lines().next().unwrap().contains(" Type "));
+    let rows: Vec<Vec<String>> = output
+        .lines()
+        .skip(1)
+        .map(|line| line.split_whitespace().map(|s| s.to_owned()).collect())
+        .collect();
+    assert_eq!(rows.len(), 3);
+    assert_eq!(rows[2][0], "total");
+    let first: u64 = rows[0][2].parse().unwrap();
+    let total: u64 = rows[2][2].parse().unwrap();
+    assert_eq!(total, 2 * first);
+}
+
+#[test]
+fn human_readable() {
+    let output = stdout(&run("df", &["-h", "/tmp"], b""));
+    assert!(output.lines().next().unwrap().contains("Size  Used Avail"));
+}
+
+#[test]
+fn missing_file() {
+    let output = run("df", &["/nonexistent/file"], b"");
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr(&output).contains("/nonexistent/file"));
+}
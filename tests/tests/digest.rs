@@ -0,0 +1,92 @@
+extern crate integration;
+
+use integration::{run, stderr, stdout, TempDir};
+
+#[test]
+fn digests_of_files_and_stdin() {
+    let output = run("md5sum", &[], b"abc");
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(stdout(&output), "900150983cd24fb0d6963f7d28e17f72  -\n");
+    let output = run("sha1sum", &["-b", "-"], b"abc");
+    assert_eq!(
+        stdout(&output),
+        "a9993e364706816aba3e25717850c26c9cd0d89d *-\n"
+    );
+    let dir = TempDir::new("digest-files");
+    let file = dir.file("abc", b"abc");
+    let output = run("sha256sum", &["--tag", &file], b"");
+    assert_eq!(
+        stdout(&output),
+        format!(
+            "SHA256 ({}) = {}\n",
+            file, "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        )
+    );
+    let output = run("sha512sum", &[&file, "/nonexistent"], b"");
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stdout(&output).starts_with("ddaf35a193617aba"));
+    assert!(stderr(&output).contains("/nonexistent"));
+}
+
+#[test]
+fn check() {
+    let dir = TempDir::new("digest-check");
+    let file = dir.file("file", b"contents");
+    let sums = stdout(&run("sha1sum", &[&file], b""));
+    let tagged = stdout(&run("sha1sum", &["--tag", &file], b""));
+    let list = dir.file("list", format!("{}{}", sums, tagged).as_bytes());
+    let output = run("sha1sum", &["-c", &list], b"");
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(stdout(&output), format!("{0}: OK\n{0}: OK\n", file));
+    let output = run("sha1sum", &["--check", "--quiet", "-"], sums.as_bytes());
+    assert!(output.status.success());
+    assert_eq!(stdout(&output), "");
+
+    // A mismatch, a missing file and an improperly formatted line.
+    let wrong = sums.replacen(&sums[..4], "0000", 1);
+    let missing = sums.replace(&file, "/nonexistent");
+    let input = format!("{}{}junk\n", wrong, missing);
+    let output = run("sha1sum", &["-c", "-w"], input.as_bytes());
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(
+        stdout(&output),
+        format!("{}: FAILED\n/nonexistent: FAILED open or read\n", file)
+    );
+    let errors = stderr(&output);
+    assert!(errors.contains("-: 3: improperly formatted SHA1 checksum line"));
+    assert!(errors.contains("WARNING: 1 line is improperly formatted"));
+    assert!(errors.contains("WARNING: 1 listed file could not be read"));
+    assert!(errors.contains("WARNING: 1 computed checksum did NOT match"));
+    let output = run("sha1sum", &["-c", "--status"], wrong.as_bytes());
+    assert_eq!(output.status.code(), Some(1));
+    assert!(output.stdout.is_empty() && output.stderr.is_empty());
+
+    // Improperly formatted lines only fail with --strict, or if nothing else is there.
+    let input = format!("{}junk\n", sums);
+    assert!(run("sha1sum", &["-c"], input.as_bytes()).status.success());
+    let output = run("sha1sum", &["-c", "--strict"], input.as_bytes());
+    assert_eq!(output.status.code(), Some(1));
+    let output = run("sha1sum", &["-c"], b"junk\n");
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr(&output).contains("no properly formatted checksum lines found"));
+    let output = run("sha1sum", &["-c", "--ignore-missing"], missing.as_bytes());
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr(&output).contains("no file was verified"));
+}
+
+#[test]
+fn invalid_arguments() {
+    for args in &[&["--tag", "-c"][..], &["--quiet"][..], &["-x"][..]] {
+        let output = run("md5sum", args, b"");
+        assert_eq!(output.status.code(), Some(2), "{:?}", args);
+    }
+}
+
+#[test]
+fn help_and_version() {
+    let help = run("sha512sum", &["--help"], b"");
+    assert!(help.status.success());
+    assert!(stdout(&help).contains("SHA512"));
+    let version = run("sha1sum", &["--version"], b"");
+    assert_eq!(stdout(&version), "sha1sum (coreutils-rs) 0.1.0\n");
+}
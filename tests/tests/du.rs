@@ -0,0 +1,127 @@
+extern crate integration;
+
+use integration::{run, stderr, stdout, TempDir};
+use std::fs;
+
+/// Creates top/{a,b/c} with a file in each directory and returns the path of top.
+fn tree(dir: &TempDir) -> String {
+    let top = dir.path().join("top");
+    fs::create_dir_all(top.join("a")).unwrap();
+    fs::create_dir_all(top.join("b/c")).unwrap();
+    for sub in &["", "a", "b", "b/c"] {
+        fs::write(top.join(sub).join("file"), vec![b'x'; 10000]).unwrap();
+    }
+    top.to_str().unwrap().to_owned()
+}
+
+/// The paths reported by du, in order.
+fn paths(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .map(|line| line.split('\t').nth(1).unwrap().to_owned())
+        .collect()
+}
+
+fn size(output: &str, path: &str) -> u64 {
+    output
+        .lines()
+        .map(|line| line.split('\t').collect::<Vec<_>>())
+        .find(|fields| fields[1] == path)
+        .unwrap()[0]
+        .parse()
+        .unwrap()
+}
+
+#[test]
+fn reports_every_directory() {
+    let dir = TempDir::new("du-all");
+    let top = tree(&dir);
+    let output = run("du", &[&top], b"");
+    assert!(output.status.success(), "{}", stderr(&output));
+    let mut reported = paths(&stdout(&output));
+    reported.sort();
+    let expected: Vec<String> = ["", "/a", "/b", "/b/c"]
+        .iter()
+        .map(|sub| format!("{}{}", top, sub))
+        .collect();
+    assert_eq!(reported, expected);
+    // Subdirectories are reported before the directories containing them.
+    assert_eq!(paths(&stdout(&output)).last().unwrap(), &top);
+}
+
+#[test]
+fn summarize() {
+    let dir = TempDir::new("du-summarize");
+    let top = tree(&dir);
+    let full = stdout(&run("du", &[&top], b""));
+    let output = run("du", &["-s", &top], b"");
+    assert!(output.status.success());
+    let summary = stdout(&output);
+    assert_eq!(paths(&summary), vec![top.clone()]);
+    assert_eq!(size(&summary, &top), size(&full, &top));
+    assert!(size(&summary, &top) >= 40);
+}
+
+#[test]
+fn max_depth() {
+    let dir = TempDir::new("du-depth");
+    let top = tree(&dir);
+    let output = run("du", &["--max-depth=1", &top], b"");
+    let mut reported = paths(&stdout(&output));
+    reported.sort();
+    assert_eq!(
+        reported,
+        vec![top.clone(), format!("{}/a", top), format!("{}/b", top)]
+    );
+    let output = run("du", &["-d", "0", &top], b"");
+    assert_eq!(paths(&stdout(&output)), vec![top.clone()]);
+}
+
+#[test]
+fn files_and_grand_total() {
+    let dir = TempDir::new("du-total");
+    let first = dir.file("first", &[b'x'; 5000]);
+    let second = dir.file("second", &[b'x'; 5000]);
+    let output = run("du", &["-c", &first, &second], b"");
+    let output = stdout(&output);
+    assert_eq!(
+        paths(&output),
+        vec![first.clone(), second.clone(), "total".to_owned()]
+    );
+    assert_eq!(
+        size(&output, "total"),
+        size(&output, &first) + size(&output, &second)
+    );
+}
+
+#[test]
+fn hard_links_are_counted_once() {
+    let dir = TempDir::new("du-links");
+    let top = tree(&dir);
+    let before = size(&stdout(&run("du", &["-s", &top], b"")), &top);
+    fs::hard_link(format!("{}/file", top), format!("{}/a/link", top)).unwrap();
+    let after = size(&stdout(&run("du", &["-s", &top], b"")), &top);
+    assert_eq!(before, after);
+}
+
+#[test]
+fn directories_are_counted_once_across_operands() {
+    let dir = TempDir::new("du-operands");
+    let top = tree(&dir);
+    let alone = stdout(&run("du", &[&top], b""));
+    let a = format!("{}/a", top);
+    let output = stdout(&run("du", &["-c", &top, &a, &top], b""));
+    // The directories were already counted with top, so they are neither reported nor
+    // counted again.
+    let mut expected = paths(&alone);
+    expected.push("total".to_owned());
+    assert_eq!(paths(&output), expected);
+    assert_eq!(size(&output, "total"), size(&alone, &top));
+}
+
+#[test]
+fn missing_file() {
+    let output = run("du", &["/nonexistent/file"], b"");
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr(&output).contains("cannot access '/nonexistent/file'"));
+}
@@ -0,0 +1,111 @@
+extern crate integration;
+
+use integration::{run, stderr, stdout};
+
+/// Runs expr with @p args and returns its output and exit status.
+fn expr(args: &[&str]) -> (String, i32) {
+    let output = run("expr", args, b"");
+    (stdout(&output), output.status.code().unwrap())
+}
+
+#[test]
+fn arithmetic() {
+    assert_eq!(expr(&["2", "+", "3"]), ("5\n".to_owned(), 0));
+    assert_eq!(expr(&["2", "-", "3"]), ("-1\n".to_owned(), 0));
+    assert_eq!(expr(&["6", "*", "7"]), ("42\n".to_owned(), 0));
+    assert_eq!(expr(&["-7", "/", "2"]), ("-3\n".to_owned(), 0));
+    assert_eq!(expr(&["-5", "%", "3"]), ("-2\n".to_owned(), 0));
+    assert_eq!(expr(&["3", "-", "3"]), ("0\n".to_owned(), 1));
+}
+
+#[test]
+fn comparisons() {
+    assert_eq!(expr(&["3", "<", "10"]), ("1\n".to_owned(), 0));
+    assert_eq!(expr(&["3", "<=", "3"]), ("1\n".to_owned(), 0));
+    assert_eq!(expr(&["3", "=", "03"]), ("1\n".to_owned(), 0));
+    assert_eq!(expr(&["a", "!=", "a"]), ("0\n".to_owned(), 1));
+    assert_eq!(expr(&["b", ">=", "a"]), ("1\n".to_owned(), 0));
+    assert_eq!(expr(&["10", ">", "9"]), ("1\n".to_owned(), 0));
+    // Not both integers, so compared as strings.
+    assert_eq!(expr(&["10", ">", "9x"]), ("0\n".to_owned(), 1));
+}
+
+#[test]
+fn logical_operators() {
+    assert_eq!(expr(&["", "|", "b"]), ("b\n".to_owned(), 0));
+    assert_eq!(expr(&["a", "|", "b"]), ("a\n".to_owned(), 0));
+    assert_eq!(expr(&["0", "|", ""]), ("0\n".to_owned(), 1));
+    assert_eq!(expr(&["a", "&", "b"]), ("a\n".to_owned(), 0));
+    assert_eq!(expr(&["a", "&", "0"]), ("0\n".to_owned(), 1));
+    assert_eq!(expr(&["1", "|", "1", "/", "0"]), ("1\n".to_owned(), 0));
+}
+
+#[test]
+fn precedence() {
+    assert_eq!(expr(&["1", "+", "2", "*", "3"]), ("7\n".to_owned(), 0));
+    assert_eq!(
+        expr(&["(", "1", "+", "2", ")", "*", "3"]),
+        ("9\n".to_owned(), 0)
+    );
+    assert_eq!(expr(&["8", "/", "2", "/", "2"]), ("2\n".to_owned(), 0));
+    assert_eq!(expr(&["1", "|", "0", "&", "0"]), ("1\n".to_owned(), 0));
+    assert_eq!(expr(&["2", "+", "2", "=", "4"]), ("1\n".to_owned(), 0));
+    assert_eq!(expr(&["abc", ":", "a.", "*", "2"]), ("4\n".to_owned(), 0));
+}
+
+#[test]
+fn match_operator() {
+    assert_eq!(expr(&["abcabc", ":", ".*c"]), ("6\n".to_owned(), 0));
+    assert_eq!(expr(&["abc", ":", "b"]), ("0\n".to_owned(), 1));
+    assert_eq!(expr(&["abc", ":", "a\\(b\\)"]), ("b\n".to_owned(), 0));
+    assert_eq!(expr(&["abc", ":", "x\\(y\\)"]), ("\n".to_owned(), 1));
+    assert_eq!(
+        expr(&["match", "file.txt", ".*\\.\\(.*\\)"]),
+        ("txt\n".to_owned(), 0)
+    );
+    assert_eq!(expr(&["match", "123abc", "[0-9]*"]), ("3\n".to_owned(), 0));
+}
+
+#[test]
+fn string_functions() {
+    assert_eq!(expr(&["length", "hello"]), ("5\n".to_owned(), 0));
+    assert_eq!(
+        expr(&["substr", "hello", "2", "3"]),
+        ("ell\n".to_owned(), 0)
+    );
+    assert_eq!(expr(&["substr", "hello", "9", "1"]), ("\n".to_owned(), 1));
+    assert_eq!(expr(&["index", "hello", "lo"]), ("3\n".to_owned(), 0));
+    assert_eq!(expr(&["index", "hello", "z"]), ("0\n".to_owned(), 1));
+    assert_eq!(expr(&["+", "length"]), ("length\n".to_owned(), 0));
+}
+
+#[test]
+fn errors() {
+    for (args, message) in &[
+        (&["5", "/", "0"][..], "division by zero"),
+        (&["5", "%", "0"][..], "division by zero"),
+        (&["a", "+", "1"][..], "non-integer argument"),
+        (&["1", "2"][..], "syntax error: unexpected argument '2'"),
+        (&["1", "+"][..], "syntax error: missing argument after '+'"),
+        (&["(", "1"][..], "syntax error: expecting ')' after '1'"),
+        (&[][..], "missing operand"),
+    ] {
+        let output = run("expr", args, b"");
+        assert_eq!(output.status.code(), Some(2), "{:?}", args);
+        assert!(output.stdout.is_empty());
+        assert!(stderr(&output).contains(message), "{:?}", args);
+    }
+}
+
+#[test]
+fn help_and_version() {
+    let help = run("expr", &["--help"], b"");
+    assert!(help.status.success());
+    assert!(stdout(&help).contains("substr"));
+    let version = run("expr", &["--version"], b"");
+    assert!(version.status.success());
+    assert_eq!(
+        stdout(&version),
+        format!("expr (coreutils-rs) {}\n", env!("CARGO_PKG_VERSION"))
+    );
+}
@@ -0,0 +1,77 @@
+extern crate integration;
+
+use integration::{binary, run, stderr, stdout, TempDir};
+use std::fs;
+use std::process::Command;
+
+#[test]
+fn selects_matching_lines() {
+    let output = run("grep", &["o+"], b"foo\nbar\nzoo\n");
+    assert_eq!(output.status.code(), Some(1));
+    let output = run("grep", &["-E", "o+"], b"foo\nbar\nzoo\n");
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(stdout(&output), "foo\nzoo\n");
+    let output = run("grep", &["-vn", "o"], b"foo\nbar\nzoo\n");
+    assert_eq!(stdout(&output), "2:bar\n");
+}
+
+#[test]
+fn multiple_files() {
+    let dir = TempDir::new("grep-files");
+    let first = dir.file("first", b"apple\nbanana\n");
+    let second = dir.file("second", b"cherry\nbanana split\n");
+    let output = run("grep", &["-n", "banana", &first, &second], b"");
+    assert_eq!(
+        stdout(&output),
+        format!("{}:2:banana\n{}:2:banana split\n", first, second)
+    );
+    let output = run("grep", &["-h", "banana", &first, &second], b"");
+    assert_eq!(stdout(&output), "banana\nbanana split\n");
+    let output = run("grep", &["-c", "an", &first, &second], b"");
+    assert_eq!(stdout(&output), format!("{}:1\n{}:1\n", first, second));
+    let output = run("grep", &["-l", "cherry", &first, &second], b"");
+    assert_eq!(stdout(&output), format!("{}\n", second));
+}
+
+#[test]
+fn recursive_search() {
+    let dir = TempDir::new("grep-recursive");
+    fs::create_dir_all(dir.path().join("sub/deeper")).unwrap();
+    fs::write(dir.path().join("top"), b"needle\n").unwrap();
+    fs::write(dir.path().join("sub/file"), b"hay\nneedle here\n").unwrap();
+    fs::write(dir.path().join("sub/deeper/file"), b"NEEDLE\n").unwrap();
+
+    let output = Command::new(binary("grep"))
+        .args(["-ri", "needle"])
+        .current_dir(dir.path())
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(
+        stdout(&output),
+        "sub/deeper/file:NEEDLE\nsub/file:needle here\ntop:needle\n"
+    );
+
+    let root = dir.path().to_str().unwrap();
+    let output = run("grep", &["-r", "-l", "needle", root], b"");
+    assert_eq!(stdout(&output), format!("{0}/sub/file\n{0}/top\n", root));
+}
+
+#[test]
+fn directory_without_recursion() {
+    let dir = TempDir::new("grep-dir");
+    let output = run("grep", &["x", dir.path().to_str().unwrap()], b"");
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stderr(&output).contains("is a directory"));
+}
+
+#[test]
+fn trouble_exit_status() {
+    let output = run("grep", &["-E", "(unclosed"], b"");
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stderr(&output).contains("Unmatched ( or \\("));
+    let output = run("grep", &["x", "/nonexistent/file", "-"], b"x\n");
+    assert_eq!(output.status.code(), Some(2));
+    assert_eq!(stdout(&output), "(standard input):x\n");
+    assert_eq!(run("grep", &[], b"").status.code(), Some(2));
+}
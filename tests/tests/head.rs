@@ -0,0 +1,82 @@
+extern crate integration;
+
+use integration::{run, stderr, stdout, TempDir};
+
+fn head(args: &[&str], input: &[u8]) -> String {
+    let output = run("head", args, input);
+    assert!(output.status.success(), "{:?}: {}", args, stderr(&output));
+    stdout(&output)
+}
+
+#[test]
+fn first_lines_and_bytes() {
+    let input: String = (1..=20).map(|n| format!("{}\n", n)).collect();
+    assert_eq!(head(&[], input.as_bytes()), input[..21]);
+    assert_eq!(head(&["-n", "3"], input.as_bytes()), "1\n2\n3\n");
+    assert_eq!(head(&["--bytes=5"], input.as_bytes()), "1\n2\n3");
+    assert_eq!(head(&["-z", "-n", "1"], b"a\nb\0c\0"), "a\nb\0");
+}
+
+#[test]
+fn all_but_the_last() {
+    let input = b"1\n2\n3\n4\n5\n";
+    assert_eq!(head(&["-n", "-2"], input), "1\n2\n3\n");
+    assert_eq!(head(&["--lines=-4"], input), "1\n");
+    assert_eq!(head(&["-c", "-4"], input), "1\n2\n3\n");
+    // Nothing is left of input shorter than N, and all of it for 0.
+    assert_eq!(head(&["-n", "-10"], input), "");
+    assert_eq!(head(&["-c", "-1k"], input), "");
+    assert_eq!(head(&["-n", "-0"], input), "1\n2\n3\n4\n5\n");
+    assert_eq!(head(&["-c", "-0"], input), "1\n2\n3\n4\n5\n");
+    // Input much longer than N streams through the window.
+    let dir = TempDir::new("head-all-but");
+    let long = dir.file("long", &vec![b'x'; 1 << 20]);
+    let output = run("head", &["-c", "-1000", &long], b"");
+    assert_eq!(output.stdout.len(), (1 << 20) - 1000);
+}
+
+#[test]
+fn headers() {
+    let dir = TempDir::new("head-headers");
+    let first = dir.file("first", b"a\nb\n");
+    let second = dir.file("second", b"c\n");
+    assert_eq!(
+        head(&["-n", "1", &first, "-", &second], b"s\n"),
+        format!(
+            "==> {} <==\na\n\n==> standard input <==\ns\n\n==> {} <==\nc\n",
+            first, second
+        )
+    );
+    assert_eq!(head(&["-q", "-n", "1", &first, &second], b""), "a\nc\n");
+    assert_eq!(
+        head(&["-v", "-n", "1", &first], b""),
+        format!("==> {} <==\na\n", first)
+    );
+}
+
+#[test]
+fn errors() {
+    let dir = TempDir::new("head-errors");
+    let file = dir.file("file", b"a\n");
+    let output = run("head", &["/nonexistent", &file], b"");
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stdout(&output).ends_with("a\n"));
+    assert!(stderr(&output).contains("/nonexistent"));
+    for args in &[
+        &["-n", "x"][..],
+        &["-c", "--1"][..],
+        &["-c", "1", "-n", "1"][..],
+    ] {
+        let output = run("head", args, b"");
+        assert_eq!(output.status.code(), Some(2), "{:?}", args);
+    }
+}
+
+#[test]
+fn help_and_version() {
+    let help = run("head", &["--help"], b"");
+    assert!(help.status.success());
+    assert!(stdout(&help).contains("--lines"));
+    let version = run("head", &["--version"], b"");
+    assert_eq!(stdout(&version), "head (coreutils-rs) 0.1.0\n");
+}
@@ -0,0 +1,70 @@
+extern crate integration;
+
+use integration::{run, stderr, stdout, TempDir};
+
+#[test]
+fn joins_files_and_stdin() {
+    let dir = TempDir::new("join-files");
+    let first = dir.file("first", b"a 1\nb 2\nb 3\nd 4\n");
+    let second = dir.file("second", b"b x\nb y\nc z\nd w extra\n");
+    let output = run("join", &[&first, &second], b"");
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(stdout(&output), "b 2 x\nb 2 y\nb 3 x\nb 3 y\nd 4 w extra\n");
+
+    let output = run(
+        "join",
+        &["-v", "2", "-a1", "-e", "-", "-o", "1.2,0,2.2", "-", &second],
+        b"a 1\nd 4\n",
+    );
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(stdout(&output), "1 a -\n- b x\n- b y\n- c z\n");
+}
+
+#[test]
+fn join_fields() {
+    let dir = TempDir::new("join-fields");
+    let first = dir.file("first", b"1,apple\n2,cherry\n");
+    let second = dir.file("second", b"APPLE,red\nBanana,yellow\nCherry,red\n");
+    let output = run(
+        "join",
+        &["-t", ",", "-i", "-1", "2", "-2", "1", &first, &second],
+        b"",
+    );
+    assert_eq!(stdout(&output), "apple,1,red\ncherry,2,red\n");
+}
+
+#[test]
+fn unicode_separator_and_keys() {
+    let dir = TempDir::new("join-unicode");
+    let first = dir.file("first", "Ärger§1\nÉcole§2\n".as_bytes());
+    let second = dir.file("second", "ärger§x\nécole§y§z\n".as_bytes());
+    let output = run("join", &["-t", "§", "-i", &first, &second], b"");
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(stdout(&output), "Ärger§1§x\nÉcole§2§y§z\n");
+}
+
+#[test]
+fn invalid_arguments() {
+    for args in &[
+        &["-a", "3", "-", "-"][..],
+        &["-1", "0", "-", "-"][..],
+        &["-o", "1.x", "-", "-"][..],
+        &["-t", "ab", "-", "-"][..],
+        &["-"][..],
+        &["-", "-", "-"][..],
+    ] {
+        let output = run("join", args, b"");
+        assert_eq!(output.status.code(), Some(2), "{:?}", args);
+        assert!(output.stdout.is_empty());
+    }
+}
+
+#[test]
+fn help_and_version() {
+    let help = run("join", &["--help"], b"");
+    assert!(help.status.success());
+    assert!(stdout(&help).contains("FILENUM"));
+    let version = run("join", &["--version"], b"");
+    assert!(version.status.success());
+    assert_eq!(stdout(&version), "join (coreutils-rs) 0.1.0\n");
+}
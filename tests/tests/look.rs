@@ -0,0 +1,55 @@
+extern crate integration;
+
+use integration::{run, stdout, TempDir};
+
+const WORDS: &[u8] = b"Apple\nBanana-split\napple\napple-pie\napplet\nbanana\ncherry\n";
+/// The same words, sorted with case folded.
+const FOLDED: &[u8] = b"Apple\napple\napple-pie\napplet\nbanana\nBanana-split\ncherry\n";
+
+#[test]
+fn prefixes_and_exit_status() {
+    let dir = TempDir::new("look-prefixes");
+    let words = dir.file("words", WORDS);
+    for args in &[&["apple"][..], &["-b", "apple"][..]] {
+        let mut args = args.to_vec();
+        args.push(&words);
+        let output = run("look", &args, b"");
+        assert_eq!(output.status.code(), Some(0));
+        assert_eq!(stdout(&output), "apple\napple-pie\napplet\n");
+    }
+    let output = run("look", &["durian", &words], b"");
+    assert_eq!(output.status.code(), Some(1));
+    assert!(output.stdout.is_empty());
+}
+
+#[test]
+fn fold_case() {
+    let dir = TempDir::new("look-fold");
+    let words = dir.file("words", FOLDED);
+    let output = run("look", &["-bf", "BANANA", &words], b"");
+    assert_eq!(stdout(&output), "banana\nBanana-split\n");
+    let output = run("look", &["-f", "apple", &words], b"");
+    assert_eq!(stdout(&output), "Apple\napple\napple-pie\napplet\n");
+}
+
+#[test]
+fn terminator() {
+    let dir = TempDir::new("look-terminator");
+    let words = dir.file("words", WORDS);
+    let output = run("look", &["-t", "-", "apple-tart", &words], b"");
+    assert_eq!(stdout(&output), "apple-pie\n");
+    let output = run("look", &["-b", "-t", "-", "apple", &words], b"");
+    assert_eq!(stdout(&output), "apple\napple-pie\napplet\n");
+}
+
+#[test]
+fn trouble() {
+    for args in &[
+        &[][..],
+        &["a", "/nonexistent"][..],
+        &["-t", "ab", "a", "-"][..],
+    ] {
+        let output = run("look", args, b"");
+        assert_eq!(output.status.code(), Some(2), "{:?}", args);
+    }
+}
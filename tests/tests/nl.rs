@@ -0,0 +1,153 @@
+extern crate integration;
+
+use integration::{run, stderr, stdout, TempDir};
+
+const DOCUMENT: &[u8] = b"\\:\\:\\:\nheader\n\\:\\:\nbody one\n\nbody two\n\\:\nfooter\n\
+    \\:\\:\\:\nsecond header\n\\:\\:\nsecond body\n";
+
+fn nl(args: &[&str], stdin: &[u8]) -> String {
+    let output = run("nl", args, stdin);
+    assert!(
+        output.status.success(),
+        "nl {:?}: {}",
+        args,
+        stderr(&output)
+    );
+    stdout(&output)
+}
+
+#[test]
+fn numbers_nonempty_lines() {
+    assert_eq!(nl(&[], b"a\n\nb\n"), "     1\ta\n       \n     2\tb\n");
+}
+
+#[test]
+fn sections() {
+    assert_eq!(
+        nl(&[], DOCUMENT),
+        concat!(
+            "\n",
+            "       header\n",
+            "\n",
+            "     1\tbody one\n",
+            "       \n",
+            "     2\tbody two\n",
+            "\n",
+            "       footer\n",
+            "\n",
+            "       second header\n",
+            "\n",
+            "     1\tsecond body\n",
+        )
+    );
+}
+
+#[test]
+fn section_styles_and_counter_resets() {
+    assert_eq!(
+        nl(&["-ha", "-bt", "-fa", "-v", "10", "-i", "5"], DOCUMENT),
+        concat!(
+            "\n",
+            "    10\theader\n",
+            "\n",
+            "    10\tbody one\n",
+            "       \n",
+            "    15\tbody two\n",
+            "\n",
+            "    10\tfooter\n",
+            "\n",
+            "    10\tsecond header\n",
+            "\n",
+            "    10\tsecond body\n",
+        )
+    );
+    // With -p, the count carries on through all sections.
+    assert_eq!(
+        nl(&["-p", "-ha", "-fa", "-s", ": "], DOCUMENT),
+        concat!(
+            "\n",
+            "     1: header\n",
+            "\n",
+            "     2: body one\n",
+            "        \n",
+            "     3: body two\n",
+            "\n",
+            "     4: footer\n",
+            "\n",
+            "     5: second header\n",
+            "\n",
+            "     6: second body\n",
+        )
+    );
+}
+
+#[test]
+fn custom_delimiter() {
+    let input = b"@@@@@@\nhead\n@@@@\nbody\n\\:\\:\n";
+    assert_eq!(
+        nl(&["-d", "@@", "-ha", "-w", "1"], input),
+        "\n1\thead\n\n1\tbody\n2\t\\:\\:\n"
+    );
+    // A single character is followed by ':'.
+    assert_eq!(nl(&["-d@", "-w1"], b"x\n@:@:\ny\n"), "1\tx\n\n1\ty\n");
+}
+
+#[test]
+fn regex_style_and_formats() {
+    assert_eq!(
+        nl(&["-bp^#", "-nln", "-w3"], b"# one\ntwo\n# three\n"),
+        "1  \t# one\n    two\n2  \t# three\n"
+    );
+    assert_eq!(nl(&["-nrz", "-w3"], b"a\n"), "001\ta\n");
+    assert_eq!(
+        nl(&["-ba", "-l", "2"], b"a\n\n\n\nb\n"),
+        "     1\ta\n       \n     2\t\n       \n     3\tb\n"
+    );
+}
+
+#[test]
+fn numbering_continues_across_files() {
+    let dir = TempDir::new("nl-files");
+    let first = dir.file("first", b"a\nb\n");
+    let second = dir.file("second", b"c\n");
+    assert_eq!(
+        nl(&[&first, &second], b""),
+        "     1\ta\n     2\tb\n     3\tc\n"
+    );
+}
+
+#[test]
+fn missing_file() {
+    let dir = TempDir::new("nl-missing");
+    let first = dir.file("first", b"a\n");
+    let output = run("nl", &[&first, "/nonexistent/file"], b"");
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(stdout(&output), "     1\ta\n");
+    assert!(stderr(&output).contains("/nonexistent/file"));
+}
+
+#[test]
+fn invalid_arguments() {
+    for args in &[
+        &["-b", "x"][..],
+        &["-n", "xx"][..],
+        &["-w", "0"][..],
+        &["-v", "x"][..],
+    ] {
+        let output = run("nl", args, b"");
+        assert_eq!(output.status.code(), Some(2), "{:?}", args);
+        assert!(output.stdout.is_empty());
+    }
+    let output = run("nl", &["-b", "x"], b"");
+    assert!(stderr(&output).contains("invalid body numbering style: 'x'"));
+}
+
+#[test]
+fn help_and_version() {
+    let help = run("nl", &["--help"], b"");
+    assert!(help.status.success());
+    assert!(stdout(&help).contains("--section-delimiter"));
+    let version = run("nl", &["--version"], b"");
+    assert!(version.status.success());
+    assert!(stdout(&version).contains("nl"));
+}
@@ -0,0 +1,46 @@
+extern crate integration;
+
+use integration::{run, stdout};
+
+/// The mask of ignored signals in the SigIgn line of /proc/PID/status.
+fn ignored_signals(status: &str) -> u64 {
+    let line = status
+        .lines()
+        .find(|line| line.starts_with("SigIgn:"))
+        .expect("no SigIgn line");
+    u64::from_str_radix(line["SigIgn:".len()..].trim(), 16).unwrap()
+}
+
+#[test]
+fn hangup_and_quit_are_ignored() {
+    let output = run("nohup", &["cat", "/proc/self/status"], b"");
+    assert!(output.status.success());
+    let ignored = ignored_signals(&stdout(&output));
+    // Bit N - 1 stands for signal N: SIGHUP is 1 and SIGQUIT 3.
+    assert_eq!(ignored & 1, 1, "{:x}", ignored);
+    assert_eq!(ignored & 4, 4, "{:x}", ignored);
+
+    let output = run("cat", &["/proc/self/status"], b"");
+    assert_eq!(ignored_signals(&stdout(&output)) & 1, 0);
+}
+
+#[test]
+fn output_is_left_alone_without_a_terminal() {
+    let output = run("nohup", &["sh", "-c", "cat; echo err >&2"], b"input\n");
+    assert_eq!(stdout(&output), "input\n");
+    assert_eq!(output.stderr, b"err\n");
+}
+
+#[test]
+fn exit_status_is_the_commands() {
+    let output = run("nohup", &["sh", "-c", "exit 7"], b"");
+    assert_eq!(output.status.code(), Some(7));
+    let output = run("nohup", &["sh", "-c", "kill -TERM $$"], b"");
+    assert_eq!(output.status.code(), Some(128 + 15));
+    assert_eq!(
+        run("nohup", &["/nonexistent"], b"").status.code(),
+        Some(127)
+    );
+    assert_eq!(run("nohup", &["/"], b"").status.code(), Some(126));
+    assert_eq!(run("nohup", &[], b"").status.code(), Some(125));
+}
@@ -0,0 +1,29 @@
+extern crate integration;
+
+use integration::{run, stderr, stdout, TempDir};
+
+fn paste(args: &[&str], input: &[u8]) -> String {
+    let output = run("paste", args, input);
+    assert!(output.status.success(), "{:?}: {}", args, stderr(&output));
+    stdout(&output)
+}
+
+#[test]
+fn standard_input_operands_take_turns() {
+    let input = b"a\nb\nc\nd\ne\n";
+    assert_eq!(paste(&["-", "-"], input), "a\tb\nc\td\ne\t\n");
+    assert_eq!(paste(&["-d", ",", "-", "-", "-"], input), "a,b,c\nd,e,\n");
+    // The first "-" reads all of the standard input, which leaves nothing for the second.
+    assert_eq!(paste(&["-s", "-", "-"], input), "a\tb\tc\td\te\n\n");
+}
+
+#[test]
+fn standard_input_among_files() {
+    let dir = TempDir::new("paste-files");
+    let file = dir.file("file", b"1\n2\n");
+    assert_eq!(
+        paste(&["-", &file, "-"], b"a\nb\nc\nd\n"),
+        "a\t1\tb\nc\t2\td\n"
+    );
+    assert_eq!(paste(&["-s", &file, "-"], b"a\nb\n"), "1\t2\na\tb\n");
+}
@@ -0,0 +1,90 @@
+extern crate integration;
+
+use integration::{run, stderr, stdout, TempDir};
+use std::fs;
+use std::os::unix::fs::symlink;
+
+#[test]
+fn prints_relative_target() {
+    let dir = TempDir::new("readlink-relative");
+    symlink("../somewhere/else", dir.path().join("link")).unwrap();
+    let output = run(
+        "readlink",
+        &[dir.path().join("link").to_str().unwrap()],
+        b"",
+    );
+    assert!(output.status.success());
+    assert_eq!(stdout(&output), "../somewhere/else\n");
+}
+
+#[test]
+fn not_a_symlink() {
+    let dir = TempDir::new("readlink-regular");
+    let file = dir.file("file", b"");
+    let output = run("readlink", &[&file], b"");
+    assert_eq!(output.status.code(), Some(1));
+    assert!(output.stdout.is_empty());
+}
+
+#[test]
+fn canonicalizes_chains() {
+    let dir = TempDir::new("readlink-chain");
+    let root = fs::canonicalize(dir.path()).unwrap();
+    let file = dir.file("file", b"");
+    symlink(&file, root.join("first")).unwrap();
+    symlink("first", root.join("second")).unwrap();
+    let second = root.join("second");
+    let output = run("readlink", &["-f", second.to_str().unwrap()], b"");
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(
+        stdout(&output),
+        format!("{}\n", root.join("file").display())
+    );
+}
+
+#[test]
+fn dangling_symlinks() {
+    let dir = TempDir::new("readlink-dangling");
+    let root = fs::canonicalize(dir.path()).unwrap();
+    symlink("missing/deeper", root.join("dangling")).unwrap();
+    let dangling = root.join("dangling");
+    let dangling = dangling.to_str().unwrap();
+
+    let output = run("readlink", &["-m", dangling], b"");
+    assert!(output.status.success());
+    let expected = format!("{}\n", root.join("missing/deeper").display());
+    assert_eq!(stdout(&output), expected);
+    assert_eq!(
+        run("readlink", &["-f", dangling], b"").status.code(),
+        Some(1)
+    );
+    assert_eq!(
+        run("readlink", &["-e", dangling], b"").status.code(),
+        Some(1)
+    );
+    // The last mode given wins.
+    assert!(run("readlink", &["-e", "-m", dangling], b"")
+        .status
+        .success());
+}
+
+#[test]
+fn no_newline() {
+    let dir = TempDir::new("readlink-newline");
+    symlink("target", dir.path().join("link")).unwrap();
+    let link = dir.path().join("link");
+    let link = link.to_str().unwrap();
+    let output = run("readlink", &["-n", link], b"");
+    assert_eq!(stdout(&output), "target");
+
+    let output = run("readlink", &["--no-newline", link, link], b"");
+    assert_eq!(stdout(&output), "target\ntarget\n");
+    assert!(stderr(&output).contains("ignoring --no-newline"));
+}
+
+#[test]
+fn missing_operand() {
+    let output = run("readlink", &[], b"");
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stderr(&output).contains("missing operand"));
+}
@@ -0,0 +1,298 @@
+extern crate integration;
+
+use integration::{binary, run, stderr, stdout, TempDir};
+use std::fs;
+use std::process::Command;
+
+fn seq(args: &[&str]) -> String {
+    let output = run("seq", args, b"");
+    assert!(
+        output.status.success(),
+        "seq {:?}: {}",
+        args,
+        stderr(&output)
+    );
+    stdout(&output)
+}
+
+#[test]
+fn last_only() {
+    assert_eq!(seq(&["3"]), "1\n2\n3\n");
+}
+
+#[test]
+fn first_and_last() {
+    assert_eq!(seq(&["2", "4"]), "2\n3\n4\n");
+}
+
+#[test]
+fn first_increment_last() {
+    assert_eq!(seq(&["1", "2", "7"]), "1\n3\n5\n7\n");
+}
+
+#[test]
+fn first_greater_than_last() {
+    assert_eq!(seq(&["10", "5"]), "");
+    assert_eq!(seq(&["2", "1", "1"]), "");
+}
+
+#[test]
+fn negative_increment() {
+    assert_eq!(seq(&["1", "-1", "5"]), "");
+    assert_eq!(seq(&["5", "-1", "1"]), "5\n4\n3\n2\n1\n");
+    assert_eq!(seq(&["-s,", "-1", "-1", "-3"]), "-1,-2,-3\n");
+}
+
+#[test]
+fn large_integers_are_printed_in_full() {
+    assert_eq!(seq(&["999999", "1000001"]), "999999\n1000000\n1000001\n");
+}
+
+#[test]
+fn fractional_operands() {
+    assert_eq!(seq(&["0.5", "1.5"]), "0.5\n1.5\n");
+    assert_eq!(seq(&["1.5", "3"]), "1.5\n2.5\n");
+}
+
+#[test]
+fn precision_of_last() {
+    assert_eq!(seq(&["1", "0.1", "1.3"]), "1.0\n1.1\n1.2\n1.3\n");
+    assert_eq!(seq(&["1", "0.1", "1.30"]), "1.00\n1.10\n1.20\n1.30\n");
+    let output = seq(&["1", "0.1", "2.00"]);
+    let lines: Vec<_> = output.lines().collect();
+    assert_eq!(lines.len(), 11);
+    assert_eq!(lines[0], "1.00");
+    assert_eq!(lines[10], "2.00");
+}
+
+#[test]
+fn hex_operands() {
+    assert_eq!(seq(&["0x1", "0x3"]), "1\n2\n3\n");
+    assert_eq!(seq(&["0xa", "0x2", "0x10"]), "10\n12\n14\n16\n");
+    assert_eq!(seq(&["0x1.8p0", "3"]), "1.5\n2.5\n");
+    // Exponents too large for a double give infinity, or NaN with a zero mantissa.
+    for args in &[&["0x1p99999"][..], &["0x0p99999", "2"][..]] {
+        let output = run("seq", args, b"");
+        assert_eq!(output.status.code(), Some(2), "{:?}", args);
+        assert!(output.stdout.is_empty());
+        assert!(stderr(&output).contains("invalid floating point argument"));
+    }
+}
+
+#[test]
+fn decimal_point_ignores_locale() {
+    let output = Command::new(binary("seq"))
+        .args(["1", "0.5", "2"])
+        .env("LC_ALL", "de_DE.UTF-8")
+        .env("LC_NUMERIC", "de_DE.UTF-8")
+        .output()
+        .unwrap();
+    assert_eq!(stdout(&output), "1.0\n1.5\n2.0\n");
+}
+
+#[test]
+fn separator() {
+    assert_eq!(seq(&["-s:", "3"]), "1:2:3\n");
+    assert_eq!(seq(&["--separator", ", ", "3"]), "1, 2, 3\n");
+}
+
+#[test]
+fn newline_follows_last_number_only() {
+    assert_eq!(seq(&["-s", ",", "1", "3"]), "1,2,3\n");
+    assert_eq!(seq(&["-s", "\n", "1", "3"]), "1\n2\n3\n");
+    assert_eq!(seq(&["-s", "", "1", "3"]), "123\n");
+    // Like GNU seq, escapes in the separator are not interpreted without -e.
+    assert_eq!(seq(&["-s", "\\n", "1", "3"]), "1\\n2\\n3\n");
+    assert_eq!(seq(&["-s", "\n\n", "1", "2"]), "1\n\n2\n");
+}
+
+#[test]
+fn escaped_separator() {
+    assert_eq!(seq(&["-e", "-s", "\\t", "1", "3"]), "1\t2\t3\n");
+    assert_eq!(seq(&["-s", "\\t", "1", "3"]), "1\\t2\\t3\n");
+    assert_eq!(seq(&["--sep-escape", "-s", "\\x2c\\\\", "2"]), "1,\\2\n");
+    assert_eq!(
+        run("seq", &["-e", "-s", "\\xff", "2"], b"").stdout,
+        b"1\xff2\n"
+    );
+}
+
+#[test]
+fn format() {
+    assert_eq!(seq(&["-f", "%.2f", "2"]), "1.00\n2.00\n");
+    assert_eq!(seq(&["--format=%.1f", "2", "3"]), "2.0\n3.0\n");
+}
+
+#[test]
+fn equal_width() {
+    assert_eq!(seq(&["-w", "8", "10"]), "08\n09\n10\n");
+    assert_eq!(seq(&["--equal-width", "-1", "1"]), "-1\n00\n01\n");
+    assert_eq!(
+        seq(&["-w", "-s", ",", "5", "-1", "-10"]),
+        "005,004,003,002,001,000,-01,-02,-03,-04,-05,-06,-07,-08,-09,-10\n"
+    );
+    let output = run("seq", &["-w", "-f", "%g", "1", "2"], b"");
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stderr(&output).contains("equal width"));
+}
+
+#[test]
+fn format_and_separator() {
+    assert_eq!(seq(&["-f", "%.1f", "-s", " ", "2"]), "1.0 2.0\n");
+}
+
+#[test]
+fn missing_operand() {
+    let output = run("seq", &[], b"");
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stderr(&output).contains("missing operand"));
+}
+
+#[test]
+fn extra_operand() {
+    let output = run("seq", &["1", "2", "3", "4"], b"");
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stderr(&output).contains("extra operand '4'"));
+}
+
+#[test]
+fn invalid_number() {
+    let output = run("seq", &["x"], b"");
+    assert_eq!(output.status.code(), Some(2));
+    assert!(output.stdout.is_empty());
+    assert!(stderr(&output).contains("'x'"));
+}
+
+#[test]
+fn infinite_and_nan_operands() {
+    for args in &[
+        &["inf"][..],
+        &["nan", "nan", "nan"][..],
+        &["1", "inf"][..],
+        &["1", "-1", "-inf"][..],
+        &["1", "NaN", "2"][..],
+    ] {
+        let output = run("seq", args, b"");
+        assert_eq!(output.status.code(), Some(2), "{:?}", args);
+        assert!(output.stdout.is_empty());
+        assert!(stderr(&output).contains("invalid floating point argument"));
+    }
+}
+
+#[test]
+fn zero_increment() {
+    for inc in &["0", "-0", "0.0"] {
+        let output = run("seq", &["1", inc, "5"], b"");
+        assert_eq!(output.status.code(), Some(2));
+        assert!(output.stdout.is_empty());
+        assert!(stderr(&output).contains("invalid Zero increment"));
+    }
+}
+
+#[test]
+fn invalid_format() {
+    let output = run("seq", &["-f", "%c", "3"], b"");
+    assert_eq!(output.status.code(), Some(2));
+    assert!(output.stdout.is_empty());
+}
+
+#[test]
+fn integer_format() {
+    assert_eq!(seq(&["-f", "%d", "1", "5"]), "1\n2\n3\n4\n5\n");
+    assert_eq!(seq(&["-f", "%03i", "10", "-5", "0"]), "010\n005\n000\n");
+    let output = run("seq", &["-f", "%d", "1", "0.5", "2"], b"");
+    assert_eq!(output.status.code(), Some(2));
+    assert!(output.stdout.is_empty());
+    assert!(stderr(&output).contains("needs integer arguments"));
+    // Outside the range of a long long, the numbers would saturate and never reach the end.
+    for args in &[
+        &["-f", "%d", "1e19", "1e19"][..],
+        &["-f", "%i", "-1e19", "0"],
+    ] {
+        let output = run("seq", args, b"");
+        assert_eq!(output.status.code(), Some(2), "{:?}", args);
+        assert!(output.stdout.is_empty());
+        assert!(stderr(&output).contains("needs integer arguments"));
+    }
+}
+
+#[test]
+fn write_error() {
+    let output = Command::new(binary("seq"))
+        .arg("3")
+        .stdout(fs::File::create("/dev/full").unwrap())
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr(&output).contains("No space left on device"));
+}
+
+#[test]
+fn json_output() {
+    let output = run("seq", &["--format-output=json", "1", "3"], b"");
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(stdout(&output), "[1,2,3]\n");
+    let output = run("seq", &["--format-output", "json", "-1", "0.5", "0"], b"");
+    assert_eq!(stdout(&output), "[-1.0,-0.5,0.0]\n");
+    assert_eq!(
+        stdout(&run("seq", &["--format-output=json", "0"], b"")),
+        "[]\n"
+    );
+    for args in &[
+        &["--format-output=json", "-s", ";", "3"][..],
+        &["--format-output=json", "-w", "3"][..],
+        &["--format-output=xml", "3"][..],
+    ] {
+        let output = run("seq", args, b"");
+        assert_eq!(output.status.code(), Some(2), "{:?}", args);
+        assert!(output.stdout.is_empty());
+    }
+}
+
+#[test]
+fn operands_from_file() {
+    let dir = TempDir::new("seq-from-file");
+    let three = dir.file("three", b"1 2 10\n");
+    assert_eq!(seq(&["--from-file", &three]), "1\n3\n5\n7\n9\n");
+    let one = dir.file("one", b"  3");
+    let arg = format!("--from-file={}", one);
+    assert_eq!(seq(&["-s", ",", &arg]), "1,2,3\n");
+    let negative = dir.file("negative", b"-1\t-1 -3\n");
+    assert_eq!(seq(&[&format!("--from-file={}", negative)]), "-1\n-2\n-3\n");
+
+    for (contents, error) in &[
+        (&b""[..], "missing operand"),
+        (&b"1 2 3 4"[..], "extra operand '4'"),
+        (&b"1\n2\n"[..], "single line"),
+    ] {
+        let file = dir.file("invalid", contents);
+        let output = run("seq", &["--from-file", &file], b"");
+        assert_eq!(output.status.code(), Some(2));
+        assert!(stderr(&output).contains(error), "{}", stderr(&output));
+    }
+    let output = run("seq", &["--from-file", &three, "5"], b"");
+    assert_eq!(output.status.code(), Some(2));
+    assert!(output.stdout.is_empty());
+    let output = run("seq", &["--from-file", "/nonexistent/file"], b"");
+    assert_eq!(output.status.code(), Some(1));
+}
+
+#[test]
+fn unknown_option() {
+    let output = run("seq", &["--bogus", "3"], b"");
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stderr(&output).contains("unrecognized option"));
+}
+
+#[test]
+fn help_and_version() {
+    let help = run("seq", &["-h"], b"");
+    assert!(help.status.success());
+    assert!(stdout(&help).contains("--separator"));
+    let version = run("seq", &["--version"], b"");
+    assert!(version.status.success());
+    assert_eq!(
+        stdout(&version),
+        format!("seq (coreutils-rs) {}\n", env!("CARGO_PKG_VERSION"))
+    );
+}
@@ -0,0 +1,106 @@
+extern crate integration;
+
+use integration::{binary, run, stderr, stdout};
+use std::env;
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Command, Output, Stdio};
+use std::sync::{mpsc, Once};
+use std::thread;
+use std::time::Duration;
+
+/// Builds the library that stdbuf preloads, which building the binary alone does not.
+fn build_library() {
+    static LIBRARY: Once = Once::new();
+    LIBRARY.call_once(|| {
+        let cargo = env::var("CARGO").unwrap_or_else(|_| "cargo".to_owned());
+        let status = Command::new(cargo)
+            .args(["build", "--quiet", "--lib", "--package", "stdbuf"])
+            .current_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/.."))
+            .status()
+            .unwrap();
+        assert!(status.success());
+    });
+}
+
+fn stdbuf(args: &[&str], stdin: &[u8]) -> Output {
+    build_library();
+    run("stdbuf", args, stdin)
+}
+
+#[test]
+fn modes_are_passed_to_the_library() {
+    let output = stdbuf(&["-o", "L", "-e0", "--input=4K", "env"], b"");
+    assert!(output.status.success(), "{}", stderr(&output));
+    let env = stdout(&output);
+    assert!(env.contains("\n_STDBUF_O=L\n"), "{}", env);
+    assert!(env.contains("\n_STDBUF_E=0\n"), "{}", env);
+    assert!(env.contains("\n_STDBUF_I=4096\n"), "{}", env);
+    let preload = env.lines().find(|l| l.starts_with("LD_PRELOAD=")).unwrap();
+    assert!(preload.ends_with("/libstdbuf.so"), "{}", preload);
+}
+
+/// The first line that sed, which writes through stdio, prints before its input ends, if any.
+fn first_line_before_eof(args: &[&str]) -> Option<String> {
+    build_library();
+    let mut child = Command::new(binary("stdbuf"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut stdin = child.stdin.take().unwrap();
+    stdin.write_all(b"a\n").unwrap();
+    stdin.flush().unwrap();
+    let stdout = child.stdout.take().unwrap();
+    let (sender, receiver) = mpsc::channel();
+    let reader = thread::spawn(move || {
+        let mut line = String::new();
+        BufReader::new(stdout).read_line(&mut line).unwrap();
+        let _ = sender.send(line);
+    });
+    let line = receiver.recv_timeout(Duration::from_millis(500)).ok();
+    drop(stdin);
+    child.wait().unwrap();
+    reader.join().unwrap();
+    line
+}
+
+#[test]
+fn line_buffered_output() {
+    assert_eq!(
+        first_line_before_eof(&["-oL", "sed", "s/a/b/"]),
+        Some("b\n".to_owned())
+    );
+    assert_eq!(
+        first_line_before_eof(&["-o0", "sed", "s/a/b/"]),
+        Some("b\n".to_owned())
+    );
+    // Into a pipe, stdio buffers fully by default.
+    assert_eq!(first_line_before_eof(&["-o1M", "sed", "s/a/b/"]), None);
+}
+
+#[test]
+fn invalid_arguments() {
+    for args in &[
+        &["env"][..],
+        &["-o", "x", "env"][..],
+        &["-i", "L", "env"][..],
+        &["-o", "L"][..],
+    ] {
+        let output = stdbuf(args, b"");
+        assert_eq!(output.status.code(), Some(125), "{:?}", args);
+        assert!(output.stdout.is_empty());
+    }
+    let output = stdbuf(&["-oL", "/nonexistent/command"], b"");
+    assert_eq!(output.status.code(), Some(127));
+}
+
+#[test]
+fn help_and_version() {
+    let help = stdbuf(&["--help"], b"");
+    assert!(help.status.success());
+    assert!(stdout(&help).contains("MODE"));
+    let version = stdbuf(&["--version"], b"");
+    assert!(version.status.success());
+    assert_eq!(stdout(&version), "stdbuf (coreutils-rs) 0.1.0\n");
+}
@@ -0,0 +1,39 @@
+extern crate integration;
+
+use integration::{run, stderr, stdout, TempDir};
+
+#[test]
+fn reads_stdin() {
+    let output = run("strings", &[], b"\x00\x01binary\x02\x03ab\x04");
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(stdout(&output), "binary\n");
+}
+
+#[test]
+fn offsets_restart_for_each_file() {
+    let dir = TempDir::new("strings-files");
+    let file = dir.file("file", b"\x00\x00first\x00second");
+    let output = run("strings", &["-t", "d", "-n", "5", &file, &file], b"");
+    assert_eq!(
+        stdout(&output),
+        "      2 first\n      8 second\n      2 first\n      8 second\n"
+    );
+}
+
+#[test]
+fn missing_file() {
+    let dir = TempDir::new("strings-missing");
+    let file = dir.file("file", b"\x00found\x00");
+    let output = run("strings", &["/nonexistent/file", &file], b"");
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(stdout(&output), "found\n");
+    assert!(stderr(&output).contains("/nonexistent/file"));
+}
+
+#[test]
+fn invalid_arguments() {
+    for args in &[&["-n", "0"][..], &["-t", "b"], &["--encoding=x"]] {
+        let output = run("strings", args, b"");
+        assert_eq!(output.status.code(), Some(2), "{:?}", args);
+    }
+}
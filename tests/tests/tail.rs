@@ -0,0 +1,204 @@
+extern crate integration;
+
+use integration::{binary, run, stderr, stdout, TempDir};
+use std::fs;
+use std::io::{Read, Write};
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+fn tail(args: &[&str], input: &[u8]) -> String {
+    let output = run("tail", args, input);
+    assert!(output.status.success(), "{:?}: {}", args, stderr(&output));
+    stdout(&output)
+}
+
+/// A `tail` left running to follow files, with its output passed on as it is read.
+struct Following {
+    child: Child,
+    output: mpsc::Receiver<Vec<u8>>,
+}
+
+impl Following {
+    fn spawn(args: &[&str]) -> Following {
+        let mut child = Command::new(binary("tail"))
+            .args(args)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .unwrap();
+        let mut stdout = child.stdout.take().unwrap();
+        let (sender, output) = mpsc::channel();
+        thread::spawn(move || {
+            let mut buffer = [0; 4096];
+            while let Ok(len) = stdout.read(&mut buffer) {
+                if len == 0 || sender.send(buffer[..len].to_vec()).is_err() {
+                    break;
+                }
+            }
+        });
+        Following { child, output }
+    }
+
+    /// Waits for @p expected to be output, failing once @p timeout passes without it.
+    fn expect(&self, expected: &str, timeout: Duration) {
+        let deadline = Instant::now() + timeout;
+        let mut received = Vec::new();
+        while received.len() < expected.len() {
+            let left = deadline.saturating_duration_since(Instant::now());
+            match self.output.recv_timeout(left) {
+                Ok(data) => received.extend(data),
+                Err(_) => panic!(
+                    "expected {:?} within {:?}, got {:?}",
+                    expected,
+                    timeout,
+                    String::from_utf8_lossy(&received)
+                ),
+            }
+        }
+        assert_eq!(String::from_utf8_lossy(&received), expected);
+    }
+}
+
+impl Drop for Following {
+    fn drop(&mut self) {
+        self.child.kill().unwrap();
+        self.child.wait().unwrap();
+    }
+}
+
+fn append(path: &str, data: &str) {
+    let mut file = fs::OpenOptions::new().append(true).open(path).unwrap();
+    file.write_all(data.as_bytes()).unwrap();
+}
+
+#[test]
+fn last_lines_and_bytes() {
+    let input: String = (1..=20).map(|n| format!("{}\n", n)).collect();
+    assert_eq!(tail(&[], input.as_bytes()), input[21..]);
+    assert_eq!(tail(&["-n", "3"], input.as_bytes()), "18\n19\n20\n");
+    assert_eq!(tail(&["--bytes=5"], input.as_bytes()), "9\n20\n");
+    assert_eq!(tail(&["-n", "+19"], input.as_bytes()), "19\n20\n");
+    assert_eq!(tail(&["-c", "+49"], input.as_bytes()), "20\n");
+    assert_eq!(tail(&["-z", "-n", "1"], b"a\0b\nc\0"), "b\nc\0");
+
+    // Regular files are read from their end, which has to give the same.
+    let dir = TempDir::new("tail-files");
+    let file = dir.file("file", input.as_bytes());
+    for args in &[&["-n", "3"][..], &["-c", "7"], &["-n", "+18"], &["-n", "0"]] {
+        let mut with_file = args.to_vec();
+        with_file.push(&file);
+        assert_eq!(
+            tail(&with_file, b""),
+            tail(args, input.as_bytes()),
+            "{:?}",
+            args
+        );
+    }
+}
+
+#[test]
+fn headers() {
+    let dir = TempDir::new("tail-headers");
+    let a = dir.file("a", b"1\n2\n");
+    let b = dir.file("b", b"3\n");
+    assert_eq!(
+        tail(&["-n", "1", &a, "-", &b], b"stdin\n"),
+        format!(
+            "==> {} <==\n2\n\n==> standard input <==\nstdin\n\n==> {} <==\n3\n",
+            a, b
+        )
+    );
+    assert_eq!(tail(&["-q", &a, &b], b""), "1\n2\n3\n");
+    assert_eq!(tail(&["-v", &b], b""), format!("==> {} <==\n3\n", b));
+}
+
+#[test]
+fn invalid_arguments() {
+    for args in &[
+        &["-n", "x"][..],
+        &["-c", "1", "-n", "1"],
+        &["--follow=sideways"],
+        &["-F", "-"],
+    ] {
+        assert_eq!(run("tail", args, b"").status.code(), Some(2), "{:?}", args);
+    }
+    let output = run("tail", &["-f", "/nonexistent"], b"");
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr(&output).contains("no files remaining"));
+}
+
+#[test]
+fn appended_data_arrives_promptly() {
+    let dir = TempDir::new("tail-follow");
+    let file = dir.file("log", b"old\n");
+    let tail = Following::spawn(&["-f", &file]);
+    tail.expect("old\n", Duration::from_secs(10));
+    for line in &["first\n", "second\n", "third\n"] {
+        let path = file.clone();
+        let writer = thread::spawn(move || append(&path, line));
+        // inotify wakes tail at once, well within its polling interval.
+        tail.expect(line, Duration::from_millis(50));
+        writer.join().unwrap();
+    }
+}
+
+#[test]
+fn truncated_files_are_read_from_the_start() {
+    let dir = TempDir::new("tail-truncate");
+    let file = dir.file("log", b"a long line\n");
+    let tail = Following::spawn(&["-f", &file]);
+    tail.expect("a long line\n", Duration::from_secs(10));
+    fs::write(&file, b"new\n").unwrap();
+    tail.expect("new\n", Duration::from_secs(10));
+}
+
+#[test]
+fn following_by_name_or_descriptor() {
+    let dir = TempDir::new("tail-rotate");
+    let by_name = dir.file("by-name", b"1\n");
+    let by_descriptor = dir.file("by-descriptor", b"1\n");
+    let names = Following::spawn(&["--follow=name", &by_name]);
+    let descriptors = Following::spawn(&["--follow=descriptor", &by_descriptor]);
+    names.expect("1\n", Duration::from_secs(10));
+    descriptors.expect("1\n", Duration::from_secs(10));
+
+    // Rotate both logs: what was written to them is still printed, but only the file followed
+    // by name is reopened and followed under its name.
+    for file in &[&by_name, &by_descriptor] {
+        append(file, "2\n");
+        fs::rename(file, format!("{}.1", file)).unwrap();
+        append(&format!("{}.1", file), "3\n");
+        fs::write(file, b"4\n").unwrap();
+    }
+    names.expect("2\n3\n4\n", Duration::from_secs(10));
+    descriptors.expect("2\n3\n", Duration::from_secs(10));
+    append(&format!("{}.1", by_descriptor), "5\n");
+    append(&by_name, "5\n");
+    names.expect("5\n", Duration::from_secs(10));
+    descriptors.expect("5\n", Duration::from_secs(10));
+}
+
+#[test]
+fn names_followed_while_missing() {
+    let dir = TempDir::new("tail-missing");
+    let file = dir.file("log", b"1\n");
+    let tail = Following::spawn(&["--follow=name", &file]);
+    tail.expect("1\n", Duration::from_secs(10));
+    // The name stays followed while no file has it, and the next file given it is read.
+    fs::remove_file(&file).unwrap();
+    thread::sleep(Duration::from_millis(300));
+    fs::write(&file, b"2\n").unwrap();
+    tail.expect("2\n", Duration::from_secs(10));
+}
+
+#[test]
+fn retrying_files_that_appear_later() {
+    let dir = TempDir::new("tail-retry");
+    let file = dir.path().join("later").to_str().unwrap().to_owned();
+    let tail = Following::spawn(&["-F", &file]);
+    thread::sleep(Duration::from_millis(200));
+    fs::write(&file, b"here\n").unwrap();
+    tail.expect("here\n", Duration::from_secs(10));
+}
@@ -0,0 +1,99 @@
+extern crate integration;
+
+use integration::{run, stderr, stdout};
+use std::time::{Duration, Instant};
+
+/// Runs timeout with @p args and returns its exit status and how long it took.
+fn timeout(args: &[&str]) -> (Option<i32>, Duration) {
+    let start = Instant::now();
+    let output = run("timeout", args, b"");
+    (output.status.code(), start.elapsed())
+}
+
+#[test]
+fn command_finishing_in_time() {
+    let output = run(
+        "timeout",
+        &["5", "sh", "-c", "echo -n $0; exit 3", "out"],
+        b"",
+    );
+    assert_eq!(output.status.code(), Some(3));
+    assert_eq!(stdout(&output), "out");
+    assert_eq!(timeout(&["0", "true"]).0, Some(0));
+    // Options after the duration belong to the command.
+    assert_eq!(timeout(&["5", "ls", "-d", "/"]).0, Some(0));
+}
+
+#[test]
+fn command_timing_out() {
+    let (code, elapsed) = timeout(&["0.2", "sleep", "10"]);
+    assert_eq!(code, Some(124));
+    assert!(elapsed < Duration::from_secs(5), "{:?}", elapsed);
+    assert_eq!(
+        timeout(&["--preserve-status", "0.2", "sleep", "10"]).0,
+        Some(128 + 15)
+    );
+    assert_eq!(
+        timeout(&["-s", "INT", "--preserve-status", "0.2", "sleep", "10"]).0,
+        Some(128 + 2)
+    );
+    assert_eq!(timeout(&["-s", "KILL", "0.2", "sleep", "10"]).0, Some(137));
+}
+
+#[test]
+fn children_of_the_command_are_signalled() {
+    let (code, elapsed) = timeout(&["0.2", "sh", "-c", "sleep 10 & sleep 10"]);
+    assert_eq!(code, Some(124));
+    assert!(elapsed < Duration::from_secs(5), "{:?}", elapsed);
+}
+
+#[test]
+fn kill_after() {
+    let args = [
+        "-v",
+        "-k",
+        "0.2",
+        "0.2",
+        "sh",
+        "-c",
+        "trap '' TERM; sleep 10",
+    ];
+    let start = Instant::now();
+    let output = run("timeout", &args, b"");
+    assert!(start.elapsed() < Duration::from_secs(5));
+    assert_eq!(output.status.code(), Some(137));
+    let messages = stderr(&output);
+    assert!(
+        messages.contains("sending signal TERM to command 'sh'"),
+        "{}",
+        messages
+    );
+    assert!(
+        messages.contains("sending signal KILL to command 'sh'"),
+        "{}",
+        messages
+    );
+}
+
+#[test]
+fn failures() {
+    assert_eq!(timeout(&["1", "/nonexistent"]).0, Some(127));
+    assert_eq!(timeout(&["1", "/"]).0, Some(126));
+    for args in &[
+        &["x", "true"][..],
+        &["-s", "FOO", "1", "true"][..],
+        &["1"][..],
+    ] {
+        assert_eq!(timeout(args).0, Some(125), "{:?}", args);
+    }
+}
+
+#[test]
+fn help_and_version() {
+    let help = run("timeout", &["--help"], b"");
+    assert!(help.status.success());
+    assert!(stdout(&help).contains("--kill-after"));
+    let version = run("timeout", &["--version"], b"");
+    assert!(version.status.success());
+    assert_eq!(stdout(&version), "timeout (coreutils-rs) 0.1.0\n");
+}
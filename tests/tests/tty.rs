@@ -0,0 +1,33 @@
+extern crate integration;
+
+use integration::{binary, run, stdout, TempDir};
+use std::fs;
+use std::process::{Command, Stdio};
+
+#[test]
+fn pipe_is_not_a_tty() {
+    let output = run("tty", &[], b"");
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(stdout(&output), "not a tty\n");
+}
+
+#[test]
+fn file_is_not_a_tty() {
+    let dir = TempDir::new("tty-file");
+    let file = dir.file("input", b"");
+    let output = Command::new(binary("tty"))
+        .stdin(Stdio::from(fs::File::open(file).unwrap()))
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+}
+
+#[test]
+fn silent_prints_nothing() {
+    for flag in &["-s", "--silent", "--quiet"] {
+        let output = run("tty", &[flag], b"");
+        assert_eq!(output.status.code(), Some(1));
+        assert!(output.stdout.is_empty());
+        assert!(output.stderr.is_empty());
+    }
+}
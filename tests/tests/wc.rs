@@ -0,0 +1,47 @@
+extern crate integration;
+
+use integration::{run, stderr, stdout, TempDir};
+
+#[test]
+fn counts_unicode_characters() {
+    let dir = TempDir::new("wc-unicode");
+    let file = dir.file("hello", "héllo".as_bytes());
+    let output = run("wc", &["-m", &file], b"");
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(stdout(&output), format!("5 {}\n", file));
+    let output = run("wc", &["-c", &file], b"");
+    assert_eq!(stdout(&output), format!("6 {}\n", file));
+    let output = run("wc", &["-lmc"], "héllo\nwörld\n".as_bytes());
+    assert_eq!(stdout(&output), "      2      12      14\n");
+}
+
+#[test]
+fn invalid_utf8_falls_back_to_bytes() {
+    let output = run("wc", &["-m"], b"h\xe9llo\n");
+    assert!(output.status.success());
+    assert_eq!(stdout(&output), "6\n");
+    assert!(stderr(&output).contains("invalid UTF-8"));
+}
+
+#[test]
+fn default_counts_and_total() {
+    let dir = TempDir::new("wc-total");
+    let first = dir.file("first", b"one two\nthree\n");
+    let second = dir.file("second", b"four five\n");
+    let output = run("wc", &[&first, &second], b"");
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(
+        stdout(&output),
+        format!(" 2  3 14 {}\n 1  2 10 {}\n 3  5 24 total\n", first, second)
+    );
+    let output = run("wc", &[], b"a b\nc");
+    assert_eq!(stdout(&output), "      1       3       5\n");
+}
+
+#[test]
+fn missing_file() {
+    let output = run("wc", &["-l", "/nonexistent", "-"], b"x\n");
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr(&output).contains("/nonexistent"));
+    assert_eq!(stdout(&output), "      1 -\n      1 total\n");
+}
@@ -0,0 +1,124 @@
+extern crate integration;
+
+use integration::{run, stderr, stdout};
+use std::time::{Duration, Instant};
+
+#[test]
+fn default_command_is_echo() {
+    let output = run("xargs", &[], b"a  b\n'c d' e\\ f\n");
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(stdout(&output), "a b c d e f\n");
+    // Without input, the command still runs once, unless -r is given.
+    assert_eq!(stdout(&run("xargs", &["echo", "x"], b"")), "x\n");
+    assert_eq!(stdout(&run("xargs", &["-r", "echo", "x"], b"")), "");
+}
+
+#[test]
+fn null_terminated_items() {
+    let output = run(
+        "xargs",
+        &["-0", "printf", "[%s]\\n"],
+        b"a b\0'c\"\n\0d\\e\0",
+    );
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(stdout(&output), "[a b]\n['c\"\n]\n[d\\e]\n");
+    let output = run("xargs", &["-d", ",", "printf", "[%s]"], b"a b,c");
+    assert_eq!(stdout(&output), "[a b][c]");
+}
+
+#[test]
+fn max_args_and_lines() {
+    let output = run("xargs", &["-n", "2"], b"1 2 3\n4 5");
+    assert_eq!(stdout(&output), "1 2\n3 4\n5\n");
+    let output = run("xargs", &["--max-args=1", "echo", "-"], b"a b");
+    assert_eq!(stdout(&output), "- a\n- b\n");
+    // A line ending with a blank goes on with the next one.
+    let output = run("xargs", &["-L", "1"], b"a b \nc\nd e\n\nf\n");
+    assert_eq!(stdout(&output), "a b c\nd e\nf\n");
+}
+
+#[test]
+fn replace_string() {
+    let output = run(
+        "xargs",
+        &["-I", "{}", "echo", "<{}>", "{}{}"],
+        b"  a b\nc\n",
+    );
+    assert_eq!(stdout(&output), "<a b> a ba b\n<c> cc\n");
+    assert_eq!(stdout(&run("xargs", &["-I{}", "echo", "{}"], b"")), "");
+}
+
+#[test]
+fn parallel_commands() {
+    let start = Instant::now();
+    let output = run(
+        "xargs",
+        &["-P", "4", "-n", "1", "sleep"],
+        b"0.5 0.5 0.5 0.5",
+    );
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(start.elapsed() < Duration::from_millis(1500));
+
+    let start = Instant::now();
+    let output = run(
+        "xargs",
+        &["-P", "2", "-n", "1", "sleep"],
+        b"0.3 0.3 0.3 0.3",
+    );
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(start.elapsed() >= Duration::from_millis(600));
+
+    let output = run("xargs", &["-P", "0", "-n", "1"], b"a b c");
+    let mut lines: Vec<_> = stdout(&output).lines().map(str::to_owned).collect();
+    lines.sort();
+    assert_eq!(lines, ["a", "b", "c"]);
+}
+
+#[test]
+fn verbose() {
+    let output = run("xargs", &["-t", "-n", "2", "echo"], b"a b c");
+    assert_eq!(stdout(&output), "a b\nc\n");
+    assert_eq!(stderr(&output), "echo a b\necho c\n");
+}
+
+#[test]
+fn exit_statuses() {
+    let output = run("xargs", &["-n", "1", "sh", "-c", "exit $0"], b"0 1 0");
+    assert_eq!(output.status.code(), Some(123));
+    let output = run(
+        "xargs",
+        &["-n", "1", "sh", "-c", "echo $0; exit 255"],
+        b"a b",
+    );
+    assert_eq!(output.status.code(), Some(124));
+    assert_eq!(stdout(&output), "a\n");
+    let output = run("xargs", &["sh", "-c", "kill $$"], b"a");
+    assert_eq!(output.status.code(), Some(125));
+    let output = run("xargs", &["/nonexistent/command"], b"a");
+    assert_eq!(output.status.code(), Some(127));
+    let output = run("xargs", &["/"], b"a");
+    assert_eq!(output.status.code(), Some(126));
+}
+
+#[test]
+fn invalid_input_and_arguments() {
+    let output = run("xargs", &[], b"a 'b");
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(stdout(&output), "a\n");
+    assert!(stderr(&output).contains("unmatched single quote"));
+    for args in &[&["-n", "0"][..], &["-P", "x"][..], &["-d", "ab"][..]] {
+        let output = run("xargs", args, b"a");
+        assert_eq!(output.status.code(), Some(2), "{:?}", args);
+        assert!(output.stdout.is_empty());
+    }
+}
+
+#[test]
+fn help_and_version() {
+    let help = run("xargs", &["--help"], b"");
+    assert!(help.status.success());
+    assert!(stdout(&help).contains("--max-procs"));
+    let version = run("xargs", &["--version"], b"");
+    assert!(version.status.success());
+    assert_eq!(stdout(&version), "xargs (coreutils-rs) 0.1.0\n");
+}
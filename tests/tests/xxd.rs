@@ -0,0 +1,55 @@
+extern crate integration;
+
+use integration::{run, stderr, stdout, TempDir};
+
+#[test]
+fn round_trip() {
+    let data: Vec<u8> = (0..5000u32).map(|i| (i * 7919 % 251) as u8).collect();
+    let dir = TempDir::new("xxd-round-trip");
+    let file = dir.file("data", &data);
+    for args in &[&[][..], &["-c", "7", "-g", "3"], &["-u", "-g", "0"]] {
+        let mut dump_args = args.to_vec();
+        dump_args.push(&file);
+        let dumped = run("xxd", &dump_args, b"");
+        assert!(dumped.status.success(), "{}", stderr(&dumped));
+        let restored = run("xxd", &["-r"], &dumped.stdout);
+        assert!(restored.status.success(), "{}", stderr(&restored));
+        assert_eq!(restored.stdout, data, "{:?}", args);
+    }
+}
+
+#[test]
+fn reverse_rejects_corrupt_dump() {
+    let data: Vec<u8> = (0..100u8).collect();
+    let dumped = run("xxd", &["-c", "10"], &data).stdout;
+    let mut corrupt = String::from_utf8(dumped).unwrap();
+    corrupt = corrupt.replacen("1415", "14x5", 1);
+    let restored = run("xxd", &["-r"], corrupt.as_bytes());
+    assert_eq!(restored.status.code(), Some(1));
+    assert!(stderr(&restored).contains("line 3: invalid hex digit 'x'"));
+}
+
+#[test]
+fn seek_and_length() {
+    let output = run("xxd", &["-s", "3", "-l", "4"], b"0123456789");
+    assert_eq!(
+        stdout(&output),
+        "00000003: 3334 3536                                3456\n"
+    );
+}
+
+#[test]
+fn output_file() {
+    let dir = TempDir::new("xxd-output");
+    let dump = dir.file("dump", b"00000000: 6869 0a  hi.\n");
+    let output = dir.path().join("restored");
+    let result = run("xxd", &["-r", &dump, output.to_str().unwrap()], b"");
+    assert!(result.status.success(), "{}", stderr(&result));
+    assert_eq!(std::fs::read(output).unwrap(), b"hi\n".to_vec());
+}
+
+#[test]
+fn include_from_stdin() {
+    let output = run("xxd", &["-i"], b"\x01\x02");
+    assert_eq!(stdout(&output), "  0x01, 0x02\n");
+}
@@ -0,0 +1,404 @@
+//! A small command line parser following the GNU conventions: short flags which may be
+//! grouped (`-abc`), long flags (`--all`), options taking a value (`-s SEP`, `-sSEP`,
+//! `--separator SEP`, `--separator=SEP`) and `--` to end the options.
+
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Value {
+    None,
+    Required,
+    Optional,
+}
+
+#[derive(Debug)]
+struct Spec {
+    short: String,
+    long: String,
+    help: String,
+    hint: String,
+    value: Value,
+}
+
+impl Spec {
+    fn is_named(&self, name: &str) -> bool {
+        !name.is_empty() && (self.short == name || self.long == name)
+    }
+}
+
+/// Why the arguments could not be parsed. Each variant holds the option as it was written,
+/// e.g. "-x" or "--long"; messages follow those of GNU getopt.
+#[derive(Debug, PartialEq)]
+pub enum ArgError {
+    UnrecognizedOption(String),
+    ArgumentMissing(String),
+    UnexpectedArgument(String),
+}
+
+impl fmt::Display for ArgError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ArgError::UnrecognizedOption(ref option) if option.starts_with("--") => {
+                write!(f, "unrecognized option '{}'", option)
+            }
+            ArgError::UnrecognizedOption(ref option) => {
+                write!(f, "invalid option -- '{}'", &option[1..])
+            }
+            ArgError::ArgumentMissing(ref option) if option.starts_with("--") => {
+                write!(f, "option '{}' requires an argument", option)
+            }
+            ArgError::ArgumentMissing(ref option) => {
+                write!(f, "option requires an argument -- '{}'", &option[1..])
+            }
+            ArgError::UnexpectedArgument(ref option) => {
+                write!(f, "option '{}' doesn't allow an argument", option)
+            }
+        }
+    }
+}
+
+/// The set of options a program accepts.
+#[derive(Debug, Default)]
+pub struct ArgParser {
+    specs: Vec<Spec>,
+    stop_at_first_free: bool,
+}
+
+impl ArgParser {
+    pub fn new() -> ArgParser {
+        ArgParser::default()
+    }
+
+    fn add(
+        &mut self,
+        short: &str,
+        long: &str,
+        help: &str,
+        hint: &str,
+        value: Value,
+    ) -> &mut ArgParser {
+        assert!(
+            short.chars().count() <= 1,
+            "short option names are one character"
+        );
+        self.specs.push(Spec {
+            short: short.to_owned(),
+            long: long.to_owned(),
+            help: help.to_owned(),
+            hint: hint.to_owned(),
+            value,
+        });
+        self
+    }
+
+    /// Adds an option without a value. Either name may be empty.
+    pub fn flag(&mut self, short: &str, long: &str, help: &str) -> &mut ArgParser {
+        self.add(short, long, help, "", Value::None)
+    }
+
+    /// Adds an option which requires a value, described by @p hint in the usage.
+    pub fn option(&mut self, short: &str, long: &str, help: &str, hint: &str) -> &mut ArgParser {
+        self.add(short, long, help, hint, Value::Required)
+    }
+
+    /// Adds an option whose value is optional. The value must then be attached: `-sVALUE` or
+    /// `--long=VALUE`.
+    pub fn optional_option(
+        &mut self,
+        short: &str,
+        long: &str,
+        help: &str,
+        hint: &str,
+    ) -> &mut ArgParser {
+        self.add(short, long, help, hint, Value::Optional)
+    }
+
+    /// Treats everything from the first operand on as operands, as needed by programs which
+    /// run a command with its own options.
+    pub fn stop_at_first_free(&mut self) -> &mut ArgParser {
+        self.stop_at_first_free = true;
+        self
+    }
+
+    fn find(&self, name: &str, option: &str) -> Result<usize, ArgError> {
+        self.specs
+            .iter()
+            .position(|spec| spec.is_named(name))
+            .ok_or_else(|| ArgError::UnrecognizedOption(option.to_owned()))
+    }
+
+    /// Parses @p args, which should not include the program name.
+    pub fn parse(&self, args: &[String]) -> Result<ParsedArgs, ArgError> {
+        let mut parsed = ParsedArgs {
+            matches: Vec::new(),
+            names: self
+                .specs
+                .iter()
+                .map(|spec| (spec.short.clone(), spec.long.clone()))
+                .collect(),
+            free: Vec::new(),
+        };
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            if arg == "--" {
+                parsed.free.extend(args.cloned());
+                break;
+            } else if let Some(long) = arg.strip_prefix("--") {
+                let (name, attached) = match long.find('=') {
+                    Some(n) => (&long[..n], Some(long[n + 1..].to_owned())),
+                    None => (long, None),
+                };
+                let option = format!("--{}", name);
+                let index = self.find(name, &option)?;
+                let value = match (self.specs[index].value, attached) {
+                    (Value::None, Some(_)) => return Err(ArgError::UnexpectedArgument(option)),
+                    (Value::Required, None) => Some(
+                        args.next()
+                            .cloned()
+                            .ok_or(ArgError::ArgumentMissing(option))?,
+                    ),
+                    (_, attached) => attached,
+                };
+                parsed.matches.push((index, value));
+            } else if arg.len() > 1 && arg.starts_with('-') {
+                let shorts = &arg[1..];
+                for (offset, ch) in shorts.char_indices() {
+                    let option = format!("-{}", ch);
+                    let index = self.find(&option[1..], &option)?;
+                    let rest = &shorts[offset + ch.len_utf8()..];
+                    let value = match self.specs[index].value {
+                        Value::None => {
+                            parsed.matches.push((index, None));
+                            continue;
+                        }
+                        Value::Optional if rest.is_empty() => None,
+                        Value::Required if rest.is_empty() => Some(
+                            args.next()
+                                .cloned()
+                                .ok_or(ArgError::ArgumentMissing(option))?,
+                        ),
+                        _ => Some(rest.to_owned()),
+                    };
+                    parsed.matches.push((index, value));
+                    break;
+                }
+            } else {
+                parsed.free.push(arg.clone());
+                if self.stop_at_first_free {
+                    parsed.free.extend(args.cloned());
+                    break;
+                }
+            }
+        }
+        Ok(parsed)
+    }
+
+    /// Formats @p brief followed by a description of every option.
+    pub fn usage(&self, brief: &str) -> String {
+        let rows: Vec<String> = self
+            .specs
+            .iter()
+            .map(|spec| {
+                let mut row = if spec.short.is_empty() {
+                    "    ".to_owned()
+                } else if spec.long.is_empty() {
+                    format!("-{}", spec.short)
+                } else {
+                    format!("-{}, ", spec.short)
+                };
+                if !spec.long.is_empty() {
+                    row += "--";
+                    row += &spec.long;
+                }
+                match spec.value {
+                    Value::None => {}
+                    Value::Required if spec.long.is_empty() => row += &format!(" {}", spec.hint),
+                    Value::Required => row += &format!("={}", spec.hint),
+                    Value::Optional if spec.long.is_empty() => row += &format!("[{}]", spec.hint),
+                    Value::Optional => row += &format!("[={}]", spec.hint),
+                }
+                row
+            })
+            .collect();
+        let width = rows
+            .iter()
+            .map(|row| row.chars().count())
+            .max()
+            .unwrap_or(0);
+        let mut usage = format!("{}\n\nOptions:\n", brief);
+        for (row, spec) in rows.iter().zip(self.specs.iter()) {
+            usage += &format!("    {:width$}  {}\n", row, spec.help, width = width);
+        }
+        usage
+    }
+}
+
+/// The result of `ArgParser::parse`. Options can be queried by either of their names.
+#[derive(Debug)]
+pub struct ParsedArgs {
+    /// The options given, in order, as indices into `names` and their values.
+    matches: Vec<(usize, Option<String>)>,
+    /// The short and long name of every option known to the parser.
+    names: Vec<(String, String)>,
+    /// The operands.
+    pub free: Vec<String>,
+}
+
+impl ParsedArgs {
+    fn is_named(&self, index: usize, name: &str) -> bool {
+        let (ref short, ref long) = self.names[index];
+        !name.is_empty() && (short == name || long == name)
+    }
+
+    fn values<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Option<String>> + 'a {
+        self.matches
+            .iter()
+            .filter(move |m| self.is_named(m.0, name))
+            .map(|m| &m.1)
+    }
+
+    pub fn opt_present(&self, name: &str) -> bool {
+        self.values(name).next().is_some()
+    }
+
+    /// The value of the last occurrence of option @p name.
+    pub fn opt_str(&self, name: &str) -> Option<String> {
+        self.values(name).flatten().last().cloned()
+    }
+
+    /// The values of every occurrence of option @p name.
+    pub fn opt_strs(&self, name: &str) -> Vec<String> {
+        self.values(name).flatten().cloned().collect()
+    }
+
+    /// The positions of option @p name among all options given, e.g. to let the last of
+    /// several conflicting options win.
+    pub fn opt_positions(&self, name: &str) -> Vec<usize> {
+        self.matches
+            .iter()
+            .enumerate()
+            .filter(|&(_, m)| self.is_named(m.0, name))
+            .map(|(position, _)| position)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parser() -> ArgParser {
+        let mut parser = ArgParser::new();
+        parser
+            .flag("n", "number", "number lines")
+            .flag("E", "show-ends", "show ends")
+            .flag("", "long-only", "no short name")
+            .option("s", "separator", "separator", "SEP")
+            .optional_option("", "numeric", "numeric suffixes", "FROM");
+        parser
+    }
+
+    fn parse(args: &[&str]) -> Result<ParsedArgs, ArgError> {
+        let args: Vec<String> = args.iter().map(|s| s.to_string()).collect();
+        parser().parse(&args)
+    }
+
+    #[test]
+    fn flags() {
+        let parsed = parse(&["-n", "a", "--show-ends", "b"]).unwrap();
+        assert!(parsed.opt_present("n") && parsed.opt_present("number"));
+        assert!(parsed.opt_present("E"));
+        assert!(!parsed.opt_present("long-only"));
+        assert!(!parsed.opt_present(""));
+        assert_eq!(parsed.free, vec!["a", "b"]);
+
+        let grouped = parse(&["-nE", "--long-only", "-"]).unwrap();
+        assert!(grouped.opt_present("number") && grouped.opt_present("show-ends"));
+        assert!(grouped.opt_present("long-only"));
+        assert_eq!(grouped.free, vec!["-"]);
+    }
+
+    #[test]
+    fn option_values() {
+        for args in [
+            &["-s", ","][..],
+            &["-s,"],
+            &["--separator", ","],
+            &["--separator=,"],
+            &["-ns,"],
+        ] {
+            assert_eq!(parse(args).unwrap().opt_str("s"), Some(",".to_owned()));
+        }
+        let parsed = parse(&["-s", "-n", "--separator=", "-s", "x"]).unwrap();
+        assert!(!parsed.opt_present("n"));
+        assert_eq!(parsed.opt_str("separator"), Some("x".to_owned()));
+        assert_eq!(parsed.opt_strs("s"), vec!["-n", "", "x"]);
+    }
+
+    #[test]
+    fn optional_values() {
+        let bare = parse(&["--numeric", "x"]).unwrap();
+        assert!(bare.opt_present("numeric"));
+        assert_eq!(bare.opt_str("numeric"), None);
+        assert_eq!(bare.free, vec!["x"]);
+        let given = parse(&["--numeric=3"]).unwrap();
+        assert_eq!(given.opt_str("numeric"), Some("3".to_owned()));
+    }
+
+    #[test]
+    fn end_of_options() {
+        let parsed = parse(&["a", "--", "-n", "--separator"]).unwrap();
+        assert!(!parsed.opt_present("n"));
+        assert_eq!(parsed.free, vec!["a", "-n", "--separator"]);
+    }
+
+    #[test]
+    fn stop_at_first_free() {
+        let mut parser = parser();
+        parser.stop_at_first_free();
+        let args: Vec<String> = ["-n", "cmd", "-E"].iter().map(|s| s.to_string()).collect();
+        let parsed = parser.parse(&args).unwrap();
+        assert!(parsed.opt_present("n") && !parsed.opt_present("E"));
+        assert_eq!(parsed.free, vec!["cmd", "-E"]);
+    }
+
+    #[test]
+    fn positions() {
+        let parsed = parse(&["-n", "-E", "--number", "-s", "x"]).unwrap();
+        assert_eq!(parsed.opt_positions("n"), vec![0, 2]);
+        assert_eq!(parsed.opt_positions("E"), vec![1]);
+    }
+
+    #[test]
+    fn errors() {
+        let error = |args: &[&str]| parse(args).unwrap_err().to_string();
+        assert_eq!(error(&["-x"]), "invalid option -- 'x'");
+        assert_eq!(error(&["--bogus"]), "unrecognized option '--bogus'");
+        assert_eq!(error(&["-s"]), "option requires an argument -- 's'");
+        assert_eq!(
+            error(&["--separator"]),
+            "option '--separator' requires an argument"
+        );
+        assert_eq!(
+            error(&["--number=3"]),
+            "option '--number' doesn't allow an argument"
+        );
+    }
+
+    #[test]
+    fn usage() {
+        assert_eq!(
+            parser().usage("Usage: test"),
+            concat!(
+                "Usage: test\n",
+                "\n",
+                "Options:\n",
+                "    -n, --number          number lines\n",
+                "    -E, --show-ends       show ends\n",
+                "        --long-only       no short name\n",
+                "    -s, --separator=SEP   separator\n",
+                "        --numeric[=FROM]  numeric suffixes\n",
+            )
+        );
+    }
+}
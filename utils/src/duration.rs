@@ -0,0 +1,45 @@
+//! Parsing of the NUMBER[SUFFIX] time intervals that sleep and timeout accept.
+
+/// Parses a single NUMBER[SUFFIX] operand into a number of seconds. SUFFIX may be 's', 'm',
+/// 'h' or 'd'.
+pub fn parse_duration(arg: &str) -> Result<f64, String> {
+    let (number, multiplier) = match arg.chars().last() {
+        Some('s') => (&arg[..arg.len() - 1], 1.0),
+        Some('m') => (&arg[..arg.len() - 1], 60.0),
+        Some('h') => (&arg[..arg.len() - 1], 60.0 * 60.0),
+        Some('d') => (&arg[..arg.len() - 1], 24.0 * 60.0 * 60.0),
+        _ => (arg, 1.0),
+    };
+    let value = match number.parse::<f64>() {
+        Ok(value) if value >= 0.0 && value.is_finite() => value,
+        _ => return Err(format!("invalid time interval '{}'", arg)),
+    };
+    Ok(value * multiplier)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn suffixes() {
+        assert_eq!(parse_duration("2"), Ok(2.0));
+        assert_eq!(parse_duration("2s"), Ok(2.0));
+        assert_eq!(parse_duration("2m"), Ok(120.0));
+        assert_eq!(parse_duration("2h"), Ok(7200.0));
+        assert_eq!(parse_duration("2d"), Ok(172_800.0));
+        assert_eq!(parse_duration("0.5"), Ok(0.5));
+        assert_eq!(parse_duration(".5m"), Ok(30.0));
+    }
+
+    #[test]
+    fn invalid_intervals() {
+        assert!(parse_duration("").is_err());
+        assert!(parse_duration("s").is_err());
+        assert!(parse_duration("-1").is_err());
+        assert!(parse_duration("1x").is_err());
+        assert!(parse_duration("1ss").is_err());
+        assert!(parse_duration("nan").is_err());
+        assert!(parse_duration("inf").is_err());
+    }
+}
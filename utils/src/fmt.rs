@@ -0,0 +1,58 @@
+//! Formatting of numbers in right-aligned columns, as cat -n and nl print line numbers.
+
+use std::io::{self, Write};
+
+/// Right-aligns numbers in a column of a fixed width, each followed by a separator.
+#[derive(Clone, Debug)]
+pub struct ColumnFormatter {
+    width: usize,
+    separator: String,
+}
+
+impl ColumnFormatter {
+    pub fn new(width: usize, separator: &str) -> ColumnFormatter {
+        ColumnFormatter {
+            width,
+            separator: separator.to_owned(),
+        }
+    }
+
+    /// The width of a column wide enough for every number from 0 to @p max.
+    pub fn width_for(max: u64) -> usize {
+        max.to_string().len()
+    }
+
+    /// @p n right-aligned in the column, followed by the separator. Numbers wider than the
+    /// column are not cut.
+    pub fn format(&self, n: i64) -> String {
+        format!("{:>width$}{}", n, self.separator, width = self.width)
+    }
+
+    /// Writes what `format` returns to @p writer, without allocating it first, for callers
+    /// which number every line.
+    pub fn write(&self, writer: &mut dyn Write, n: i64) -> io::Result<()> {
+        write!(writer, "{:>width$}", n, width = self.width)?;
+        writer.write_all(self.separator.as_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn columns() {
+        let formatter = ColumnFormatter::new(6, "\t");
+        assert_eq!(formatter.format(1), "     1\t");
+        assert_eq!(formatter.format(-42), "   -42\t");
+        assert_eq!(formatter.format(1234567), "1234567\t");
+        assert_eq!(ColumnFormatter::new(0, "").format(7), "7");
+        let mut output = Vec::new();
+        formatter.write(&mut output, -42).unwrap();
+        assert_eq!(output, formatter.format(-42).as_bytes());
+        assert_eq!(ColumnFormatter::width_for(0), 1);
+        assert_eq!(ColumnFormatter::width_for(9), 1);
+        assert_eq!(ColumnFormatter::width_for(10), 2);
+        assert_eq!(ColumnFormatter::width_for(u64::MAX), 20);
+    }
+}
@@ -0,0 +1,216 @@
+//! Opening of the FILE... operands most tools accept.
+
+use std::fs;
+use std::io::{self, BufRead, BufReader, Read};
+
+/// An input operand: the standard input ("-") or a named file.
+pub enum FileOrStdin {
+    Stdin(io::StdinLock<'static>),
+    File(BufReader<fs::File>),
+}
+
+impl FileOrStdin {
+    /// Opens @p name, where "-" denotes the standard input. Directories are rejected up front
+    /// rather than failing on the first read.
+    pub fn open(name: &str) -> io::Result<FileOrStdin> {
+        if name == "-" {
+            return Ok(FileOrStdin::Stdin(io::stdin().lock()));
+        }
+        let file = fs::File::open(name)?;
+        if file.metadata()?.is_dir() {
+            return Err(io::ErrorKind::IsADirectory.into());
+        }
+        Ok(FileOrStdin::File(BufReader::new(file)))
+    }
+
+    pub fn is_stdin(&self) -> bool {
+        match *self {
+            FileOrStdin::Stdin(_) => true,
+            FileOrStdin::File(_) => false,
+        }
+    }
+}
+
+impl Read for FileOrStdin {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match *self {
+            FileOrStdin::Stdin(ref mut stdin) => stdin.read(buf),
+            FileOrStdin::File(ref mut file) => file.read(buf),
+        }
+    }
+}
+
+impl BufRead for FileOrStdin {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        match *self {
+            FileOrStdin::Stdin(ref mut stdin) => stdin.fill_buf(),
+            FileOrStdin::File(ref mut file) => file.fill_buf(),
+        }
+    }
+
+    fn consume(&mut self, amount: usize) {
+        match *self {
+            FileOrStdin::Stdin(ref mut stdin) => stdin.consume(amount),
+            FileOrStdin::File(ref mut file) => file.consume(amount),
+        }
+    }
+}
+
+/// Reads from another reader with every "\r\n" line ending turned into "\n". A lone "\r" is
+/// left alone. Tools with a binary mode should read the underlying reader directly there.
+pub struct CrlfAwareBufReader<R> {
+    inner: R,
+    buf: Vec<u8>,
+    pos: usize,
+    // The last chunk ended with '\r', which is kept back until the next byte is known.
+    pending_cr: bool,
+}
+
+impl<R: BufRead> CrlfAwareBufReader<R> {
+    pub fn new(inner: R) -> CrlfAwareBufReader<R> {
+        CrlfAwareBufReader {
+            inner,
+            buf: Vec::new(),
+            pos: 0,
+            pending_cr: false,
+        }
+    }
+}
+
+impl<R: BufRead> Read for CrlfAwareBufReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = {
+            let available = self.fill_buf()?;
+            let n = available.len().min(buf.len());
+            buf[..n].copy_from_slice(&available[..n]);
+            n
+        };
+        self.consume(n);
+        Ok(n)
+    }
+}
+
+impl<R: BufRead> BufRead for CrlfAwareBufReader<R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        while self.pos >= self.buf.len() {
+            self.buf.clear();
+            self.pos = 0;
+            let input = self.inner.fill_buf()?;
+            if input.is_empty() {
+                if self.pending_cr {
+                    self.pending_cr = false;
+                    self.buf.push(b'\r');
+                }
+                break;
+            }
+            if self.pending_cr && input[0] != b'\n' {
+                self.buf.push(b'\r');
+            }
+            self.pending_cr = false;
+            for (i, &byte) in input.iter().enumerate() {
+                if byte != b'\r' {
+                    self.buf.push(byte);
+                } else if i + 1 == input.len() {
+                    self.pending_cr = true;
+                } else if input[i + 1] != b'\n' {
+                    self.buf.push(byte);
+                }
+            }
+            let len = input.len();
+            self.inner.consume(len);
+        }
+        Ok(&self.buf[self.pos..])
+    }
+
+    fn consume(&mut self, amount: usize) {
+        self.pos = (self.pos + amount).min(self.buf.len());
+    }
+}
+
+/// Opens each of @p files in turn, or just the standard input if there are none. Files are
+/// opened lazily, and each comes with the result of opening it: a file which cannot be opened is
+/// for the caller to report before it goes on to the next one.
+///
+/// Every "-" locks the standard input, so each reader must be dropped before the next one is
+/// requested.
+pub fn open_inputs(files: &[String]) -> impl Iterator<Item = (String, io::Result<FileOrStdin>)> {
+    let names = if files.is_empty() {
+        vec!["-".to_owned()]
+    } else {
+        files.to_vec()
+    };
+    names.into_iter().map(|name| {
+        let reader = FileOrStdin::open(&name);
+        (name, reader)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    #[test]
+    fn no_files_means_stdin() {
+        let inputs: Vec<String> = open_inputs(&[]).map(|(name, _)| name).collect();
+        assert_eq!(inputs, vec!["-".to_owned()]);
+        assert!(FileOrStdin::open("-").unwrap().is_stdin());
+    }
+
+    #[test]
+    fn named_files_are_read() {
+        let path = env::temp_dir().join(format!("utils-io-test-{}", std::process::id()));
+        fs::write(&path, "line\n").unwrap();
+        let name = path.to_str().unwrap().to_owned();
+        for (opened, reader) in open_inputs(std::slice::from_ref(&name)) {
+            let mut contents = String::new();
+            reader.unwrap().read_to_string(&mut contents).unwrap();
+            assert_eq!((opened, contents), (name.clone(), "line\n".to_owned()));
+        }
+        fs::remove_file(&path).unwrap();
+        assert!(FileOrStdin::open("/").is_err());
+    }
+
+    fn normalized(input: &[u8], capacity: usize) -> Vec<u8> {
+        let inner = BufReader::with_capacity(capacity, input);
+        let mut output = Vec::new();
+        CrlfAwareBufReader::new(inner)
+            .read_to_end(&mut output)
+            .unwrap();
+        output
+    }
+
+    #[test]
+    fn crlf_becomes_lf() {
+        let input = b"one\r\ntwo\rthree\n\r\r\nend\r";
+        // Small buffers split the input between '\r' and '\n'.
+        for capacity in 1..input.len() + 1 {
+            assert_eq!(
+                normalized(input, capacity),
+                b"one\ntwo\rthree\n\r\nend\r".to_vec(),
+                "capacity {}",
+                capacity
+            );
+        }
+        let lines: Vec<String> = CrlfAwareBufReader::new(&b"a\r\nb\r\n"[..])
+            .lines()
+            .map(|line| line.unwrap())
+            .collect();
+        assert_eq!(lines, vec!["a", "b"]);
+    }
+
+    #[test]
+    fn missing_file_is_an_error() {
+        let names = ["/nonexistent/file".to_owned(), "-".to_owned()];
+        let opened: Vec<(String, bool)> = open_inputs(&names)
+            .map(|(name, reader)| (name, reader.is_ok()))
+            .collect();
+        assert_eq!(
+            opened,
+            vec![
+                ("/nonexistent/file".to_owned(), false),
+                ("-".to_owned(), true)
+            ]
+        );
+    }
+}
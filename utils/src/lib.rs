@@ -1,15 +1,203 @@
+#[macro_use]
 pub mod utils {
 
+    /// Prints an error message prefixed with the program name and exits, with status 1 or the
+    /// one given as `code = N` before the message: `die!(code = 2, "missing operand")`. POSIX
+    /// tools use 2 for usage errors, such as bad options or operands, and 1 for failures at run
+    /// time. Debug builds end the message with the call site, as in
+    /// "cat: oops [src/cat/src/main.rs:42]".
+    ///
+    /// The message is always a format string, checked at compile time like `format!`'s, so a
+    /// placeholder without an argument does not build, with or without a status:
+    ///
+    /// ```compile_fail
+    /// #[macro_use(die)]
+    /// extern crate utils;
+    ///
+    /// fn main() {
+    ///     die!("literal with braces: {}");
+    /// }
+    /// ```
+    ///
+    /// ```compile_fail
+    /// #[macro_use(die)]
+    /// extern crate utils;
+    ///
+    /// fn main() {
+    ///     die!(code = 2, "literal with braces: {}");
+    /// }
+    /// ```
+    ///
+    /// Literal arguments are just arguments, whatever their type:
+    ///
+    /// ```no_run
+    /// #[macro_use(die)]
+    /// extern crate utils;
+    ///
+    /// fn main() {
+    ///     die!("{} is not {}", 5, "five");
+    /// }
+    /// ```
     #[macro_export]
     macro_rules! die {
+        (code = $code:expr, $fmt:expr, $($arg:tt)*) => ({
+            eprintln!(
+                "{}{}",
+                format_args!(concat!("{}: ", $fmt), std::env::args().nth(0).unwrap(), $($arg)*),
+                $crate::die_location!()
+            );
+            ::std::process::exit($code);
+        });
+        (code = $code:expr, $fmt:expr) => ({
+            eprintln!(
+                "{}{}",
+                format_args!(concat!("{}: ", $fmt), std::env::args().nth(0).unwrap()),
+                $crate::die_location!()
+            );
+            ::std::process::exit($code);
+        });
+        ($fmt:expr, $($arg:tt)*) => ($crate::die!(code = 1, $fmt, $($arg)*));
+        ($fmt:expr) => ($crate::die!(code = 1, $fmt));
+    }
+
+    /// The " [file:line]" suffix `die!` adds in debug builds, and nothing in release ones.
+    /// The cfg is checked where `die!` is used, so it follows the tool's build, not utils'.
+    #[doc(hidden)]
+    #[macro_export]
+    macro_rules! die_location {
+        () => ({
+            #[cfg(debug_assertions)]
+            let location = concat!(" [", file!(), ":", line!(), "]");
+            #[cfg(not(debug_assertions))]
+            let location = "";
+            location
+        });
+    }
+
+    /// Prints an error message prefixed with the program name, like `die!`, but carries on.
+    #[macro_export]
+    macro_rules! warn {
         ($fmt:expr, $($arg:tt)*) => ({
             eprintln!(concat!("{}: ", $fmt), std::env::args().nth(0).unwrap(), $($arg)*);
-            ::std::process::exit(1);
         });
         ($fmt:expr) => ({
             eprintln!(concat!("{}: ", $fmt), std::env::args().nth(0).unwrap());
-            ::std::process::exit(1);
         });
     }
 
+    /// Like `warn!`, but also records the failure so that `exit_if_error` exits with 1.
+    #[macro_export]
+    macro_rules! note_error {
+        ($($arg:tt)*) => ({
+            warn!($($arg)*);
+            $crate::EXIT_FAILURE.with(|failed| failed.set(true));
+        });
+    }
+}
+
+extern crate libc;
+
+pub mod args;
+pub mod duration;
+pub mod fmt;
+pub mod io;
+pub mod regex;
+pub mod size;
+pub mod users;
+pub mod version;
+
+use std::cell::Cell;
+
+thread_local! {
+    /// Set by `note_error!`; not meant to be used directly.
+    #[doc(hidden)]
+    pub static EXIT_FAILURE: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Returns whether an error was noted since the last call, and clears the flag.
+pub fn take_error() -> bool {
+    EXIT_FAILURE.with(|failed| failed.replace(false))
+}
+
+/// Exits with status 1 if any error was noted with `note_error!`.
+pub fn exit_if_error() {
+    if take_error() {
+        std::process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn noted_errors_are_remembered() {
+        assert!(!take_error());
+        warn!("a warning does not count: {}", 1);
+        assert!(!take_error());
+        note_error!("an error: {}", 2);
+        note_error!("another error");
+        assert!(take_error());
+        assert!(!take_error());
+        exit_if_error();
+    }
+
+    #[test]
+    fn die_exits_with_the_given_status() {
+        let x = "x";
+        match std::env::var("UTILS_TEST_CHILD").ok().as_deref() {
+            Some("usage") => die!(code = 2, "bad usage: {}", x),
+            Some("plain") => die!("failed: {}", x),
+            _ => {}
+        }
+        for &(child, code) in &[("usage", 2), ("plain", 1)] {
+            let status = std::process::Command::new(std::env::current_exe().unwrap())
+                .args(["--exact", "tests::die_exits_with_the_given_status"])
+                .env("UTILS_TEST_CHILD", child)
+                .status()
+                .unwrap();
+            assert_eq!(status.code(), Some(code));
+        }
+    }
+
+    #[test]
+    fn die_names_the_call_site_in_debug_builds() {
+        if std::env::var_os("UTILS_TEST_CHILD").is_some() {
+            die!("failed");
+        }
+        let line = line!() - 2;
+        let output = std::process::Command::new(std::env::current_exe().unwrap())
+            .args([
+                "--exact",
+                "tests::die_names_the_call_site_in_debug_builds",
+                "--nocapture",
+            ])
+            .env("UTILS_TEST_CHILD", "1")
+            .output()
+            .unwrap();
+        assert_eq!(output.status.code(), Some(1));
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        let location = format!("failed [{}:{}]\n", file!(), line);
+        if cfg!(debug_assertions) {
+            assert!(stderr.ends_with(&location), "{}", stderr);
+        } else {
+            assert!(stderr.ends_with(": failed\n"), "{}", stderr);
+        }
+    }
+
+    #[test]
+    fn exit_if_error_exits() {
+        // Run this very test again in a child process, as exit_if_error ends it.
+        if std::env::var_os("UTILS_TEST_CHILD").is_some() {
+            note_error!("failed");
+            exit_if_error();
+            return;
+        }
+        let status = std::process::Command::new(std::env::current_exe().unwrap())
+            .args(["--exact", "tests::exit_if_error_exits"])
+            .env("UTILS_TEST_CHILD", "1")
+            .status()
+            .unwrap();
+        assert_eq!(status.code(), Some(1));
+    }
 }
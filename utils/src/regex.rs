@@ -0,0 +1,947 @@
+//! A small regular expression engine, as used by grep and expr: patterns are parsed into a
+//! syntax tree, compiled into a Thompson NFA and simulated over the text, so matching takes
+//! time linear in the length of the text.
+
+/// A set of bytes, one bit each.
+#[derive(Clone)]
+struct ByteSet([u64; 4]);
+
+impl ByteSet {
+    fn empty() -> ByteSet {
+        ByteSet([0; 4])
+    }
+
+    fn single(byte: u8) -> ByteSet {
+        let mut set = ByteSet::empty();
+        set.insert(byte);
+        set
+    }
+
+    fn from_fn<F: Fn(u8) -> bool>(f: F) -> ByteSet {
+        let mut set = ByteSet::empty();
+        for byte in 0..=255u8 {
+            if f(byte) {
+                set.insert(byte);
+            }
+        }
+        set
+    }
+
+    fn contains(&self, byte: u8) -> bool {
+        self.0[byte as usize / 64] & (1 << (byte % 64)) != 0
+    }
+
+    fn insert(&mut self, byte: u8) {
+        self.0[byte as usize / 64] |= 1 << (byte % 64);
+    }
+
+    fn remove(&mut self, byte: u8) {
+        self.0[byte as usize / 64] &= !(1 << (byte % 64));
+    }
+
+    fn negate(&mut self) {
+        for word in self.0.iter_mut() {
+            *word = !*word;
+        }
+    }
+
+    /// Adds the other case of every letter in the set.
+    fn fold_case(&mut self) {
+        for byte in b'a'..=b'z' {
+            let upper = byte.to_ascii_uppercase();
+            if self.contains(byte) || self.contains(upper) {
+                self.insert(byte);
+                self.insert(upper);
+            }
+        }
+    }
+}
+
+/// Zero-width assertions.
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Assertion {
+    LineStart,
+    LineEnd,
+    WordBoundary,
+    NotWordBoundary,
+    WordStart,
+    WordEnd,
+}
+
+/// The syntax tree of a pattern.
+#[derive(Clone)]
+enum Node {
+    Empty,
+    Bytes(ByteSet),
+    Assert(Assertion),
+    Concat(Vec<Node>),
+    Alternate(Vec<Node>),
+    /// A parenthesized subexpression and its number, counting from zero.
+    Group(Box<Node>, usize),
+    Repeat {
+        node: Box<Node>,
+        min: u32,
+        max: Option<u32>,
+    },
+}
+
+/// The flavour of the pattern syntax.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Syntax {
+    /// POSIX basic regular expressions, where `\(`, `\|`, `\+` and friends are operators.
+    Basic,
+    /// POSIX extended regular expressions.
+    Extended,
+    /// Plain strings, without any operators.
+    Fixed,
+}
+
+/// A token of the pattern, with the differences between the basic and extended syntax
+/// already resolved.
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Token {
+    Byte(u8),
+    Escaped(u8),
+    Any,
+    Caret,
+    Dollar,
+    Star,
+    Plus,
+    Question,
+    OpenBrace,
+    Pipe,
+    Open,
+    Close,
+    OpenBracket,
+}
+
+/// Upper bound for the counts in {n,m}, which keeps the compiled program reasonably small.
+const MAX_REPEAT: u32 = 255;
+
+struct Parser<'a> {
+    pattern: &'a [u8],
+    pos: usize,
+    syntax: Syntax,
+    ignore_case: bool,
+    depth: usize,
+    groups: usize,
+}
+
+impl<'a> Parser<'a> {
+    /// Reads the next token without consuming it, returning it and its length.
+    fn peek(&self) -> Option<(Token, usize)> {
+        let byte = *self.pattern.get(self.pos)?;
+        let basic = self.syntax == Syntax::Basic;
+        if byte == b'\\' {
+            let next = match self.pattern.get(self.pos + 1) {
+                Some(&next) => next,
+                None => return Some((Token::Byte(b'\\'), 1)),
+            };
+            let token = match next {
+                b'(' if basic => Token::Open,
+                b')' if basic => Token::Close,
+                b'|' if basic => Token::Pipe,
+                b'+' if basic => Token::Plus,
+                b'?' if basic => Token::Question,
+                b'{' if basic => Token::OpenBrace,
+                _ => Token::Escaped(next),
+            };
+            return Some((token, 2));
+        }
+        let token = match byte {
+            b'.' => Token::Any,
+            b'^' => Token::Caret,
+            b'$' => Token::Dollar,
+            b'*' => Token::Star,
+            b'[' => Token::OpenBracket,
+            b'(' if !basic => Token::Open,
+            b')' if !basic => Token::Close,
+            b'|' if !basic => Token::Pipe,
+            b'+' if !basic => Token::Plus,
+            b'?' if !basic => Token::Question,
+            b'{' if !basic => Token::OpenBrace,
+            _ => Token::Byte(byte),
+        };
+        Some((token, 1))
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let (token, len) = self.peek()?;
+        self.pos += len;
+        Some(token)
+    }
+
+    fn literal(&self, byte: u8) -> Node {
+        literal(byte, self.ignore_case)
+    }
+
+    /// Whether a valid interval follows an opening brace. In the extended syntax other
+    /// braces are taken literally, as GNU grep does.
+    fn interval_ahead(&self) -> bool {
+        let rest = &self.pattern[self.pos..];
+        let end = match rest.iter().position(|&byte| byte == b'}') {
+            Some(end) => end,
+            None => return false,
+        };
+        let contents = &rest[..end];
+        let (min, max) = match contents.iter().position(|&byte| byte == b',') {
+            Some(comma) => (&contents[..comma], &contents[comma + 1..]),
+            None => (contents, &b""[..]),
+        };
+        let digits = |s: &[u8]| s.iter().all(|byte| byte.is_ascii_digit());
+        digits(min) && digits(max) && !(min.is_empty() && max.is_empty())
+    }
+
+    fn parse_alternation(&mut self) -> Result<Node, String> {
+        let mut branches = vec![self.parse_concat()?];
+        while let Some((Token::Pipe, _)) = self.peek() {
+            self.next();
+            branches.push(self.parse_concat()?);
+        }
+        Ok(if branches.len() == 1 {
+            branches.pop().unwrap()
+        } else {
+            Node::Alternate(branches)
+        })
+    }
+
+    fn parse_concat(&mut self) -> Result<Node, String> {
+        let mut nodes: Vec<Node> = Vec::new();
+        loop {
+            let token = match self.peek() {
+                None | Some((Token::Pipe, _)) => break,
+                Some((Token::Close, _)) if self.depth > 0 => break,
+                Some((token, _)) => token,
+            };
+            let at_start = nodes.is_empty();
+            self.next();
+            let node = match token {
+                Token::Close => return Err("Unmatched ) or \\)".to_owned()),
+                Token::OpenBrace if self.syntax == Syntax::Extended && !self.interval_ahead() => {
+                    self.literal(b'{')
+                }
+                Token::Star | Token::Plus | Token::Question | Token::OpenBrace
+                    if !at_start || self.syntax == Syntax::Extended =>
+                {
+                    if at_start {
+                        // Like GNU grep, a leading repetition operator stands for itself.
+                        self.literal(self.pattern[self.pos - 1])
+                    } else {
+                        let node = nodes.pop().unwrap();
+                        self.parse_repeat(node, token)?
+                    }
+                }
+                Token::Star | Token::Plus | Token::Question | Token::OpenBrace => {
+                    self.literal(self.pattern[self.pos - 1])
+                }
+                Token::Caret if self.syntax == Syntax::Basic && !at_start => self.literal(b'^'),
+                Token::Caret => Node::Assert(Assertion::LineStart),
+                Token::Dollar if self.syntax == Syntax::Basic && !self.at_branch_end() => {
+                    self.literal(b'$')
+                }
+                Token::Dollar => Node::Assert(Assertion::LineEnd),
+                Token::Any => Node::Bytes(ByteSet::from_fn(|byte| byte != b'\n')),
+                Token::Open => {
+                    let group = self.groups;
+                    self.groups += 1;
+                    self.depth += 1;
+                    let node = self.parse_alternation()?;
+                    self.depth -= 1;
+                    match self.next() {
+                        Some(Token::Close) => Node::Group(Box::new(node), group),
+                        _ => return Err("Unmatched ( or \\(".to_owned()),
+                    }
+                }
+                Token::OpenBracket => self.parse_bracket()?,
+                Token::Escaped(byte) => self.escape(byte)?,
+                Token::Byte(byte) => self.literal(byte),
+                Token::Pipe => unreachable!(),
+            };
+            nodes.push(node);
+        }
+        Ok(match nodes.len() {
+            0 => Node::Empty,
+            1 => nodes.pop().unwrap(),
+            _ => Node::Concat(nodes),
+        })
+    }
+
+    /// Whether the parser is at the end of a branch, where `$` is an anchor in basic syntax.
+    fn at_branch_end(&self) -> bool {
+        match self.peek() {
+            None | Some((Token::Pipe, _)) => true,
+            Some((Token::Close, _)) => self.depth > 0,
+            _ => false,
+        }
+    }
+
+    /// Applies the repetition operator @p token, and any following it, to @p node.
+    fn parse_repeat(&mut self, node: Node, token: Token) -> Result<Node, String> {
+        let (min, max) = match token {
+            Token::Star => (0, None),
+            Token::Plus => (1, None),
+            Token::Question => (0, Some(1)),
+            _ => self.parse_interval()?,
+        };
+        let node = Node::Repeat {
+            node: Box::new(node),
+            min,
+            max,
+        };
+        match self.peek() {
+            Some((token @ Token::Star, _))
+            | Some((token @ Token::Plus, _))
+            | Some((token @ Token::Question, _))
+            | Some((token @ Token::OpenBrace, _)) => {
+                self.next();
+                self.parse_repeat(node, token)
+            }
+            _ => Ok(node),
+        }
+    }
+
+    /// Parses the rest of an interval {n}, {n,} or {n,m} after the opening brace.
+    fn parse_interval(&mut self) -> Result<(u32, Option<u32>), String> {
+        let invalid = || "Invalid content of \\{\\}".to_owned();
+        let close: &[u8] = if self.syntax == Syntax::Basic {
+            b"\\}"
+        } else {
+            b"}"
+        };
+        let rest = &self.pattern[self.pos..];
+        let end = rest
+            .windows(close.len())
+            .position(|window| window == close)
+            .ok_or_else(|| "Unmatched \\{".to_owned())?;
+        let contents = String::from_utf8_lossy(&rest[..end]).into_owned();
+        self.pos += end + close.len();
+        let number = |s: &str| -> Result<Option<u32>, String> {
+            if s.is_empty() {
+                return Ok(None);
+            }
+            match s.parse::<u32>() {
+                Ok(n) if n <= MAX_REPEAT => Ok(Some(n)),
+                Ok(_) => Err("Regular expression too big".to_owned()),
+                Err(_) => Err(invalid()),
+            }
+        };
+        let (min, max) = match contents.find(',') {
+            Some(comma) => (
+                number(&contents[..comma])?.unwrap_or(0),
+                number(&contents[comma + 1..])?,
+            ),
+            None => {
+                let n = number(&contents)?.ok_or_else(invalid)?;
+                (n, Some(n))
+            }
+        };
+        if max.is_some_and(|max| max < min) {
+            return Err(invalid());
+        }
+        Ok((min, max))
+    }
+
+    fn escape(&self, byte: u8) -> Result<Node, String> {
+        let word = |byte: u8| byte.is_ascii_alphanumeric() || byte == b'_';
+        let space = |byte: u8| byte.is_ascii_whitespace();
+        Ok(match byte {
+            b'b' => Node::Assert(Assertion::WordBoundary),
+            b'B' => Node::Assert(Assertion::NotWordBoundary),
+            b'<' => Node::Assert(Assertion::WordStart),
+            b'>' => Node::Assert(Assertion::WordEnd),
+            b'w' => Node::Bytes(ByteSet::from_fn(word)),
+            b'W' => Node::Bytes(ByteSet::from_fn(|byte| !word(byte))),
+            b's' => Node::Bytes(ByteSet::from_fn(space)),
+            b'S' => Node::Bytes(ByteSet::from_fn(|byte| !space(byte))),
+            b'1'..=b'9' => return Err("back-references are not supported".to_owned()),
+            _ => self.literal(byte),
+        })
+    }
+
+    /// Parses a bracket expression after its opening bracket.
+    fn parse_bracket(&mut self) -> Result<Node, String> {
+        let unmatched = || "Unmatched [, [^, [:, [., or [=".to_owned();
+        let mut set = ByteSet::empty();
+        let negated = self.pattern.get(self.pos) == Some(&b'^');
+        if negated {
+            self.pos += 1;
+        }
+        let mut first = true;
+        loop {
+            let byte = *self.pattern.get(self.pos).ok_or_else(unmatched)?;
+            if byte == b']' && !first {
+                self.pos += 1;
+                break;
+            }
+            first = false;
+            if self.pattern[self.pos..].starts_with(b"[:") {
+                let rest = &self.pattern[self.pos + 2..];
+                let end = rest
+                    .windows(2)
+                    .position(|window| window == b":]")
+                    .ok_or_else(unmatched)?;
+                let name = String::from_utf8_lossy(&rest[..end]).into_owned();
+                let class = character_class(&name)
+                    .ok_or_else(|| format!("invalid character class '{}'", name))?;
+                for member in 0..=255u8 {
+                    if class(member) {
+                        set.insert(member);
+                    }
+                }
+                self.pos += 2 + end + 2;
+                continue;
+            }
+            self.pos += 1;
+            let is_range = self.pattern.get(self.pos) == Some(&b'-')
+                && self
+                    .pattern
+                    .get(self.pos + 1)
+                    .is_some_and(|&end| end != b']');
+            if is_range {
+                let end = self.pattern[self.pos + 1];
+                if end < byte {
+                    return Err("Invalid range end".to_owned());
+                }
+                for member in byte..=end {
+                    set.insert(member);
+                }
+                self.pos += 2;
+            } else {
+                set.insert(byte);
+            }
+        }
+        if self.ignore_case {
+            set.fold_case();
+        }
+        if negated {
+            set.negate();
+            set.remove(b'\n');
+        }
+        Ok(Node::Bytes(set))
+    }
+}
+
+fn literal(byte: u8, ignore_case: bool) -> Node {
+    let mut set = ByteSet::single(byte);
+    if ignore_case {
+        set.fold_case();
+    }
+    Node::Bytes(set)
+}
+
+/// The members of the POSIX character class @p name, as in [[:alpha:]].
+fn character_class(name: &str) -> Option<fn(u8) -> bool> {
+    let class: fn(u8) -> bool = match name {
+        "alnum" => |b| b.is_ascii_alphanumeric(),
+        "alpha" => |b| b.is_ascii_alphabetic(),
+        "blank" => |b| b == b' ' || b == b'\t',
+        "cntrl" => |b| b.is_ascii_control(),
+        "digit" => |b| b.is_ascii_digit(),
+        "graph" => |b| b.is_ascii_graphic(),
+        "lower" => |b| b.is_ascii_lowercase(),
+        "print" => |b| b.is_ascii_graphic() || b == b' ',
+        "punct" => |b| b.is_ascii_punctuation(),
+        "space" => |b| b.is_ascii_whitespace() || b == 0x0b,
+        "upper" => |b| b.is_ascii_uppercase(),
+        "xdigit" => |b| b.is_ascii_hexdigit(),
+        _ => return None,
+    };
+    Some(class)
+}
+
+/// An instruction of the compiled NFA.
+enum Inst {
+    Bytes(ByteSet),
+    Assert(Assertion),
+    /// Records the current position in the given capture slot.
+    Save(usize),
+    /// Continue at both targets, preferring the first.
+    Split(usize, usize),
+    Jump(usize),
+    Match,
+}
+
+/// Builds the NFA program, one fragment per syntax tree node.
+struct Compiler {
+    program: Vec<Inst>,
+}
+
+impl Compiler {
+    fn emit(&mut self, inst: Inst) -> usize {
+        self.program.push(inst);
+        self.program.len() - 1
+    }
+
+    /// Points the placeholder jump or split at @p pc to continue at @p target.
+    fn patch(&mut self, pc: usize, target: usize) {
+        match self.program[pc] {
+            Inst::Jump(ref mut to) => *to = target,
+            Inst::Split(_, ref mut second) => *second = target,
+            _ => unreachable!(),
+        }
+    }
+
+    fn compile(&mut self, node: &Node) {
+        match *node {
+            Node::Empty => {}
+            Node::Bytes(ref set) => {
+                self.emit(Inst::Bytes(set.clone()));
+            }
+            Node::Assert(assertion) => {
+                self.emit(Inst::Assert(assertion));
+            }
+            Node::Concat(ref nodes) => {
+                for node in nodes {
+                    self.compile(node);
+                }
+            }
+            Node::Group(ref node, group) => {
+                self.emit(Inst::Save(2 * group));
+                self.compile(node);
+                self.emit(Inst::Save(2 * group + 1));
+            }
+            Node::Alternate(ref branches) => {
+                let mut jumps = Vec::new();
+                for (i, branch) in branches.iter().enumerate() {
+                    if i + 1 < branches.len() {
+                        let split = self.emit(Inst::Split(0, 0));
+                        self.program[split] = Inst::Split(split + 1, 0);
+                        self.compile(branch);
+                        jumps.push(self.emit(Inst::Jump(0)));
+                        let next = self.program.len();
+                        self.patch(split, next);
+                    } else {
+                        self.compile(branch);
+                    }
+                }
+                let end = self.program.len();
+                for jump in jumps {
+                    self.patch(jump, end);
+                }
+            }
+            Node::Repeat { ref node, min, max } => {
+                for _ in 0..min {
+                    self.compile(node);
+                }
+                match max {
+                    None => {
+                        // L: split body, end; body; jump L
+                        let split = self.emit(Inst::Split(0, 0));
+                        self.program[split] = Inst::Split(split + 1, 0);
+                        self.compile(node);
+                        self.emit(Inst::Jump(split));
+                        let end = self.program.len();
+                        self.patch(split, end);
+                    }
+                    Some(max) => {
+                        let mut splits = Vec::new();
+                        for _ in min..max {
+                            let split = self.emit(Inst::Split(0, 0));
+                            self.program[split] = Inst::Split(split + 1, 0);
+                            splits.push(split);
+                            self.compile(node);
+                        }
+                        let end = self.program.len();
+                        for split in splits {
+                            self.patch(split, end);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// A compiled pattern.
+pub struct Regex {
+    program: Vec<Inst>,
+    groups: usize,
+}
+
+/// The result of an anchored match: where it ended and where its groups matched.
+#[derive(Debug, PartialEq)]
+pub struct Captures {
+    pub end: usize,
+    slots: Vec<Option<usize>>,
+}
+
+impl Captures {
+    /// The range of the text that group @p n matched, counting from one, if it took part.
+    pub fn group(&self, n: usize) -> Option<(usize, usize)> {
+        match (self.slots.get(2 * n - 2), self.slots.get(2 * n - 1)) {
+            (Some(&Some(start)), Some(&Some(end))) => Some((start, end)),
+            _ => None,
+        }
+    }
+}
+
+/// A thread of the capturing simulation.
+struct Thread {
+    pc: usize,
+    slots: Vec<Option<usize>>,
+}
+
+impl Regex {
+    /// Compiles @p patterns into one expression that matches wherever any of them does.
+    pub fn new(patterns: &[String], syntax: Syntax, ignore_case: bool) -> Result<Regex, String> {
+        let mut branches = Vec::new();
+        let mut groups = 0;
+        for pattern in patterns {
+            let node = if syntax == Syntax::Fixed {
+                Node::Concat(
+                    pattern
+                        .bytes()
+                        .map(|byte| literal(byte, ignore_case))
+                        .collect(),
+                )
+            } else {
+                let mut parser = Parser {
+                    pattern: pattern.as_bytes(),
+                    pos: 0,
+                    syntax,
+                    ignore_case,
+                    depth: 0,
+                    groups,
+                };
+                let node = parser.parse_alternation()?;
+                groups = parser.groups;
+                node
+            };
+            branches.push(node);
+        }
+        let mut compiler = Compiler {
+            program: Vec::new(),
+        };
+        compiler.compile(&Node::Alternate(branches));
+        compiler.emit(Inst::Match);
+        Ok(Regex {
+            program: compiler.program,
+            groups,
+        })
+    }
+
+    /// The number of parenthesized groups in the expression.
+    pub fn groups(&self) -> usize {
+        self.groups
+    }
+
+    /// Matches the expression against the start of @p text. Of all the matches, the longest
+    /// one is taken; the groups are those of the preferred way to match that much, where
+    /// repetitions are greedy and earlier alternatives come first.
+    pub fn match_prefix(&self, text: &[u8]) -> Option<Captures> {
+        let mut current = Vec::new();
+        let mut next = Vec::new();
+        let mut present = vec![false; self.program.len()];
+        let mut best = None;
+        let slots = vec![None; 2 * self.groups];
+        self.add_capturing(&mut current, &mut present, 0, slots, text, 0);
+        for pos in 0..=text.len() {
+            let mut matched = false;
+            present.fill(false);
+            for thread in current.drain(..) {
+                match self.program[thread.pc] {
+                    Inst::Match if !matched => {
+                        matched = true;
+                        best = Some(Captures {
+                            end: pos,
+                            slots: thread.slots,
+                        });
+                    }
+                    Inst::Bytes(ref set) if pos < text.len() && set.contains(text[pos]) => {
+                        let pc = thread.pc + 1;
+                        self.add_capturing(
+                            &mut next,
+                            &mut present,
+                            pc,
+                            thread.slots,
+                            text,
+                            pos + 1,
+                        );
+                    }
+                    _ => {}
+                }
+            }
+            if next.is_empty() {
+                break;
+            }
+            std::mem::swap(&mut current, &mut next);
+        }
+        best
+    }
+
+    /// Like `add_thread`, but carries the capture slots along and records positions in them.
+    fn add_capturing(
+        &self,
+        threads: &mut Vec<Thread>,
+        present: &mut [bool],
+        pc: usize,
+        mut slots: Vec<Option<usize>>,
+        text: &[u8],
+        pos: usize,
+    ) {
+        if present[pc] {
+            return;
+        }
+        present[pc] = true;
+        match self.program[pc] {
+            Inst::Jump(target) => self.add_capturing(threads, present, target, slots, text, pos),
+            Inst::Split(first, second) => {
+                self.add_capturing(threads, present, first, slots.clone(), text, pos);
+                self.add_capturing(threads, present, second, slots, text, pos);
+            }
+            Inst::Assert(assertion) => {
+                if holds(assertion, text, pos) {
+                    self.add_capturing(threads, present, pc + 1, slots, text, pos);
+                }
+            }
+            Inst::Save(slot) => {
+                slots[slot] = Some(pos);
+                self.add_capturing(threads, present, pc + 1, slots, text, pos);
+            }
+            Inst::Bytes(_) | Inst::Match => threads.push(Thread { pc, slots }),
+        }
+    }
+
+    /// Whether the expression matches anywhere in @p line.
+    pub fn is_match(&self, line: &[u8]) -> bool {
+        let mut current = Threads::new(self.program.len());
+        let mut next = Threads::new(self.program.len());
+        for pos in 0..=line.len() {
+            // Starting a new thread at every position makes the search unanchored.
+            self.add_thread(&mut current, 0, line, pos);
+            if current.contains(self.program.len() - 1) {
+                return true;
+            }
+            if pos == line.len() {
+                break;
+            }
+            next.clear();
+            for i in 0..current.len() {
+                if let Inst::Bytes(ref set) = self.program[current.pcs[i]] {
+                    if set.contains(line[pos]) {
+                        self.add_thread(&mut next, current.pcs[i] + 1, line, pos + 1);
+                    }
+                }
+            }
+            std::mem::swap(&mut current, &mut next);
+        }
+        false
+    }
+
+    /// Adds the thread at @p pc to @p threads, following jumps, splits and the assertions
+    /// that hold at @p pos.
+    fn add_thread(&self, threads: &mut Threads, pc: usize, line: &[u8], pos: usize) {
+        if threads.contains(pc) {
+            return;
+        }
+        threads.insert(pc);
+        match self.program[pc] {
+            Inst::Jump(target) => self.add_thread(threads, target, line, pos),
+            Inst::Split(first, second) => {
+                self.add_thread(threads, first, line, pos);
+                self.add_thread(threads, second, line, pos);
+            }
+            Inst::Assert(assertion) => {
+                if holds(assertion, line, pos) {
+                    self.add_thread(threads, pc + 1, line, pos);
+                }
+            }
+            Inst::Save(_) => self.add_thread(threads, pc + 1, line, pos),
+            Inst::Bytes(_) | Inst::Match => {}
+        }
+    }
+}
+
+/// Whether @p assertion holds between `line[pos - 1]` and `line[pos]`.
+fn holds(assertion: Assertion, line: &[u8], pos: usize) -> bool {
+    let word = |i: Option<usize>| {
+        i.and_then(|i| line.get(i))
+            .is_some_and(|&byte| byte.is_ascii_alphanumeric() || byte == b'_')
+    };
+    let before = word(pos.checked_sub(1));
+    let after = word(Some(pos));
+    match assertion {
+        Assertion::LineStart => pos == 0,
+        Assertion::LineEnd => pos == line.len(),
+        Assertion::WordBoundary => before != after,
+        Assertion::NotWordBoundary => before == after,
+        Assertion::WordStart => !before && after,
+        Assertion::WordEnd => before && !after,
+    }
+}
+
+/// A set of NFA states that keeps the order they were added in.
+struct Threads {
+    pcs: Vec<usize>,
+    present: Vec<bool>,
+}
+
+impl Threads {
+    fn new(size: usize) -> Threads {
+        Threads {
+            pcs: Vec::with_capacity(size),
+            present: vec![false; size],
+        }
+    }
+
+    fn contains(&self, pc: usize) -> bool {
+        self.present[pc]
+    }
+
+    fn insert(&mut self, pc: usize) {
+        self.present[pc] = true;
+        self.pcs.push(pc);
+    }
+
+    fn len(&self) -> usize {
+        self.pcs.len()
+    }
+
+    fn clear(&mut self) {
+        for &pc in &self.pcs {
+            self.present[pc] = false;
+        }
+        self.pcs.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(pattern: &str, syntax: Syntax, line: &str) -> bool {
+        Regex::new(&[pattern.to_owned()], syntax, false)
+            .unwrap()
+            .is_match(line.as_bytes())
+    }
+
+    fn ere(pattern: &str, line: &str) -> bool {
+        matches(pattern, Syntax::Extended, line)
+    }
+
+    fn bre(pattern: &str, line: &str) -> bool {
+        matches(pattern, Syntax::Basic, line)
+    }
+
+    #[test]
+    fn literals_and_any() {
+        assert!(ere("abc", "xxabcxx"));
+        assert!(!ere("abc", "ab c"));
+        assert!(ere("a.c", "abc"));
+        assert!(!ere("a.c", "ac"));
+        assert!(ere("", "anything"));
+        assert!(ere("a\\.c", "a.c"));
+        assert!(!ere("a\\.c", "abc"));
+    }
+
+    #[test]
+    fn alternation() {
+        assert!(ere("cat|dog", "hotdog"));
+        assert!(ere("cat|dog", "cats"));
+        assert!(!ere("cat|dog", "cow"));
+        assert!(ere("^(ab|cd)+$", "abcdab"));
+        assert!(!ere("^(ab|cd)+$", "abcda"));
+        assert!(ere("a(|b)c", "ac"));
+        assert!(ere("a(|b)c", "abc"));
+        assert!(bre("cat\\|dog", "dog"));
+        assert!(bre("cat|dog", "cat|dog"));
+        assert!(!bre("cat|dog", "cat"));
+    }
+
+    #[test]
+    fn repetition() {
+        assert!(ere("^ab*c$", "ac"));
+        assert!(ere("^ab*c$", "abbbc"));
+        assert!(!ere("^ab+c$", "ac"));
+        assert!(ere("^ab+c$", "abbc"));
+        assert!(ere("^ab?c$", "abc"));
+        assert!(!ere("^ab?c$", "abbc"));
+        assert!(ere("^(a*)*$", "aaaa"));
+        assert!(!ere("^(a*)*$", "aab"));
+        assert!(ere("^a{2,3}$", "aaa"));
+        assert!(!ere("^a{2,3}$", "aaaa"));
+        assert!(ere("^a{2}$", "aa"));
+        assert!(ere("^a{2,}$", "aaaaa"));
+        assert!(ere("^a{,2}$", ""));
+        assert!(ere("a{", "a{"));
+        assert!(bre("^a\\{2\\}$", "aa"));
+        assert!(bre("a+", "a+"));
+        assert!(bre("^a\\+$", "aaa"));
+        // A leading repetition operator is taken literally.
+        assert!(ere("*a", "*a"));
+        assert!(bre("*a", "*a"));
+    }
+
+    #[test]
+    fn brackets() {
+        assert!(ere("^[abc]+$", "cab"));
+        assert!(!ere("^[abc]+$", "cad"));
+        assert!(ere("^[^abc]$", "d"));
+        assert!(!ere("^[^abc]$", "a"));
+        assert!(ere("^[a-f0-9]+$", "deadbeef42"));
+        assert!(ere("[]]", "]"));
+        assert!(ere("[a-]", "-"));
+        assert!(ere("^[[:digit:][:upper:]]+$", "A1B2"));
+        assert!(!ere("[[:digit:]]", "abc"));
+        assert!(Regex::new(&["[abc".to_owned()], Syntax::Extended, false).is_err());
+        assert!(Regex::new(&["[[:nope:]]".to_owned()], Syntax::Extended, false).is_err());
+        assert!(Regex::new(&["[z-a]".to_owned()], Syntax::Extended, false).is_err());
+    }
+
+    #[test]
+    fn anchors_and_words() {
+        assert!(ere("^abc", "abcd"));
+        assert!(!ere("^abc", "xabc"));
+        assert!(ere("abc$", "xabc"));
+        assert!(!ere("abc$", "abcx"));
+        assert!(ere("^$", ""));
+        assert!(ere("\\bbar\\b", "foo bar baz"));
+        assert!(!ere("\\bbar\\b", "foobar"));
+        assert!(ere("\\Bbar", "foobar"));
+        assert!(ere("\\<foo\\>", "a foo."));
+        assert!(!ere("\\<foo\\>", "afoo"));
+        assert!(bre("a^b$c", "a^b$c"));
+        assert!(bre("\\(^a\\)", "a"));
+    }
+
+    #[test]
+    fn groups() {
+        assert!(Regex::new(&["(ab".to_owned()], Syntax::Extended, false).is_err());
+        assert!(Regex::new(&["ab)".to_owned()], Syntax::Extended, false).is_err());
+        assert!(bre("(ab)", "(ab)"));
+        assert!(bre("\\(ab\\)*c", "ababc"));
+        assert!(Regex::new(&["\\(a\\)\\1".to_owned()], Syntax::Basic, false).is_err());
+    }
+
+    #[test]
+    fn fixed_strings_and_case() {
+        assert!(matches("a.c(", Syntax::Fixed, "xa.c(x"));
+        assert!(!matches("a.c", Syntax::Fixed, "abc"));
+        let regex = Regex::new(
+            &["HeLLo".to_owned(), "[x-z]".to_owned()],
+            Syntax::Basic,
+            true,
+        )
+        .unwrap();
+        assert!(regex.is_match(b"say hello"));
+        assert!(regex.is_match(b"XYZ"));
+        assert!(!regex.is_match(b"help"));
+    }
+
+    #[test]
+    fn anchored_prefix_match() {
+        let prefix = |pattern: &str, text: &str| {
+            let regex = Regex::new(&[pattern.to_owned()], Syntax::Basic, false).unwrap();
+            regex
+                .match_prefix(text.as_bytes())
+                .map(|captures| (captures.end, captures.group(1)))
+        };
+        assert_eq!(prefix(".*c", "abcabc"), Some((6, None)));
+        assert_eq!(prefix("b", "abc"), None);
+        assert_eq!(prefix("a*", "bbb"), Some((0, None)));
+        assert_eq!(prefix("a\\(b\\)", "abc"), Some((2, Some((1, 2)))));
+        assert_eq!(prefix("\\(a*\\)\\(a*\\)", "aaa"), Some((3, Some((0, 3)))));
+        assert_eq!(prefix("x\\|\\(y\\)", "x"), Some((1, None)));
+        assert_eq!(prefix("\\([a-z]*\\)[0-9]*$", "ab12"), Some((4, Some((0, 2)))));
+    }
+}
@@ -0,0 +1,41 @@
+//! Formatting of file and disk sizes.
+
+/// Formats @p bytes the way GNU du and df do with -h: sizes below ten units get one decimal,
+/// larger ones none, and both are rounded up.
+pub fn human_readable(bytes: u64) -> String {
+    const UNITS: [char; 6] = ['K', 'M', 'G', 'T', 'P', 'E'];
+    if bytes < 1024 {
+        return bytes.to_string();
+    }
+    let mut value = bytes as f64;
+    for unit in UNITS.iter() {
+        value /= 1024.0;
+        let tenths = (value * 10.0).ceil() / 10.0;
+        if tenths < 10.0 {
+            return format!("{:.1}{}", tenths, unit);
+        }
+        let whole = value.ceil();
+        if whole < 1024.0 {
+            return format!("{}{}", whole, unit);
+        }
+    }
+    format!("{}E", value.ceil())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn human_readable_sizes() {
+        assert_eq!(human_readable(0), "0");
+        assert_eq!(human_readable(1023), "1023");
+        assert_eq!(human_readable(1024), "1.0K");
+        assert_eq!(human_readable(4096), "4.0K");
+        assert_eq!(human_readable(1025), "1.1K");
+        assert_eq!(human_readable(10 * 1024 - 1), "10K");
+        assert_eq!(human_readable(1258291), "1.2M");
+        assert_eq!(human_readable(1023 * 1024 + 1), "1.0M");
+        assert_eq!(human_readable(3 << 30), "3.0G");
+    }
+}
@@ -0,0 +1,66 @@
+//! Looking up the names of users and groups in the passwd and group databases.
+
+use std::ffi::CStr;
+use std::io;
+use std::mem;
+use std::ptr;
+
+use libc;
+
+// Enough for the entries of any sane database; lookups needing more fail with ERANGE.
+const BUFFER_SIZE: usize = 4096;
+
+/// Looks up the name of the user @p uid, or None if there is no passwd entry for it.
+pub fn user_name(uid: libc::uid_t) -> io::Result<Option<String>> {
+    let mut buf = vec![0 as libc::c_char; BUFFER_SIZE];
+    let mut pwd: libc::passwd = unsafe { mem::zeroed() };
+    let mut result: *mut libc::passwd = ptr::null_mut();
+    let ret = unsafe { libc::getpwuid_r(uid, &mut pwd, buf.as_mut_ptr(), buf.len(), &mut result) };
+    if ret != 0 {
+        return Err(io::Error::from_raw_os_error(ret));
+    }
+    if result.is_null() {
+        return Ok(None);
+    }
+    Ok(Some(
+        unsafe { CStr::from_ptr(pwd.pw_name) }
+            .to_string_lossy()
+            .into_owned(),
+    ))
+}
+
+/// Looks up the name of the group @p gid, or None if there is no group entry for it.
+pub fn group_name(gid: libc::gid_t) -> io::Result<Option<String>> {
+    let mut buf = vec![0 as libc::c_char; BUFFER_SIZE];
+    let mut grp: libc::group = unsafe { mem::zeroed() };
+    let mut result: *mut libc::group = ptr::null_mut();
+    let ret = unsafe { libc::getgrgid_r(gid, &mut grp, buf.as_mut_ptr(), buf.len(), &mut result) };
+    if ret != 0 {
+        return Err(io::Error::from_raw_os_error(ret));
+    }
+    if result.is_null() {
+        return Ok(None);
+    }
+    Ok(Some(
+        unsafe { CStr::from_ptr(grp.gr_name) }
+            .to_string_lossy()
+            .into_owned(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn root_is_known() {
+        assert_eq!(user_name(0).unwrap(), Some("root".to_owned()));
+        assert_eq!(group_name(0).unwrap(), Some("root".to_owned()));
+    }
+
+    #[test]
+    fn unknown_ids() {
+        assert_eq!(user_name(4_000_000_000).unwrap(), None);
+        assert_eq!(group_name(4_000_000_000).unwrap(), None);
+    }
+}
@@ -0,0 +1,27 @@
+//! The `--version` output shared by all the tools.
+
+/// The version of the tools. Every package inherits it from the workspace, so the one this
+/// crate is built with is theirs as well.
+const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+fn version_line(tool: &str) -> String {
+    format!("{} (coreutils-rs) {}", tool, VERSION)
+}
+
+/// Prints the version line of @p tool to standard output.
+pub fn print_version(tool: &str) {
+    println!("{}", version_line(tool));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn version_line_names_the_tool() {
+        assert_eq!(
+            version_line("cat"),
+            format!("cat (coreutils-rs) {}", env!("CARGO_PKG_VERSION"))
+        );
+    }
+}
@@ -0,0 +1,254 @@
+//! Development tasks, run with `cargo xtask`.
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+#[macro_use(die, warn)]
+extern crate utils;
+
+use utils::args::ArgParser;
+
+/// The packages of the workspace that build tools, and the binaries each of them builds.
+const TOOLS: &[(&str, &[&str])] = &[
+    ("base32", &["base32"]),
+    ("base64", &["base64"]),
+    ("cat", &["cat"]),
+    ("cmp", &["cmp"]),
+    ("comm", &["comm"]),
+    ("csplit", &["csplit"]),
+    ("date", &["date"]),
+    ("df", &["df"]),
+    ("digest", &["md5sum", "sha1sum", "sha256sum", "sha512sum"]),
+    ("du", &["du"]),
+    ("env", &["env"]),
+    ("expand", &["expand", "unexpand"]),
+    ("expr", &["expr"]),
+    ("factor", &["factor"]),
+    ("grep", &["grep"]),
+    ("groups", &["groups"]),
+    ("head", &["head"]),
+    ("id", &["id"]),
+    ("join", &["join"]),
+    ("logname", &["logname"]),
+    ("look", &["look"]),
+    ("nl", &["nl"]),
+    ("nohup", &["nohup"]),
+    ("paste", &["paste"]),
+    ("printenv", &["printenv"]),
+    ("pwd", &["pwd"]),
+    ("readlink", &["readlink"]),
+    ("seq", &["seq"]),
+    ("shuf", &["shuf"]),
+    ("sleep", &["sleep"]),
+    ("split", &["split"]),
+    ("stat", &["stat"]),
+    ("stdbuf", &["stdbuf"]),
+    ("strings", &["strings"]),
+    ("sum", &["sum", "cksum"]),
+    ("tail", &["tail"]),
+    ("timeout", &["timeout"]),
+    ("truncate", &["truncate"]),
+    ("tty", &["tty"]),
+    ("uname", &["uname"]),
+    ("wc", &["wc"]),
+    ("whoami", &["whoami"]),
+    ("xargs", &["xargs"]),
+    ("xxd", &["xxd"]),
+];
+
+/// The libraries that tools load at run time, each with the tool that needs it. They are
+/// installed to PREFIX/lib/coreutils-rs.
+const LIBRARIES: &[(&str, &str)] = &[("stdbuf", "libstdbuf.so")];
+
+fn workspace_root() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("..")
+}
+
+/// The package that builds the binary @p tool.
+fn package_of(tool: &str) -> Option<&'static str> {
+    TOOLS
+        .iter()
+        .find(|&&(_, binaries)| binaries.contains(&tool))
+        .map(|&(package, _)| package)
+}
+
+/// Builds the binaries @p tools in release mode and copies them, and the libraries they need,
+/// to @p prefix.
+fn install(tools: &[&str], prefix: &Path) {
+    let bin_dir = &prefix.join("bin");
+    let mut packages: Vec<&str> = tools.iter().filter_map(|tool| package_of(tool)).collect();
+    packages.sort();
+    packages.dedup();
+    println!("Building {} tool(s) in release mode", tools.len());
+    let cargo = env::var("CARGO").unwrap_or_else(|_| "cargo".to_owned());
+    let mut build = Command::new(cargo);
+    build
+        .args(["build", "--release"])
+        .current_dir(workspace_root());
+    for package in &packages {
+        build.args(["--package", package]);
+    }
+    match build.status() {
+        Ok(status) if status.success() => {}
+        Ok(_) => die!("cargo build failed"),
+        Err(e) => die!("cannot run cargo: {}", e),
+    }
+
+    fs::create_dir_all(bin_dir)
+        .unwrap_or_else(|e| die!("cannot create '{}': {}", bin_dir.display(), e));
+    let release = workspace_root().join("target/release");
+    for tool in tools {
+        let target = bin_dir.join(tool);
+        println!("Installing {}", target.display());
+        // Remove the old binary first, so that one that is running does not get in the way.
+        let _ = fs::remove_file(&target);
+        fs::copy(release.join(tool), &target)
+            .unwrap_or_else(|e| die!("cannot install '{}': {}", target.display(), e));
+    }
+    let lib_dir = prefix.join("lib/coreutils-rs");
+    for &(_, library) in LIBRARIES.iter().filter(|&&(tool, _)| tools.contains(&tool)) {
+        fs::create_dir_all(&lib_dir)
+            .unwrap_or_else(|e| die!("cannot create '{}': {}", lib_dir.display(), e));
+        let target = lib_dir.join(library);
+        println!("Installing {}", target.display());
+        let _ = fs::remove_file(&target);
+        fs::copy(release.join(library), &target)
+            .unwrap_or_else(|e| die!("cannot install '{}': {}", target.display(), e));
+    }
+
+    let in_path = env::var_os("PATH")
+        .is_some_and(|path| env::split_paths(&path).any(|dir| same_dir(&dir, bin_dir)));
+    if !in_path {
+        warn!("'{}' is not in PATH", bin_dir.display());
+    }
+}
+
+fn same_dir(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+fn show_help(opts: &ArgParser) {
+    let brief = format!(
+        concat!(
+            "Usage: {}: install [OPTION]... [TOOL]...\n",
+            "Build the TOOLs, or all of them, in release mode and install them to PREFIX/bin,\n",
+            "and the libraries they need to PREFIX/lib/coreutils-rs."
+        ),
+        env::args().next().unwrap()
+    );
+    print!("{}", opts.usage(&brief));
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let mut opts = ArgParser::new();
+    opts.option(
+        "",
+        "prefix",
+        "install to PREFIX/bin (default: ~/.local)",
+        "PREFIX",
+    );
+    opts.flag("h", "help", "display this help and exit");
+    let options = match opts.parse(&args[1..]) {
+        Ok(m) => m,
+        Err(f) => die!("{}", f.to_string()),
+    };
+    if options.opt_present("h") {
+        return show_help(&opts);
+    }
+    match options.free.first().map(|task| task.as_str()) {
+        Some("install") => {}
+        Some(task) => die!("unknown task '{}'", task),
+        None => die!("missing task; try 'cargo xtask --help'"),
+    }
+
+    let prefix = match options.opt_str("prefix") {
+        Some(prefix) => PathBuf::from(prefix),
+        None => match env::var_os("HOME") {
+            Some(home) => Path::new(&home).join(".local"),
+            None => die!("HOME is not set; use --prefix"),
+        },
+    };
+    let mut tools: Vec<&str> = options.free[1..].iter().map(|tool| tool.as_str()).collect();
+    if tools.is_empty() {
+        tools = TOOLS
+            .iter()
+            .flat_map(|&(_, binaries)| binaries.iter().cloned())
+            .collect();
+    }
+    for tool in &tools {
+        if package_of(tool).is_none() {
+            die!("unknown tool '{}'", tool);
+        }
+    }
+    install(&tools, &prefix);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The binaries that the sources of the workspace's tool packages define.
+    fn binaries_in(package: &Path) -> Vec<String> {
+        let mut binaries = Vec::new();
+        if package.join("src/main.rs").exists() {
+            binaries.push(package.file_name().unwrap().to_str().unwrap().to_owned());
+        }
+        if let Ok(entries) = fs::read_dir(package.join("src/bin")) {
+            let mut extra: Vec<String> = entries
+                .map(|entry| entry.unwrap().path())
+                .map(|path| path.file_stem().unwrap().to_str().unwrap().to_owned())
+                .collect();
+            extra.sort();
+            binaries.extend(extra);
+        }
+        binaries
+    }
+
+    #[test]
+    fn tools_match_the_workspace() {
+        let manifest = fs::read_to_string(workspace_root().join("Cargo.toml")).unwrap();
+        let mut expected = Vec::new();
+        for line in manifest.lines() {
+            let member = line.trim().trim_end_matches(',').trim_matches('"');
+            if !member.starts_with("src/") {
+                continue;
+            }
+            let binaries = binaries_in(&workspace_root().join(member));
+            if !binaries.is_empty() {
+                expected.push((member[4..].to_owned(), binaries));
+            }
+        }
+        expected.sort();
+        let mut listed: Vec<(String, Vec<String>)> = TOOLS
+            .iter()
+            .map(|&(package, binaries)| {
+                let binaries = binaries.iter().map(|b| b.to_string()).collect();
+                (package.to_owned(), binaries)
+            })
+            .collect();
+        listed.sort();
+        assert_eq!(listed, expected);
+    }
+
+    #[test]
+    fn libraries_belong_to_tools() {
+        for &(tool, library) in LIBRARIES {
+            assert_eq!(package_of(tool), Some(tool));
+            assert!(library.starts_with("lib") && library.ends_with(".so"));
+        }
+    }
+
+    #[test]
+    fn packages_of_tools() {
+        assert_eq!(package_of("cat"), Some("cat"));
+        assert_eq!(package_of("unexpand"), Some("expand"));
+        assert_eq!(package_of("cksum"), Some("sum"));
+        assert_eq!(package_of("checksum"), None);
+    }
+}